@@ -0,0 +1,276 @@
+use super::{AnchorTable, AnchorIndex};
+
+// Only the nearest preceding anchors are tried as the predecessor
+//  to bound the chaining to linear time for the repetitive targets.
+const MAXIMUM_PREDECESSOR_COUNT: usize = 50;
+
+/**
+Co-linear chain of anchors
+  - score: sum of the lengths of the anchors minus the gap costs
+  - anchor_indices: (pattern index, index in the pattern) sorted by the query position
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnchorChain {
+    pub score: u32,
+    pub anchor_indices: Vec<AnchorIndex>,
+}
+
+struct ChainNode {
+    anchor_index: AnchorIndex,
+    query_start: u32,
+    query_end: u32,
+    target_start: u32,
+    target_end: u32,
+    score: u32,
+    predecessor: Option<usize>,
+}
+
+impl AnchorTable {
+    /// Chain the co-linear anchors by the dynamic programming (like the chaining of minimap2).
+    ///  - The score of a chain is the sum of the lengths of the anchors minus the gap costs.
+    ///  - The gap cost between two anchors is the difference of the gaps in the query and the target,
+    ///    as if the middle between them is a single gap and the rest of it is matched.
+    ///  - Each anchor belongs to one chain. The chains are sorted by the score in descending order.
+    pub fn chain_anchors(&self, pattern_size: u32) -> Vec<AnchorChain> {
+        self.chain_anchors_by(pattern_size, estimated_middle_score)
+    }
+    /// Chain the anchors as `chain_anchors`, aligning the middle between two anchors exactly
+    /// if it is not longer than `max_middle_size` in both the query and the target.
+    ///  - The middle is scored like the anchors: the matched bases are added,
+    ///    and the mismatched, inserted and deleted bases are subtracted (see `exact_middle_score`).
+    ///  - The single gap estimate of `chain_anchors` ignores the mismatches and the matched bases of the middle,
+    ///    so it links the anchors across a dissimilar middle and does not link the anchors far apart across a similar one.
+    ///  - The longer middles are estimated as `chain_anchors`.
+    pub fn chain_anchors_with_exact_middle(
+        &self,
+        query: &[u8],
+        target: &[u8],
+        pattern_size: u32,
+        max_middle_size: u32,
+    ) -> Vec<AnchorChain> {
+        self.chain_anchors_by(pattern_size, |predecessor, node| {
+            let query_gap = node.query_start - predecessor.query_end;
+            let target_gap = node.target_start - predecessor.target_end;
+            if query_gap <= max_middle_size && target_gap <= max_middle_size {
+                exact_middle_score(
+                    &query[predecessor.query_end as usize..node.query_start as usize],
+                    &target[predecessor.target_end as usize..node.target_start as usize],
+                )
+            } else {
+                estimated_middle_score(predecessor, node)
+            }
+        })
+    }
+    /// Mark the anchors that are not in the chains scored at least `minimum_chain_score` to be skipped.
+    pub fn skip_anchors_out_of_chains(&mut self, pattern_size: u32, minimum_chain_score: u32) {
+        let chains = self.chain_anchors(pattern_size);
+        self.skip_anchors_out_of(chains, minimum_chain_score);
+    }
+    /// Mark the anchors that are not in the chains scored at least `minimum_chain_score` to be skipped,
+    /// aligning the middles not longer than `max_middle_size` exactly (see `chain_anchors_with_exact_middle`).
+    ///  - No anchor is skipped if `minimum_chain_score` is `None` or `max_middle_size` is 0
+    ///    (the anchors are chained by `skip_anchors_out_of_chains` before the target is buffered).
+    pub fn skip_anchors_out_of_exact_chains(
+        &mut self,
+        query: &[u8],
+        target: &[u8],
+        pattern_size: u32,
+        minimum_chain_score: Option<u32>,
+        max_middle_size: u32,
+    ) {
+        let Some(minimum_chain_score) = minimum_chain_score else {
+            return;
+        };
+        if max_middle_size == 0 {
+            return;
+        }
+        let chains = self.chain_anchors_with_exact_middle(query, target, pattern_size, max_middle_size);
+        self.skip_anchors_out_of(chains, minimum_chain_score);
+    }
+    fn chain_anchors_by<F>(&self, pattern_size: u32, middle_score: F) -> Vec<AnchorChain> where
+        F: Fn(&ChainNode, &ChainNode) -> i64,
+    {
+        let mut nodes = self.chain_nodes(pattern_size);
+        (0..nodes.len()).for_each(|index| {
+            let node = &nodes[index];
+            let own_score = node.query_end - node.query_start;
+            let mut best = (own_score, None);
+            (index.saturating_sub(MAXIMUM_PREDECESSOR_COUNT)..index).for_each(|predecessor_index| {
+                let predecessor = &nodes[predecessor_index];
+                if (predecessor.query_end > node.query_start) || (predecessor.target_end > node.target_start) {
+                    return;
+                }
+                let score = predecessor.score as i64 + own_score as i64 + middle_score(predecessor, node);
+                if score > best.0 as i64 {
+                    best = (score as u32, Some(predecessor_index));
+                }
+            });
+            nodes[index].score = best.0;
+            nodes[index].predecessor = best.1;
+        });
+
+        // Backtrace from the highest score, stopping at the anchor already in the other chain
+        let mut order: Vec<usize> = (0..nodes.len()).collect();
+        order.sort_by_key(|&index| std::cmp::Reverse(nodes[index].score));
+        let mut is_chained = vec![false; nodes.len()];
+        let mut chains = Vec::new();
+        order.into_iter().for_each(|end_index| {
+            if is_chained[end_index] {
+                return;
+            }
+            let mut node_indices = Vec::new();
+            let mut current = Some(end_index);
+            while let Some(index) = current {
+                if is_chained[index] {
+                    break;
+                }
+                is_chained[index] = true;
+                node_indices.push(index);
+                current = nodes[index].predecessor;
+            }
+            let score = match current {
+                Some(index) => nodes[end_index].score.saturating_sub(nodes[index].score),
+                None => nodes[end_index].score,
+            };
+            chains.push(AnchorChain {
+                score,
+                anchor_indices: node_indices.into_iter().rev().map(|index| nodes[index].anchor_index).collect(),
+            });
+        });
+        chains.sort_by_key(|chain| std::cmp::Reverse(chain.score));
+        chains
+    }
+    fn skip_anchors_out_of(&mut self, chains: Vec<AnchorChain>, minimum_chain_score: u32) {
+        let mut is_in_chain: Vec<Vec<bool>> = self.0.iter().map(|anchors| vec![false; anchors.len()]).collect();
+        chains.into_iter()
+            .take_while(|chain| chain.score >= minimum_chain_score)
+            .for_each(|chain| {
+                chain.anchor_indices.into_iter().for_each(|(pattern_index, anchor_index)| {
+                    is_in_chain[pattern_index as usize][anchor_index as usize] = true;
+                });
+            });
+        self.0.iter_mut().zip(is_in_chain).for_each(|(anchors, is_in_chain)| {
+            anchors.iter_mut().zip(is_in_chain).for_each(|(anchor, is_in_chain)| {
+                if !is_in_chain {
+                    anchor.to_skip = true;
+                }
+            });
+        });
+    }
+    // Nodes sorted by the query position, then by the target position
+    fn chain_nodes(&self, pattern_size: u32) -> Vec<ChainNode> {
+        let mut nodes = Vec::with_capacity(self.anchor_count());
+        self.0.iter().enumerate().for_each(|(pattern_index, anchors)| {
+            let query_start = pattern_index as u32 * pattern_size;
+            anchors.iter().enumerate().for_each(|(anchor_index, anchor)| {
+                let length = anchor.pattern_count * pattern_size;
+                nodes.push(ChainNode {
+                    anchor_index: (pattern_index as u32, anchor_index as u32),
+                    query_start,
+                    query_end: query_start + length,
+                    target_start: anchor.target_position,
+                    target_end: anchor.target_position + length,
+                    score: 0,
+                    predecessor: None,
+                });
+            });
+        });
+        nodes
+    }
+}
+
+// Minus the difference of the gaps between the anchors (a single gap)
+fn estimated_middle_score(predecessor: &ChainNode, node: &ChainNode) -> i64 {
+    let query_gap = node.query_start - predecessor.query_end;
+    let target_gap = node.target_start - predecessor.target_end;
+    -(query_gap.abs_diff(target_gap) as i64)
+}
+
+// Best score of the global alignment of the middle between two anchors
+//  - +1 for a matched base, -1 for a mismatched, inserted or deleted base.
+//  - Filled by two rows of the query, so the middle should be short.
+fn exact_middle_score(query: &[u8], target: &[u8]) -> i64 {
+    let mut previous_row: Vec<i64> = (0..=target.len() as i64).map(|v| -v).collect();
+    let mut current_row = vec![0; target.len() + 1];
+    query.iter().enumerate().for_each(|(query_index, query_base)| {
+        current_row[0] = -(query_index as i64 + 1);
+        target.iter().enumerate().for_each(|(target_index, target_base)| {
+            let diagonal = previous_row[target_index] + if query_base == target_base { 1 } else { -1 };
+            current_row[target_index + 1] = diagonal
+                .max(previous_row[target_index + 1] - 1)
+                .max(current_row[target_index] - 1);
+        });
+        std::mem::swap(&mut previous_row, &mut current_row);
+    });
+    previous_row[target.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Anchor;
+    use crate::test_utils::{SEED, random_dna};
+
+    fn anchor_table(pattern_count: usize, anchors: &[(usize, u32)]) -> AnchorTable {
+        let mut anchor_table = AnchorTable(vec![Vec::new(); pattern_count]);
+        anchors.iter().for_each(|&(pattern_index, target_position)| {
+            anchor_table.0[pattern_index].extend(Anchor::new_vec(vec![target_position]));
+        });
+        anchor_table
+    }
+
+    #[test]
+    fn chaining_finds_the_best_co_linear_anchors() {
+        let pattern_size = 10;
+        // A(0, 100) - C(2, 122) - D(4, 142): a 2 bp deletion between A and C
+        // B(1, 200) - E(3, 210): following the next pattern from A (A-B-E) has a 90 bp gap
+        let mut anchor_table = anchor_table(5, &[(0, 100), (1, 200), (2, 122), (3, 210), (4, 142)]);
+        let chains = anchor_table.chain_anchors(pattern_size);
+        assert_eq!(chains[0], AnchorChain {
+            score: 28,
+            anchor_indices: vec![(0, 0), (2, 0), (4, 0)],
+        });
+        assert!(chains[1..].iter().all(|chain| chain.score <= 10));
+        assert_eq!(chains.iter().map(|chain| chain.anchor_indices.len()).sum::<usize>(), 5);
+
+        anchor_table.skip_anchors_out_of_chains(pattern_size, 20);
+        let skipped: Vec<bool> = anchor_table.0.iter().map(|anchors| anchors[0].to_skip).collect();
+        assert_eq!(skipped, vec![false, true, false, true, false]);
+    }
+    #[test]
+    fn exact_middle_links_the_anchors_across_a_similar_middle() {
+        let pattern_size = 10;
+        let query = random_dna(SEED, 120);
+        // 15 bp inserted in the query and three mismatches in the middle
+        let mut target = [&query[..50], &query[65..]].concat();
+        [20, 40, 70].iter().for_each(|&position| {
+            target[position] = if target[position] == b'A' { b'C' } else { b'A' };
+        });
+        // A(0, 0) - B(11, 95): the estimate is 10 + 10 - 15 (< 10), not linked
+        let mut pair = anchor_table(12, &[(0, 0), (11, 95)]);
+        let chains = pair.chain_anchors(pattern_size);
+        assert_eq!(chains.iter().map(|chain| chain.score).collect::<Vec<_>>(), vec![10, 10]);
+        // Exact: 10 + 10 + (82 - 3 - 15)
+        let chains = pair.chain_anchors_with_exact_middle(&query, &target, pattern_size, 100);
+        assert_eq!(chains, vec![AnchorChain {
+            score: 84,
+            anchor_indices: vec![(0, 0), (11, 0)],
+        }]);
+        // Middle longer than the maximum size is estimated
+        assert_eq!(pair.chain_anchors_with_exact_middle(&query, &target, pattern_size, 99).len(), 2);
+
+        let mut estimated = pair.clone();
+        estimated.skip_anchors_out_of_chains(pattern_size, 50);
+        assert!(estimated.0.iter().flatten().all(|anchor| anchor.to_skip));
+        pair.skip_anchors_out_of_exact_chains(&query, &target, pattern_size, Some(50), 100);
+        assert!(pair.0.iter().flatten().all(|anchor| !anchor.to_skip));
+
+        // A dissimilar middle of the same length is linked only by the estimate
+        let query = [&query[..10], &[b'A'; 20], &query[30..40]].concat();
+        let target = [&query[..10], &[b'C'; 20], &query[30..40]].concat();
+        let pair = anchor_table(4, &[(0, 0), (3, 30)]);
+        assert_eq!(pair.chain_anchors(pattern_size)[0].score, 20);
+        let chains = pair.chain_anchors_with_exact_middle(&query, &target, pattern_size, 100);
+        assert_eq!(chains.iter().map(|chain| chain.score).collect::<Vec<_>>(), vec![10, 10]);
+    }
+}
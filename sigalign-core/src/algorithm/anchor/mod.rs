@@ -1,6 +1,12 @@
-use crate::core::BufferedPatternLocator;
+use crate::core::{
+    BufferedPatternLocator,
+    PatternLocation,
+    regulators::{LowComplexityFilter, SeedPattern, SeedingOptions},
+};
 use ahash::AHashMap;
 
+mod chaining;
+
 /**
 Anchor Table: Sorted target positions by pattern
   - 1st Vec: Pattern index
@@ -33,17 +39,44 @@ impl AnchorTable {
         query: &[u8],
         sorted_target_indices: &[u32],
         pattern_size: u32,
-    ) -> AHashMap<u32, Self> {
+        seeding_options: &SeedingOptions,
+    ) -> Vec<(u32, Self)> {
+        let SeedingOptions {
+            minimum_anchor_count,
+            low_complexity_filter,
+            seed_stride,
+            seed_pattern,
+            minimizer_seeder,
+            max_locations_per_pattern,
+            ..
+        } = *seeding_options;
+        // Chained after the target is buffered, if the middles are aligned exactly
+        let minimum_chain_score = seeding_options.minimum_chain_score.filter(|_| seeding_options.exact_chain_middle_size == 0);
         let qry_len = query.len();
         let pattern_count = qry_len / pattern_size as usize;
 
         let mut anchor_table_by_target_index: AHashMap<u32, Self> = AHashMap::new();
+        let masked_patterns = low_complexity_filter.map(|filter| filter.masked_patterns(query, pattern_size));
+        // Patterns not selected by the minimizers are skipped as the masked ones
+        let masked_patterns = match minimizer_seeder {
+            Some(minimizer_seeder) => {
+                let selected_patterns = minimizer_seeder.selected_patterns(query, pattern_size);
+                let masked_patterns = masked_patterns.unwrap_or_else(|| vec![false; pattern_count]);
+                Some(masked_patterns.iter().zip(selected_patterns.iter()).map(|(masked, selected)| *masked || !selected).collect::<Vec<bool>>())
+            },
+            None => masked_patterns,
+        };
 
         (0..pattern_count).for_each(|pattern_index| {
+            if let Some(masked_patterns) = &masked_patterns {
+                if masked_patterns[pattern_index] {
+                    return;
+                }
+            }
             let qry_pos = pattern_index * pattern_size as usize;
             let pattern = &query[qry_pos..qry_pos+pattern_size as usize];
             
-            let pattern_locations = pattern_locater.locate(pattern, sorted_target_indices);
+            let pattern_locations = Self::locate_within_cap(pattern_locater, pattern, sorted_target_indices, max_locations_per_pattern);
 
             pattern_locations.into_iter().for_each(|pattern_location| {
                 match anchor_table_by_target_index.get_mut(&pattern_location.target_index) {
@@ -65,23 +98,400 @@ impl AnchorTable {
             });
         });
 
-        anchor_table_by_target_index.iter_mut().for_each(|(_, pos_table)| {
+        // Seeds shifted by the stride only count toward the minimum anchor count
+        let shifted_seed_counts = match seed_stride {
+            Some(seed_stride) if minimum_anchor_count > 1 => Self::shifted_seed_counts_by_target_index(
+                pattern_locater,
+                query,
+                sorted_target_indices,
+                pattern_size,
+                seed_stride,
+                low_complexity_filter,
+                max_locations_per_pattern,
+            ),
+            _ => AHashMap::new(),
+        };
+        // So are the patterns located only by the spaced seed
+        let spaced_seed_counts = match seed_pattern {
+            Some(seed_pattern) if minimum_anchor_count > 1 => Self::spaced_seed_counts_by_target_index(
+                pattern_locater,
+                query,
+                sorted_target_indices,
+                pattern_size,
+                seed_pattern,
+                masked_patterns.as_deref(),
+                max_locations_per_pattern,
+            ),
+            _ => AHashMap::new(),
+        };
+
+        // Sorted by target index to make the order of the results deterministic
+        let mut sorted_anchor_tables: Vec<(u32, Self)> = anchor_table_by_target_index.into_iter().filter(|(target_index, pos_table)| {
+            // Targets with too few anchors are not extended
+            let shifted_seed_count = shifted_seed_counts.get(target_index).copied().unwrap_or(0);
+            let spaced_seed_count = spaced_seed_counts.get(target_index).copied().unwrap_or(0);
+            pos_table.anchor_count() + shifted_seed_count + spaced_seed_count >= minimum_anchor_count as usize
+        }).collect();
+        sorted_anchor_tables.sort_unstable_by_key(|(target_index, _)| *target_index);
+        sorted_anchor_tables.iter_mut().for_each(|(target_index, pos_table)| {
             pos_table.merge_ungapped_anchors(pattern_size);
+            pos_table.skip_unseedable_anchors(pattern_locater, *target_index, pattern_size);
+            if let Some(minimum_chain_score) = minimum_chain_score {
+                pos_table.skip_anchors_out_of_chains(pattern_size, minimum_chain_score);
+            }
+            if log::log_enabled!(log::Level::Trace) {
+                let skipped_anchor_count = pos_table.0.iter().flatten().filter(|anchor| anchor.to_skip).count();
+                log::trace!(
+                    "anchor table: target_index={}, anchor_count={}, skipped_anchor_count={}",
+                    target_index, pos_table.anchor_count(), skipped_anchor_count,
+                );
+            }
         });
+        log::debug!(
+            "anchors located: query_length={}, pattern_size={}, pattern_count={}, target_count={}",
+            qry_len, pattern_size, pattern_count, sorted_anchor_tables.len(),
+        );
 
-        anchor_table_by_target_index
+        sorted_anchor_tables
+    }
+    // Count the located seeds starting between the patterns (at the multiples of the stride)
+    //  - The seeds are not anchors: the extension needs the anchors on the pattern boundaries.
+    fn shifted_seed_counts_by_target_index<L: BufferedPatternLocator>(
+        pattern_locater: &L,
+        query: &[u8],
+        sorted_target_indices: &[u32],
+        pattern_size: u32,
+        seed_stride: u32,
+        low_complexity_filter: Option<LowComplexityFilter>,
+        max_locations_per_pattern: Option<u32>,
+    ) -> AHashMap<u32, usize> {
+        let mut seed_counts: AHashMap<u32, usize> = AHashMap::new();
+        if (seed_stride == 0) || (seed_stride >= pattern_size) || (query.len() < pattern_size as usize) {
+            return seed_counts;
+        }
+        let pattern_size = pattern_size as usize;
+        let masked_bases = low_complexity_filter.map(|filter| filter.masked_bases(query));
+        let last_seed_position = query.len() - pattern_size;
+
+        (seed_stride as usize..pattern_size).step_by(seed_stride as usize).for_each(|offset| {
+            (offset..=last_seed_position).step_by(pattern_size).for_each(|qry_pos| {
+                let seed_range = qry_pos..qry_pos+pattern_size;
+                if let Some(masked_bases) = &masked_bases {
+                    if masked_bases[seed_range.clone()].iter().all(|masked| *masked) {
+                        return;
+                    }
+                }
+                Self::locate_within_cap(
+                    pattern_locater, &query[seed_range], sorted_target_indices, max_locations_per_pattern,
+                ).into_iter().for_each(|pattern_location| {
+                    *seed_counts.entry(pattern_location.target_index).or_insert(0) += pattern_location.sorted_positions.len();
+                });
+            });
+        });
+        seed_counts
     }
+    // Count the locations of the variants of each pattern at the don't-care positions of the spaced seed
+    //  - The variants differ from the pattern, so their locations are not the anchors already counted.
+    fn spaced_seed_counts_by_target_index<L: BufferedPatternLocator>(
+        pattern_locater: &L,
+        query: &[u8],
+        sorted_target_indices: &[u32],
+        pattern_size: u32,
+        seed_pattern: SeedPattern,
+        masked_patterns: Option<&[bool]>,
+        max_locations_per_pattern: Option<u32>,
+    ) -> AHashMap<u32, usize> {
+        let mut seed_counts: AHashMap<u32, usize> = AHashMap::new();
+        let pattern_size = pattern_size as usize;
+        query.chunks_exact(pattern_size).enumerate().for_each(|(pattern_index, pattern)| {
+            if let Some(masked_patterns) = masked_patterns {
+                if masked_patterns[pattern_index] {
+                    return;
+                }
+            }
+            seed_pattern.variants(pattern).iter().for_each(|variant| {
+                Self::locate_within_cap(
+                    pattern_locater, variant, sorted_target_indices, max_locations_per_pattern,
+                ).into_iter().for_each(|pattern_location| {
+                    *seed_counts.entry(pattern_location.target_index).or_insert(0) += pattern_location.sorted_positions.len();
+                });
+            });
+        });
+        seed_counts
+    }
+    // Locate the pattern, but drop all of its locations if they are more than the cap
+    //  - The repetitive patterns are located in so many positions that they are not informative as anchors.
+    fn locate_within_cap<L: BufferedPatternLocator>(
+        pattern_locater: &L,
+        pattern: &[u8],
+        sorted_target_indices: &[u32],
+        max_locations_per_pattern: Option<u32>,
+    ) -> Vec<PatternLocation> {
+        let pattern_locations = pattern_locater.locate(pattern, sorted_target_indices);
+        if let Some(max_locations_per_pattern) = max_locations_per_pattern {
+            let location_count: usize = pattern_locations.iter().map(|v| v.sorted_positions.len()).sum();
+            if location_count > max_locations_per_pattern as usize {
+                return Vec::new();
+            }
+        }
+        pattern_locations
+    }
+    /// Whether each pattern of the query is located in any target.
+    ///  - The patterns masked by the low-complexity filter are not located.
+    ///  - The patterns located more than `max_locations_per_pattern` times are treated as not located.
+    #[cfg(feature = "debug")]
+    pub fn anchor_existence<L: BufferedPatternLocator>(
+        pattern_locater: &L,
+        query: &[u8],
+        sorted_target_indices: &[u32],
+        pattern_size: u32,
+        seeding_options: &SeedingOptions,
+    ) -> Vec<bool> {
+        let pattern_count = query.len() / pattern_size as usize;
+        let max_locations_per_pattern = seeding_options.max_locations_per_pattern;
+        let masked_patterns = seeding_options.low_complexity_filter.map(|filter| filter.masked_patterns(query, pattern_size));
+        (0..pattern_count).map(|pattern_index| {
+            if let Some(masked_patterns) = &masked_patterns {
+                if masked_patterns[pattern_index] {
+                    return false;
+                }
+            }
+            let qry_pos = pattern_index * pattern_size as usize;
+            let pattern = &query[qry_pos..qry_pos+pattern_size as usize];
+            Self::locate_within_cap(
+                pattern_locater, pattern, sorted_target_indices, max_locations_per_pattern,
+            ).iter().any(|pattern_location| {
+                !pattern_location.sorted_positions.is_empty()
+            })
+        }).collect()
+    }
+    /// Anchor table of one target from the seed, without locating the patterns.
+    ///  - `seed`: (target position, query position, length) of the region expected to be matched.
+    ///  - Only the patterns inside the seed that exactly match on the diagonal of the seed are anchors.
+    ///  - None if there is no such pattern.
+    pub fn new_from_seed(
+        query: &[u8],
+        target: &[u8],
+        seed: (u32, u32, u32),
+        pattern_size: u32,
+    ) -> Option<Self> {
+        let (seed_target_position, seed_query_position, seed_length) = seed;
+        if (seed_target_position as usize > target.len()) || (seed_query_position as usize > query.len()) {
+            return None;
+        }
+        // Clip the seed to the sequences
+        let seed_length = seed_length
+            .min(target.len() as u32 - seed_target_position)
+            .min(query.len() as u32 - seed_query_position);
+        let pattern_count = query.len() / pattern_size as usize;
+        let mut anchor_table = Self::new_empty(pattern_count);
+
+        let mut has_anchor = false;
+        let first_pattern_index = num::integer::div_ceil(seed_query_position, pattern_size);
+        let last_pattern_index = (seed_query_position + seed_length) / pattern_size; // Exclusive
+        for pattern_index in first_pattern_index..last_pattern_index {
+            let query_position = pattern_index * pattern_size;
+            let target_position = seed_target_position + (query_position - seed_query_position);
+            let pattern = &query[query_position as usize..(query_position + pattern_size) as usize];
+            let target_slice = &target[target_position as usize..(target_position + pattern_size) as usize];
+            if pattern == target_slice {
+                anchor_table.add_new_positions(pattern_index as usize, vec![target_position]);
+                has_anchor = true;
+            }
+        }
+        if !has_anchor {
+            return None;
+        }
+        anchor_table.merge_ungapped_anchors(pattern_size);
+        Some(anchor_table)
+    }
+    // The positions are sorted here if not, since the merging of the anchors depends on the order.
+    //  - The locator can return the positions in the order of its index (e.g., suffix array of FM-index).
     fn add_new_positions(
         &mut self,
         pattern_index: usize,
-        sorted_target_positions: Vec<u32>,
+        mut target_positions: Vec<u32>,
     ) {
-        self.0[pattern_index] = Anchor::new_vec(sorted_target_positions);
+        if target_positions.windows(2).any(|w| w[0] > w[1]) {
+            target_positions.sort_unstable();
+        }
+        self.0[pattern_index] = Anchor::new_vec(target_positions);
+    }
+    pub fn anchor_count(&self) -> usize {
+        self.0.iter().map(|anchors| anchors.len()).sum()
+    }
+    #[cfg(feature = "debug")]
+    pub fn skipped_anchor_count(&self) -> usize {
+        self.0.iter().map(|anchors| anchors.iter().filter(|anchor| anchor.to_skip).count()).sum()
     }
     fn new_empty(pattern_count: usize) -> Self {
         Self(vec![Vec::new(); pattern_count])
     }
-    fn merge_ungapped_anchors(&mut self, pattern_size: u32) {
+    /// Skip the anchors by the options checked on the buffered target.
+    ///  - The anchors out of the exact chains, without matched flanks, shorter than the minimum anchor size,
+    ///    and merged across the mismatches, in this order.
+    pub fn skip_anchors_in_buffered_target(
+        &mut self,
+        query: &[u8],
+        target: &[u8],
+        pattern_size: u32,
+        seeding_options: &SeedingOptions,
+    ) {
+        self.skip_anchors_out_of_exact_chains(query, target, pattern_size, seeding_options.minimum_chain_score, seeding_options.exact_chain_middle_size);
+        self.skip_anchors_without_matched_flanks(query, target, pattern_size, seeding_options.seed_extension_check);
+        self.skip_anchors_shorter_than(query, target, pattern_size, seeding_options.minimum_anchor_size);
+        self.skip_anchors_across_mismatches(query, target, pattern_size, seeding_options.max_merge_mismatches);
+    }
+    /// Skip the anchors of a single pattern whose `flank_size` bases on either side do not match.
+    ///  - The flanks are clipped at the ends of the sequences.
+    ///  - The isolated exact matches of the repetitive patterns are pruned before the extension.
+    ///    The anchors merged from several patterns already match longer than a pattern, so they are not checked.
+    ///  - No anchor is skipped if `flank_size` is 0.
+    pub fn skip_anchors_without_matched_flanks(
+        &mut self,
+        query: &[u8],
+        target: &[u8],
+        pattern_size: u32,
+        flank_size: u32,
+    ) {
+        if flank_size == 0 {
+            return;
+        }
+        let flank_size = flank_size as usize;
+        let pattern_size = pattern_size as usize;
+        self.0.iter_mut().enumerate().for_each(|(pattern_index, anchors)| {
+            let query_start = pattern_index * pattern_size;
+            let query_end = query_start + pattern_size;
+            anchors.iter_mut().filter(|anchor| anchor.pattern_count == 1).for_each(|anchor| {
+                let target_start = anchor.target_position as usize;
+                let target_end = target_start + pattern_size;
+                let left_size = flank_size.min(query_start).min(target_start);
+                let right_size = flank_size.min(query.len() - query_end).min(target.len() - target_end);
+                let is_left_matched = query[query_start-left_size..query_start] == target[target_start-left_size..target_start];
+                let is_right_matched = query[query_end..query_end+right_size] == target[target_end..target_end+right_size];
+                if !(is_left_matched && is_right_matched) {
+                    anchor.to_skip = true;
+                }
+            });
+        });
+    }
+    /// Skip the anchors of which the exact match is shorter than `minimum_size`.
+    ///  - The exact match of an anchor is its patterns extended by the matched bases on both sides,
+    ///    like the impeccable extension without any penalty.
+    ///  - The anchors already skipped are not checked.
+    ///  - No anchor is skipped if `minimum_size` is not larger than the pattern size.
+    pub fn skip_anchors_shorter_than(
+        &mut self,
+        query: &[u8],
+        target: &[u8],
+        pattern_size: u32,
+        minimum_size: u32,
+    ) {
+        if minimum_size <= pattern_size {
+            return;
+        }
+        let minimum_size = minimum_size as usize;
+        let pattern_size = pattern_size as usize;
+        self.0.iter_mut().enumerate().for_each(|(pattern_index, anchors)| {
+            let query_start = pattern_index * pattern_size;
+            anchors.iter_mut().filter(|anchor| !anchor.to_skip).for_each(|anchor| {
+                let anchor_size = anchor.pattern_count as usize * pattern_size;
+                if anchor_size >= minimum_size {
+                    return;
+                }
+                let query_end = query_start + anchor_size;
+                let target_start = anchor.target_position as usize;
+                let target_end = target_start + anchor_size;
+                let left_size = query[..query_start].iter().rev()
+                    .zip(target[..target_start].iter().rev())
+                    .take(minimum_size - anchor_size)
+                    .take_while(|(query_base, target_base)| query_base == target_base)
+                    .count();
+                let right_size = query[query_end..].iter()
+                    .zip(&target[target_end..])
+                    .take(minimum_size - anchor_size)
+                    .take_while(|(query_base, target_base)| query_base == target_base)
+                    .count();
+                if anchor_size + left_size + right_size < minimum_size {
+                    anchor.to_skip = true;
+                }
+            });
+        });
+    }
+    /// Merge the anchors split by at most `max_mismatches` mismatches on the same diagonal, by skipping the right anchor.
+    ///  - The bases between the end of the left anchor and the start of the right anchor are compared.
+    ///    Since each pattern between them has a mismatch, at most `max_mismatches` patterns can be between.
+    ///  - The anchors are the exact matches, so the right anchor is not joined to the left anchor,
+    ///    but skipped to be passed over by the extension of the left anchor. A merged anchor merges its right anchor in turn.
+    ///  - The anchors skipped by the other filters do not merge the others, since they are not extended.
+    ///  - No anchor is merged if `max_mismatches` is 0.
+    pub fn skip_anchors_across_mismatches(
+        &mut self,
+        query: &[u8],
+        target: &[u8],
+        pattern_size: u32,
+        max_mismatches: u32,
+    ) {
+        if max_mismatches == 0 {
+            return;
+        }
+        let pattern_count = self.0.len();
+        let mut is_merged: Vec<Vec<bool>> = self.0.iter().map(|anchors| vec![false; anchors.len()]).collect();
+        for pattern_index in 0..pattern_count {
+            for anchor_index in 0..self.0[pattern_index].len() {
+                let anchor = &self.0[pattern_index][anchor_index];
+                if anchor.to_skip && !is_merged[pattern_index][anchor_index] {
+                    continue;
+                }
+                let end_pattern_index = pattern_index + anchor.pattern_count as usize;
+                let end_target_position = anchor.target_position + anchor.pattern_count * pattern_size;
+                let gap_pattern_counts = 1..=(max_mismatches as usize).min(pattern_count.saturating_sub(end_pattern_index + 1));
+                for gap_pattern_count in gap_pattern_counts {
+                    let right_pattern_index = end_pattern_index + gap_pattern_count;
+                    let gap_size = gap_pattern_count * pattern_size as usize;
+                    let right_target_position = end_target_position + gap_size as u32;
+                    let Ok(right_anchor_index) = self.0[right_pattern_index].binary_search_by_key(
+                        &right_target_position, |anchor| anchor.target_position,
+                    ) else {
+                        continue;
+                    };
+                    let query_start = end_pattern_index * pattern_size as usize;
+                    let target_start = end_target_position as usize;
+                    let mismatch_count = query[query_start..query_start + gap_size].iter()
+                        .zip(&target[target_start..target_start + gap_size])
+                        .filter(|(query_base, target_base)| query_base != target_base)
+                        .count();
+                    if mismatch_count <= max_mismatches as usize {
+                        self.0[right_pattern_index][right_anchor_index].to_skip = true;
+                        is_merged[right_pattern_index][right_anchor_index] = true;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    // Skip the anchors of which no pattern is seedable (see `BufferedPatternLocator::is_seedable`)
+    //  - After merging, so an anchor is extended if any of its patterns is seedable.
+    fn skip_unseedable_anchors<L: BufferedPatternLocator>(
+        &mut self,
+        pattern_locater: &L,
+        target_index: u32,
+        pattern_size: u32,
+    ) {
+        self.0.iter_mut().flatten().for_each(|anchor| {
+            let is_seedable = (0..anchor.pattern_count).any(|pattern_offset| {
+                pattern_locater.is_seedable(target_index, anchor.target_position + pattern_offset * pattern_size, pattern_size)
+            });
+            if !is_seedable {
+                anchor.to_skip = true;
+            }
+        });
+    }
+    #[cfg(feature = "debug")]
+    pub(crate) fn from_sorted_positions_list(sorted_positions_list: Vec<Vec<u32>>) -> Self {
+        Self(sorted_positions_list.into_iter().map(Anchor::new_vec).collect())
+    }
+    pub(crate) fn merge_ungapped_anchors(&mut self, pattern_size: u32) {
         let pattern_count = self.0.len();
 
         for right_index in (1..pattern_count).rev() {
@@ -108,9 +518,11 @@ impl Anchor {
             }
         }).collect()
     }
-    fn merge_right_to_left(left: &mut Vec<Self>, right: &mut Vec<Self>, pattern_size: u32) {
+    // Consecutive anchors are merged by compacting `right` in place,
+    //  so the merging is linear to the number of anchors.
+    fn merge_right_to_left(left: &mut [Self], right: &mut Vec<Self>, pattern_size: u32) {
         let left_count = left.len();
-        let mut right_count = right.len();
+        let right_count = right.len();
 
         if (left_count == 0) || (right_count == 0) {
             return
@@ -118,6 +530,8 @@ impl Anchor {
 
         let mut left_index = 0;
         let mut right_index = 0;
+        // Index to place the next right anchor that is not merged
+        let mut right_retained_count = 0;
 
         while (left_index < left_count) && (right_index < right_count) {
             let left_anchor_position = &mut left[left_index];
@@ -130,18 +544,249 @@ impl Anchor {
                         let right_pattern_count = right_anchor_position.pattern_count;
                         left_anchor_position.pattern_count += right_pattern_count;
 
-                        right.remove(right_index);
-
                         left_index += 1;
-                        right_count -= 1;
                     } else {
-                        right_index += 1;
+                        right.swap(right_retained_count, right_index);
+                        right_retained_count += 1;
                     }
+                    right_index += 1;
                 },
                 None => {
                     left_index += 1;
                 },
             }
         }
+        // Remaining right anchors are not merged
+        while right_index < right_count {
+            right.swap(right_retained_count, right_index);
+            right_retained_count += 1;
+            right_index += 1;
+        }
+        right.truncate(right_retained_count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::regulators::MinimizerSeeder;
+    use crate::test_utils::{TEST_TARGET, SEED, Xorshift, NaiveLocator};
+
+    fn merge_right_to_left_with_removal(left: &mut [Anchor], right: &mut Vec<Anchor>, pattern_size: u32) {
+        let mut left_index = 0;
+        let mut right_index = 0;
+        while (left_index < left.len()) && (right_index < right.len()) {
+            let right_target_position = right[right_index].target_position;
+            match (left[left_index].target_position + pattern_size).checked_sub(right_target_position) {
+                Some(0) => {
+                    left[left_index].pattern_count += right[right_index].pattern_count;
+                    right.remove(right_index);
+                    left_index += 1;
+                },
+                Some(_) => right_index += 1,
+                None => left_index += 1,
+            }
+        }
+    }
+
+    #[test]
+    fn merging_anchors_is_same_as_removal() {
+        let mut rng = Xorshift::new(SEED);
+        let pattern_size = 5;
+
+        for _ in 0..1000 {
+            let mut sorted_positions_list: Vec<Vec<u32>> = (0..6).map(|_| {
+                let count = rng.gen_range(20);
+                let mut positions: Vec<u32> = (0..count).map(|_| rng.gen_range(100)).collect();
+                positions.sort_unstable();
+                positions.dedup();
+                positions
+            }).collect();
+            sorted_positions_list.iter_mut().enumerate().for_each(|(idx, positions)| {
+                positions.iter_mut().for_each(|pos| *pos += idx as u32 * pattern_size);
+            });
+
+            let mut answer: Vec<Vec<Anchor>> = sorted_positions_list.iter().map(|v| Anchor::new_vec(v.clone())).collect();
+            for right_index in (1..answer.len()).rev() {
+                let (l, r) = answer.split_at_mut(right_index);
+                merge_right_to_left_with_removal(&mut l[right_index-1], &mut r[0], pattern_size);
+            }
+
+            let mut anchor_table = AnchorTable(
+                sorted_positions_list.into_iter().map(Anchor::new_vec).collect()
+            );
+            anchor_table.merge_ungapped_anchors(pattern_size);
+
+            assert_eq!(anchor_table.0, answer);
+        }
+    }
+    #[test]
+    fn low_complexity_patterns_are_not_anchored() {
+        let unit = TEST_TARGET;
+        let locator = NaiveLocator::new(vec![[&[b'A'; 1000][..], unit, &[b'A'; 1000][..]].concat()]);
+        let query = [&[b'A'; 100][..], unit].concat();
+        let anchor_count = |low_complexity_filter| {
+            AnchorTable::new_by_target_index(&locator, &query, &[0], 10, &SeedingOptions { low_complexity_filter, ..Default::default() })
+                .iter().map(|(_, anchor_table)| anchor_table.anchor_count()).sum::<usize>()
+        };
+        // Poly-A patterns are located at every position of the poly-A runs
+        assert!(anchor_count(None) > 1000);
+        // Only the patterns of the unit remain (merged to one anchor)
+        assert_eq!(anchor_count(Some(LowComplexityFilter::default())), 1);
+    }
+    #[test]
+    fn anchors_do_not_depend_on_the_order_of_located_positions() {
+        let unit = TEST_TARGET;
+        // The patterns of the unit occur at several positions
+        let target = [&unit[..], b"GGG", unit, b"TTTTT", &unit[..40], unit].concat();
+        let query = unit;
+        let anchor_tables = |reversed| {
+            let locator = NaiveLocator::new(vec![target.clone()]).with_reversed_positions(reversed);
+            AnchorTable::new_by_target_index(&locator, query, &[0], 10, &SeedingOptions::default())
+        };
+        let sorted = anchor_tables(false);
+        assert_eq!(sorted, anchor_tables(true));
+        // Merged to one anchor for each copy of the unit, plus the partial copy
+        let anchor_table = &sorted[0].1;
+        let first_anchors: Vec<(u32, u32)> = anchor_table.0[0].iter()
+            .map(|anchor| (anchor.target_position, anchor.pattern_count))
+            .collect();
+        assert_eq!(first_anchors, vec![(0, 7), (73, 7), (148, 4), (188, 7)]);
+    }
+    #[test]
+    fn repetitive_patterns_over_the_cap_are_skipped() {
+        let unit = TEST_TARGET;
+        let locator = NaiveLocator::new(vec![[&[b'A'; 1000][..], unit, &[b'A'; 1000][..]].concat()]);
+        let anchor_count = |query: &[u8], max_locations_per_pattern| {
+            AnchorTable::new_by_target_index(&locator, query, &[0], 10, &SeedingOptions { max_locations_per_pattern, ..Default::default() })
+                .iter().map(|(_, anchor_table)| anchor_table.anchor_count()).sum::<usize>()
+        };
+        let query = [&[b'A'; 100][..], unit].concat();
+        assert!(anchor_count(&query, None) > 1000);
+        assert_eq!(anchor_count(&query, Some(10)), 1);
+        // Query full of the repetitive pattern has no anchor
+        let query = [b'A'; 1000];
+        assert!(anchor_count(&query, None) > 1000);
+        assert_eq!(anchor_count(&query, Some(10)), 0);
+        // Not skipped when the located positions are within the cap
+        assert!(anchor_count(&query, Some(u32::MAX)) > 1000);
+    }
+    #[test]
+    fn anchors_without_matched_flanks_are_skipped() {
+        let unit = TEST_TARGET;
+        // The second pattern of the unit is repeated between the spacers
+        let repeat = [&b"TTTTTT"[..], &unit[10..20]].concat();
+        let target = [&unit[..], &repeat.repeat(100), b"TTTTTT"].concat();
+        let locator = NaiveLocator::new(vec![target.clone()]);
+        let extended_anchor_count = |flank_size| {
+            let mut anchor_tables = AnchorTable::new_by_target_index(&locator, unit, &[0], 10, &SeedingOptions::default());
+            let anchor_table = &mut anchor_tables[0].1;
+            anchor_table.skip_anchors_without_matched_flanks(unit, &target, 10, flank_size);
+            anchor_table.0.iter().flatten().filter(|anchor| !anchor.to_skip).count()
+        };
+        // The unit (merged to one anchor) and the repeats
+        assert_eq!(extended_anchor_count(0), 101);
+        assert_eq!(extended_anchor_count(4), 1);
+        // Flanks are clipped at the ends
+        let mut anchor_table = AnchorTable(vec![Anchor::new_vec(vec![0])]);
+        anchor_table.skip_anchors_without_matched_flanks(&unit[..10], &unit[..10], 10, 4);
+        assert!(!anchor_table.0[0][0].to_skip);
+    }
+    #[test]
+    fn anchors_split_by_a_mismatch_are_merged() {
+        let unit = TEST_TARGET;
+        let locator = NaiveLocator::new(vec![unit.to_vec()]);
+        let anchors = |query: &[u8], max_mismatches| {
+            let mut anchor_tables = AnchorTable::new_by_target_index(&locator, query, &[0], 10, &SeedingOptions::default());
+            let anchor_table = &mut anchor_tables[0].1;
+            anchor_table.skip_anchors_across_mismatches(query, unit, 10, max_mismatches);
+            anchor_table.0.iter().flatten().map(|anchor| (anchor.target_position, anchor.pattern_count, anchor.to_skip)).collect::<Vec<_>>()
+        };
+        // A SNP in the fourth pattern
+        let mut query = unit.to_vec();
+        query[35] = b'G';
+        assert_eq!(anchors(&query, 0), vec![(0, 3, false), (40, 3, false)]);
+        assert_eq!(anchors(&query, 1), vec![(0, 3, false), (40, 3, true)]);
+        // Two mismatches in the pattern
+        query[38] = b'G';
+        assert_eq!(anchors(&query, 1), vec![(0, 3, false), (40, 3, false)]);
+        assert_eq!(anchors(&query, 2), vec![(0, 3, false), (40, 3, true)]);
+        // Merged in turn across two patterns with the mismatches
+        query[52] = b'N';
+        assert_eq!(anchors(&query, 2), vec![(0, 3, false), (40, 1, true), (60, 1, true)]);
+    }
+    #[test]
+    fn isolated_short_anchors_are_skipped() {
+        let unit = TEST_TARGET;
+        // The second pattern of the unit is repeated between the spacers
+        let repeat = [&b"TTTTTT"[..], &unit[10..20]].concat();
+        let target = [&unit[..], &repeat.repeat(100), b"TTTTTT"].concat();
+        let locator = NaiveLocator::new(vec![target.clone()]);
+        let extended_anchors = |query: &[u8], minimum_size| {
+            let mut anchor_tables = AnchorTable::new_by_target_index(&locator, query, &[0], 10, &SeedingOptions::default());
+            let anchor_table = &mut anchor_tables[0].1;
+            anchor_table.skip_anchors_shorter_than(query, &target, 10, minimum_size);
+            anchor_table.0.iter().flatten()
+                .filter(|anchor| !anchor.to_skip)
+                .map(|anchor| (anchor.target_position, anchor.pattern_count))
+                .collect::<Vec<_>>()
+        };
+        // The repeats are single patterns without the matched flanks
+        assert_eq!(extended_anchors(unit, 10).len(), 101);
+        assert_eq!(extended_anchors(unit, 11), vec![(0, 7)]);
+        assert_eq!(extended_anchors(unit, 70), vec![(0, 7)]);
+        assert!(extended_anchors(unit, 71).is_empty());
+
+        // A single pattern anchor extended by a matched base on each side
+        let mut query = unit.to_vec();
+        query[8] = b'T';
+        query[21] = b'G';
+        let anchors = extended_anchors(&query, 12);
+        assert!(anchors.contains(&(10, 1)));
+        assert!(!extended_anchors(&query, 13).contains(&(10, 1)));
+    }
+    #[test]
+    fn spaced_seeds_count_toward_the_minimum_anchor_count() {
+        let unit = TEST_TARGET;
+        let locator = NaiveLocator::new(vec![unit.to_vec()]);
+        // Mismatches at the third base of the first five patterns
+        let mut query = unit.to_vec();
+        (0..5).for_each(|pattern_index| {
+            let position = pattern_index * 10 + 2;
+            query[position] = if query[position] == b'T' { b'G' } else { b'T' };
+        });
+        let target_count = |minimum_anchor_count, seed_pattern| {
+            AnchorTable::new_by_target_index(&locator, &query, &[0], 10, &SeedingOptions { minimum_anchor_count, seed_pattern, ..Default::default() }).len()
+        };
+        assert_eq!(target_count(2, None), 1);
+        assert_eq!(target_count(3, None), 0);
+        // Five more patterns located with the spaced seed
+        let seed_pattern = SeedPattern::new("1101111111");
+        assert_eq!(target_count(7, seed_pattern), 1);
+        assert_eq!(target_count(8, seed_pattern), 0);
+        // The don't-care position does not cover the mismatches
+        assert_eq!(target_count(3, SeedPattern::new("1110111111")), 0);
+    }
+    #[test]
+    fn only_minimizer_patterns_are_anchored() {
+        let unit = TEST_TARGET;
+        let query = [&unit[..], unit, unit].concat();
+        let locator = NaiveLocator::new(vec![query.clone()]);
+        let anchored_patterns = |minimizer_seeder| {
+            let anchor_tables = AnchorTable::new_by_target_index(&locator, &query, &[0], 10, &SeedingOptions { minimizer_seeder, ..Default::default() });
+            let mut anchored = vec![false; query.len() / 10];
+            anchor_tables[0].1.0.iter().enumerate().for_each(|(pattern_index, anchors)| {
+                // Patterns located at the same position (on the diagonal) of the query
+                anchors.iter().filter(|anchor| anchor.target_position == pattern_index as u32 * 10).for_each(|anchor| {
+                    anchored[pattern_index..pattern_index + anchor.pattern_count as usize].iter_mut().for_each(|v| *v = true);
+                });
+            });
+            anchored
+        };
+        assert!(anchored_patterns(None).iter().all(|v| *v));
+        let minimizer_seeder = MinimizerSeeder::new(3);
+        let selected_patterns = minimizer_seeder.selected_patterns(&query, 10);
+        assert!(selected_patterns.iter().any(|v| !v));
+        assert_eq!(anchored_patterns(Some(minimizer_seeder)), selected_patterns);
     }
 }
@@ -0,0 +1,65 @@
+use crate::{
+    core::{
+        BufferedPatternLocator,
+        regulators::{Penalty, Cutoff, SeedingOptions},
+    },
+    aligner::WorkEstimate,
+};
+use super::{AnchorTable, SparePenaltyCalculator, WaveFront};
+
+// Project the work of the alignment from the anchors, without extending them
+//  - Each anchor is assumed to be extended to both sides until its spare penalty is used up,
+//    so the wave front cells are the upper bound.
+#[allow(clippy::too_many_arguments)]
+pub fn estimate_work<L: BufferedPatternLocator>(
+    pattern_locater: &L,
+    query: &[u8],
+    sorted_target_indices: &[u32],
+    pattern_size: u32,
+    seeding_options: &SeedingOptions,
+    penalties: &Penalty,
+    cutoff: &Cutoff,
+    spare_penalty_calculator: &mut SparePenaltyCalculator,
+    ungapped: bool,
+) -> WorkEstimate {
+    // The anchors are chained by the estimated middles, since the targets are not buffered
+    let seeding_options = SeedingOptions {
+        exact_chain_middle_size: 0,
+        ..seeding_options.clone()
+    };
+    let anchor_table_map = AnchorTable::new_by_target_index(pattern_locater, query, sorted_target_indices, pattern_size, &seeding_options);
+    let pattern_count = query.len() as u32 / pattern_size;
+    spare_penalty_calculator.change_last_pattern_index(pattern_count - 1);
+
+    let mut work_estimate = WorkEstimate {
+        target_count: anchor_table_map.len() as u32,
+        ..Default::default()
+    };
+    anchor_table_map.iter().for_each(|(_, anchor_table)| {
+        anchor_table.0.iter().enumerate().for_each(|(pattern_index, anchors)| {
+            anchors.iter().filter(|anchor| !anchor.to_skip).for_each(|_| {
+                work_estimate.anchor_count += 1;
+                if ungapped {
+                    return;
+                }
+                let pattern_index = pattern_index as u32;
+                let right_spare_penalty = cutoff.cap_spare_penalty(
+                    spare_penalty_calculator.get_right_spare_penalty(pattern_index),
+                    0,
+                );
+                // As if the anchor and the right side are extended to the end of the query without any penalty
+                let right_length = query.len() as u32 - pattern_index * pattern_size;
+                let left_spare_penalty = cutoff.cap_spare_penalty(
+                    spare_penalty_calculator.get_left_spare_penalty(
+                        (right_length * cutoff.maximum_scaled_penalty_per_length) as i32,
+                        pattern_index,
+                    ),
+                    0,
+                );
+                work_estimate.estimated_wave_front_cells += WaveFront::component_count(penalties, right_spare_penalty as usize)
+                    + WaveFront::component_count(penalties, left_spare_penalty as usize);
+            });
+        });
+    });
+    work_estimate
+}
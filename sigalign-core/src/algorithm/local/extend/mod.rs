@@ -54,7 +54,10 @@ pub fn extend_anchor(
     let right_target_slice = &target[right_target_start_index as usize..];
     let right_query_slice = &query[right_query_start_index as usize..];
     // 2.2. Calculate the left spare penalty
-    let right_spare_penalty = spare_penalty_calculator.get_right_spare_penalty(anchor_index.0);
+    let right_spare_penalty = cutoff.cap_spare_penalty(
+        spare_penalty_calculator.get_right_spare_penalty(anchor_index.0),
+        0,
+    );
     // 2.3. Extend the side with wave front
     right_wave_front.align_right_to_end_point(
         right_target_slice,
@@ -77,9 +80,13 @@ pub fn extend_anchor(
     let max_scaled_penalty_delta_of_right = right_vpc_buffer[0].scaled_penalty_delta
         + (anchor_size * cutoff.maximum_scaled_penalty_per_length) as i32
     ;
-    let left_spare_penalty = spare_penalty_calculator.get_left_spare_penalty(
-        max_scaled_penalty_delta_of_right,
-        anchor_index.0,
+    //   - The penalty of the right side is not fixed yet, so the maximum penalty is checked again after 4.1.
+    let left_spare_penalty = cutoff.cap_spare_penalty(
+        spare_penalty_calculator.get_left_spare_penalty(
+            max_scaled_penalty_delta_of_right,
+            anchor_index.0,
+        ),
+        0,
     );
     // 3.3. Extend the side with wave front
     left_wave_front.align_left_to_end_point(
@@ -122,18 +129,32 @@ pub fn extend_anchor(
             a1 + a2 + anchor_size,
         )
     };
-    if alignment_length < cutoff.minimum_length {
+    if !cutoff.is_long_enough(
+        alignment_length,
+        alignment_position.query.1 - alignment_position.query.0,
+        alignment_position.target.1 - alignment_position.target.0,
+    ) || !cutoff.is_under_maximum_penalty(left_optimal_vpc.penalty + right_optimal_vpc.penalty) {
+        log::trace!(
+            "anchor dropped: anchor_index={:?}, target_position={}, penalty={}, length={}, reason=cutoff",
+            anchor_index, anchor.target_position, left_optimal_vpc.penalty + right_optimal_vpc.penalty, alignment_length,
+        );
         return None
     }
     // 4.3. Backtrace from left
     //   - None if this anchor is not leftmost (= having traversed anchor on the left)
-    let left_operation_range_in_buffer = left_wave_front.backtrace_of_left_side_while_checking_this_anchor_is_leftmost(
+    let Some(left_operation_range_in_buffer) = left_wave_front.backtrace_of_left_side_while_checking_this_anchor_is_leftmost(
         left_optimal_vpc.penalty,
         *pattern_size,
         left_optimal_vpc.component_index,
         penalties,
         operations_buffer,
-    )?;
+    ) else {
+        log::trace!(
+            "anchor dropped: anchor_index={:?}, target_position={}, reason=not_leftmost",
+            anchor_index, anchor.target_position,
+        );
+        return None
+    };
     // 4.4. Backtrace from right
     let right_operation_range_in_buffer = right_wave_front.backtrace_of_right_side_with_checking_traversed(
         right_optimal_vpc.penalty,
@@ -154,6 +175,11 @@ pub fn extend_anchor(
     );
 
     // 5. Push extension
+    log::trace!(
+        "anchor extended: anchor_index={:?}, target_position={}, penalty={}, length={}, traversed_anchor_count={}",
+        anchor_index, anchor.target_position, left_optimal_vpc.penalty + right_optimal_vpc.penalty, alignment_length,
+        traversed_anchors_buffer.len(),
+    );
     let extension = Extension {
         alignment_position,
         penalty: left_optimal_vpc.penalty + right_optimal_vpc.penalty,
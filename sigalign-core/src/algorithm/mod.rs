@@ -1,15 +1,14 @@
 // Common data structures and functions
 mod anchor;
-use anchor::{
-    Anchor,
-    AnchorTable,
-};
+use anchor::Anchor;
+pub use anchor::AnchorTable;
 pub use anchor::AnchorIndex;
 
-mod wave_front;
+pub(crate) mod wave_front;
 use wave_front::{
     WaveFrontScore,
     BackTraceMarker,
+    ComponentType,
 };
 pub use wave_front::{WaveFront, TraversedAnchor};
 
@@ -22,16 +21,37 @@ use traversed::transform_right_additive_positions_to_traversed_anchor_index;
 mod extension;
 pub use extension::Extension;
 
+mod recorder;
+pub use recorder::ExtensionRecorder;
+
+mod estimate;
+pub use estimate::estimate_work;
+
 // Alignment algorithms
 mod local;
 pub use local::{
     local_alignment_algorithm,
     local_alignment_algorithm_with_limit,
+    local_alignment_query_to_target,
     Vpc,
 };
+#[cfg(feature = "debug")]
+pub use local::local_alignment_diagnostics;
 
 mod semi_global;
 pub use semi_global::{
     semi_global_alignment_algorithm,
     semi_global_alignment_algorithm_with_limit,
+    semi_global_alignment_query_to_target,
+};
+#[cfg(feature = "debug")]
+pub use semi_global::semi_global_alignment_diagnostics;
+
+mod ungapped;
+pub use ungapped::{
+    local_ungapped_alignment_algorithm,
+    semi_global_ungapped_alignment_algorithm,
+    ungapped_alignment_query_to_target,
 };
+#[cfg(feature = "debug")]
+pub use ungapped::ungapped_alignment_diagnostics;
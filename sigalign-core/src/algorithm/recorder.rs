@@ -0,0 +1,15 @@
+use super::AnchorIndex;
+
+// Observer of the wave front extensions of the anchors (e.g., for profiling)
+//  - The no-op implementation of `()` is inlined away,
+//    so the algorithms without the recorder are not changed.
+pub trait ExtensionRecorder {
+    // Only the profiling with the `debug` feature records the targets
+    #[cfg_attr(not(feature = "debug"), allow(dead_code))]
+    fn start_target(&mut self, _target_index: u32) {}
+    fn start_extension(&mut self) {}
+    // `penalty`: the penalty of the alignment, or the penalty of the wave front if dropped
+    fn end_extension(&mut self, _anchor_index: AnchorIndex, _penalty: u32, _is_aligned: bool) {}
+}
+
+impl ExtensionRecorder for () {}
@@ -1,15 +1,9 @@
 use crate::core::regulators::{Penalty, PREC_SCALE};
 use super::{
-    WaveFront, BackTraceMarker, TraversedAnchor,
+    WaveFront, BackTraceMarker, ComponentType, TraversedAnchor,
 };
 use num::integer::div_rem;
 
-enum ComponentType {
-    M,
-    D,
-    I,
-}
-
 impl WaveFront {
     // Codes for "operation" is removed from original "backtrace_of_right_side" method
     #[inline]
@@ -43,9 +37,9 @@ impl WaveFront {
                 /* M */
                 ComponentType::M => {
                     match component.bt {
-                        BackTraceMarker::FromM => {
+                        BackTraceMarker::FromM | BackTraceMarker::FromMWithN => {
                             // (1) Next penalty
-                            penalty -= penalties.x;
+                            penalty -= component.bt.substitution_penalty(penalties);
                             // (2) Next k
                             // not change
                             // (3) Next WFS
@@ -100,7 +94,7 @@ impl WaveFront {
                             // (9) Next fr to fr
                             fr = next_fr;
                         },
-                        BackTraceMarker::FromD => {
+                        BackTraceMarker::FromD | BackTraceMarker::FromD2 => {
                             // (1) Next penalty
                             // not change
                             // (2) Next k
@@ -108,9 +102,9 @@ impl WaveFront {
                             // (3) Next WFS
                             // not change
                             // (4) Component type
-                            component_type = ComponentType::D;
+                            component_type = ComponentType::of_gap_marker(component.bt);
                             // (5) Next component
-                            component = wave_front_score.d_component_of_k(k);
+                            component = component_type.component_of_k(wave_front_score, k);
                             // (6) Next fr
                             let next_fr = component.fr;
                             // (7) Check traversed
@@ -155,7 +149,7 @@ impl WaveFront {
                             // (9) Next fr to fr
                             fr = next_fr;
                         },
-                        BackTraceMarker::FromI => {
+                        BackTraceMarker::FromI | BackTraceMarker::FromI2 => {
                             // (1) Next penalty
                             // not change
                             // (2) Next k
@@ -163,9 +157,9 @@ impl WaveFront {
                             // (3) Next WFS
                             // not change
                             // (4) Component type
-                            component_type = ComponentType::I;
+                            component_type = ComponentType::of_gap_marker(component.bt);
                             // (5) Next component
-                            component = wave_front_score.i_component_of_k(k);
+                            component = component_type.component_of_k(wave_front_score, k);
                             // (6) Next fr
                             let next_fr = component.fr;
                             // (7) Check traversed
@@ -227,11 +221,11 @@ impl WaveFront {
                     }
                 },
                 /* I */
-                ComponentType::D => {
+                ComponentType::D | ComponentType::D2 => {
                     match component.bt {
-                        BackTraceMarker::FromM => {
+                        BackTraceMarker::FromM | BackTraceMarker::FromMInHomopolymer => {
                             // (1) Next penalty
-                            penalty -= penalties.o + penalties.e;
+                            penalty -= component_type.gap_opening_penalty(component.bt, penalties);
                             // (2) Next k
                             k -= 1;
                             // (3) Next WFS
@@ -249,7 +243,7 @@ impl WaveFront {
                         },
                         _ => { // FROM_I
                             // (1) Next penalty
-                            penalty -= penalties.e;
+                            penalty -= component_type.gap_extend_penalty(penalties);
                             // (2) Next k
                             k -= 1;
                             // (3) Next WFS
@@ -257,7 +251,7 @@ impl WaveFront {
                             // (4) Component type
                             // not change
                             // (5) Next component
-                            component = wave_front_score.d_component_of_k(k);
+                            component = component_type.component_of_k(wave_front_score, k);
                             // (6) Next fr
                             let next_fr = component.fr;
                             // (7) Add operation
@@ -268,11 +262,11 @@ impl WaveFront {
                     }
                 },
                 /* D */
-                ComponentType::I => {
+                ComponentType::I | ComponentType::I2 => {
                     match component.bt {
-                        BackTraceMarker::FromM => {
+                        BackTraceMarker::FromM | BackTraceMarker::FromMInHomopolymer => {
                             // (1) Next penalty
-                            penalty -= penalties.o + penalties.e;
+                            penalty -= component_type.gap_opening_penalty(component.bt, penalties);
                             // (2) Next k
                             k += 1;
                             // (3) Next WFS
@@ -290,7 +284,7 @@ impl WaveFront {
                         },
                         _ => { // FROM_D
                             // (1) Next penalty
-                            penalty -= penalties.e;
+                            penalty -= component_type.gap_extend_penalty(penalties);
                             // (2) Next k
                             k += 1;
                             // (3) Next WFS
@@ -298,7 +292,7 @@ impl WaveFront {
                             // (4) Component type
                             // not change
                             // (5) Next component
-                            component = wave_front_score.i_component_of_k(k);
+                            component = component_type.component_of_k(wave_front_score, k);
                             // (6) Next fr
                             let next_fr = component.fr;
                             // (7) Add operation
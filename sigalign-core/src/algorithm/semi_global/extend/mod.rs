@@ -8,7 +8,7 @@ use crate::{
 };
 use super::{
     AnchorTable, AnchorIndex,
-    WaveFront, BackTraceMarker, TraversedAnchor,
+    WaveFront, BackTraceMarker, ComponentType, TraversedAnchor,
     Extension,
     SparePenaltyCalculator,
     transform_right_additive_positions_to_traversed_anchor_index,
@@ -54,14 +54,25 @@ pub fn extend_anchor(
     let right_target_slice = &target[right_target_start_index as usize..];
     let right_query_slice = &query[right_query_start_index as usize..];
     // 2.2. Calculate the left spare penalty
-    let right_spare_penalty = spare_penalty_calculator.get_right_spare_penalty(anchor_index.0);
+    let right_spare_penalty = cutoff.cap_spare_penalty(
+        spare_penalty_calculator.get_right_spare_penalty(anchor_index.0),
+        0,
+    );
     // 2.3. Extend the side with wave front
-    wave_front.align_right_to_end_point(
+    //   - Without gap first (hamming prefilter), and fall back to the whole wave front
+    if !wave_front.align_right_to_end_point_without_gap(
         right_target_slice,
         right_query_slice,
         penalties,
         right_spare_penalty,
-    );
+    ) {
+        wave_front.align_right_to_end_point(
+            right_target_slice,
+            right_query_slice,
+            penalties,
+            right_spare_penalty,
+        );
+    }
     // 2.4. Check if invalid
     //   - confirm invalid: early drop here
     let right_end_point = match wave_front.get_optional_end_point() {
@@ -120,18 +131,28 @@ pub fn extend_anchor(
             ((right_alignment_length + anchor_size) * cutoff.maximum_scaled_penalty_per_length) as i32
             - (right_end_point.0 * PREC_SCALE) as i32
         };
-        spare_penalty_calculator.get_left_spare_penalty(
-            max_scaled_penalty_delta_of_right,
-            anchor_index.0,
+        cutoff.cap_spare_penalty(
+            spare_penalty_calculator.get_left_spare_penalty(
+                max_scaled_penalty_delta_of_right,
+                anchor_index.0,
+            ),
+            right_end_point.0,
         )
     };
     // 3.3. Extend the side with wave front
-    wave_front.align_left_to_end_point(
+    if !wave_front.align_left_to_end_point_without_gap(
         left_target_slice,
         left_query_slice,
         penalties,
         left_spare_penalty,
-    );
+    ) {
+        wave_front.align_left_to_end_point(
+            left_target_slice,
+            left_query_slice,
+            penalties,
+            left_spare_penalty,
+        );
+    }
     // 3.4. Check if invalid
     //   - confirm invalid: early drop here
     let left_end_point = match wave_front.get_optional_end_point() {
@@ -148,20 +169,35 @@ pub fn extend_anchor(
     let alignment_length = left_alignment_length + right_alignment_length + anchor_size;
     let penalty = left_end_point.0 + right_end_point.0;
     let is_valid = {
-        (alignment_length >= cutoff.minimum_length)
-        && (cutoff.maximum_scaled_penalty_per_length * alignment_length >= penalty * PREC_SCALE)
+        cutoff.is_long_enough(
+            alignment_length,
+            left_query_length + right_query_length + anchor_size,
+            left_target_length + right_target_length + anchor_size,
+        )
+        && (cutoff.maximum_scaled_penalty_per_length as u64 * alignment_length as u64 >= penalty as u64 * PREC_SCALE as u64)
+        && cutoff.is_under_maximum_penalty(penalty)
     };
     if !is_valid {
+        log::trace!(
+            "anchor dropped: anchor_index={:?}, target_position={}, penalty={}, length={}, reason=cutoff",
+            anchor_index, anchor.target_position, penalty, alignment_length,
+        );
         return None;
     }
     // 3.5. Get the operations range
-    let left_operation_range_in_buffer = wave_front.backtrace_of_left_side_while_checking_this_anchor_is_leftmost(
+    let Some(left_operation_range_in_buffer) = wave_front.backtrace_of_left_side_while_checking_this_anchor_is_leftmost(
         left_end_point.0,
         *pattern_size,
         left_end_point.1,
         penalties,
         operations_buffer,
-    )?;
+    ) else {
+        log::trace!(
+            "anchor dropped: anchor_index={:?}, target_position={}, reason=not_leftmost",
+            anchor_index, anchor.target_position,
+        );
+        return None;
+    };
 
     // 5. Push extension
     let alignment_position = AlignmentPosition {
@@ -174,6 +210,10 @@ pub fn extend_anchor(
             right_target_start_index + right_target_length,
         ),
     };
+    log::trace!(
+        "anchor extended: anchor_index={:?}, target_position={}, penalty={}, length={}",
+        anchor_index, anchor.target_position, penalty, alignment_length,
+    );
     let extension = Extension {
         alignment_position,
         penalty,
@@ -2,7 +2,7 @@ use crate::{
     core::{
         BufferedPatternLocator, SequenceBuffer,
         regulators::{
-            Penalty, Cutoff,
+            Penalty, Cutoff, SeedingOptions,
         }
     },
     results::{
@@ -12,13 +12,15 @@ use crate::{
 };
 use super::{
     AnchorTable, AnchorIndex,
-    WaveFront, BackTraceMarker, TraversedAnchor,
-    Extension, SparePenaltyCalculator,
+    WaveFront, BackTraceMarker, ComponentType, TraversedAnchor,
+    Extension, SparePenaltyCalculator, ExtensionRecorder,
     transform_right_additive_positions_to_traversed_anchor_index,
 };
 
 mod extend;
 use extend::extend_anchor;
+#[cfg(feature = "debug")]
+use crate::debug::AlignmentDiagnostics;
 
 // Find all semi-global alignments
 #[inline]
@@ -28,6 +30,7 @@ pub fn semi_global_alignment_algorithm<L: BufferedPatternLocator>(
     query: &[u8],
     sorted_target_indices: &[u32],
     pattern_size: u32,
+    seeding_options: &SeedingOptions,
     penalties: &Penalty,
     cutoff: &Cutoff,
     spare_penalty_calculator: &mut SparePenaltyCalculator,
@@ -36,10 +39,11 @@ pub fn semi_global_alignment_algorithm<L: BufferedPatternLocator>(
     traversed_anchors_buffer: &mut Vec<TraversedAnchor>,
     operations_buffer: &mut Vec<AlignmentOperations>,
 ) -> QueryAlignment {
-    let mut anchor_table_map = AnchorTable::new_by_target_index(pattern_locater, query, sorted_target_indices, pattern_size);
+    let mut anchor_table_map = AnchorTable::new_by_target_index(pattern_locater, query, sorted_target_indices, pattern_size, seeding_options);
     let target_alignment_results: Vec<TargetAlignment> = anchor_table_map.iter_mut().filter_map(|(target_index, anchor_table)| {
         pattern_locater.fill_buffer(*target_index, sequence_buffer);
         let target = sequence_buffer.buffered_sequence();
+        anchor_table.skip_anchors_in_buffered_target(query, target, pattern_size, seeding_options);
         let anchor_alignment_results = semi_global_alignment_query_to_target(
             anchor_table,
             pattern_size,
@@ -66,7 +70,69 @@ pub fn semi_global_alignment_algorithm<L: BufferedPatternLocator>(
     QueryAlignment(target_alignment_results)
 }
 
-fn semi_global_alignment_query_to_target(
+// Find all semi-global alignments with the counts of anchors
+//  - Anchors are counted from the anchor table after extension.
+//    `to_skip` is only marked on the anchors that are not visited yet.
+#[cfg(feature = "debug")]
+#[allow(clippy::too_many_arguments)]
+pub fn semi_global_alignment_diagnostics<L: BufferedPatternLocator, R: ExtensionRecorder>(
+    pattern_locater: &L,
+    sequence_buffer: &mut L::Buffer,
+    query: &[u8],
+    sorted_target_indices: &[u32],
+    pattern_size: u32,
+    seeding_options: &SeedingOptions,
+    penalties: &Penalty,
+    cutoff: &Cutoff,
+    spare_penalty_calculator: &mut SparePenaltyCalculator,
+    // Buffers
+    wave_front: &mut WaveFront,
+    traversed_anchors_buffer: &mut Vec<TraversedAnchor>,
+    operations_buffer: &mut Vec<AlignmentOperations>,
+    recorder: &mut R,
+) -> (QueryAlignment, AlignmentDiagnostics) {
+    let mut anchor_table_map = AnchorTable::new_by_target_index(pattern_locater, query, sorted_target_indices, pattern_size, seeding_options);
+    let mut diagnostics = AlignmentDiagnostics::default();
+
+    let target_alignment_results: Vec<TargetAlignment> = anchor_table_map.iter_mut().filter_map(|(target_index, anchor_table)| {
+        pattern_locater.fill_buffer(*target_index, sequence_buffer);
+        let target = sequence_buffer.buffered_sequence();
+        anchor_table.skip_anchors_in_buffered_target(query, target, pattern_size, seeding_options);
+        let anchor_count = anchor_table.anchor_count() as u32;
+        recorder.start_target(*target_index);
+        let anchor_alignment_results = semi_global_alignment_query_to_target_with_recorder(
+            anchor_table,
+            pattern_size,
+            target,
+            query,
+            penalties,
+            cutoff,
+            spare_penalty_calculator,
+            wave_front,
+            traversed_anchors_buffer,
+            operations_buffer,
+            recorder,
+        );
+        diagnostics.add_target(
+            anchor_count,
+            anchor_table.skipped_anchor_count() as u32,
+            anchor_alignment_results.len() as u32,
+        );
+
+        if anchor_alignment_results.is_empty() {
+            None
+        } else {
+            Some(TargetAlignment {
+                index: *target_index,
+                alignments: anchor_alignment_results,
+            })
+        }
+    }).collect();
+
+    (QueryAlignment(target_alignment_results), diagnostics)
+}
+
+pub fn semi_global_alignment_query_to_target(
     anchor_table: &mut AnchorTable,
     pattern_size: u32,
     target: &[u8],
@@ -78,6 +144,38 @@ fn semi_global_alignment_query_to_target(
     wave_front: &mut WaveFront,
     traversed_anchors_buffer: &mut Vec<TraversedAnchor>,
     operations_buffer: &mut Vec<AlignmentOperations>,
+) -> Vec<Alignment> {
+    semi_global_alignment_query_to_target_with_recorder(
+        anchor_table,
+        pattern_size,
+        target,
+        query,
+        penalties,
+        cutoff,
+        spare_penalty_calculator,
+        wave_front,
+        traversed_anchors_buffer,
+        operations_buffer,
+        &mut (),
+    )
+}
+
+// The extensions of the anchors are reported to the `recorder`
+#[inline]
+#[allow(clippy::too_many_arguments)]
+pub fn semi_global_alignment_query_to_target_with_recorder<R: ExtensionRecorder>(
+    anchor_table: &mut AnchorTable,
+    pattern_size: u32,
+    target: &[u8],
+    query: &[u8],
+    penalties: &Penalty,
+    cutoff: &Cutoff,
+    // Buffers
+    spare_penalty_calculator: &mut SparePenaltyCalculator,
+    wave_front: &mut WaveFront,
+    traversed_anchors_buffer: &mut Vec<TraversedAnchor>,
+    operations_buffer: &mut Vec<AlignmentOperations>,
+    recorder: &mut R,
 ) -> Vec<Alignment> {
     // Initialize
     //   - (1) Clear the buffers
@@ -97,6 +195,7 @@ fn semi_global_alignment_query_to_target(
             };
             if !skipped {
                 // (1) Extend the anchor if not skipped
+                recorder.start_extension();
                 let optional_extension = extend_anchor(
                     anchor_table,
                     (pattern_index as u32, anchor_index_in_pattern as u32),
@@ -111,6 +210,14 @@ fn semi_global_alignment_query_to_target(
                     traversed_anchors_buffer,
                 );
                 // After extension, "traversed_anchors_buffer" is filled with right traversed anchors
+                recorder.end_extension(
+                    (pattern_index as u32, anchor_index_in_pattern as u32),
+                    match &optional_extension {
+                        Some(extension) => extension.penalty,
+                        None => (wave_front.end_point.penalty) as u32,
+                    },
+                    optional_extension.is_some(),
+                );
 
                 // (2) If extension exists
                 //   - Mark skipped anchors:
@@ -145,6 +252,7 @@ pub fn semi_global_alignment_algorithm_with_limit<L: BufferedPatternLocator>(
     query: &[u8],
     sorted_target_indices: &[u32],
     pattern_size: u32,
+    seeding_options: &SeedingOptions,
     penalties: &Penalty,
     cutoff: &Cutoff,
     spare_penalty_calculator: &mut SparePenaltyCalculator,
@@ -155,12 +263,13 @@ pub fn semi_global_alignment_algorithm_with_limit<L: BufferedPatternLocator>(
     // Limit of the number of alignments
     mut limit: u32,
 ) -> QueryAlignment {
-    let mut anchor_table_map = AnchorTable::new_by_target_index(pattern_locater, query, sorted_target_indices, pattern_size);
+    let mut anchor_table_map = AnchorTable::new_by_target_index(pattern_locater, query, sorted_target_indices, pattern_size, seeding_options);
     let mut target_alignment_results: Vec<TargetAlignment> = Vec::new();
 
     for (target_index, anchor_table) in anchor_table_map.iter_mut() {
         pattern_locater.fill_buffer(*target_index, sequence_buffer);
         let target = sequence_buffer.buffered_sequence();
+        anchor_table.skip_anchors_in_buffered_target(query, target, pattern_size, seeding_options);
         let alignment_results = semi_global_alignment_query_to_target_with_limit(
             anchor_table,
             pattern_size,
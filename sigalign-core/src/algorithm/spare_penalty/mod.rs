@@ -10,13 +10,29 @@ use crate::core::regulators::{
 3. `change_last_pattern_index` must be invoked for every new query to update the `last_pattern_index`.
 
 Failure to follow these usage rules could lead to unexpected behavior.
+
+## Invariant
+The spare penalty is the largest penalty of one side that can still be a part of an alignment satisfying the cutoff,
+so no valid alignment is dropped by the budget of the wave front.
+With `P` the scaled maximum penalty per length, `S` the `PREC_SCALE`, and `ps` the pattern size:
+  - An alignment satisfies the cutoff if `S * penalty <= P * length`.
+    The "penalty delta" of a part is `P * length - S * penalty`, and the deltas of the parts are added.
+  - A side with the penalty `p` is lengthened by the deletions at most `(p - o) / e`, when all of them are in one gap.
+  - Right: the anchor is the leftmost exact pattern of the alignment, so the left side has the delta of at most `(ps - 1) * P`
+    (the part of a pattern before the anchor). With the anchor (at least `ps`) and the query of `ps * x + (ps - 1)` on the right,
+    `S * p <= P * (ps * x + 3 * ps - 2 + (p - o) / e)`, so `p <= P * (e * (ps * x + 3 * ps - 2) - o) / (e * S - P)`.
+  - Left: the right side is already extended, and its largest delta `y` (with the anchor) is given.
+    With the query of `ps * z` on the left, `S * p <= y + P * (ps * z + (p - o) / e)`, so `p <= (e * y + P * e * ps * z - P * o) / (e * S - P)`.
+  - Not smaller than the gap-open penalty, so a side can always have a gap.
 */
 #[derive(Debug, Clone)]
 pub struct SparePenaltyCalculator {
     precalculated_right_spare_penalty: Vec<u32>,
     last_pattern_index: u32,
-    coefficient_for_right: (u32, u32, u32),
-    coefficient_for_left: (u32, u32, u32, u32),
+    // Coefficients are multiplied by the pattern index and the query length,
+    // so they are kept in i64 not to overflow for the long queries.
+    coefficient_for_right: (i64, i64, i64),
+    coefficient_for_left: (i64, i64, i64, i64),
     min_penalty: u32,
 }
 
@@ -49,14 +65,14 @@ impl SparePenaltyCalculator {
         pattern_index: u32,
     ) -> u32 {
         let ce = &self.coefficient_for_left;
-        i32::max(
+        saturating_u32(i64::max(
             (
-                ce.0 as i32 * right_penalty_delta
-                + ce.1 as i32 * pattern_index as i32
-                - ce.2 as i32
-            ) / ce.3 as i32,
-            self.min_penalty as i32
-        ) as u32
+                ce.0 * right_penalty_delta as i64
+                + ce.1 * pattern_index as i64
+                - ce.2
+            ) / ce.3,
+            self.min_penalty as i64
+        ))
     }
     #[inline]
     fn new_only_with_coefficient(
@@ -64,24 +80,26 @@ impl SparePenaltyCalculator {
         maximum_scaled_penalty_per_length: u32,
         pattern_size: u32,
     ) -> Self {
-        // (1) For right spare penalty
+        // (1) For right spare penalty (see the invariant)
         //   - f(x) = (a * x + b) / c
         //   - x: reversed pattern index (= last pattern index - pattern index)
         //   - all coefficient is scaled
-        let a = maximum_scaled_penalty_per_length * penalties.e * pattern_size;
-        let b = maximum_scaled_penalty_per_length * (
-            penalties.e * (3 * pattern_size - 2) - penalties.o
+        let (scaled_ppl, e, o) = (maximum_scaled_penalty_per_length as i64, penalties.e as i64, penalties.o as i64);
+        let pattern_size = pattern_size as i64;
+        let a = scaled_ppl * e * pattern_size;
+        let b = scaled_ppl * (
+            e * (3 * pattern_size - 2) - o
         );
-        let c = penalties.e * PREC_SCALE - maximum_scaled_penalty_per_length;
+        let c = e * PREC_SCALE as i64 - scaled_ppl;
 
-        // (2) For left spare penalty
+        // (2) For left spare penalty (see the invariant)
         //   - g(y,z) = (d * y + e * z - f) / g
         //   - y: right penalty delta
         //   - z: pattern index
         //   - all coefficient is scaled
-        let d = penalties.e;
-        let e = maximum_scaled_penalty_per_length * penalties.e * pattern_size;
-        let f = maximum_scaled_penalty_per_length * penalties.o;
+        let d = e;
+        let e = scaled_ppl * e * pattern_size;
+        let f = scaled_ppl * o;
         // g is same as c
         let g = c;
 
@@ -101,10 +119,10 @@ impl SparePenaltyCalculator {
         let calculated_pattern_count = self.precalculated_right_spare_penalty.len() as u32;
         let ce = &self.coefficient_for_right;
         for reversed_pattern_index in calculated_pattern_count..max_pattern_count {
-            let v = u32::max(
-                (ce.0 * reversed_pattern_index + ce.1) / ce.2,
-                self.min_penalty,
-            );
+            let v = saturating_u32(i64::max(
+                (ce.0 * reversed_pattern_index as i64 + ce.1) / ce.2,
+                self.min_penalty as i64,
+            ));
             self.precalculated_right_spare_penalty.push(v);
         }
     }
@@ -116,3 +134,168 @@ impl SparePenaltyCalculator {
         self.last_pattern_index = last_pattern_index;
     }
 }
+
+// The spare penalty over `u32` is not reachable in any case
+#[inline(always)]
+fn saturating_u32(value: i64) -> u32 {
+    value.clamp(0, u32::MAX as i64) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aligner::{AlignmentRegulator, semi_global::SemiGlobalAligner};
+    use crate::results::Alignment;
+    use crate::test_utils::{SEED, random_dna, NaiveLocator, NaiveBuffer};
+
+    #[test]
+    fn spare_penalty_is_not_overflowed_for_the_long_query() {
+        let penalties = Penalty { x: 2, o: 3, e: 1, n: 2, h: 0, o2: 3, e2: 1 };
+        let (maximum_scaled_penalty_per_length, pattern_size) = (5_000_i128, 20_i128);
+        // Pattern index multiplied by the coefficient is over `i32::MAX` from about 21,000 patterns
+        let last_pattern_index = 30_000;
+        let mut calculator = SparePenaltyCalculator::new(
+            &penalties, maximum_scaled_penalty_per_length as u32, pattern_size as u32, last_pattern_index + 1,
+        );
+        calculator.change_last_pattern_index(last_pattern_index);
+
+        let (e, o) = (penalties.e as i128, penalties.o as i128);
+        let c = e * PREC_SCALE as i128 - maximum_scaled_penalty_per_length;
+        let right = |reversed_pattern_index: i128| {
+            (maximum_scaled_penalty_per_length * e * pattern_size * reversed_pattern_index
+            + maximum_scaled_penalty_per_length * (e * (3 * pattern_size - 2) - o)) / c
+        };
+        assert_eq!(calculator.get_right_spare_penalty(0) as i128, right(last_pattern_index as i128));
+        let left = |right_penalty_delta: i128, pattern_index: i128| {
+            (e * right_penalty_delta + maximum_scaled_penalty_per_length * e * pattern_size * pattern_index
+            - maximum_scaled_penalty_per_length * o) / c
+        };
+        assert_eq!(calculator.get_left_spare_penalty(100, last_pattern_index) as i128, left(100, last_pattern_index as i128));
+        assert_eq!(calculator.get_left_spare_penalty(-100, 0), penalties.o);
+    }
+    #[test]
+    fn spare_penalty_covers_every_valid_side() {
+        let scale = PREC_SCALE as i64;
+        for (x, o, e) in [(4, 6, 2), (1, 0, 1), (5, 1, 1), (2, 9, 3), (6, 3, 1)] {
+            let penalties = Penalty { x, o, e, n: x, h: 0, o2: o, e2: e };
+            for maximum_penalty_per_length in [0.02, 0.1, 0.25, 0.9] {
+                let scaled_ppl = (maximum_penalty_per_length * scale as f64) as i64;
+                if scaled_ppl >= e as i64 * scale {
+                    continue;
+                }
+                for pattern_size in [4, 10, 25] {
+                    let last_pattern_index = 30;
+                    let mut calculator = SparePenaltyCalculator::new(
+                        &penalties, scaled_ppl as u32, pattern_size, last_pattern_index + 1,
+                    );
+                    calculator.change_last_pattern_index(last_pattern_index);
+                    // (penalty, lengthened by the deletions) of a side: a gap of `d` with `m` mismatches
+                    let sides: Vec<(i64, i64)> = (0..60).flat_map(|m| {
+                        (0..200).map(move |d| {
+                            let gap_penalty = if d == 0 { 0 } else { o + d * e };
+                            ((m * x + gap_penalty) as i64, d as i64)
+                        })
+                    }).collect();
+                    for pattern_index in 0..=last_pattern_index {
+                        // Right of the anchor at the pattern index, with the largest delta of the left
+                        let right_query_length = (pattern_size * (last_pattern_index - pattern_index) + pattern_size - 1) as i64;
+                        let largest_left_delta = (pattern_size as i64 - 1) * scaled_ppl;
+                        let right_spare_penalty = calculator.get_right_spare_penalty(pattern_index) as i64;
+                        sides.iter().filter(|(penalty, deletions)| {
+                            scale * penalty <= largest_left_delta + scaled_ppl * (pattern_size as i64 + right_query_length + deletions)
+                        }).for_each(|(penalty, _)| {
+                            assert!(*penalty <= right_spare_penalty, "right: {:?}", (x, o, e, scaled_ppl, pattern_size, pattern_index, penalty));
+                        });
+                        // Left of the anchor, with the delta of the right
+                        let left_query_length = (pattern_size * pattern_index) as i64;
+                        for right_delta in [-5 * scale, 0, scaled_ppl * 7, scaled_ppl * 100] {
+                            let left_spare_penalty = calculator.get_left_spare_penalty(right_delta as i32, pattern_index) as i64;
+                            sides.iter().filter(|(penalty, deletions)| {
+                                scale * penalty <= right_delta + scaled_ppl * (left_query_length + deletions)
+                            }).for_each(|(penalty, _)| {
+                                assert!(*penalty <= left_spare_penalty, "left: {:?}", (x, o, e, scaled_ppl, pattern_size, pattern_index, right_delta, penalty));
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+    // Calculator of the aligner, with the query of `last_pattern_index + 1` patterns
+    fn calculator_of_regulator(regulator: &AlignmentRegulator, last_pattern_index: u32) -> SparePenaltyCalculator {
+        let (x, o, e) = regulator.get_compressed_penalties();
+        let mut calculator = SparePenaltyCalculator::new(
+            &Penalty { x, o, e, n: x, h: 0, o2: o, e2: e },
+            regulator.get_compressed_scaled_maximum_penalty_per_length(),
+            regulator.get_pattern_size(),
+            last_pattern_index + 1,
+        );
+        calculator.change_last_pattern_index(last_pattern_index);
+        calculator
+    }
+    fn semi_global_alignments(regulator: AlignmentRegulator, query: &[u8], target: Vec<u8>) -> Vec<Alignment> {
+        let mut aligner = SemiGlobalAligner::new(regulator);
+        let locator = NaiveLocator::new(vec![target]);
+        let result = aligner.align(query, &locator, &mut NaiveBuffer(Vec::new()), &[0]);
+        result.0.into_iter().flat_map(|target_alignment| target_alignment.alignments).collect()
+    }
+    #[test]
+    fn alignment_with_the_longest_end_point_is_reported() {
+        // The left side has two end points of the smallest penalty (10) with the length of 14 and 17.
+        // With the shorter one, the alignment (32 / 158) was dropped by the cutoff.
+        let target = b"GGGTAGCATTTTACCTGTAGGGTCCGATCAGATCTATCTTTAAACCGACTGGTTAGGTGGGAAACTGAGTCTCAGGGTCTGATATGCGGTTAAGGGAACCAGCTCTTTGTATGAATAGATATCACGGTAATCCTTTTTTTGTCGAGTTGTTTATCTTTCCAATACGAGCGACTAGCACCAACGTTGTACG";
+        let query = b"AGGGGACGCTCAGATCTATCTTTAATCAACCGACTGGTTAGGTGGGAAACTGAGTCTCGGGGTCTGATATGCGGTTAAGGGAACCAGCTCTTTGTAGCCCCTGAATAGATATCACGGTAATCCTTTTTTTGTCGAGTTGATCTTTCCAATACGAG";
+        let regulator = AlignmentRegulator::new(5, 2, 1, 50, 0.2).unwrap();
+        let alignments = semi_global_alignments(regulator, query, target.to_vec());
+        assert!(alignments.iter().any(|alignment| {
+            alignment.penalty == 32 && alignment.length == 161
+        }), "{:?}", alignments);
+    }
+    #[test]
+    fn side_at_the_spare_penalty_is_reported() {
+        let regulator = AlignmentRegulator::new(4, 3, 1, 50, 0.1).unwrap();
+        let (_, o, _) = regulator.get_compressed_penalties();
+        let pattern_size = regulator.get_pattern_size() as usize;
+        let target = random_dna(SEED, pattern_size * 30);
+
+        // Right: the anchor at the pattern index 1 with the largest delta of the left
+        //  - The left is the `pattern_size - 1` matches to the start of the target.
+        //  - The right is the `pattern_size * 10 + pattern_size - 1` of the query with one deletion gap.
+        let right_spare_penalty = calculator_of_regulator(&regulator, 11).get_right_spare_penalty(1);
+        let deletion = (right_spare_penalty - o) as usize;
+        let (anchor_end, gap_start) = (2 * pattern_size - 1, 3 * pattern_size);
+        let target_end = anchor_end + pattern_size * 11 - 1 + deletion;
+        let query = [
+            b"N",
+            &target[..gap_start],
+            &target[gap_start + deletion..target_end],
+        ].concat();
+        let alignments = semi_global_alignments(regulator.clone(), &query, target.clone());
+        assert!(alignments.iter().any(|alignment| {
+            alignment.penalty == right_spare_penalty
+            && alignment.position.query == (1, query.len() as u32)
+            && alignment.position.target == (0, target_end as u32)
+        }), "right: {:?}", alignments);
+
+        // Left: the anchor at the pattern index 1 with the matches to the end of the query
+        //  - The left is the first pattern with one deletion gap.
+        let query_length = pattern_size * 8;
+        let right_penalty_delta = (query_length - pattern_size) as u32 * regulator.get_compressed_scaled_maximum_penalty_per_length();
+        let left_spare_penalty = calculator_of_regulator(&regulator, 7).get_left_spare_penalty(right_penalty_delta as i32, 1);
+        let deletion = (left_spare_penalty - o) as usize;
+        // The gap is the cheapest to the start of the query
+        let gap_start = pattern_size - 1;
+        assert!(0 < deletion && deletion + 1 < gap_start);
+        let target_end = query_length + deletion;
+        let query = [
+            &target[..gap_start],
+            &target[gap_start + deletion..target_end],
+        ].concat();
+        let alignments = semi_global_alignments(regulator, &query, target);
+        assert!(alignments.iter().any(|alignment| {
+            alignment.penalty == left_spare_penalty
+            && alignment.position.query == (0, query.len() as u32)
+            && alignment.position.target == (0, target_end as u32)
+        }), "left: {:?}", alignments);
+    }
+}
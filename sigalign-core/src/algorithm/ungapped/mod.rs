@@ -0,0 +1,382 @@
+use crate::{
+    core::{
+        BufferedPatternLocator, SequenceBuffer,
+        regulators::{
+            Penalty, Cutoff, PREC_SCALE, SeedingOptions,
+        },
+    },
+    results::{
+        QueryAlignment, TargetAlignment, Alignment,
+        AlignmentOperation, AlignmentOperations, AlignmentPosition,
+    },
+};
+use super::AnchorTable;
+#[cfg(feature = "debug")]
+use crate::debug::AlignmentDiagnostics;
+
+/*
+Ungapped alignment
+  - Anchors are extended along their diagonal allowing only the mismatches.
+  - Semi-global: extended to the end of the query or the target in both sides.
+  - Local: the longest range containing the anchor that satisfies the cutoff.
+  - Anchors inside of the found alignment are skipped.
+*/
+
+// Find all ungapped alignments (both sides of each alignment reach the end of the query or the target)
+#[inline]
+#[allow(clippy::too_many_arguments)]
+pub fn semi_global_ungapped_alignment_algorithm<L: BufferedPatternLocator>(
+    pattern_locater: &L,
+    sequence_buffer: &mut L::Buffer,
+    query: &[u8],
+    sorted_target_indices: &[u32],
+    pattern_size: u32,
+    seeding_options: &SeedingOptions,
+    penalties: &Penalty,
+    cutoff: &Cutoff,
+    limit: Option<u32>,
+) -> QueryAlignment {
+    ungapped_alignment_algorithm(
+        pattern_locater, sequence_buffer, query, sorted_target_indices,
+        pattern_size, seeding_options, penalties, cutoff,
+        false, limit,
+    )
+}
+
+// Find all ungapped alignments (the longest range satisfying the cutoff for each anchor)
+#[inline]
+#[allow(clippy::too_many_arguments)]
+pub fn local_ungapped_alignment_algorithm<L: BufferedPatternLocator>(
+    pattern_locater: &L,
+    sequence_buffer: &mut L::Buffer,
+    query: &[u8],
+    sorted_target_indices: &[u32],
+    pattern_size: u32,
+    seeding_options: &SeedingOptions,
+    penalties: &Penalty,
+    cutoff: &Cutoff,
+    limit: Option<u32>,
+) -> QueryAlignment {
+    ungapped_alignment_algorithm(
+        pattern_locater, sequence_buffer, query, sorted_target_indices,
+        pattern_size, seeding_options, penalties, cutoff,
+        true, limit,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn ungapped_alignment_algorithm<L: BufferedPatternLocator>(
+    pattern_locater: &L,
+    sequence_buffer: &mut L::Buffer,
+    query: &[u8],
+    sorted_target_indices: &[u32],
+    pattern_size: u32,
+    seeding_options: &SeedingOptions,
+    penalties: &Penalty,
+    cutoff: &Cutoff,
+    is_local: bool,
+    limit: Option<u32>,
+) -> QueryAlignment {
+    let mut anchor_table_map = AnchorTable::new_by_target_index(pattern_locater, query, sorted_target_indices, pattern_size, seeding_options);
+    let mut limit = limit.unwrap_or(u32::MAX);
+    let mut target_alignment_results: Vec<TargetAlignment> = Vec::new();
+
+    for (target_index, anchor_table) in anchor_table_map.iter_mut() {
+        pattern_locater.fill_buffer(*target_index, sequence_buffer);
+        let target = sequence_buffer.buffered_sequence();
+        anchor_table.skip_anchors_in_buffered_target(query, target, pattern_size, seeding_options);
+        let alignment_results = ungapped_alignment_query_to_target(
+            anchor_table,
+            pattern_size,
+            target,
+            query,
+            penalties,
+            cutoff,
+            is_local,
+            &mut limit,
+        );
+        if !alignment_results.is_empty() {
+            target_alignment_results.push(TargetAlignment {
+                index: *target_index,
+                alignments: alignment_results,
+            });
+        }
+        if limit == 0 {
+            break;
+        }
+    }
+
+    QueryAlignment(target_alignment_results)
+}
+
+// Find all ungapped alignments with the counts of anchors
+#[cfg(feature = "debug")]
+#[allow(clippy::too_many_arguments)]
+pub fn ungapped_alignment_diagnostics<L: BufferedPatternLocator>(
+    pattern_locater: &L,
+    sequence_buffer: &mut L::Buffer,
+    query: &[u8],
+    sorted_target_indices: &[u32],
+    pattern_size: u32,
+    seeding_options: &SeedingOptions,
+    penalties: &Penalty,
+    cutoff: &Cutoff,
+    is_local: bool,
+) -> (QueryAlignment, AlignmentDiagnostics) {
+    let mut anchor_table_map = AnchorTable::new_by_target_index(pattern_locater, query, sorted_target_indices, pattern_size, seeding_options);
+    let mut diagnostics = AlignmentDiagnostics::default();
+    let mut limit = u32::MAX;
+
+    let target_alignment_results: Vec<TargetAlignment> = anchor_table_map.iter_mut().filter_map(|(target_index, anchor_table)| {
+        pattern_locater.fill_buffer(*target_index, sequence_buffer);
+        let target = sequence_buffer.buffered_sequence();
+        anchor_table.skip_anchors_in_buffered_target(query, target, pattern_size, seeding_options);
+        let anchor_count = anchor_table.anchor_count() as u32;
+        let alignment_results = ungapped_alignment_query_to_target(
+            anchor_table,
+            pattern_size,
+            target,
+            query,
+            penalties,
+            cutoff,
+            is_local,
+            &mut limit,
+        );
+        diagnostics.add_target(
+            anchor_count,
+            anchor_table.skipped_anchor_count() as u32,
+            alignment_results.len() as u32,
+        );
+
+        if alignment_results.is_empty() {
+            None
+        } else {
+            Some(TargetAlignment {
+                index: *target_index,
+                alignments: alignment_results,
+            })
+        }
+    }).collect();
+
+    (QueryAlignment(target_alignment_results), diagnostics)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn ungapped_alignment_query_to_target(
+    anchor_table: &mut AnchorTable,
+    pattern_size: u32,
+    target: &[u8],
+    query: &[u8],
+    penalties: &Penalty,
+    cutoff: &Cutoff,
+    is_local: bool,
+    limit: &mut u32,
+) -> Vec<Alignment> {
+    let mut alignment_results: Vec<Alignment> = Vec::new();
+
+    for pattern_index in 0..anchor_table.0.len() {
+        for anchor_index_in_pattern in 0..anchor_table.0[pattern_index].len() {
+            if *limit == 0 {
+                return alignment_results;
+            }
+            let anchor = &anchor_table.0[pattern_index][anchor_index_in_pattern];
+            if anchor.to_skip {
+                continue;
+            }
+            let query_start = pattern_index as u32 * pattern_size;
+            let anchor_size = anchor.pattern_count * pattern_size;
+            let diagonal = DiagonalRange::new(
+                query,
+                target,
+                query_start,
+                anchor.target_position,
+                anchor_size,
+            );
+            let optional_range = if is_local {
+                diagonal.longest_valid_range(query, target, penalties, cutoff)
+            } else {
+                Some((0, diagonal.length))
+            };
+            let Some((start, end)) = optional_range else {
+                continue;
+            };
+
+            let alignment = diagonal.to_alignment(query, target, start, end, penalties);
+            let is_valid = is_valid_alignment(&alignment, cutoff);
+            log::trace!(
+                "ungapped alignment: pattern_index={}, target_position={}, penalty={}, length={}, is_valid={}",
+                pattern_index, anchor.target_position, alignment.penalty, alignment.length, is_valid,
+            );
+            // Anchors inside of the alignment are not extended again
+            //  - Semi-global: all anchors in the diagonal give the same alignment.
+            if is_valid || !is_local {
+                mark_anchors_inside_to_skip(anchor_table, pattern_size, &alignment.position);
+            }
+            if is_valid {
+                alignment_results.push(alignment);
+                *limit -= 1;
+            }
+        }
+    }
+    alignment_results
+}
+
+// The diagonal that the anchor is on, clipped to the query and the target
+struct DiagonalRange {
+    query_start: u32,
+    target_start: u32,
+    length: u32,
+    // Range of the anchor in the diagonal
+    anchor_start: u32,
+    anchor_end: u32,
+}
+
+impl DiagonalRange {
+    fn new(
+        query: &[u8],
+        target: &[u8],
+        anchor_query_start: u32,
+        anchor_target_start: u32,
+        anchor_size: u32,
+    ) -> Self {
+        let left_length = anchor_query_start.min(anchor_target_start);
+        let right_length = (
+            query.len() as u32 - anchor_query_start
+        ).min(
+            target.len() as u32 - anchor_target_start
+        );
+        Self {
+            query_start: anchor_query_start - left_length,
+            target_start: anchor_target_start - left_length,
+            length: left_length + right_length,
+            anchor_start: left_length,
+            anchor_end: left_length + anchor_size,
+        }
+    }
+    #[inline]
+    fn is_matched(&self, query: &[u8], target: &[u8], index: u32) -> bool {
+        query[(self.query_start + index) as usize] == target[(self.target_start + index) as usize]
+    }
+    #[inline]
+    fn substitution_penalty(&self, query: &[u8], target: &[u8], index: u32, penalties: &Penalty) -> u32 {
+        penalties.of_substitution(
+            query[(self.query_start + index) as usize],
+            target[(self.target_start + index) as usize],
+        )
+    }
+    // The longest (start, end) containing the anchor that satisfies the maximum penalty per length
+    //  - Each base gives the margin of the scaled penalty:
+    //    `maximum_scaled_penalty_per_length` minus `substitution penalty * PREC_SCALE`.
+    //  - The range is valid if the sum of the margins is not negative.
+    fn longest_valid_range(
+        &self,
+        query: &[u8],
+        target: &[u8],
+        penalties: &Penalty,
+        cutoff: &Cutoff,
+    ) -> Option<(u32, u32)> {
+        let match_margin = cutoff.maximum_scaled_penalty_per_length as i64;
+        let anchor_margin = match_margin * (self.anchor_end - self.anchor_start) as i64;
+
+        // Cumulative margins from the anchor: index `i` is the sum of `i` bases
+        let cumulative_margins = |indices: &mut dyn Iterator<Item = u32>| -> Vec<i64> {
+            let mut margins = vec![0];
+            let mut sum = 0;
+            for index in indices {
+                sum += match_margin - (self.substitution_penalty(query, target, index, penalties) * PREC_SCALE) as i64;
+                margins.push(sum);
+            }
+            margins
+        };
+        let left_margins = cumulative_margins(&mut (0..self.anchor_start).rev());
+        let mut right_margins = cumulative_margins(&mut (self.anchor_end..self.length));
+        // Maximum of the margins of the right side longer or equal (non-increasing)
+        for index in (0..right_margins.len() - 1).rev() {
+            right_margins[index] = right_margins[index].max(right_margins[index + 1]);
+        }
+
+        let mut optimal: Option<(u32, u32)> = None;
+        for (left_length, left_margin) in left_margins.iter().enumerate() {
+            let required_margin = -(anchor_margin + left_margin);
+            if right_margins[0] < required_margin {
+                continue;
+            }
+            // The longest right side with the margin of `required_margin`
+            let right_length = right_margins.partition_point(|v| *v >= required_margin) - 1;
+            let length = left_length + right_length;
+            match optimal {
+                Some((left, right)) if (left + right) as usize >= length => {},
+                _ => optimal = Some((left_length as u32, right_length as u32)),
+            }
+        }
+
+        optimal.map(|(left_length, right_length)| {
+            (self.anchor_start - left_length, self.anchor_end + right_length)
+        })
+    }
+    fn to_alignment(
+        &self,
+        query: &[u8],
+        target: &[u8],
+        start: u32,
+        end: u32,
+        penalties: &Penalty,
+    ) -> Alignment {
+        let mut operations: Vec<AlignmentOperations> = Vec::new();
+        let mut penalty = 0;
+        for index in start..end {
+            let operation = if self.is_matched(query, target, index) {
+                AlignmentOperation::Match
+            } else {
+                penalty += self.substitution_penalty(query, target, index, penalties);
+                AlignmentOperation::Subst
+            };
+            match operations.last_mut() {
+                Some(last) if last.operation == operation => {
+                    last.count += 1;
+                },
+                _ => {
+                    operations.push(AlignmentOperations { operation, count: 1 });
+                },
+            }
+        }
+        Alignment {
+            penalty,
+            length: end - start,
+            position: AlignmentPosition {
+                query: (self.query_start + start, self.query_start + end),
+                target: (self.target_start + start, self.target_start + end),
+            },
+            operations,
+        }
+    }
+}
+
+fn mark_anchors_inside_to_skip(
+    anchor_table: &mut AnchorTable,
+    pattern_size: u32,
+    position: &AlignmentPosition,
+) {
+    let diagonal = position.target.0 as i64 - position.query.0 as i64;
+    for pattern_index in (position.query.0 / pattern_size)..(position.query.1 / pattern_size) {
+        let query_start = pattern_index * pattern_size;
+        if query_start < position.query.0 {
+            continue;
+        }
+        let target_position = query_start as i64 + diagonal;
+        let anchors = &mut anchor_table.0[pattern_index as usize];
+        if let Ok(index) = anchors.binary_search_by_key(&target_position, |v| v.target_position as i64) {
+            let anchor = &mut anchors[index];
+            if query_start + anchor.pattern_count * pattern_size <= position.query.1 {
+                anchor.to_skip = true;
+            }
+        }
+    }
+}
+
+fn is_valid_alignment(alignment: &Alignment, cutoff: &Cutoff) -> bool {
+    // Ungapped: the spans of the query and the target are the same as the length
+    cutoff.is_long_enough(alignment.length, alignment.length, alignment.length)
+    && (cutoff.maximum_scaled_penalty_per_length as u64 * alignment.length as u64 >= alignment.penalty as u64 * PREC_SCALE as u64)
+    && cutoff.is_under_maximum_penalty(alignment.penalty)
+}
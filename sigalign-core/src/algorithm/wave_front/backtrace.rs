@@ -5,16 +5,10 @@ use crate::{
     },
 };
 use super::{
-    WaveFront, BackTraceMarker,
+    WaveFront, BackTraceMarker, ComponentType,
 };
 use num::integer::div_rem;
 
-enum ComponentType {
-    M,
-    I,
-    D,
-}
-
 #[derive(Debug, Clone)]
 pub struct TraversedAnchor {
     pub addt_pattern_index: u32,
@@ -71,9 +65,9 @@ impl WaveFront {
                 /* M */
                 ComponentType::M => {
                     match component.bt {
-                        BackTraceMarker::FromM => {
+                        BackTraceMarker::FromM | BackTraceMarker::FromMWithN => {
                             // (1) Next penalty
-                            penalty -= penalties.x;
+                            penalty -= component.bt.substitution_penalty(penalties);
                             // (2) Next k
                             // not change
                             // (3) Next WFS
@@ -126,7 +120,7 @@ impl WaveFront {
                             // (9) Next fr to fr
                             fr = next_fr;
                         },
-                        BackTraceMarker::FromD => {
+                        BackTraceMarker::FromD | BackTraceMarker::FromD2 => {
                             // (1) Next penalty
                             // not change
                             // (2) Next k
@@ -134,9 +128,9 @@ impl WaveFront {
                             // (3) Next WFS
                             // not change
                             // (4) Component type
-                            component_type = ComponentType::D;
+                            component_type = ComponentType::of_gap_marker(component.bt);
                             // (5) Next component
-                            component = wave_front_score.d_component_of_k(k);
+                            component = component_type.component_of_k(wave_front_score, k);
                             // (6) Next fr
                             let next_fr = component.fr;
                             // (7) Check traversed
@@ -160,7 +154,7 @@ impl WaveFront {
                             // (9) Next fr to fr
                             fr = next_fr;
                         },
-                        BackTraceMarker::FromI => {
+                        BackTraceMarker::FromI | BackTraceMarker::FromI2 => {
                             // (1) Next penalty
                             // not change
                             // (2) Next k
@@ -168,9 +162,9 @@ impl WaveFront {
                             // (3) Next WFS
                             // not change
                             // (4) Component type
-                            component_type = ComponentType::I;
+                            component_type = ComponentType::of_gap_marker(component.bt);
                             // (5) Next component
-                            component = wave_front_score.i_component_of_k(k);
+                            component = component_type.component_of_k(wave_front_score, k);
                             // (6) Next fr
                             let next_fr = component.fr;
                             // (7) Check traversed
@@ -211,11 +205,11 @@ impl WaveFront {
                     }
                 },
                 /* I */
-                ComponentType::D => {
+                ComponentType::D | ComponentType::D2 => {
                     match component.bt {
-                        BackTraceMarker::FromM => {
+                        BackTraceMarker::FromM | BackTraceMarker::FromMInHomopolymer => {
                             // (1) Next penalty
-                            penalty -= penalties.o + penalties.e;
+                            penalty -= component_type.gap_opening_penalty(component.bt, penalties);
                             // (2) Next k
                             k -= 1;
                             // (3) Next WFS
@@ -246,7 +240,7 @@ impl WaveFront {
                         },
                         _ => { // FROM_I
                             // (1) Next penalty
-                            penalty -= penalties.e;
+                            penalty -= component_type.gap_extend_penalty(penalties);
                             // (2) Next k
                             k -= 1;
                             // (3) Next WFS
@@ -254,7 +248,7 @@ impl WaveFront {
                             // (4) Component type
                             // not change
                             // (5) Next component
-                            component = wave_front_score.d_component_of_k(k);
+                            component = component_type.component_of_k(wave_front_score, k);
                             // (6) Next fr
                             let next_fr = component.fr;
                             // (7) Add operation
@@ -278,11 +272,11 @@ impl WaveFront {
                     }
                 },
                 /* D */
-                ComponentType::I => {
+                ComponentType::I | ComponentType::I2 => {
                     match component.bt {
-                        BackTraceMarker::FromM => {
+                        BackTraceMarker::FromM | BackTraceMarker::FromMInHomopolymer => {
                             // (1) Next penalty
-                            penalty -= penalties.o + penalties.e;
+                            penalty -= component_type.gap_opening_penalty(component.bt, penalties);
                             // (2) Next k
                             k += 1;
                             // (3) Next WFS
@@ -313,7 +307,7 @@ impl WaveFront {
                         },
                         _ => { // FROM_D
                             // (1) Next penalty
-                            penalty -= penalties.e;
+                            penalty -= component_type.gap_extend_penalty(penalties);
                             // (2) Next k
                             k += 1;
                             // (3) Next WFS
@@ -321,7 +315,7 @@ impl WaveFront {
                             // (4) Component type
                             // not change
                             // (5) Next component
-                            component = wave_front_score.i_component_of_k(k);
+                            component = component_type.component_of_k(wave_front_score, k);
                             // (6) Next fr
                             let next_fr = component.fr;
                             // (7) Add operation
@@ -386,9 +380,9 @@ impl WaveFront {
                 /* M */
                 ComponentType::M => {
                     match component.bt {
-                        BackTraceMarker::FromM => {
+                        BackTraceMarker::FromM | BackTraceMarker::FromMWithN => {
                             // (1) Next penalty
-                            penalty -= penalties.x;
+                            penalty -= component.bt.substitution_penalty(penalties);
                             // (2) Next k
                             // not change
                             // (3) Next WFS
@@ -471,7 +465,7 @@ impl WaveFront {
                             // (9) Next fr to fr
                             fr = next_fr;
                         },
-                        BackTraceMarker::FromD => {
+                        BackTraceMarker::FromD | BackTraceMarker::FromD2 => {
                             // (1) Next penalty
                             // not change
                             // (2) Next k
@@ -479,9 +473,9 @@ impl WaveFront {
                             // (3) Next WFS
                             // not change
                             // (4) Component type
-                            component_type = ComponentType::D;
+                            component_type = ComponentType::of_gap_marker(component.bt);
                             // (5) Next component
-                            component = wave_front_score.d_component_of_k(k);
+                            component = component_type.component_of_k(wave_front_score, k);
                             // (6) Next fr
                             let next_fr = component.fr;
                             // (7) Check traversed
@@ -533,7 +527,7 @@ impl WaveFront {
                             // (9) Next fr to fr
                             fr = next_fr;
                         },
-                        BackTraceMarker::FromI => {
+                        BackTraceMarker::FromI | BackTraceMarker::FromI2 => {
                             // (1) Next penalty
                             // not change
                             // (2) Next k
@@ -541,9 +535,9 @@ impl WaveFront {
                             // (3) Next WFS
                             // not change
                             // (4) Component type
-                            component_type = ComponentType::I;
+                            component_type = ComponentType::of_gap_marker(component.bt);
                             // (5) Next component
-                            component = wave_front_score.i_component_of_k(k);
+                            component = component_type.component_of_k(wave_front_score, k);
                             // (6) Next fr
                             let next_fr = component.fr;
                             // (7) Check traversed
@@ -623,11 +617,11 @@ impl WaveFront {
                     }
                 },
                 /* I */
-                ComponentType::D => {
+                ComponentType::D | ComponentType::D2 => {
                     match component.bt {
-                        BackTraceMarker::FromM => {
+                        BackTraceMarker::FromM | BackTraceMarker::FromMInHomopolymer => {
                             // (1) Next penalty
-                            penalty -= penalties.o + penalties.e;
+                            penalty -= component_type.gap_opening_penalty(component.bt, penalties);
                             // (2) Next k
                             k -= 1;
                             // (3) Next WFS
@@ -658,7 +652,7 @@ impl WaveFront {
                         },
                         _ => { // FROM_I
                             // (1) Next penalty
-                            penalty -= penalties.e;
+                            penalty -= component_type.gap_extend_penalty(penalties);
                             // (2) Next k
                             k -= 1;
                             // (3) Next WFS
@@ -666,7 +660,7 @@ impl WaveFront {
                             // (4) Component type
                             // not change
                             // (5) Next component
-                            component = wave_front_score.d_component_of_k(k);
+                            component = component_type.component_of_k(wave_front_score, k);
                             // (6) Next fr
                             let next_fr = component.fr;
                             // (7) Add operation
@@ -690,11 +684,11 @@ impl WaveFront {
                     }
                 },
                 /* D */
-                ComponentType::I => {
+                ComponentType::I | ComponentType::I2 => {
                     match component.bt {
-                        BackTraceMarker::FromM => {
+                        BackTraceMarker::FromM | BackTraceMarker::FromMInHomopolymer => {
                             // (1) Next penalty
-                            penalty -= penalties.o + penalties.e;
+                            penalty -= component_type.gap_opening_penalty(component.bt, penalties);
                             // (2) Next k
                             k += 1;
                             // (3) Next WFS
@@ -725,7 +719,7 @@ impl WaveFront {
                         },
                         _ => { // FROM_D
                             // (1) Next penalty
-                            penalty -= penalties.e;
+                            penalty -= component_type.gap_extend_penalty(penalties);
                             // (2) Next k
                             k += 1;
                             // (3) Next WFS
@@ -733,7 +727,7 @@ impl WaveFront {
                             // (4) Component type
                             // not change
                             // (5) Next component
-                            component = wave_front_score.i_component_of_k(k);
+                            component = component_type.component_of_k(wave_front_score, k);
                             // (6) Next fr
                             let next_fr = component.fr;
                             // (7) Add operation
@@ -759,4 +753,85 @@ impl WaveFront {
             };
         }
     }
+    /// Backtrace from the end point to the start point.
+    ///  - Returns the penalty and the operations. None if the end point is not reached.
+    pub fn backtrace_from_end_point(
+        &self,
+        penalties: &Penalty,
+    ) -> Option<(u32, Vec<AlignmentOperations>)> {
+        let mut k = self.end_point.k?;
+        let end_penalty = self.end_point.penalty as u32;
+        let mut penalty = end_penalty;
+        let mut reversed_operations: Vec<AlignmentOperations> = Vec::new();
+        let mut component_type = ComponentType::M;
+
+        loop {
+            let wave_front_score = &self.wave_front_scores[penalty as usize];
+            match component_type {
+                ComponentType::M => {
+                    let component = wave_front_score.m_component_of_k(k);
+                    match component.bt {
+                        BackTraceMarker::FromM | BackTraceMarker::FromMWithN => {
+                            penalty -= component.bt.substitution_penalty(penalties);
+                            let next_fr = self.wave_front_scores[penalty as usize].m_component_of_k(k).fr;
+                            push_operation(&mut reversed_operations, AlignmentOperation::Match, (component.fr - next_fr - 1) as u32);
+                            push_operation(&mut reversed_operations, AlignmentOperation::Subst, 1);
+                        },
+                        BackTraceMarker::FromD | BackTraceMarker::FromI | BackTraceMarker::FromD2 | BackTraceMarker::FromI2 => {
+                            component_type = ComponentType::of_gap_marker(component.bt);
+                            let next_fr = component_type.component_of_k(wave_front_score, k).fr;
+                            push_operation(&mut reversed_operations, AlignmentOperation::Match, (component.fr - next_fr) as u32);
+                        },
+                        _ => { // Start point
+                            push_operation(&mut reversed_operations, AlignmentOperation::Match, component.fr as u32);
+                            break;
+                        },
+                    }
+                },
+                ComponentType::D | ComponentType::D2 => {
+                    let component = component_type.component_of_k(wave_front_score, k);
+                    push_operation(&mut reversed_operations, AlignmentOperation::Deletion, 1);
+                    k -= 1;
+                    if matches!(component.bt, BackTraceMarker::FromM | BackTraceMarker::FromMInHomopolymer) {
+                        penalty -= component_type.gap_opening_penalty(component.bt, penalties);
+                        component_type = ComponentType::M;
+                    } else {
+                        penalty -= component_type.gap_extend_penalty(penalties);
+                    }
+                },
+                ComponentType::I | ComponentType::I2 => {
+                    let component = component_type.component_of_k(wave_front_score, k);
+                    push_operation(&mut reversed_operations, AlignmentOperation::Insertion, 1);
+                    k += 1;
+                    if matches!(component.bt, BackTraceMarker::FromM | BackTraceMarker::FromMInHomopolymer) {
+                        penalty -= component_type.gap_opening_penalty(component.bt, penalties);
+                        component_type = ComponentType::M;
+                    } else {
+                        penalty -= component_type.gap_extend_penalty(penalties);
+                    }
+                },
+            }
+        }
+
+        reversed_operations.reverse();
+        Some((end_penalty, reversed_operations))
+    }
+}
+
+fn push_operation(
+    operations: &mut Vec<AlignmentOperations>,
+    operation: AlignmentOperation,
+    count: u32,
+) {
+    if count == 0 {
+        return
+    }
+    match operations.last_mut() {
+        Some(last) if last.operation == operation => {
+            last.count += count;
+        },
+        _ => {
+            operations.push(AlignmentOperations { operation, count });
+        },
+    }
 }
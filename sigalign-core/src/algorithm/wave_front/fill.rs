@@ -1,6 +1,6 @@
 use crate::core::regulators::Penalty;
 use super::{
-    WaveFront, WaveEndPoint, WaveFrontScore, Components, Component, BackTraceMarker,
+    WaveFront, WaveEndPoint, WaveFrontScore, Components, SecondGapComponents, Component, BackTraceMarker,
     MatchCounter, ForwardMatchCounter, ReverseMatchCounter,
 };
 
@@ -25,6 +25,76 @@ impl WaveFront {
     ) {
         self.align_to_end_point::<ReverseMatchCounter>(tgt_seq, qry_seq, penalties, spare_penalty)
     }
+    // Hamming prefilter
+    //  - Count the substitutions along the diagonal (k=0) instead of filling the whole wave front.
+    //  - Only the components of k=0 on the path are filled, and the end point is set.
+    //  - Return false if the end point can not be reached without gap under the spare penalty.
+    //    Then, the wave front should be filled with `align_*_to_end_point`.
+    #[inline]
+    pub fn align_right_to_end_point_without_gap(
+        &mut self,
+        tgt_seq: &[u8],
+        qry_seq: &[u8],
+        penalties: &Penalty,
+        spare_penalty: u32,
+    ) -> bool {
+        self.align_to_end_point_without_gap::<ForwardMatchCounter>(tgt_seq, qry_seq, penalties, spare_penalty)
+    }
+    #[inline]
+    pub fn align_left_to_end_point_without_gap(
+        &mut self,
+        tgt_seq: &[u8],
+        qry_seq: &[u8],
+        penalties: &Penalty,
+        spare_penalty: u32,
+    ) -> bool {
+        self.align_to_end_point_without_gap::<ReverseMatchCounter>(tgt_seq, qry_seq, penalties, spare_penalty)
+    }
+    #[inline]
+    fn align_to_end_point_without_gap<C: MatchCounter>(
+        &mut self,
+        tgt_seq: &[u8],
+        qry_seq: &[u8],
+        penalties: &Penalty,
+        spare_penalty: u32,
+    ) -> bool {
+        let tgt_len = tgt_seq.len();
+        let qry_len = qry_seq.len();
+        // The ungapped path is the only optimal path, if its penalty is less than the penalty of the cheapest gap.
+        //   - Then, the components on the path are the same as those of the whole wave front.
+        //   - The gap of the bounds is not costlier than any gap, and the wave front scores under it have only k=0.
+        let bounds = penalties.for_bounds();
+        let max_penalty = spare_penalty
+            .min((self.wave_front_scores.len() - 1) as u32)
+            .min(bounds.o + bounds.e - 1);
+        let n_penalty = if penalties.n != penalties.x { Some(penalties.n) } else { None };
+
+        let first_match_count = C::count_consecutive_match(qry_seq, tgt_seq, 0, 0);
+        self.wave_front_scores[0].add_first_components(first_match_count);
+
+        let mut penalty = 0;
+        let mut fr = first_match_count as usize;
+        while fr != tgt_len && fr != qry_len {
+            let (substitution_penalty, bt) = match n_penalty {
+                Some(n_penalty) if C::is_ambiguous_pair(qry_seq, tgt_seq, fr, fr) => {
+                    (n_penalty, BackTraceMarker::FromMWithN)
+                },
+                _ => (penalties.x, BackTraceMarker::FromM),
+            };
+            penalty += substitution_penalty;
+            if penalty > max_penalty {
+                return false;
+            }
+            fr += 1 + C::count_consecutive_match(qry_seq, tgt_seq, fr + 1, fr + 1) as usize;
+            // Under the penalty of the cheapest gap, the wave front score has only the component of k=0.
+            self.wave_front_scores[penalty as usize].components_by_k[0] = Components {
+                m: Component { fr: fr as i32, insertion_count: 0, bt },
+                ..Components::default()
+            };
+        }
+        self.end_point = WaveEndPoint { penalty: penalty as usize, k: Some(0) };
+        true
+    }
     #[inline]
     fn align_to_end_point<C: MatchCounter>(
         &mut self,
@@ -68,8 +138,10 @@ impl WaveFront {
         if self.wave_front_scores.len() as u32 <= spare_penalty {
             spare_penalty = (self.wave_front_scores.len() - 1) as u32;
         }
+        // Filling stops at the spare penalty
+        //   - No end point over the spare penalty can satisfy the cutoff
         for penalty in 1..=spare_penalty {
-            self.update_components_of_next_wave_front_score(penalty, penalties);
+            self.update_components_of_next_wave_front_score::<C>(penalty, penalties, tgt_seq, qry_seq);
 
             let optional_last_k = self.wave_front_scores[penalty as usize].extend_m_components_to_the_end::<C>(tgt_seq, qry_seq);
 
@@ -81,25 +153,40 @@ impl WaveFront {
         WaveEndPoint { penalty: spare_penalty as usize, k: None }
     }
     #[inline]
-    fn update_components_of_next_wave_front_score(
+    fn update_components_of_next_wave_front_score<C: MatchCounter>(
         &mut self,
         penalty: u32,
         penalties: &Penalty,
+        tgt_seq: &[u8],
+        qry_seq: &[u8],
     ) {
         let mismatch_penalty = &penalties.x;
+        // The substitutions of the ambiguous bases are separated only if penalized differently
+        let n_penalty = if penalties.n != penalties.x { Some(penalties.n) } else { None };
         let gap_open_penalty = &penalties.o;
         let gap_extend_penalty = &penalties.e;
+        // The gaps extending the homopolymers are separated only if discounted
+        let homopolymer_gap_discount = if penalties.h != 0 { Some(penalties.h) } else { None };
+        // The gaps of the second piece are separated only if two-piece
+        let second_gap_piece = penalties.second_gap_piece();
 
         let (
             max_k,
             num_components,
             new_components_ptr,
+            new_second_gap_components_ptr,
         ) = {
             let next_wave_front_score = &mut self.wave_front_scores[penalty as usize];
+            // The wave front must be allocated with the same penalties
+            assert_eq!(
+                second_gap_piece.is_some(),
+                next_wave_front_score.second_gap_components_by_k.len() == next_wave_front_score.components_by_k.len(),
+            );
             (
                 next_wave_front_score.max_k,
                 next_wave_front_score.components_by_k.len(),
                 next_wave_front_score.components_by_k.as_mut_ptr(),
+                next_wave_front_score.second_gap_components_by_k.as_mut_ptr(),
             )
         };
 
@@ -109,12 +196,18 @@ impl WaveFront {
             let ptr = new_components_ptr as *mut u8;
             let byte_count = num_components * std::mem::size_of::<Components>();
             std::ptr::write_bytes(ptr, 0, byte_count);
+            // The components of the second piece are allocated only if two-piece
+            if second_gap_piece.is_some() {
+                let ptr = new_second_gap_components_ptr as *mut u8;
+                let byte_count = num_components * std::mem::size_of::<SecondGapComponents>();
+                std::ptr::write_bytes(ptr, 0, byte_count);
+            }
         }
 
         // Update components of next wave front score
-        //   1. Update I, D from previous M
-        //   2. Update I from previous I, D from previous D
-        //   3. Update M from previous M or current D, I
+        //   1. Update I, D (and I2, D2) from previous M
+        //   2. Update I from previous I, D from previous D (and I2, D2 from previous I2, D2)
+        //   3. Update M from previous M or current D, I (and D2, I2)
 
         // (1) From score: s-o-e
         // New insertion or deletion
@@ -129,7 +222,10 @@ impl WaveFront {
                 //       i.e., copy component and only add fr+1 and mark bt as FromM. do not define new values.
                 if let Some(pre_components) = pre_wave_front_score.components_of_k_checked(k-1) {
                     let pre_m_component = &pre_components.m;
-                    if pre_m_component.bt != BackTraceMarker::Empty {
+                    if pre_m_component.bt != BackTraceMarker::Empty && !(
+                        homopolymer_gap_discount.is_some()
+                        && C::is_homopolymer_extension(tgt_seq, pre_m_component.fr as usize)
+                    ) {
                         unsafe {
                             (*new_components_of_k).d = Component {
                                 fr: pre_m_component.fr + 1,
@@ -143,7 +239,10 @@ impl WaveFront {
                 // TODO: Can be all components from previous wave front score be copied + mark only Non-empty cell?
                 if let Some(pre_components) = pre_wave_front_score.components_of_k_checked(k+1) {
                     let pre_m_component = &pre_components.m;
-                    if pre_m_component.bt != BackTraceMarker::Empty {
+                    if pre_m_component.bt != BackTraceMarker::Empty && !(
+                        homopolymer_gap_discount.is_some()
+                        && C::is_homopolymer_extension(qry_seq, (pre_m_component.fr - k - 1) as usize)
+                    ) {
                         unsafe {
                             (*new_components_of_k).i = Component {
                                 fr: pre_m_component.fr,
@@ -155,6 +254,51 @@ impl WaveFront {
                 }
             }
         }
+        // (1-1) From score: s-(o-h)-e
+        // New insertion or deletion extending the homopolymer
+        if let Some(pre_score) = homopolymer_gap_discount.and_then(|discount| {
+            penalty.checked_sub(gap_open_penalty - discount + gap_extend_penalty)
+        }) {
+            let pre_wave_front_score = &self.wave_front_scores[pre_score as usize];
+            for index_of_k in 0..num_components {
+                let k = index_of_k as i32 - max_k;
+                let new_components_of_k = unsafe { new_components_ptr.add(index_of_k) };
+                // 1. Update D from previous M
+                if let Some(pre_components) = pre_wave_front_score.components_of_k_checked(k-1) {
+                    let pre_m_component = &pre_components.m;
+                    if pre_m_component.bt != BackTraceMarker::Empty
+                        && C::is_homopolymer_extension(tgt_seq, pre_m_component.fr as usize)
+                    {
+                        unsafe {
+                            if (*new_components_of_k).d.bt == BackTraceMarker::Empty || (*new_components_of_k).d.fr < pre_m_component.fr + 1 {
+                                (*new_components_of_k).d = Component {
+                                    fr: pre_m_component.fr + 1,
+                                    insertion_count: pre_m_component.insertion_count,
+                                    bt: BackTraceMarker::FromMInHomopolymer,
+                                };
+                            }
+                        }
+                    }
+                }
+                // 2. Update I from previous M
+                if let Some(pre_components) = pre_wave_front_score.components_of_k_checked(k+1) {
+                    let pre_m_component = &pre_components.m;
+                    if pre_m_component.bt != BackTraceMarker::Empty
+                        && C::is_homopolymer_extension(qry_seq, (pre_m_component.fr - k - 1) as usize)
+                    {
+                        unsafe {
+                            if (*new_components_of_k).i.bt == BackTraceMarker::Empty || (*new_components_of_k).i.fr < pre_m_component.fr {
+                                (*new_components_of_k).i = Component {
+                                    fr: pre_m_component.fr,
+                                    insertion_count: pre_m_component.insertion_count + 1,
+                                    bt: BackTraceMarker::FromMInHomopolymer,
+                                };
+                            }
+                        }
+                    }
+                }
+            }
+        }
         // (2) From score: s-e
         // Extended insertion or deletion
         if let Some(pre_score) = penalty.checked_sub(*gap_extend_penalty) {
@@ -195,6 +339,84 @@ impl WaveFront {
                 }
             }
         }
+        // (1-2) From score: s-o2-e2
+        // New insertion or deletion of the second piece
+        if let Some(pre_score) = second_gap_piece.and_then(|(gap_open_penalty_2, gap_extend_penalty_2)| {
+            penalty.checked_sub(gap_open_penalty_2 + gap_extend_penalty_2)
+        }) {
+            let pre_wave_front_score = &self.wave_front_scores[pre_score as usize];
+            for index_of_k in 0..num_components {
+                let k = index_of_k as i32 - max_k;
+                let new_second_gap_components_of_k = unsafe { new_second_gap_components_ptr.add(index_of_k) };
+                // 1. Update D2 from previous M
+                if let Some(pre_components) = pre_wave_front_score.components_of_k_checked(k-1) {
+                    let pre_m_component = &pre_components.m;
+                    if pre_m_component.bt != BackTraceMarker::Empty {
+                        unsafe {
+                            (*new_second_gap_components_of_k).d2 = Component {
+                                fr: pre_m_component.fr + 1,
+                                insertion_count: pre_m_component.insertion_count,
+                                bt: BackTraceMarker::FromM,
+                            };
+                        }
+                    }
+                }
+                // 2. Update I2 from previous M
+                if let Some(pre_components) = pre_wave_front_score.components_of_k_checked(k+1) {
+                    let pre_m_component = &pre_components.m;
+                    if pre_m_component.bt != BackTraceMarker::Empty {
+                        unsafe {
+                            (*new_second_gap_components_of_k).i2 = Component {
+                                fr: pre_m_component.fr,
+                                insertion_count: pre_m_component.insertion_count + 1,
+                                bt: BackTraceMarker::FromM,
+                            };
+                        }
+                    }
+                }
+            }
+        }
+        // (2-2) From score: s-e2
+        // Extended insertion or deletion of the second piece
+        if let Some(pre_score) = second_gap_piece.and_then(|(_, gap_extend_penalty_2)| {
+            penalty.checked_sub(gap_extend_penalty_2)
+        }) {
+            let pre_wave_front_score = &self.wave_front_scores[pre_score as usize];
+            for index_of_k in 0..num_components {
+                let k = index_of_k as i32 - max_k;
+                let new_second_gap_components_of_k = unsafe { new_second_gap_components_ptr.add(index_of_k) };
+                // 1. Update D2 from previous D2
+                if let Some(pre_second_gap_components) = pre_wave_front_score.second_gap_components_of_k_checked(k-1) {
+                    let pre_d2_component = &pre_second_gap_components.d2;
+                    if pre_d2_component.bt != BackTraceMarker::Empty {
+                        unsafe {
+                            if (*new_second_gap_components_of_k).d2.bt == BackTraceMarker::Empty || (*new_second_gap_components_of_k).d2.fr < pre_d2_component.fr + 1 {
+                                (*new_second_gap_components_of_k).d2 = Component {
+                                    fr: pre_d2_component.fr + 1,
+                                    insertion_count: pre_d2_component.insertion_count,
+                                    bt: BackTraceMarker::FromD2,
+                                };
+                            }
+                        }
+                    }
+                }
+                // 2. Update I2 from previous I2
+                if let Some(pre_second_gap_components) = pre_wave_front_score.second_gap_components_of_k_checked(k+1) {
+                    let pre_i2_component = &pre_second_gap_components.i2;
+                    if pre_i2_component.bt != BackTraceMarker::Empty {
+                        unsafe {
+                            if (*new_second_gap_components_of_k).i2.bt == BackTraceMarker::Empty || (*new_second_gap_components_of_k).i2.fr < pre_i2_component.fr {
+                                (*new_second_gap_components_of_k).i2 = Component {
+                                    fr: pre_i2_component.fr,
+                                    insertion_count: pre_i2_component.insertion_count + 1,
+                                    bt: BackTraceMarker::FromI2,
+                                };
+                            }
+                        }
+                    }
+                }
+            }
+        }
         // (3) From score: s-x
         // Substitution
         if let Some(pre_score) = penalty.checked_sub(*mismatch_penalty) {
@@ -207,6 +429,15 @@ impl WaveFront {
 
                 if let Some(pre_components) = pre_wave_front_score.components_by_k.get(pre_component_index) {
                     let pre_m_component = &pre_components.m;
+                    // Can be empty (e.g., not reachable when only the gaps in the homopolymers are discounted)
+                    if pre_m_component.bt == BackTraceMarker::Empty {
+                        continue;
+                    }
+                    if n_penalty.is_some() && C::is_ambiguous_pair(
+                        qry_seq, tgt_seq, (pre_m_component.fr - k) as usize, pre_m_component.fr as usize,
+                    ) {
+                        continue;
+                    }
                     // Update M
                     unsafe {
                         (*new_components_of_k).m = Component {
@@ -218,6 +449,33 @@ impl WaveFront {
                 }
             }
         }
+        // (4) From score: s-n
+        // Substitution with the ambiguous base
+        if let Some(pre_score) = n_penalty.and_then(|n_penalty| penalty.checked_sub(n_penalty)) {
+            let pre_wave_front_score = &self.wave_front_scores[pre_score as usize];
+            for index_of_k in 0..num_components {
+                let k = index_of_k as i32 - max_k;
+                let new_components_of_k = unsafe { new_components_ptr.add(index_of_k) };
+                // 1. Update M from previous M
+                if let Some(pre_components) = pre_wave_front_score.components_of_k_checked(k) {
+                    let pre_m_component = &pre_components.m;
+                    if pre_m_component.bt == BackTraceMarker::Empty || !C::is_ambiguous_pair(
+                        qry_seq, tgt_seq, (pre_m_component.fr - k) as usize, pre_m_component.fr as usize,
+                    ) {
+                        continue;
+                    }
+                    unsafe {
+                        if (*new_components_of_k).m.bt == BackTraceMarker::Empty || (*new_components_of_k).m.fr < pre_m_component.fr + 1 {
+                            (*new_components_of_k).m = Component {
+                                fr: pre_m_component.fr + 1,
+                                insertion_count: pre_m_component.insertion_count,
+                                bt: BackTraceMarker::FromMWithN,
+                            };
+                        }
+                    }
+                }
+            }
+        }
         // TODO: Optimization
         for index_of_k in 0..num_components {
             let new_components_of_k = unsafe { new_components_ptr.add(index_of_k) };
@@ -246,13 +504,44 @@ impl WaveFront {
                 }
             }
         }
+        if second_gap_piece.is_some() {
+            for index_of_k in 0..num_components {
+                let new_components_of_k = unsafe { new_components_ptr.add(index_of_k) };
+                let new_second_gap_components_of_k = unsafe { new_second_gap_components_ptr.add(index_of_k) };
+                unsafe {
+                    // 4. Update M from current D2, I2 (the first piece is kept if the same)
+                    if (*new_second_gap_components_of_k).d2.bt != BackTraceMarker::Empty && (
+                        (*new_components_of_k).m.bt == BackTraceMarker::Empty
+                        || (*new_second_gap_components_of_k).d2.fr > (*new_components_of_k).m.fr
+                    ) {
+                        (*new_components_of_k).m = Component {
+                            fr: (*new_second_gap_components_of_k).d2.fr,
+                            insertion_count: (*new_second_gap_components_of_k).d2.insertion_count,
+                            bt: BackTraceMarker::FromD2,
+                        };
+                    }
+                    if (*new_second_gap_components_of_k).i2.bt != BackTraceMarker::Empty && (
+                        (*new_components_of_k).m.bt == BackTraceMarker::Empty
+                        || (*new_second_gap_components_of_k).i2.fr > (*new_components_of_k).m.fr
+                    ) {
+                        (*new_components_of_k).m = Component {
+                            fr: (*new_second_gap_components_of_k).i2.fr,
+                            insertion_count: (*new_second_gap_components_of_k).i2.insertion_count,
+                            bt: BackTraceMarker::FromI2,
+                        };
+                    }
+                }
+            }
+        }
     }
 }
 
 impl WaveFrontScore {
     #[inline]
     fn add_first_components(&mut self, first_match_count: i32) {
-        self.components_by_k = vec![Components::new_start_point(first_match_count)];
+        // The first wave front score has only one component (max_k is 0).
+        //  - Overwritten in place so that the buffer is not reallocated for each extension.
+        self.components_by_k[0] = Components::new_start_point(first_match_count);
     }
     #[inline]
     fn extend_m_components_to_the_end<C: MatchCounter>(
@@ -260,6 +549,10 @@ impl WaveFrontScore {
         tgt_seq: &[u8],
         qry_seq: &[u8],
     ) -> Option<i32> {
+        // Of the end points with the same penalty, the longest one is taken
+        //  - The longer side leaves the larger spare penalty for the other side,
+        //    so the alignment is not dropped by the other side or the cutoff.
+        let mut longest_end: Option<(u32, i32)> = None;
         for (components, k) in self.components_by_k.iter_mut().zip(-self.max_k..=self.max_k) {
             let m_component = &mut components.m;
 
@@ -273,17 +566,23 @@ impl WaveFrontScore {
                 v += match_count as usize;
                 h += match_count as usize;
                 if h == tgt_seq.len() || v == qry_seq.len() {
-                    return Some(k);
+                    let alignment_length = m_component.fr as u32 + m_component.insertion_count as u32;
+                    match longest_end {
+                        Some((longest, _)) if longest >= alignment_length => {},
+                        _ => longest_end = Some((alignment_length, k)),
+                    }
                 }
             };
         }
-        None
+        longest_end.map(|(_, k)| k)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::{TEST_TARGET, SEED, Xorshift};
+    use crate::results::AlignmentOperation;
     #[test]
     fn test_if_the_default_value_of_component_is_filled_with_zero() {
         let components = Components::default();
@@ -296,4 +595,239 @@ mod tests {
 
         println!("All bytes in the struct are zero")
     }
+    #[test]
+    fn wave_front_is_reused_without_reallocation() {
+        let penalties = Penalty { x: 4, o: 6, e: 2, n: 4, h: 0, o2: 6, e2: 2 };
+        let mut wave_front = WaveFront::new_allocated(&penalties, 30);
+        let buffer_pointers = |wave_front: &WaveFront| -> Vec<*const Components> {
+            wave_front.wave_front_scores.iter().map(|v| v.components_by_k.as_ptr()).collect()
+        };
+        let allocated = buffer_pointers(&wave_front);
+
+        let target = TEST_TARGET;
+        let query = b"ACACAGATCGCAAACTCAGAATTGTATTTCTTTGCCACTGGGCATATACTTTTTGCGCCCCCTCATTTA";
+        wave_front.align_right_to_end_point(target, query, &penalties, 30);
+        let right_end_point = wave_front.get_optional_end_point();
+        assert!(right_end_point.is_some());
+        // Same buffer for the next extension
+        wave_front.align_left_to_end_point(target, query, &penalties, 30);
+        wave_front.align_right_to_end_point(target, query, &penalties, 30);
+        assert_eq!(wave_front.get_optional_end_point(), right_end_point);
+        assert_eq!(buffer_pointers(&wave_front), allocated);
+    }
+    #[test]
+    fn components_of_the_second_gap_piece_are_allocated_only_if_two_piece() {
+        let one_piece = Penalty { x: 4, o: 6, e: 2, n: 4, h: 0, o2: 6, e2: 2 };
+        let wave_front = WaveFront::new_allocated(&one_piece, 30);
+        assert!(wave_front.wave_front_scores.iter().all(|v| v.second_gap_components_by_k.is_empty()));
+
+        let two_piece = Penalty { o2: 12, e2: 1, ..one_piece };
+        let mut wave_front = WaveFront::new_allocated(&two_piece, 30);
+        assert!(wave_front.wave_front_scores.iter().all(|v| {
+            v.second_gap_components_by_k.len() == v.components_by_k.len()
+        }));
+        let target = TEST_TARGET;
+        let query = b"ACACAGATCGCAAACTCAGAATTGTATTTCTTTGCCACTGGGCATATACTTTTTGCGCCCCCTCATTTA";
+        wave_front.align_right_to_end_point(target, query, &two_piece, 30);
+        assert!(wave_front.get_optional_end_point().is_some());
+    }
+    #[test]
+    fn hamming_prefilter_gives_the_same_extension_as_the_wave_front() {
+        let mut rng = Xorshift::new(SEED);
+        let mut prefiltered_count = 0;
+        let mut fallback_count = 0;
+        for penalties in [
+            Penalty { x: 4, o: 6, e: 2, n: 4, h: 0, o2: 6, e2: 2 },
+            Penalty { x: 4, o: 6, e: 2, n: 1, h: 0, o2: 6, e2: 2 },
+            Penalty { x: 4, o: 6, e: 2, n: 4, h: 4, o2: 6, e2: 2 },
+        ] {
+            let mut wave_front = WaveFront::new_allocated(&penalties, 30);
+            for _ in 0..200 {
+                let target: Vec<u8> = (0..60).map(|_| b"ACGTN"[rng.gen_range(5) as usize]).collect();
+                let mut query = target.clone();
+                for _ in 0..rng.gen_range(4) {
+                    let index = rng.gen_range(60) as usize;
+                    query[index] = b"ACGT"[rng.gen_range(4) as usize];
+                }
+                if rng.gen_range(4) == 0 {
+                    query.remove(rng.gen_range(60) as usize);
+                }
+                let spare_penalty = rng.gen_range(30);
+
+                let extension_of = |wave_front: &WaveFront| {
+                    let (penalty, component_index) = wave_front.get_optional_end_point().unwrap();
+                    let mut operations_buffer = Vec::new();
+                    let mut traversed_anchors_buffer = Vec::new();
+                    wave_front.backtrace_of_right_side_with_checking_traversed(
+                        penalty, 2000, 4, 1, component_index, &penalties,
+                        &mut operations_buffer, &mut traversed_anchors_buffer,
+                    );
+                    format!(
+                        "{:?} {:?} {:?} {:?}",
+                        (penalty, component_index),
+                        wave_front.get_proceed_length(penalty, component_index),
+                        operations_buffer,
+                        traversed_anchors_buffer,
+                    )
+                };
+                for is_right in [true, false] {
+                    let prefiltered = if is_right {
+                        wave_front.align_right_to_end_point_without_gap(&target, &query, &penalties, spare_penalty)
+                    } else {
+                        wave_front.align_left_to_end_point_without_gap(&target, &query, &penalties, spare_penalty)
+                    };
+                    let prefiltered_extension = if prefiltered {
+                        prefiltered_count += 1;
+                        Some(extension_of(&wave_front))
+                    } else {
+                        fallback_count += 1;
+                        None
+                    };
+                    if is_right {
+                        wave_front.align_right_to_end_point(&target, &query, &penalties, spare_penalty);
+                    } else {
+                        wave_front.align_left_to_end_point(&target, &query, &penalties, spare_penalty);
+                    }
+                    if let Some(prefiltered_extension) = prefiltered_extension {
+                        assert_eq!(prefiltered_extension, extension_of(&wave_front));
+                    }
+                }
+            }
+        }
+        assert!(prefiltered_count > 0 && fallback_count > 0);
+    }
+    #[test]
+    fn stopping_at_the_spare_penalty_misses_no_end_point() {
+        let mut rng = Xorshift::new(SEED);
+        let mut found_count = 0;
+        let mut dropped_count = 0;
+        for penalties in [
+            Penalty { x: 4, o: 6, e: 2, n: 4, h: 0, o2: 6, e2: 2 },
+            Penalty { x: 4, o: 6, e: 2, n: 4, h: 0, o2: 24, e2: 1 },
+        ] {
+            let max_penalty: u32 = 80;
+            let mut wave_front = WaveFront::new_allocated(&penalties, max_penalty as usize);
+            for _ in 0..300 {
+                let target: Vec<u8> = (0..60).map(|_| b"ACGT"[rng.gen_range(4) as usize]).collect();
+                let mut query = target.clone();
+                for _ in 0..rng.gen_range(6) {
+                    let index = rng.gen_range(query.len() as u32) as usize;
+                    match rng.gen_range(3) {
+                        0 => query[index] = b"ACGT"[rng.gen_range(4) as usize],
+                        1 => query.insert(index, b"ACGT"[rng.gen_range(4) as usize]),
+                        _ => { query.remove(index); },
+                    }
+                }
+                let spare_penalty = rng.gen_range(40);
+
+                for is_right in [true, false] {
+                    let mut end_point_with_spare_penalty = |spare_penalty: u32| {
+                        if is_right {
+                            wave_front.align_right_to_end_point(&target, &query, &penalties, spare_penalty);
+                        } else {
+                            wave_front.align_left_to_end_point(&target, &query, &penalties, spare_penalty);
+                        }
+                        wave_front.get_optional_end_point().map(|(penalty, component_index)| {
+                            (penalty, component_index, wave_front.get_proceed_length(penalty, component_index))
+                        })
+                    };
+                    // Filled past the spare penalty
+                    let end_point = end_point_with_spare_penalty(max_penalty);
+                    let stopped_end_point = end_point_with_spare_penalty(spare_penalty);
+                    match end_point {
+                        Some((penalty, ..)) if penalty <= spare_penalty => {
+                            found_count += 1;
+                            assert_eq!(stopped_end_point, end_point);
+                        },
+                        _ => {
+                            dropped_count += 1;
+                            assert_eq!(stopped_end_point, None);
+                        },
+                    }
+                }
+            }
+        }
+        assert!(found_count > 0 && dropped_count > 0);
+    }
+    #[test]
+    fn two_piece_gap_penalty_is_same_as_the_dynamic_programming() {
+        // Minimum penalty to reach the end of either sequence from the start (Gotoh with two gap pieces)
+        fn min_penalty_by_dp(target: &[u8], query: &[u8], penalties: &Penalty) -> u32 {
+            let inf = u32::MAX / 2;
+            let (tl, ql) = (target.len(), query.len());
+            // [M, D, I, D2, I2] of each cell
+            let mut matrix = vec![vec![[inf; 5]; tl + 1]; ql + 1];
+            let mut min_penalty = inf;
+            for i in 0..=ql {
+                for j in 0..=tl {
+                    let mut cell = [inf; 5];
+                    if i == 0 && j == 0 {
+                        cell[0] = 0;
+                    }
+                    if i > 0 && j > 0 {
+                        let previous = matrix[i - 1][j - 1].iter().min().unwrap();
+                        cell[0] = previous + penalties.of_substitution(query[i - 1], target[j - 1]);
+                    }
+                    if j > 0 {
+                        let previous = &matrix[i][j - 1];
+                        let opened = previous.iter().min().unwrap();
+                        cell[1] = (opened + penalties.o + penalties.e).min(previous[1] + penalties.e);
+                        cell[3] = (opened + penalties.o2 + penalties.e2).min(previous[3] + penalties.e2);
+                    }
+                    if i > 0 {
+                        let previous = &matrix[i - 1][j];
+                        let opened = previous.iter().min().unwrap();
+                        cell[2] = (opened + penalties.o + penalties.e).min(previous[2] + penalties.e);
+                        cell[4] = (opened + penalties.o2 + penalties.e2).min(previous[4] + penalties.e2);
+                    }
+                    matrix[i][j] = cell;
+                    if i == ql || j == tl {
+                        min_penalty = min_penalty.min(*cell.iter().min().unwrap());
+                    }
+                }
+            }
+            min_penalty
+        }
+
+        let mut rng = Xorshift::new(SEED);
+        let penalties = Penalty { x: 4, o: 6, e: 2, n: 4, h: 0, o2: 24, e2: 1 };
+        let spare_penalty: u32 = 60;
+        let mut wave_front = WaveFront::new_allocated(&penalties, spare_penalty as usize);
+        let mut long_gap_count = 0;
+        for _ in 0..300 {
+            let target: Vec<u8> = (0..80).map(|_| b"ACGT"[rng.gen_range(4) as usize]).collect();
+            let mut query = target.clone();
+            for _ in 0..rng.gen_range(3) {
+                let index = rng.gen_range(80) as usize;
+                query[index] = b"ACGT"[rng.gen_range(4) as usize];
+            }
+            let gap_length = 1 + rng.gen_range(30) as usize;
+            let gap_start = rng.gen_range(40) as usize;
+            if rng.gen_range(2) == 0 {
+                query.drain(gap_start..gap_start + gap_length);
+            } else {
+                let inserted: Vec<u8> = (0..gap_length).map(|_| b"ACGT"[rng.gen_range(4) as usize]).collect();
+                query.splice(gap_start..gap_start, inserted);
+            }
+
+            let expected = min_penalty_by_dp(&target, &query, &penalties);
+            wave_front.align_right_to_end_point(&target, &query, &penalties, spare_penalty);
+            match wave_front.backtrace_from_end_point(&penalties) {
+                Some((penalty, operations)) => {
+                    assert_eq!(penalty, expected);
+                    assert_eq!(penalties.of_operations(&operations), penalty);
+                    if operations.iter().any(|operations| {
+                        operations.operation != AlignmentOperation::Match
+                        && operations.operation != AlignmentOperation::Subst
+                        && operations.count > 18
+                    }) {
+                        long_gap_count += 1;
+                    }
+                },
+                None => assert!(expected > spare_penalty),
+            }
+        }
+        // The second piece is used for the long gaps
+        assert!(long_gap_count > 0);
+    }
 }
@@ -1,3 +1,5 @@
+use crate::core::regulators::is_ambiguous_base;
+
 // TODO: apply SIMD
 pub trait MatchCounter {
     fn count_consecutive_match(
@@ -6,6 +8,19 @@ pub trait MatchCounter {
         qry_start_index: usize,
         tgt_start_index: usize,
     ) -> i32;
+    // If either base of the next pair is ambiguous (false if out of the sequences)
+    fn is_ambiguous_pair(
+        qry_seq: &[u8],
+        tgt_seq: &[u8],
+        qry_index: usize,
+        tgt_index: usize,
+    ) -> bool;
+    // If the base at the index is the same as the previous base in the direction of the extension
+    //  - false for the first base (the previous one is out of the slice) or out of the sequence
+    fn is_homopolymer_extension(
+        seq: &[u8],
+        index: usize,
+    ) -> bool;
 }
 
 pub struct ForwardMatchCounter;
@@ -27,6 +42,25 @@ impl MatchCounter for ForwardMatchCounter {
         }
         match_count
     }
+    #[inline(always)]
+    fn is_ambiguous_pair(
+        qry_seq: &[u8],
+        tgt_seq: &[u8],
+        qry_index: usize,
+        tgt_index: usize,
+    ) -> bool {
+        match (qry_seq.get(qry_index), tgt_seq.get(tgt_index)) {
+            (Some(qry_base), Some(tgt_base)) => is_ambiguous_base(*qry_base) || is_ambiguous_base(*tgt_base),
+            _ => false,
+        }
+    }
+    #[inline(always)]
+    fn is_homopolymer_extension(
+        seq: &[u8],
+        index: usize,
+    ) -> bool {
+        index != 0 && index < seq.len() && seq[index] == seq[index - 1]
+    }
 }
 pub struct ReverseMatchCounter;
 impl MatchCounter for ReverseMatchCounter {
@@ -47,4 +81,25 @@ impl MatchCounter for ReverseMatchCounter {
         }
         match_count
     }
-}
+    #[inline(always)]
+    fn is_ambiguous_pair(
+        qry_seq: &[u8],
+        tgt_seq: &[u8],
+        qry_index: usize,
+        tgt_index: usize,
+    ) -> bool {
+        if qry_index >= qry_seq.len() || tgt_index >= tgt_seq.len() {
+            return false
+        }
+        let qry_base = qry_seq[qry_seq.len() - 1 - qry_index];
+        let tgt_base = tgt_seq[tgt_seq.len() - 1 - tgt_index];
+        is_ambiguous_base(qry_base) || is_ambiguous_base(tgt_base)
+    }
+    #[inline(always)]
+    fn is_homopolymer_extension(
+        seq: &[u8],
+        index: usize,
+    ) -> bool {
+        index != 0 && index < seq.len() && seq[seq.len() - 1 - index] == seq[seq.len() - index]
+    }
+}
\ No newline at end of file
@@ -25,6 +25,8 @@ pub struct WaveEndPoint {
 pub struct WaveFrontScore {
     pub max_k: i32,
     pub components_by_k: Vec<Components>, // (-max_k..=max_k)
+    // Allocated only if the gap penalty is two-piece (empty otherwise)
+    pub second_gap_components_by_k: Vec<SecondGapComponents>, // (-max_k..=max_k)
 }
 
 impl WaveFront {
@@ -33,11 +35,14 @@ impl WaveFront {
         max_penalty: usize,
     ) -> Self {
         let wave_front_score_count = max_penalty + 1;
-        let gap_open_penalty = penalties.o;
-        let gap_extend_penalty = penalties.e;
+        // The cheapest gap-open and gap-extend penalties define the components of each wave front score
+        let bounds = penalties.for_bounds();
+        let gap_open_penalty = bounds.o;
+        let gap_extend_penalty = bounds.e;
+        let is_two_piece = penalties.second_gap_piece().is_some();
 
         let mut wave_front_scores: Vec<WaveFrontScore> = Vec::with_capacity(wave_front_score_count);
-        let first_wave_front_score = WaveFrontScore::with_max_k(0);
+        let first_wave_front_score = WaveFrontScore::with_max_k(0, is_two_piece);
 
         let optional_penalty_from_one_gap = max_penalty.checked_sub((gap_open_penalty + gap_extend_penalty) as usize);
 
@@ -51,11 +56,11 @@ impl WaveFront {
                 let rem = penalty_from_one_gap as u32 % gap_extend_penalty;
                 for max_k in 1..quot+1 {
                     (0..gap_extend_penalty).for_each(|_| {
-                        wave_front_scores.push(WaveFrontScore::with_max_k(max_k));
+                        wave_front_scores.push(WaveFrontScore::with_max_k(max_k, is_two_piece));
                     });
                 };
                 (0..rem+1).for_each(|_| {
-                    wave_front_scores.push(WaveFrontScore::with_max_k(quot+1));
+                    wave_front_scores.push(WaveFrontScore::with_max_k(quot+1, is_two_piece));
                 });
             },
             None => {
@@ -71,6 +76,23 @@ impl WaveFront {
             wave_front_scores,
         }
     }
+    // Number of the components of the wave front scores until the penalty
+    //  - Same as the wave front allocated with `new_allocated`.
+    pub fn component_count(
+        penalties: &Penalty,
+        max_penalty: usize,
+    ) -> u64 {
+        let bounds = penalties.for_bounds();
+        let gap_open_penalty = bounds.o as usize;
+        let gap_extend_penalty = bounds.e as usize;
+        (0..=max_penalty).map(|penalty| {
+            let max_k = match penalty.checked_sub(gap_open_penalty + gap_extend_penalty) {
+                Some(penalty_from_one_gap) => penalty_from_one_gap / gap_extend_penalty + 1,
+                None => 0,
+            };
+            (max_k * 2 + 1) as u64
+        }).sum()
+    }
     #[inline]
     // Return penalty and component index
     pub fn get_optional_end_point(&self) -> Option<(u32, u32)> {
@@ -84,10 +106,16 @@ impl WaveFront {
 
 impl WaveFrontScore {
     // New
-    fn with_max_k(max_k: i32) -> Self {
+    fn with_max_k(max_k: i32, is_two_piece: bool) -> Self {
+        let component_count = max_k as usize * 2 + 1;
         Self {
             max_k,
-            components_by_k: vec![Components::default(); max_k as usize * 2 + 1],
+            components_by_k: vec![Components::default(); component_count],
+            second_gap_components_by_k: if is_two_piece {
+                vec![SecondGapComponents::default(); component_count]
+            } else {
+                Vec::new()
+            },
         }
     }
     // Get
@@ -108,9 +136,21 @@ impl WaveFrontScore {
         &self.components_of_k(k).i
     }
     #[inline(always)]
+    pub fn d2_component_of_k(&self, k: i32) -> &Component {
+        &self.second_gap_components_by_k[(self.max_k + k) as usize].d2
+    }
+    #[inline(always)]
+    pub fn i2_component_of_k(&self, k: i32) -> &Component {
+        &self.second_gap_components_by_k[(self.max_k + k) as usize].i2
+    }
+    #[inline(always)]
     pub fn components_of_k_checked(&self, k: i32) -> Option<&Components> {
         self.components_by_k.get((self.max_k + k) as usize)
     }
+    #[inline(always)]
+    pub fn second_gap_components_of_k_checked(&self, k: i32) -> Option<&SecondGapComponents> {
+        self.second_gap_components_by_k.get((self.max_k + k) as usize)
+    }
 }
 
 // Components
@@ -122,6 +162,14 @@ pub struct Components {
     pub i: Component,
 }
 
+// Components of the gaps of the second piece of the gap penalty
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct SecondGapComponents {
+    pub d2: Component,
+    pub i2: Component,
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct Component {
@@ -140,6 +188,31 @@ pub enum BackTraceMarker {
     FromM = 2,
     FromD = 3,
     FromI = 4,
+    // Substitution with the ambiguous base from previous M
+    FromMWithN = 5,
+    // Gap extending the homopolymer from previous M (only in D, I)
+    FromMInHomopolymer = 6,
+    // Gap of the second piece (in M from current D2, I2, or in D2, I2 from previous D2, I2)
+    FromD2 = 7,
+    FromI2 = 8,
+}
+impl BackTraceMarker {
+    #[inline(always)]
+    pub fn substitution_penalty(&self, penalties: &Penalty) -> u32 {
+        if *self == Self::FromMWithN {
+            penalties.n
+        } else {
+            penalties.x
+        }
+    }
+    #[inline(always)]
+    pub fn gap_opening_penalty(&self, penalties: &Penalty) -> u32 {
+        if *self == Self::FromMInHomopolymer {
+            penalties.o - penalties.h + penalties.e
+        } else {
+            penalties.o + penalties.e
+        }
+    }
 }
 impl Default for Components {
     fn default() -> Self {
@@ -150,12 +223,67 @@ impl Default for Components {
         }
     }
 }
+impl Default for SecondGapComponents {
+    fn default() -> Self {
+        Self {
+            d2: Component::empty(),
+            i2: Component::empty(),
+        }
+    }
+}
 impl Components {
     fn new_start_point(first_fr: i32) -> Self {
         Self {
             m: Component::start_point(first_fr),
-            d: Component::empty(),
-            i: Component::empty(),
+            ..Self::default()
+        }
+    }
+}
+
+// Type of the component followed in the backtrace
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComponentType {
+    M,
+    D,
+    I,
+    D2,
+    I2,
+}
+impl ComponentType {
+    // Gap component that the M component comes from
+    #[inline(always)]
+    pub fn of_gap_marker(bt: BackTraceMarker) -> Self {
+        match bt {
+            BackTraceMarker::FromD => Self::D,
+            BackTraceMarker::FromI => Self::I,
+            BackTraceMarker::FromD2 => Self::D2,
+            BackTraceMarker::FromI2 => Self::I2,
+            _ => Self::M,
+        }
+    }
+    #[inline(always)]
+    pub fn component_of_k<'a>(&self, wave_front_score: &'a WaveFrontScore, k: i32) -> &'a Component {
+        match self {
+            Self::M => wave_front_score.m_component_of_k(k),
+            Self::D => wave_front_score.d_component_of_k(k),
+            Self::I => wave_front_score.i_component_of_k(k),
+            Self::D2 => wave_front_score.d2_component_of_k(k),
+            Self::I2 => wave_front_score.i2_component_of_k(k),
+        }
+    }
+    // Penalty of the gap opened from M (`bt` is the marker of this gap component)
+    #[inline(always)]
+    pub fn gap_opening_penalty(&self, bt: BackTraceMarker, penalties: &Penalty) -> u32 {
+        match self {
+            Self::D2 | Self::I2 => penalties.o2 + penalties.e2,
+            _ => bt.gap_opening_penalty(penalties),
+        }
+    }
+    #[inline(always)]
+    pub fn gap_extend_penalty(&self, penalties: &Penalty) -> u32 {
+        match self {
+            Self::D2 | Self::I2 => penalties.e2,
+            _ => penalties.e,
         }
     }
 }
@@ -0,0 +1,55 @@
+use crate::{
+    core::BufferedPatternLocator,
+    algorithm::{estimate_work, SparePenaltyCalculator},
+};
+use super::AlignmentRegulator;
+
+/// Projected work to align a query, without aligning it (see `AlignmentRegulator::estimate_work`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkEstimate {
+    /// Number of targets having enough anchors to be extended.
+    pub target_count: u32,
+    /// Number of anchors to be extended at most (after ungapped anchors are merged).
+    pub anchor_count: u32,
+    /// Number of wave front cells filled at most, if every anchor is extended until its spare penalty.
+    ///  - Always 0 for the ungapped extension.
+    pub estimated_wave_front_cells: u64,
+}
+
+impl AlignmentRegulator {
+    /// Estimate the work to align the query as a dry run, to decide the batch sizes or to find the expensive queries.
+    ///  - The patterns of the query are located and the anchors are made as the aligners do,
+    ///    but the anchors are not extended.
+    ///  - Both are upper bounds: the anchors traversed by other extensions are not extended,
+    ///    and most extensions stop far before their spare penalties.
+    ///    The flanks of the anchors (see `set_seed_extension_check`) are not verified.
+    pub fn estimate_work<L: BufferedPatternLocator>(
+        &self,
+        query: &[u8],
+        pattern_locator: &L,
+        sorted_target_indices: &[u32],
+    ) -> WorkEstimate {
+        let pattern_count = query.len() as u32 / self.pattern_size;
+        if pattern_count == 0 {
+            return WorkEstimate::default();
+        }
+        let cutoff = self.cutoff_for_query(query.len());
+        let mut spare_penalty_calculator = SparePenaltyCalculator::new(
+            &self.penalties.for_bounds(),
+            cutoff.maximum_scaled_penalty_per_length,
+            self.pattern_size,
+            pattern_count,
+        );
+        estimate_work(
+            pattern_locator,
+            query,
+            sorted_target_indices,
+            self.pattern_size,
+            &self.seeding_options,
+            &self.penalties,
+            &cutoff,
+            &mut spare_penalty_calculator,
+            self.ungapped,
+        )
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{
+    core::regulators::PREC_SCALE,
+    results::{Alignment, AlignmentOperation},
+    algorithm::WaveFront,
+};
+use super::AlignmentRegulator;
+
+impl AlignmentRegulator {
+    /// Extend the alignment from its end to the end of either sequence,
+    /// when more of the sequences are available (e.g., streaming assembly).
+    ///  - `target` and `query` are the whole sequences including the bases after the previous ends.
+    ///  - The wave front is filled again from the end of the alignment, so the aligned bases are not aligned again.
+    ///    A gap at the end is not continued but opened again.
+    ///  - Returns `false` and leaves the alignment unchanged if the extended alignment exceeds
+    ///    the maximum penalty per length or the maximum penalty.
+    ///  - Nothing is changed if either sequence has no more bases.
+    ///  - The aligned sequences are filled again if they were filled.
+    pub fn extend_alignment(&self, alignment: &mut Alignment, target: &[u8], query: &[u8]) -> bool {
+        let query_end = alignment.position.query.1 as usize;
+        let target_end = alignment.position.target.1 as usize;
+        let (extra_target, extra_query) = (&target[target_end..], &query[query_end..]);
+        if extra_target.is_empty() || extra_query.is_empty() {
+            return true;
+        }
+
+        let penalty = alignment.penalty / self.gcd_for_compression;
+        let maximum_length = alignment.length as u64 + (extra_target.len() + extra_query.len()) as u64;
+        let mut maximum_penalty = (
+            self.cutoff.maximum_scaled_penalty_per_length as u64 * maximum_length / PREC_SCALE as u64
+        ) as u32;
+        if let Some(hard_limit) = self.cutoff.maximum_penalty {
+            maximum_penalty = maximum_penalty.min(hard_limit);
+        }
+        let Some(spare_penalty) = maximum_penalty.checked_sub(penalty) else {
+            return false;
+        };
+
+        let mut wave_front = WaveFront::new_allocated(&self.penalties, spare_penalty as usize);
+        wave_front.align_right_to_end_point(extra_target, extra_query, &self.penalties, spare_penalty);
+        let Some((extended_penalty, operations)) = wave_front.backtrace_from_end_point(&self.penalties) else {
+            return false;
+        };
+        let (query_length, target_length, length) = operations.iter().fold((0, 0, 0), |(q, t, l), op| {
+            match op.operation {
+                AlignmentOperation::Match | AlignmentOperation::Subst => (q + op.count, t + op.count, l + op.count),
+                AlignmentOperation::Insertion => (q + op.count, t, l + op.count),
+                AlignmentOperation::Deletion => (q, t + op.count, l + op.count),
+            }
+        });
+        let total_penalty = (penalty + extended_penalty) as u64;
+        let total_length = (alignment.length + length) as u64;
+        if total_penalty * PREC_SCALE as u64 > self.cutoff.maximum_scaled_penalty_per_length as u64 * total_length {
+            return false;
+        }
+
+        let mut operations = operations.into_iter();
+        if let (Some(last), Some(first)) = (alignment.operations.last_mut(), operations.as_slice().first()) {
+            if last.operation == first.operation {
+                last.count += first.count;
+                operations.next();
+            }
+        }
+        alignment.operations.extend(operations);
+        alignment.penalty += extended_penalty * self.gcd_for_compression;
+        alignment.length += length;
+        alignment.position.query.1 += query_length;
+        alignment.position.target.1 += target_length;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::{AlignmentOperations, AlignmentPosition};
+    use crate::test_utils::TEST_TARGET;
+
+    #[test]
+    fn alignment_is_extended_with_more_sequences() {
+        let regulator = AlignmentRegulator::new(4, 6, 2, 50, 0.2).unwrap();
+        let unit = TEST_TARGET;
+        let target = [&unit[..], &unit[..]].concat();
+        let alignment = Alignment {
+            penalty: 0,
+            length: 60,
+            position: AlignmentPosition { query: (0, 60), target: (0, 60) },
+            operations: vec![AlignmentOperations { operation: AlignmentOperation::Match, count: 60 }],
+        };
+
+        // Extending by zero
+        let mut extended = alignment.clone();
+        assert!(regulator.extend_alignment(&mut extended, &target, &unit[..60]));
+        assert_eq!(extended, alignment);
+        assert!(regulator.extend_alignment(&mut extended, &target[..60], unit));
+        assert_eq!(extended, alignment);
+
+        // A substitution in the new bases
+        let mut query = unit.to_vec();
+        query[64] = b'G';
+        let mut extended = alignment.clone();
+        assert!(regulator.extend_alignment(&mut extended, &target, &query));
+        assert_eq!(extended.to_cigar(true), "64=1X5=");
+        assert_eq!(extended.position, AlignmentPosition { query: (0, 70), target: (0, 70) });
+        assert_eq!((extended.penalty, extended.length), (4, 70));
+        assert_eq!(extended.get_aligned_sequences(&target, &query).query, &query[..]);
+        assert_eq!(extended.verify(&target, &query, &regulator), Ok(()));
+
+        // Too many penalties to reach the end
+        let query = [&unit[..60], &[b'T'; 20][..]].concat();
+        let mut extended = alignment.clone();
+        assert!(!regulator.extend_alignment(&mut extended, &target, &query));
+        assert_eq!(extended, alignment);
+    }
+}
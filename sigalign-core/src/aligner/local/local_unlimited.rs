@@ -1,8 +1,15 @@
-use crate::results::QueryAlignment;
-use crate::reference::{
-    Reference, PatternIndex, SequenceStorage,
+use crate::results::{QueryAlignment, Alignment};
+use crate::core::BufferedPatternLocator;
+use crate::algorithm::{
+    AnchorTable,
+    local_alignment_algorithm, local_ungapped_alignment_algorithm,
+    local_alignment_query_to_target, ungapped_alignment_query_to_target,
+};
+#[cfg(feature = "debug")]
+use crate::{
+    algorithm::{local_alignment_diagnostics, ungapped_alignment_diagnostics, ExtensionRecorder},
+    debug::{AlignmentDiagnostics, AlignmentProfile, AnchorExistence},
 };
-use crate::algorithm::local_alignment_algorithm;
 use super::{
     AlignmentRegulator,
     LocalWorkspace,
@@ -25,15 +32,37 @@ impl LocalAligner {
     }
     /// Low-level alignment function
     #[inline]
-    pub fn align<I: PatternIndex, S: SequenceStorage> (
+    ///  - `Reference` is the default `BufferedPatternLocator`.
+    pub fn align<L: BufferedPatternLocator> (
         &mut self,
         // Query
         query: &[u8],
         // Targets
-        reference: &Reference<I, S>,
-        sequence_buffer: &mut S::Buffer,
+        pattern_locator: &L,
+        sequence_buffer: &mut L::Buffer,
         sorted_target_indices: &[u32],
     ) -> QueryAlignment {
+        // Query without any pattern can not be anchored
+        //  - Such query can satisfy the cutoffs only with long deletions, which is
+        //    prevented by the maximum penalty per length lower than the gap-extend penalty.
+        if query.len() < self.regulator.pattern_size as usize {
+            return QueryAlignment::default();
+        }
+        if self.regulator.ungapped {
+            let mut result = local_ungapped_alignment_algorithm(
+                pattern_locator,
+                sequence_buffer,
+                query,
+                sorted_target_indices,
+                self.regulator.pattern_size,
+                &self.regulator.seeding_options,
+                &self.regulator.penalties,
+                &self.regulator.cutoff_for_query(query.len()),
+                None,
+            );
+            self.regulator.decompress_result_with_gcd(&mut result);
+            return result;
+        }
         // Initialization
         self.workspace.allocate_more_space_if_needed(
             query.len() as u32,
@@ -42,13 +71,14 @@ impl LocalAligner {
         
         // Perform alignment
         let mut result = local_alignment_algorithm(
-            reference,
+            pattern_locator,
             sequence_buffer,
             query,
             sorted_target_indices,
             self.regulator.pattern_size,
+            &self.regulator.seeding_options,
             &self.regulator.penalties,
-            &self.regulator.cutoff,
+            &self.regulator.cutoff_for_query(query.len()),
             &mut self.workspace.spare_penalty_calculator,
             &mut self.workspace.wave_front_buffer_1.as_mut(),
             &mut self.workspace.wave_front_buffer_2.as_mut(),
@@ -60,6 +90,155 @@ impl LocalAligner {
         self.regulator.decompress_result_with_gcd(&mut result);
         result
     }
+    /// Alignment with the counts of anchors consumed (see `AlignmentDiagnostics`).
+    #[cfg(feature = "debug")]
+    pub fn align_with_diagnostics<L: BufferedPatternLocator> (
+        &mut self,
+        query: &[u8],
+        pattern_locator: &L,
+        sequence_buffer: &mut L::Buffer,
+        sorted_target_indices: &[u32],
+    ) -> (QueryAlignment, AlignmentDiagnostics) {
+        self.align_with_recorder(query, pattern_locator, sequence_buffer, sorted_target_indices, &mut ())
+    }
+    /// Alignment with the elapsed time and the wave front size of each extension (see `AlignmentProfile`).
+    #[cfg(feature = "debug")]
+    pub fn align_with_profile<L: BufferedPatternLocator> (
+        &mut self,
+        query: &[u8],
+        pattern_locator: &L,
+        sequence_buffer: &mut L::Buffer,
+        sorted_target_indices: &[u32],
+    ) -> (QueryAlignment, AlignmentProfile) {
+        let mut profile = AlignmentProfile::default();
+        let (result, _) = self.align_with_recorder(query, pattern_locator, sequence_buffer, sorted_target_indices, &mut profile);
+        profile.scale_penalties(self.regulator.gcd_for_compression);
+        (result, profile)
+    }
+    #[cfg(feature = "debug")]
+    fn align_with_recorder<L: BufferedPatternLocator, R: ExtensionRecorder> (
+        &mut self,
+        query: &[u8],
+        pattern_locator: &L,
+        sequence_buffer: &mut L::Buffer,
+        sorted_target_indices: &[u32],
+        recorder: &mut R,
+    ) -> (QueryAlignment, AlignmentDiagnostics) {
+        if query.len() < self.regulator.pattern_size as usize {
+            return (QueryAlignment::default(), AlignmentDiagnostics::default());
+        }
+        if self.regulator.ungapped {
+            let (mut result, diagnostics) = ungapped_alignment_diagnostics(
+                pattern_locator,
+                sequence_buffer,
+                query,
+                sorted_target_indices,
+                self.regulator.pattern_size,
+                &self.regulator.seeding_options,
+                &self.regulator.penalties,
+                &self.regulator.cutoff_for_query(query.len()),
+                true,
+            );
+            self.regulator.decompress_result_with_gcd(&mut result);
+            return (result, diagnostics);
+        }
+        self.workspace.allocate_more_space_if_needed(
+            query.len() as u32,
+            &self.regulator,
+        );
+        let (mut result, diagnostics) = local_alignment_diagnostics(
+            pattern_locator,
+            sequence_buffer,
+            query,
+            sorted_target_indices,
+            self.regulator.pattern_size,
+            &self.regulator.seeding_options,
+            &self.regulator.penalties,
+            &self.regulator.cutoff_for_query(query.len()),
+            &mut self.workspace.spare_penalty_calculator,
+            &mut self.workspace.wave_front_buffer_1.as_mut(),
+            &mut self.workspace.wave_front_buffer_2.as_mut(),
+            &mut self.workspace.left_vpc_buffer,
+            &mut self.workspace.right_vpc_buffer,
+            &mut self.workspace.traversed_anchors_buffer,
+            &mut self.workspace.operations_buffer,
+            recorder,
+        );
+        self.regulator.decompress_result_with_gcd(&mut result);
+        (result, diagnostics)
+    }
+    /// Whether each pattern of the query is located in the targets (see `AnchorExistence`).
+    ///  - Only the patterns are located; the query is not aligned.
+    #[cfg(feature = "debug")]
+    pub fn get_anchor_existence<L: BufferedPatternLocator>(
+        &self,
+        query: &[u8],
+        pattern_locator: &L,
+        sorted_target_indices: &[u32],
+    ) -> AnchorExistence {
+        AnchorExistence {
+            pattern_size: self.regulator.pattern_size,
+            existence: AnchorTable::anchor_existence(
+                pattern_locator,
+                query,
+                sorted_target_indices,
+                self.regulator.pattern_size,
+                &self.regulator.seeding_options,
+            ),
+        }
+    }
+    /// Align the query to the target through the seed, without locating the patterns.
+    ///  - `seed`: (target position, query position, length) of the region expected to be matched.
+    ///  - The patterns inside the seed exactly matched on its diagonal are the anchors,
+    ///    so the seed may have errors. Empty if there is no such pattern.
+    ///  - The minimum anchor count is not applied.
+    pub fn align_with_seed(
+        &mut self,
+        query: &[u8],
+        target: &[u8],
+        seed: (u32, u32, u32),
+    ) -> Vec<Alignment> {
+        let Some(mut anchor_table) = AnchorTable::new_from_seed(
+            query, target, seed, self.regulator.pattern_size,
+        ) else {
+            return Vec::new();
+        };
+        let mut alignments = if self.regulator.ungapped {
+            let mut limit = u32::MAX;
+            ungapped_alignment_query_to_target(
+                &mut anchor_table,
+                self.regulator.pattern_size,
+                target,
+                query,
+                &self.regulator.penalties,
+                &self.regulator.cutoff_for_query(query.len()),
+                true,
+                &mut limit,
+            )
+        } else {
+            self.workspace.allocate_more_space_if_needed(
+                query.len() as u32,
+                &self.regulator,
+            );
+            local_alignment_query_to_target(
+                &mut anchor_table,
+                self.regulator.pattern_size,
+                target,
+                query,
+                &self.regulator.penalties,
+                &self.regulator.cutoff_for_query(query.len()),
+                &mut self.workspace.spare_penalty_calculator,
+                &mut self.workspace.wave_front_buffer_1.as_mut(),
+                &mut self.workspace.wave_front_buffer_2.as_mut(),
+                &mut self.workspace.left_vpc_buffer,
+                &mut self.workspace.right_vpc_buffer,
+                &mut self.workspace.traversed_anchors_buffer,
+                &mut self.workspace.operations_buffer,
+            )
+        };
+        self.regulator.decompress_alignments_with_gcd(&mut alignments);
+        alignments
+    }
     pub fn regulator(&self) -> &AlignmentRegulator {
         &self.regulator
     }
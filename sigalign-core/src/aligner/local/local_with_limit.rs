@@ -1,8 +1,6 @@
 use crate::results::QueryAlignment;
-use crate::reference::{
-    Reference, PatternIndex, SequenceStorage,
-};
-use crate::algorithm::local_alignment_algorithm_with_limit;
+use crate::core::BufferedPatternLocator;
+use crate::algorithm::{local_alignment_algorithm_with_limit, local_ungapped_alignment_algorithm};
 use super::{
     AlignmentRegulator,
     LocalWorkspace,
@@ -24,15 +22,37 @@ impl LocalWithLimitAligner {
     }
     /// Low-level alignment function
     #[inline]
-    pub fn align<I: PatternIndex, S: SequenceStorage> (
+    ///  - `Reference` is the default `BufferedPatternLocator`.
+    pub fn align<L: BufferedPatternLocator> (
         &mut self,
         // Query
         query: &[u8],
         // Targets
-        reference: &Reference<I, S>,
-        sequence_buffer: &mut S::Buffer,
+        pattern_locator: &L,
+        sequence_buffer: &mut L::Buffer,
         sorted_target_indices: &[u32],
     ) -> QueryAlignment {
+        // Query without any pattern can not be anchored
+        //  - Such query can satisfy the cutoffs only with long deletions, which is
+        //    prevented by the maximum penalty per length lower than the gap-extend penalty.
+        if query.len() < self.regulator.pattern_size as usize {
+            return QueryAlignment::default();
+        }
+        if self.regulator.ungapped {
+            let mut result = local_ungapped_alignment_algorithm(
+                pattern_locator,
+                sequence_buffer,
+                query,
+                sorted_target_indices,
+                self.regulator.pattern_size,
+                &self.regulator.seeding_options,
+                &self.regulator.penalties,
+                &self.regulator.cutoff_for_query(query.len()),
+                Some(self.limit),
+            );
+            self.regulator.decompress_result_with_gcd(&mut result);
+            return result;
+        }
         // Initialization
         self.workspace.allocate_more_space_if_needed(
             query.len() as u32,
@@ -41,13 +61,14 @@ impl LocalWithLimitAligner {
         
         // Perform alignment
         let mut result = local_alignment_algorithm_with_limit(
-            reference,
+            pattern_locator,
             sequence_buffer,
             query,
             sorted_target_indices,
             self.regulator.pattern_size,
+            &self.regulator.seeding_options,
             &self.regulator.penalties,
-            &self.regulator.cutoff,
+            &self.regulator.cutoff_for_query(query.len()),
             &mut self.workspace.spare_penalty_calculator,
             &mut self.workspace.wave_front_buffer_1.as_mut(),
             &mut self.workspace.wave_front_buffer_2.as_mut(),
@@ -8,4 +8,6 @@ pub use local_unlimited::LocalAligner;
 mod local_with_limit;
 pub use local_with_limit::LocalWithLimitAligner;
 
+impl_option_setters!(LocalAligner, LocalWithLimitAligner);
+
 mod switch_modes;
@@ -27,6 +27,43 @@ mod workspace;
 //  - To define input parameters
 mod regulator;
 pub use regulator::{AlignmentRegulator, RegulatorError};
+//  - To project the work of a query
+mod estimate;
+pub use estimate::WorkEstimate;
+//  - To extend an alignment with more of the sequences
+mod extend;
+pub use crate::core::regulators::{LengthMode, LowComplexityFilter, SeedPattern, MinimizerSeeder};
+//  - To change the options of the aligner
+//    Penalties and cutoffs define the workspace, so only the options used at each query can be changed.
+macro_rules! impl_option_setters {
+    ($($aligner:ty),*) => { $(
+        impl_option_setters!(@setters $aligner;
+            set_minimum_anchor_count(count: u32),
+            set_minimum_chain_score(minimum_chain_score: Option<u32>),
+            set_low_complexity_filter(low_complexity_filter: Option<$crate::aligner::LowComplexityFilter>),
+            set_seed_stride(seed_stride: Option<u32>),
+            set_seed_pattern(seed_pattern: Option<$crate::aligner::SeedPattern>),
+            set_minimizer_seeder(minimizer_seeder: Option<$crate::aligner::MinimizerSeeder>),
+            set_max_locations_per_pattern(max_locations_per_pattern: Option<u32>),
+            set_seed_extension_check(seed_extension_check: u32),
+            set_max_merge_mismatches(max_merge_mismatches: u32),
+            set_minimum_anchor_size(minimum_anchor_size: u32),
+            set_exact_chain_middle_size(exact_chain_middle_size: u32),
+            set_minimum_aligned_fraction(minimum_aligned_fraction: Option<f32>),
+            set_length_mode(length_mode: $crate::aligner::LengthMode),
+            set_maximum_penalty(maximum_penalty: Option<u32>),
+            set_ungapped(ungapped: bool)
+        );
+    )* };
+    (@setters $aligner:ty; $($setter:ident($value:ident: $type:ty)),*) => {
+        impl $aligner { $(
+            #[doc = concat!("See `AlignmentRegulator::", stringify!($setter), "`.")]
+            pub fn $setter(&mut self, $value: $type) {
+                self.regulator.$setter($value);
+            }
+        )* }
+    };
+}
 
 /// Executing "local" alignment algorithm.
 pub mod local;
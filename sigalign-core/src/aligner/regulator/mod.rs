@@ -1,10 +1,11 @@
 use crate::core::regulators::{
-    Penalty, PREC_SCALE, Cutoff, MinPenaltyForPattern,
+    Penalty, PREC_SCALE, Cutoff, LengthMode, LowComplexityFilter, SeedPattern, MinimizerSeeder, SeedingOptions, MinPenaltyForPattern,
     calculate_max_pattern_size,
 };
 use crate::results::{
     QueryAlignment, Alignment, TargetAlignment,
 };
+use super::workspace::safe_max_penalty_from_len;
 use thiserror::Error;
 use num::integer::gcd;
 
@@ -15,9 +16,23 @@ pub enum RegulatorError {
     InvalidGapExtendPenalty,
     #[error("Maximum penalty per length only allow positive value.")]
     InvalidMaxPenaltyPerLength,
+    #[error("Maximum penalty per length must be lower than gap extend penalty.")]
+    TooLargeMaxPenaltyPerLength,
+    #[error("Penalties are too large to be scaled by the precision scale.")]
+    TooLargePenalty,
+    #[error("Penalty of the ambiguous base (N) only allow positive integer.")]
+    InvalidNPenalty,
+    #[error("Homopolymer gap discount must not be larger than gap open penalty.")]
+    TooLargeHomopolymerGapDiscount,
+    #[error("Second piece of gap penalty must be costlier for the gap of length 1 and have lower gap extend penalty.")]
+    InvalidTwoPieceGap,
 }
 
 /// Definition for the alignment results.
+///
+/// Internally, the penalties are divided by their GCD and the maximum penalty per length
+/// is stored as an integer multiplied by `PRECISION_SCALE` (then divided by the same GCD).
+/// The penalties of the results are multiplied back by the GCD before they are returned.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct AlignmentRegulator {
     pub(super) penalties: Penalty,
@@ -25,10 +40,20 @@ pub struct AlignmentRegulator {
     pub(super) min_penalty_for_pattern: MinPenaltyForPattern,
     pub(super) gcd_for_compression: u32,
     pub(super) pattern_size: u32,
+    pub(super) seeding_options: SeedingOptions,
+    // Scaled by `PRECISION_SCALE`
+    pub(super) minimum_aligned_fraction: Option<u32>,
+    pub(super) ungapped: bool,
 }
 
 impl AlignmentRegulator {
+    /// Scale to store the maximum penalty per length as an integer.
+    ///  - The maximum penalty per length is truncated below `1 / PRECISION_SCALE`.
+    pub const PRECISION_SCALE: u32 = PREC_SCALE;
+
     /// Generate new aligner.
+    ///  - The gap-open penalty can be 0 for the linear gap penalty (e.g., the edit distance with `(1, 0, 1)`).
+    ///    The gap-extend penalty must be positive.
     pub fn new(
         mismatch_penalty: u32,
         gap_open_penalty: u32,
@@ -36,29 +61,70 @@ impl AlignmentRegulator {
         minimum_alignment_length: u32,
         maximum_penalty_per_alignment_length: f32,
     ) -> Result<Self, RegulatorError> {
+        let (penalties, cutoff) = Self::checked_penalties_and_cutoff(
+            mismatch_penalty, gap_open_penalty, gap_extend_penalty,
+            minimum_alignment_length, maximum_penalty_per_alignment_length,
+        )?;
+        let aligner = Self::new_with_gcd_compressed_from_penalties_and_cutoff(penalties, cutoff);
+        
+        Ok(aligner)
+    }
+    /// Generate new aligner keeping the penalties as provided.
+    ///  - The penalties are not divided by their GCD (the GCD is regarded as 1),
+    ///    so the algorithm uses exactly the given penalties and maximum penalty per length.
+    ///  - The results are the same as the `new`, except the rounding of the maximum penalty per length.
+    ///    This is for the comparison with the other aligners or debugging.
+    pub fn new_without_gcd_compression(
+        mismatch_penalty: u32,
+        gap_open_penalty: u32,
+        gap_extend_penalty: u32,
+        minimum_alignment_length: u32,
+        maximum_penalty_per_alignment_length: f32,
+    ) -> Result<Self, RegulatorError> {
+        let (penalties, cutoff) = Self::checked_penalties_and_cutoff(
+            mismatch_penalty, gap_open_penalty, gap_extend_penalty,
+            minimum_alignment_length, maximum_penalty_per_alignment_length,
+        )?;
+        let aligner = Self::new_from_penalties_and_cutoff(penalties, cutoff, 1);
+
+        Ok(aligner)
+    }
+    fn checked_penalties_and_cutoff(
+        mismatch_penalty: u32,
+        gap_open_penalty: u32,
+        gap_extend_penalty: u32,
+        minimum_alignment_length: u32,
+        maximum_penalty_per_alignment_length: f32,
+    ) -> Result<(Penalty, Cutoff), RegulatorError> {
         if gap_extend_penalty == 0 {
             return Err(RegulatorError::InvalidGapExtendPenalty);
-        } else if maximum_penalty_per_alignment_length <= 0.0 {
+        } else if maximum_penalty_per_alignment_length.is_nan() || maximum_penalty_per_alignment_length <= 0.0 {
             return Err(RegulatorError::InvalidMaxPenaltyPerLength);
         }
+        // The penalties scaled by `PREC_SCALE` are compared in `i32` during the extension
+        let largest_penalty = mismatch_penalty.max(gap_open_penalty.saturating_add(gap_extend_penalty));
+        if largest_penalty as u64 * PREC_SCALE as u64 > i32::MAX as u64 {
+            return Err(RegulatorError::TooLargePenalty);
+        }
 
         let penalties = Penalty::new(mismatch_penalty, gap_open_penalty, gap_extend_penalty);
         let cutoff = Cutoff::new(minimum_alignment_length, maximum_penalty_per_alignment_length);
-        let aligner = Self::new_with_gcd_compressed_from_penalties_and_cutoff(penalties, cutoff);
-        
-        Ok(aligner)
+        // Otherwise, deletion-only alignment of any length satisfies the cutoff
+        //   and the spare penalty can not be bounded.
+        if cutoff.maximum_scaled_penalty_per_length as u64 >= gap_extend_penalty as u64 * PREC_SCALE as u64 {
+            return Err(RegulatorError::TooLargeMaxPenaltyPerLength);
+        }
+        Ok((penalties, cutoff))
     }
     fn new_with_gcd_compressed_from_penalties_and_cutoff(mut penalties: Penalty, mut cutoff: Cutoff) -> Self {
         let gcd = penalties.gcd_of_penalties();
         penalties.divide_by_gcd(gcd);
         cutoff.divide_by_gcd(gcd);
 
-        let min_penalty_for_pattern = MinPenaltyForPattern::new(&penalties);
-        let max_pattern_size = calculate_max_pattern_size(
-            &penalties,
-            &cutoff,
-            &min_penalty_for_pattern,
-        );
+        Self::new_from_penalties_and_cutoff(penalties, cutoff, gcd)
+    }
+    fn new_from_penalties_and_cutoff(penalties: Penalty, cutoff: Cutoff, gcd: u32) -> Self {
+        let (min_penalty_for_pattern, max_pattern_size) = Self::bounds_of_patterns(&penalties, &cutoff);
         
         Self {
             penalties,
@@ -66,17 +132,142 @@ impl AlignmentRegulator {
             min_penalty_for_pattern,
             gcd_for_compression: gcd,
             pattern_size: max_pattern_size,
+            seeding_options: SeedingOptions::default(),
+            minimum_aligned_fraction: None,
+            ungapped: false,
         }
     }
+    fn bounds_of_patterns(penalties: &Penalty, cutoff: &Cutoff) -> (MinPenaltyForPattern, u32) {
+        let penalties_for_bounds = penalties.for_bounds();
+        let min_penalty_for_pattern = MinPenaltyForPattern::new(&penalties_for_bounds);
+        let max_pattern_size = calculate_max_pattern_size(
+            &penalties_for_bounds,
+            cutoff,
+            &min_penalty_for_pattern,
+        );
+        (min_penalty_for_pattern, max_pattern_size)
+    }
     pub(super) fn decompress_result_with_gcd(&self, alignment_result: &mut QueryAlignment) {
         if self.gcd_for_compression != 1 {
             alignment_result.multiply_gcd(self.gcd_for_compression);
         }
     }
+    pub(super) fn decompress_alignments_with_gcd(&self, alignments: &mut [Alignment]) {
+        if self.gcd_for_compression != 1 {
+            alignments.iter_mut().for_each(|v| v.multiply_gcd(self.gcd_for_compression));
+        }
+    }
     /// Get mismatch penalty
     pub fn get_mismatch_penalty(&self) -> u32 {
         self.penalties.x * self.gcd_for_compression
     }
+    /// Get the penalty of the substitution with the ambiguous base (N)
+    pub fn get_n_penalty(&self) -> u32 {
+        self.penalties.n * self.gcd_for_compression
+    }
+    /// Make the regulator with the penalty of the substitution with the ambiguous base (N) in either sequence
+    /// (default: the mismatch penalty).
+    ///  - The sequencing ambiguity is not penalized as much as the mismatch, if lower than the mismatch penalty.
+    ///  - N and N are matched as the same bases.
+    ///  - The pattern size is recalculated with the lower of this and the mismatch penalty,
+    ///    so the lower penalty gives the smaller pattern size.
+    ///  - The GCD for the compression is updated to divide this penalty.
+    ///  - Error if the penalty is 0.
+    ///  - The penalties define the workspace of the aligner, so the regulator is consumed
+    ///    to be given to a new aligner (not changed in the aligner).
+    pub fn with_n_penalty(mut self, n_penalty: u32) -> Result<Self, RegulatorError> {
+        if n_penalty == 0 {
+            return Err(RegulatorError::InvalidNPenalty);
+        }
+        let gcd_for_compression = gcd(self.gcd_for_compression, n_penalty);
+        if gcd_for_compression != self.gcd_for_compression {
+            let factor = self.gcd_for_compression / gcd_for_compression;
+            self.penalties.multiply(factor);
+            self.cutoff.multiply(factor);
+            self.gcd_for_compression = gcd_for_compression;
+        }
+        self.penalties.n = n_penalty / gcd_for_compression;
+        (self.min_penalty_for_pattern, self.pattern_size) = Self::bounds_of_patterns(&self.penalties, &self.cutoff);
+        Ok(self)
+    }
+    /// Get the discount of the gap-open penalty for the gaps extending the homopolymers
+    pub fn get_homopolymer_gap_discount(&self) -> Option<u32> {
+        if self.penalties.h == 0 {
+            None
+        } else {
+            Some(self.penalties.h * self.gcd_for_compression)
+        }
+    }
+    /// Make the regulator with the discount of the gap-open penalty for the gaps extending the homopolymers
+    /// (default: `None`).
+    ///  - The indels in the homopolymer runs are common artifacts of the sequencing (e.g., ion torrent and nanopore).
+    ///  - The gap-open penalty is discounted, if the first gapped base in the direction of the extension
+    ///    is the same as the previous base of the same sequence. The base next to the anchor is not discounted.
+    ///  - Error if the discount is larger than the gap-open penalty.
+    ///  - The pattern size is recalculated with the discounted gap-open penalty.
+    ///  - The GCD for the compression is updated to divide this discount.
+    ///  - The penalties define the workspace of the aligner, so the regulator is consumed
+    ///    to be given to a new aligner (not changed in the aligner).
+    pub fn with_homopolymer_gap_discount(mut self, discount: Option<u32>) -> Result<Self, RegulatorError> {
+        let discount = discount.unwrap_or(0);
+        if discount > self.get_gap_open_penalty() {
+            return Err(RegulatorError::TooLargeHomopolymerGapDiscount);
+        }
+        let gcd_for_compression = gcd(self.gcd_for_compression, discount);
+        if gcd_for_compression != self.gcd_for_compression {
+            let factor = self.gcd_for_compression / gcd_for_compression;
+            self.penalties.multiply(factor);
+            self.cutoff.multiply(factor);
+            self.gcd_for_compression = gcd_for_compression;
+        }
+        self.penalties.h = discount / gcd_for_compression;
+        (self.min_penalty_for_pattern, self.pattern_size) = Self::bounds_of_patterns(&self.penalties, &self.cutoff);
+        Ok(self)
+    }
+    /// Get the gap-open and gap-extend penalties of the second piece of the gap penalty
+    pub fn get_two_piece_gap_penalties(&self) -> Option<(u32, u32)> {
+        self.penalties.second_gap_piece().map(|(gap_open_penalty, gap_extend_penalty)| {
+            (gap_open_penalty * self.gcd_for_compression, gap_extend_penalty * self.gcd_for_compression)
+        })
+    }
+    /// Make the regulator with the second piece of the gap penalty (default: single-piece).
+    ///  - A gap of length `l` is penalized by `min(o + e * l, o2 + e2 * l)` (the two-piece affine gap penalty, as in minimap2),
+    ///    so the long gaps (e.g., of the long reads) are penalized by the lower slope of the second piece.
+    ///  - Error if the second piece is not costlier for the gap of length 1 (`o + e < o2 + e2`)
+    ///    or its gap-extend penalty is not lower than the first (`0 < e2 < e`).
+    ///  - Error if the maximum penalty per length is not lower than the gap-extend penalty of the second piece.
+    ///  - The pattern size and the spare penalties are recalculated with the lower gap-extend penalty,
+    ///    so the second piece gives the smaller pattern size.
+    ///  - The GCD for the compression is updated to divide these penalties.
+    ///  - The penalties define the workspace of the aligner, so the regulator is consumed
+    ///    to be given to a new aligner (not changed in the aligner).
+    pub fn with_two_piece_gap(mut self, gap_open_penalty_2: u32, gap_extend_penalty_2: u32) -> Result<Self, RegulatorError> {
+        let (gap_open_penalty, gap_extend_penalty) = (self.get_gap_open_penalty(), self.get_gap_extend_penalty());
+        if gap_extend_penalty_2 == 0
+            || gap_extend_penalty_2 >= gap_extend_penalty
+            || gap_open_penalty_2.saturating_add(gap_extend_penalty_2) <= gap_open_penalty + gap_extend_penalty
+        {
+            return Err(RegulatorError::InvalidTwoPieceGap);
+        }
+        if gap_open_penalty_2.saturating_add(gap_extend_penalty_2) as u64 * PREC_SCALE as u64 > i32::MAX as u64 {
+            return Err(RegulatorError::TooLargePenalty);
+        }
+        let gcd_for_compression = gcd(gcd(self.gcd_for_compression, gap_open_penalty_2), gap_extend_penalty_2);
+        if gcd_for_compression != self.gcd_for_compression {
+            let factor = self.gcd_for_compression / gcd_for_compression;
+            self.penalties.multiply(factor);
+            self.cutoff.multiply(factor);
+            self.gcd_for_compression = gcd_for_compression;
+        }
+        // Same as in `new`, the deletion-only alignment must not satisfy the cutoff
+        if self.cutoff.maximum_scaled_penalty_per_length as u64 >= (gap_extend_penalty_2 / gcd_for_compression) as u64 * PREC_SCALE as u64 {
+            return Err(RegulatorError::TooLargeMaxPenaltyPerLength);
+        }
+        self.penalties.o2 = gap_open_penalty_2 / gcd_for_compression;
+        self.penalties.e2 = gap_extend_penalty_2 / gcd_for_compression;
+        (self.min_penalty_for_pattern, self.pattern_size) = Self::bounds_of_patterns(&self.penalties, &self.cutoff);
+        Ok(self)
+    }
     /// Get gap-open penalty
     pub fn get_gap_open_penalty(&self) -> u32 {
         self.penalties.o * self.gcd_for_compression
@@ -97,6 +288,239 @@ impl AlignmentRegulator {
     pub fn get_pattern_size(&self) -> u32 {
         self.pattern_size
     }
+    /// Get the maximum length of the query to be aligned at once
+    ///  - The penalties scaled by `PRECISION_SCALE` are compared in `i32` during the extension,
+    ///    and the penalty of the longest possible alignment of the query must not overflow it.
+    ///  - The longer query should be aligned in the windows.
+    pub fn get_maximum_query_length(&self) -> u32 {
+        let penalties = self.penalties.for_bounds();
+        let fits = |query_length: u32| {
+            let max_penalty = safe_max_penalty_from_len(query_length, self.cutoff.maximum_scaled_penalty_per_length, &penalties);
+            max_penalty as u64 * PREC_SCALE as u64 <= i32::MAX as u64
+        };
+        if fits(u32::MAX) {
+            return u32::MAX;
+        }
+        // The maximum penalty increases with the query length
+        let (mut low, mut high) = (0, u32::MAX);
+        while low + 1 < high {
+            let middle = low + (high - low) / 2;
+            if fits(middle) {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        low
+    }
+    // Penalties multiplied back by the GCD (the penalties of the results)
+    pub(crate) fn decompressed_penalties(&self) -> Penalty {
+        let mut penalties = self.penalties.clone();
+        penalties.multiply(self.gcd_for_compression);
+        penalties
+    }
+    /// Get the GCD of penalties used to compress the penalties
+    pub fn get_gcd_for_compression(&self) -> u32 {
+        self.gcd_for_compression
+    }
+    /// Get the penalties divided by the GCD as (mismatch, gap-open, gap-extend)
+    ///  - These are the penalties used in the algorithm.
+    pub fn get_compressed_penalties(&self) -> (u32, u32, u32) {
+        (self.penalties.x, self.penalties.o, self.penalties.e)
+    }
+    /// Get the maximum penalty per length used in the algorithm
+    ///  - Multiplied by `PRECISION_SCALE` and divided by the GCD.
+    pub fn get_compressed_scaled_maximum_penalty_per_length(&self) -> u32 {
+        self.cutoff.maximum_scaled_penalty_per_length
+    }
+    /// Get minimum number of anchors in a target to be extended
+    pub fn get_minimum_anchor_count(&self) -> u32 {
+        self.seeding_options.minimum_anchor_count
+    }
+    /// Set minimum number of anchors in a target to be extended (default: 1).
+    ///  - Targets with fewer anchors (exactly matched patterns) are skipped before the extension.
+    ///  - The alignments that are found with fewer anchors are not reported.
+    pub fn set_minimum_anchor_count(&mut self, count: u32) {
+        self.seeding_options.minimum_anchor_count = count;
+    }
+    /// Get the minimum score of the anchor chains to be extended
+    pub fn get_minimum_chain_score(&self) -> Option<u32> {
+        self.seeding_options.minimum_chain_score
+    }
+    /// Set the minimum score of the anchor chains to be extended (default: `None`, no chaining).
+    ///  - The co-linear anchors of each target are chained before the extension (see `AnchorTable::chain_anchors`).
+    ///    The score is the length of the chained anchors minus the difference of the gaps in the query and the target.
+    ///  - Only the anchors in the chains scored at least this value are extended,
+    ///    so fewer extensions are performed for the targets with many spurious anchors (e.g., long reads).
+    ///  - The alignments that are found only from the anchors out of the chains are not reported.
+    pub fn set_minimum_chain_score(&mut self, minimum_chain_score: Option<u32>) {
+        self.seeding_options.minimum_chain_score = minimum_chain_score;
+    }
+    /// Get the filter of the low-complexity patterns of the query
+    pub fn get_low_complexity_filter(&self) -> Option<LowComplexityFilter> {
+        self.seeding_options.low_complexity_filter
+    }
+    /// Set the filter to skip the patterns in the low-complexity regions of the query (default: `None`, no filter).
+    ///  - Homopolymers and short tandem repeats are located in so many positions that
+    ///    locating and extending them dominate the alignment time.
+    ///  - The alignments that can be anchored only in the low-complexity regions are not reported.
+    pub fn set_low_complexity_filter(&mut self, low_complexity_filter: Option<LowComplexityFilter>) {
+        self.seeding_options.low_complexity_filter = low_complexity_filter;
+    }
+    /// Get the stride of the seeds between the patterns
+    pub fn get_seed_stride(&self) -> Option<u32> {
+        self.seeding_options.seed_stride
+    }
+    /// Set the stride of the overlapping seeds counted toward the minimum anchor count (default: `None`).
+    ///  - The patterns do not overlap, so a cluster of errors in a read can leave it few anchors.
+    ///    The seeds of the pattern size starting at every `seed_stride` bases between the patterns are also located,
+    ///    and the targets with enough anchors and seeds pass the `minimum_anchor_count`.
+    ///  - The extension still starts only from the anchors (an alignment satisfying the cutoff always has one),
+    ///    so the stride changes the results only if the minimum anchor count is larger than 1.
+    ///  - Smaller stride locates more seeds. Ignored if 0 or not smaller than the pattern size.
+    pub fn set_seed_stride(&mut self, seed_stride: Option<u32>) {
+        self.seeding_options.seed_stride = seed_stride;
+    }
+    /// Get the spaced seed of the patterns
+    pub fn get_seed_pattern(&self) -> Option<SeedPattern> {
+        self.seeding_options.seed_pattern
+    }
+    /// Set the spaced seed counted toward the minimum anchor count (default: `None`).
+    ///  - Each pattern is also located with any base at the don't-care positions of the seed,
+    ///    so the reads with periodic errors (e.g., at the third bases of the codons) keep enough seeds.
+    ///  - As the `seed_stride`, it changes the results only if the minimum anchor count is larger than 1.
+    pub fn set_seed_pattern(&mut self, seed_pattern: Option<SeedPattern>) {
+        self.seeding_options.seed_pattern = seed_pattern;
+    }
+    /// Get the minimizer sketch of the patterns
+    pub fn get_minimizer_seeder(&self) -> Option<MinimizerSeeder> {
+        self.seeding_options.minimizer_seeder
+    }
+    /// Set the minimizer sketch to locate only the minimizers of the patterns (default: `None`).
+    ///  - `None` locates every pattern of the query (the exhaustive seeding).
+    ///  - Fewer patterns are located, but the alignments having the exact patterns only in the unselected ones are missed.
+    pub fn set_minimizer_seeder(&mut self, minimizer_seeder: Option<MinimizerSeeder>) {
+        self.seeding_options.minimizer_seeder = minimizer_seeder;
+    }
+    /// Get the maximum number of locations of a pattern to be used as anchors
+    pub fn get_max_locations_per_pattern(&self) -> Option<u32> {
+        self.seeding_options.max_locations_per_pattern
+    }
+    /// Set the maximum number of locations of a pattern to be used as anchors (default: `None`, no limit).
+    ///  - A pattern located in more positions than this (summed over the targets) is skipped entirely,
+    ///    as if it were not located. The seeds of the `seed_stride` are also skipped in the same way.
+    ///  - Repetitive patterns dominate the cost of locating and extending the anchors,
+    ///    but the alignments that can be anchored only by them are missed.
+    pub fn set_max_locations_per_pattern(&mut self, max_locations_per_pattern: Option<u32>) {
+        self.seeding_options.max_locations_per_pattern = max_locations_per_pattern;
+    }
+    /// Get the number of the flanking bases to verify the anchors of a single pattern
+    pub fn get_seed_extension_check(&self) -> u32 {
+        self.seeding_options.seed_extension_check
+    }
+    /// Set the number of the flanking bases to verify the anchors of a single pattern (default: 0, no verification).
+    ///  - An anchor of a single pattern is extended only if this number of bases on both sides also match
+    ///    (clipped at the ends of the sequences). The anchors merged from several patterns are not checked.
+    ///  - The isolated exact matches in the repetitive targets are pruned before the costly extension,
+    ///    but an alignment is missed if all of its anchors are next to the mismatches or the gaps.
+    pub fn set_seed_extension_check(&mut self, seed_extension_check: u32) {
+        self.seeding_options.seed_extension_check = seed_extension_check;
+    }
+    /// Get the maximum number of mismatches between two anchors to be merged
+    pub fn get_max_merge_mismatches(&self) -> u32 {
+        self.seeding_options.max_merge_mismatches
+    }
+    /// Set the maximum number of mismatches between two anchors on the same diagonal to be merged (default: 0, not merged).
+    ///  - A single SNP breaks the pattern containing it, so an exact match is split into two anchors.
+    ///    If the bases between the anchors have at most this number of mismatches, the right anchor is merged to the left.
+    ///  - The merged anchor is not extended by itself, since the extension of the left anchor passes over it
+    ///    (the anchors are the exact matches, so the mismatches are aligned by the extension).
+    pub fn set_max_merge_mismatches(&mut self, max_merge_mismatches: u32) {
+        self.seeding_options.max_merge_mismatches = max_merge_mismatches;
+    }
+    /// Get the minimum length of the exact match around an anchor to be extended
+    pub fn get_minimum_anchor_size(&self) -> u32 {
+        self.seeding_options.minimum_anchor_size
+    }
+    /// Set the minimum length of the exact match around an anchor to be extended (default: 0, no limit).
+    ///  - The size of an anchor is the length of its patterns extended by the matched bases on both sides,
+    ///    so it is not limited to the multiples of the pattern size.
+    ///  - The isolated short matches (e.g., of the short repeats) are dropped before the costly extension,
+    ///    while the anchors in the longer exact matches are kept.
+    ///  - Anchors shorter than this are also not merged by `set_max_merge_mismatches`.
+    pub fn set_minimum_anchor_size(&mut self, minimum_anchor_size: u32) {
+        self.seeding_options.minimum_anchor_size = minimum_anchor_size;
+    }
+    /// Get the maximum size of the middle between two chained anchors to be aligned exactly
+    pub fn get_exact_chain_middle_size(&self) -> u32 {
+        self.seeding_options.exact_chain_middle_size
+    }
+    /// Set the maximum size of the middle between two chained anchors to be aligned exactly (default: 0, estimated).
+    ///  - Only used with `set_minimum_chain_score`. By default, the middle between two anchors is estimated as a single gap,
+    ///    ignoring its mismatches and matched bases, so the anchors far apart across a similar middle are not chained.
+    ///  - The middles not longer than this size in both the query and the target are aligned exactly
+    ///    (see `AnchorTable::chain_anchors_with_exact_middle`), and the longer ones are estimated.
+    ///  - The anchors are chained after the target is buffered, and each middle takes the time of its area,
+    ///    so a large size slows down the chaining of the targets with many anchors.
+    pub fn set_exact_chain_middle_size(&mut self, exact_chain_middle_size: u32) {
+        self.seeding_options.exact_chain_middle_size = exact_chain_middle_size;
+    }
+    /// Get the minimum length as a fraction of the query length
+    pub fn get_minimum_aligned_fraction(&self) -> Option<f32> {
+        self.minimum_aligned_fraction.map(|v| v as f32 / PREC_SCALE as f32)
+    }
+    /// Set the minimum length as a fraction of the query length (default: `None`, only the minimum length).
+    ///  - The minimum length of each query is the larger of the minimum length and `fraction * query length` (rounded up).
+    ///  - The fraction only raises the minimum length, since the pattern size is fixed by the minimum length
+    ///    to guarantee the anchors. Set the minimum length low for the short queries, and the fraction for the long ones.
+    ///  - The fraction is clamped to `[0, 1]`, and truncated below `1 / PRECISION_SCALE`.
+    pub fn set_minimum_aligned_fraction(&mut self, minimum_aligned_fraction: Option<f32>) {
+        self.minimum_aligned_fraction = minimum_aligned_fraction.map(|v| {
+            (v.clamp(0.0, 1.0) * PREC_SCALE as f32) as u32
+        });
+    }
+    // Cutoff with the minimum length derived for the query length
+    pub(super) fn cutoff_for_query(&self, query_length: usize) -> Cutoff {
+        let mut cutoff = self.cutoff.clone();
+        if let Some(minimum_aligned_fraction) = self.minimum_aligned_fraction {
+            let scaled_length = query_length as u64 * minimum_aligned_fraction as u64;
+            let minimum_length = (scaled_length as f64 / PREC_SCALE as f64).ceil() as u32;
+            cutoff.minimum_length = cutoff.minimum_length.max(minimum_length);
+        }
+        cutoff
+    }
+    /// Get the length compared with the minimum length
+    pub fn get_length_mode(&self) -> LengthMode {
+        self.cutoff.length_mode
+    }
+    /// Set the length compared with the minimum length (default: `LengthMode::Total`).
+    ///  - `Total` counts the gaps of both sequences, so the spans of the query and the target are not longer than it.
+    ///  - The other modes only drop more alignments, so no valid alignment is missed by the pattern size.
+    pub fn set_length_mode(&mut self, length_mode: LengthMode) {
+        self.cutoff.length_mode = length_mode;
+    }
+    /// Get the maximum penalty of an alignment regardless of the length
+    pub fn get_maximum_penalty(&self) -> Option<u32> {
+        self.cutoff.maximum_penalty.map(|v| v * self.gcd_for_compression)
+    }
+    /// Set the maximum penalty of an alignment regardless of the length (default: `None`, no limit).
+    ///  - The penalty is also limited by the maximum penalty per length, so this only matters for the long alignments.
+    ///  - The extension stops when the penalty exceeds this value, so the long alignments of the diverged sequences are not reported.
+    ///  - The value is compressed by the GCD of the penalties (rounded down).
+    pub fn set_maximum_penalty(&mut self, maximum_penalty: Option<u32>) {
+        self.cutoff.maximum_penalty = maximum_penalty.map(|v| v / self.gcd_for_compression);
+    }
+    /// Get whether the anchors are extended without gaps
+    pub fn get_ungapped(&self) -> bool {
+        self.ungapped
+    }
+    /// Extend the anchors only with the mismatches (default: false).
+    ///  - Much faster than the gapped extension, when gaps are rare (e.g., high-identity short reads).
+    ///  - Semi-global: the alignments are the same as the gapped ones if their optimal alignments have no gap.
+    ///  - Local: the longest ungapped range containing each anchor that satisfies the cutoff is reported.
+    pub fn set_ungapped(&mut self, ungapped: bool) {
+        self.ungapped = ungapped;
+    }
 }
 
 impl QueryAlignment {
@@ -129,15 +553,32 @@ impl Penalty {
             x: mismatch,
             o: gap_open,
             e: gap_extend,
+            n: mismatch,
+            h: 0,
+            o2: gap_open,
+            e2: gap_extend,
         }
     }
     fn gcd_of_penalties(&self) -> u32 {
-        gcd(gcd(self.x, self.o), self.e)
+        gcd(gcd(gcd(gcd(gcd(gcd(self.x, self.o), self.e), self.n), self.h), self.o2), self.e2)
     }
     fn divide_by_gcd(&mut self, gcd: u32) {
         self.x /= gcd;
         self.o /= gcd;
         self.e /= gcd;
+        self.n /= gcd;
+        self.h /= gcd;
+        self.o2 /= gcd;
+        self.e2 /= gcd;
+    }
+    fn multiply(&mut self, factor: u32) {
+        self.x *= factor;
+        self.o *= factor;
+        self.e *= factor;
+        self.n *= factor;
+        self.h *= factor;
+        self.o2 *= factor;
+        self.e2 *= factor;
     }
 }
 
@@ -150,11 +591,17 @@ impl Cutoff {
         Self {
             minimum_length,
             maximum_scaled_penalty_per_length: maximum_penalty_per_scale,
+            length_mode: LengthMode::default(),
+            maximum_penalty: None,
         }
     }
     fn divide_by_gcd(&mut self, gcd: u32) {
         self.maximum_scaled_penalty_per_length /= gcd;
     }
+    fn multiply(&mut self, factor: u32) {
+        self.maximum_scaled_penalty_per_length *= factor;
+        self.maximum_penalty = self.maximum_penalty.map(|v| v * factor);
+    }
 }
 
 #[cfg(test)]
@@ -176,6 +623,79 @@ mod tests {
         assert_eq!(penalties, Penalty::new(4, 5, 3));
     }
 
+    #[test]
+    fn test_compressed_values_of_regulator() {
+        let regulator = AlignmentRegulator::new(4, 6, 2, 50, 0.1).unwrap();
+        assert_eq!(regulator.get_gcd_for_compression(), 2);
+        assert_eq!(regulator.get_compressed_penalties(), (2, 3, 1));
+        assert_eq!(
+            regulator.get_compressed_scaled_maximum_penalty_per_length(),
+            (0.1 * AlignmentRegulator::PRECISION_SCALE as f32) as u32 / 2,
+        );
+        assert_eq!(
+            (regulator.get_mismatch_penalty(), regulator.get_gap_open_penalty(), regulator.get_gap_extend_penalty()),
+            (4, 6, 2),
+        );
+    }
+
+    #[test]
+    fn max_penalty_per_length_not_lower_than_gap_extend_is_rejected() {
+        assert!(matches!(
+            AlignmentRegulator::new(5, 7, 1, 100, 1.0),
+            Err(RegulatorError::TooLargeMaxPenaltyPerLength),
+        ));
+        assert!(matches!(
+            AlignmentRegulator::new(4, 6, 2, 100, 2.5),
+            Err(RegulatorError::TooLargeMaxPenaltyPerLength),
+        ));
+        assert!(AlignmentRegulator::new(5, 7, 1, 100, 0.99).is_ok());
+    }
+
+    #[test]
+    fn penalties_are_kept_without_gcd_compression() {
+        let compressed = AlignmentRegulator::new(4, 6, 2, 50, 0.1).unwrap();
+        let regulator = AlignmentRegulator::new_without_gcd_compression(4, 6, 2, 50, 0.1).unwrap();
+        assert_eq!(regulator.get_gcd_for_compression(), 1);
+        assert_eq!(regulator.get_compressed_penalties(), (4, 6, 2));
+        assert_eq!(
+            regulator.get_compressed_scaled_maximum_penalty_per_length(),
+            (0.1 * AlignmentRegulator::PRECISION_SCALE as f32) as u32,
+        );
+        assert_eq!(regulator.get_maximum_penalty_per_length(), compressed.get_maximum_penalty_per_length());
+        assert_eq!(regulator.get_pattern_size(), compressed.get_pattern_size());
+        assert!(matches!(
+            AlignmentRegulator::new_without_gcd_compression(4, 6, 0, 50, 0.1),
+            Err(RegulatorError::InvalidGapExtendPenalty),
+        ));
+    }
+
+    #[test]
+    fn two_piece_gap_bounds_the_patterns_with_the_lower_slope() {
+        let regulator = AlignmentRegulator::new(4, 6, 2, 100, 0.1).unwrap();
+        assert_eq!(regulator.get_two_piece_gap_penalties(), None);
+        for (gap_open_penalty_2, gap_extend_penalty_2) in [(24, 0), (24, 2), (24, 3), (6, 1), (7, 1)] {
+            assert!(matches!(
+                regulator.clone().with_two_piece_gap(gap_open_penalty_2, gap_extend_penalty_2),
+                Err(RegulatorError::InvalidTwoPieceGap),
+            ));
+        }
+        assert!(matches!(
+            AlignmentRegulator::new(4, 6, 2, 100, 1.5).unwrap().with_two_piece_gap(24, 1),
+            Err(RegulatorError::TooLargeMaxPenaltyPerLength),
+        ));
+
+        let two_piece = regulator.clone().with_two_piece_gap(24, 1).unwrap();
+        assert_eq!(two_piece.get_two_piece_gap_penalties(), Some((24, 1)));
+        assert_eq!(two_piece.get_compressed_penalties(), (4, 6, 2));
+        assert_eq!(two_piece.get_gcd_for_compression(), 1);
+        assert_eq!(two_piece.get_maximum_penalty_per_length(), regulator.get_maximum_penalty_per_length());
+        // Bounded as the single-piece with the lower gap-extend penalty
+        let lower_slope = AlignmentRegulator::new(4, 6, 1, 100, 0.1).unwrap();
+        assert_eq!(two_piece.min_penalty_for_pattern, lower_slope.min_penalty_for_pattern);
+        assert_eq!(two_piece.get_pattern_size(), lower_slope.get_pattern_size());
+        assert!(two_piece.get_pattern_size() <= regulator.get_pattern_size());
+    }
+
     #[allow(dead_code)]
     fn print_calculate_maximum_kmer() {
         let penalties = Penalty::new(4, 6, 2);
@@ -188,4 +708,48 @@ mod tests {
         );
         println!("{}", pattern_size);
     }
+    #[test]
+    fn maximum_query_length_is_at_the_overflow_boundary() {
+        let regulator = AlignmentRegulator::new(4, 6, 2, 100, 0.1).unwrap();
+        let maximum_query_length = regulator.get_maximum_query_length();
+        let scaled_max_penalty = |query_length| {
+            safe_max_penalty_from_len(
+                query_length, regulator.cutoff.maximum_scaled_penalty_per_length, &regulator.penalties.for_bounds(),
+            ) as u64 * PREC_SCALE as u64
+        };
+        assert!(scaled_max_penalty(maximum_query_length) <= i32::MAX as u64);
+        assert!(scaled_max_penalty(maximum_query_length + 1) > i32::MAX as u64);
+        // Hundreds of kilobases
+        assert!(maximum_query_length > 100_000 && maximum_query_length < 1_000_000);
+        // Not overflowed for any query length
+        assert!(scaled_max_penalty(u32::MAX) > scaled_max_penalty(maximum_query_length));
+
+        assert!(matches!(AlignmentRegulator::new(4, 6, 2, 100, f32::NAN), Err(RegulatorError::InvalidMaxPenaltyPerLength)));
+        assert!(matches!(AlignmentRegulator::new(4, 30_000, 2, 100, 0.1), Err(RegulatorError::TooLargePenalty)));
+        assert!(matches!(AlignmentRegulator::new(4, 6, 2, 100, f32::INFINITY), Err(RegulatorError::TooLargeMaxPenaltyPerLength)));
+    }
+    #[test]
+    fn n_penalty_is_set_on_the_new_regulator() {
+        let regulator = AlignmentRegulator::new(4, 6, 2, 100, 0.1).unwrap();
+        assert!(matches!(regulator.clone().with_n_penalty(0), Err(RegulatorError::InvalidNPenalty)));
+        let regulator = regulator.with_n_penalty(1).unwrap();
+        assert_eq!(regulator.get_n_penalty(), 1);
+        assert_eq!(
+            (regulator.get_mismatch_penalty(), regulator.get_gap_open_penalty(), regulator.get_gap_extend_penalty()),
+            (4, 6, 2),
+        );
+        assert_eq!(regulator.gcd_for_compression, 1);
+    }
+    #[test]
+    fn homopolymer_gap_discount_is_not_larger_than_gap_open_penalty() {
+        let regulator = AlignmentRegulator::new(4, 6, 2, 100, 0.1).unwrap();
+        assert!(matches!(
+            regulator.clone().with_homopolymer_gap_discount(Some(7)),
+            Err(RegulatorError::TooLargeHomopolymerGapDiscount),
+        ));
+        let regulator = regulator.with_homopolymer_gap_discount(Some(6)).unwrap();
+        assert_eq!(regulator.get_homopolymer_gap_discount(), Some(6));
+        let regulator = regulator.with_homopolymer_gap_discount(None).unwrap();
+        assert_eq!(regulator.get_homopolymer_gap_discount(), None);
+    }
 }
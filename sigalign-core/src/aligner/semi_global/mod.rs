@@ -8,4 +8,6 @@ pub use semi_global_unlimited::SemiGlobalAligner;
 mod semi_global_with_limit;
 pub use semi_global_with_limit::SemiGlobalWithLimitAligner;
 
+impl_option_setters!(SemiGlobalAligner, SemiGlobalWithLimitAligner);
+
 mod switch_modes;
\ No newline at end of file
@@ -24,8 +24,9 @@ impl SemiGlobalWorkspace {
         let query_length_checker = QueryLengthChecker::new(allocation_strategy);
         let initial_query_length = query_length_checker.get_allocated_length();
 
+        // The spare penalties and the wave fronts are bounded with the cheapest penalties
         let spare_penalty_calculator = SparePenaltyCalculator::new(
-            &regulator.penalties,
+            &regulator.penalties.for_bounds(),
             regulator.cutoff.maximum_scaled_penalty_per_length,
             regulator.pattern_size,
             initial_query_length / regulator.pattern_size,
@@ -11,3 +11,4 @@ pub use allocation_strategy::{DefaultLinearStrategy, DefaultDoublingStrategy};
 // Wave Front
 mod wave_front_buffer;
 pub use wave_front_buffer::WaveFrontBuffer;
+pub(crate) use wave_front_buffer::safe_max_penalty_from_len;
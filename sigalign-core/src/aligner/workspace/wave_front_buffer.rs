@@ -13,12 +13,13 @@ impl AsMut<WaveFront> for WaveFrontBuffer {
 }
 
 impl WaveFrontBuffer {
+    // The penalties are not the bounds, since the components of the second gap piece are allocated only if two-piece.
     pub fn new(
         query_length: u32,
         maximum_scaled_penalty_per_length: u32,
         penalties: &Penalty,
     ) -> Self {
-        let max_penalty = safe_max_penalty_from_len(query_length, maximum_scaled_penalty_per_length, penalties);
+        let max_penalty = safe_max_penalty_from_len(query_length, maximum_scaled_penalty_per_length, &penalties.for_bounds());
         let wave_front = WaveFront::new_allocated(penalties, max_penalty as usize);
         Self(wave_front)
     }
@@ -28,7 +29,7 @@ impl WaveFrontBuffer {
         maximum_scaled_penalty_per_length: u32,
         penalties: &Penalty,
     ) {
-        let max_penalty = safe_max_penalty_from_len(query_length, maximum_scaled_penalty_per_length, penalties);
+        let max_penalty = safe_max_penalty_from_len(query_length, maximum_scaled_penalty_per_length, &penalties.for_bounds());
         // TODO: not to allocate whole space.
         let wave_front = WaveFront::new_allocated(penalties, max_penalty as usize);
         self.0 = wave_front;
@@ -36,20 +37,22 @@ impl WaveFrontBuffer {
 }
 
 #[inline(always)]
-fn safe_max_penalty_from_len(
+pub(crate) fn safe_max_penalty_from_len(
     query_len: u32,
     maximum_scaled_penalty_per_length: u32,
     penalties: &Penalty,
 ) -> u32 {
+    // In u64 not to overflow for the long queries
+    let max_penalty = (
+        maximum_scaled_penalty_per_length as u64 * (
+            (penalties.e as u64 * query_len as u64).saturating_sub(penalties.o as u64)
+        )
+    ) / (
+        PREC_SCALE as u64 * penalties.e as u64 - maximum_scaled_penalty_per_length as u64
+    ) + 1;
     u32::max(
         penalties.o,
-        (
-            maximum_scaled_penalty_per_length * (
-                penalties.e * query_len - penalties.o
-            )
-        ) / (
-            PREC_SCALE * penalties.e - maximum_scaled_penalty_per_length
-        ) + 1
+        u32::try_from(max_penalty).unwrap_or(u32::MAX),
     )
 }
 
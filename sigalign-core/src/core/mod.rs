@@ -5,11 +5,24 @@ pub mod regulators;
 /// This trait serves two main purposes:
 ///   - It provides pattern locations for algorithms.
 ///   - It can help the algorithm can be defined without knowing the exact type of the `Reference` struct.
+///
+/// `Reference` implements this trait, but aligners accept any implementation.
+/// e.g., an index on the disk that does not fit in memory can be used directly.
 pub trait BufferedPatternLocator {
     type Buffer: SequenceBuffer;
 
+    /// Locate the pattern in the targets of `sorted_target_indices`.
+    ///  - Only the positions where the whole pattern is inside of the target are returned.
+    ///  - Positions are relative to the start of each target.
     fn locate(&self, pattern: &[u8], sorted_target_indices: &[u32]) -> Vec<PatternLocation>;
+    /// Fill the buffer with the whole sequence of the target.
     fn fill_buffer(&self, target_index: u32, buffer: &mut Self::Buffer);
+    /// Whether an anchor can be seeded by the pattern at the position of the target (default: always).
+    ///  - The unseedable patterns are still located, so the extensions can pass through them,
+    ///    but the anchors made only of the unseedable patterns are not extended.
+    fn is_seedable(&self, _target_index: u32, _position: u32, _pattern_size: u32) -> bool {
+        true
+    }
 }
 
 pub trait SequenceBuffer {
@@ -18,12 +31,13 @@ pub trait SequenceBuffer {
 
 /// `PatternLocation` holds the index of a pattern within a target.
 /// 
-/// The positions within `PatternLocation` should be sorted in ascending order. In general,
-/// these positions are automatically sorted when searching for an index within a target.
-/// **Note that the algorithm does not perform reordering**.
+/// The positions within `PatternLocation` are expected to be sorted in ascending order.
+/// The order of the positions located by an index is not guaranteed (e.g., FM-index returns them
+/// in the order of the suffix array), so the algorithm sorts the positions that are not sorted
+/// before making the anchors. This makes the anchors the same regardless of the index implementation.
 /// 
 /// Each position's value is restricted to the bounds of a `u32`, limiting the range of each position.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PatternLocation {
     pub target_index: u32,
     pub sorted_positions: Vec<u32>,
@@ -0,0 +1,108 @@
+/// DUST-like filter to skip the patterns in the low-complexity regions of the query (e.g., homopolymers).
+///  - The score of a window is `sum(c * (c - 1) / 2) / (l - 1)`,
+///    where `c` is the count of each triplet and `l` is the number of triplets in the window.
+///  - The bases in the windows scored higher than `threshold` are masked.
+///  - The patterns entirely in the masked bases are not located, so they can not be anchors.
+///    They are still aligned when the extension from the other anchors passes through them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LowComplexityFilter {
+    pub window_size: u32,
+    pub threshold: u32,
+}
+
+impl Default for LowComplexityFilter {
+    // Same as the defaults of `dustmasker`
+    fn default() -> Self {
+        Self {
+            window_size: 64,
+            threshold: 20,
+        }
+    }
+}
+
+impl LowComplexityFilter {
+    pub fn new(window_size: u32, threshold: u32) -> Self {
+        Self { window_size, threshold }
+    }
+    /// Get whether each pattern of the query is masked.
+    pub fn masked_patterns(&self, query: &[u8], pattern_size: u32) -> Vec<bool> {
+        let pattern_size = pattern_size as usize;
+        let masked_bases = self.masked_bases(query);
+        masked_bases.chunks_exact(pattern_size).map(|bases| {
+            bases.iter().all(|masked| *masked)
+        }).collect()
+    }
+    /// Get whether each base of the query is masked.
+    pub fn masked_bases(&self, query: &[u8]) -> Vec<bool> {
+        let mut masked_bases = vec![false; query.len()];
+        let window_size = (self.window_size as usize).min(query.len());
+        if window_size < 3 {
+            return masked_bases;
+        }
+        let triplets: Vec<Option<usize>> = query.windows(3).map(triplet_code).collect();
+        let triplet_count_in_window = window_size - 2;
+        let threshold_sum = self.threshold as u64 * (triplet_count_in_window as u64 - 1);
+
+        let mut counts = [0_u64; 64];
+        // Sum of `c * (c - 1) / 2`
+        let mut sum = 0;
+        // Bases before this are already masked
+        let mut masked_until = 0;
+        for (index, triplet) in triplets.iter().enumerate() {
+            if let Some(code) = triplet {
+                sum += counts[*code];
+                counts[*code] += 1;
+            }
+            if index >= triplet_count_in_window {
+                if let Some(code) = triplets[index - triplet_count_in_window] {
+                    counts[code] -= 1;
+                    sum -= counts[code];
+                }
+            }
+            if (index + 1 >= triplet_count_in_window) && (sum > threshold_sum) {
+                let window_start = index + 1 - triplet_count_in_window;
+                let window_end = index + 3;
+                masked_bases[window_start.max(masked_until)..window_end].iter_mut().for_each(|v| *v = true);
+                masked_until = window_end;
+            }
+        }
+        masked_bases
+    }
+}
+
+// None if any base is not a nucleotide
+fn triplet_code(triplet: &[u8]) -> Option<usize> {
+    triplet.iter().try_fold(0, |code, base| {
+        let base_code = match base {
+            b'A' | b'a' => 0,
+            b'C' | b'c' => 1,
+            b'G' | b'g' => 2,
+            b'T' | b't' => 3,
+            _ => return None,
+        };
+        Some(code * 4 + base_code)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TEST_TARGET;
+
+    #[test]
+    fn only_low_complexity_patterns_are_masked() {
+        let unit = TEST_TARGET;
+        let filter = LowComplexityFilter::default();
+        assert!(filter.masked_patterns(unit, 10).iter().all(|masked| !masked));
+        assert!(filter.masked_patterns(&[b'A'; 100], 10).iter().all(|masked| *masked));
+        // Homopolymer in the middle
+        let query = [&unit[..], &[b'A'; 80], &unit[..]].concat();
+        let masked = filter.masked_patterns(&query, 10);
+        assert!(!masked[0] && !masked[masked.len() - 1]);
+        assert!(masked[9] && masked[12]);
+        // Lower threshold masks the dinucleotide repeat
+        let dinucleotide_repeat = b"AC".repeat(40);
+        assert!(!filter.masked_patterns(&dinucleotide_repeat, 10).iter().any(|masked| *masked));
+        assert!(LowComplexityFilter::new(64, 10).masked_patterns(&dinucleotide_repeat, 10).iter().all(|masked| *masked));
+    }
+}
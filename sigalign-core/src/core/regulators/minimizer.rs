@@ -0,0 +1,74 @@
+/// Minimizer sketch of the patterns: only the patterns selected as the minimizers are located.
+///  - In each window of `window_size` consecutive patterns of the query, the pattern with the smallest hash is selected
+///    (the leftmost one, if tied). The query shorter than a window selects one pattern.
+///  - The hash depends only on the pattern, so the queries sharing a region select the same patterns in it.
+///  - At least one in `window_size` consecutive patterns is located, and about `2 / (window_size + 1)` of the patterns in average.
+///  - The patterns not selected are not anchors. They are still aligned when the extension from the other anchors passes through them,
+///    but the alignments having the exact patterns only in the unselected ones are missed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MinimizerSeeder {
+    pub window_size: u32,
+}
+
+impl MinimizerSeeder {
+    pub fn new(window_size: u32) -> Self {
+        Self { window_size }
+    }
+    /// Get whether each pattern of the query is selected to be located.
+    ///  - All patterns are selected if `window_size` is not larger than 1 (same as the exhaustive seeding).
+    pub fn selected_patterns(&self, query: &[u8], pattern_size: u32) -> Vec<bool> {
+        let hashes: Vec<u64> = query.chunks_exact(pattern_size as usize).map(hash_of_pattern).collect();
+        if self.window_size <= 1 {
+            return vec![true; hashes.len()];
+        }
+        let mut selected = vec![false; hashes.len()];
+        let window_size = (self.window_size as usize).min(hashes.len());
+        if window_size == 0 {
+            return selected;
+        }
+        hashes.windows(window_size).enumerate().for_each(|(window_start, window)| {
+            let (offset, _) = window.iter().enumerate().fold((0, u64::MAX), |(min_offset, min_hash), (offset, hash)| {
+                if *hash < min_hash { (offset, *hash) } else { (min_offset, min_hash) }
+            });
+            selected[window_start + offset] = true;
+        });
+        selected
+    }
+}
+
+// FNV-1a mixed by the finalizer of MurmurHash3, to spread the similar patterns
+fn hash_of_pattern(pattern: &[u8]) -> u64 {
+    let mut hash = pattern.iter().fold(0xcbf29ce484222325_u64, |hash, base| {
+        (hash ^ *base as u64).wrapping_mul(0x100000001b3)
+    });
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51afd7ed558ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ceb9fe1a85ec53);
+    hash ^ (hash >> 33)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TEST_TARGET;
+
+    #[test]
+    fn every_window_has_a_selected_pattern() {
+        let unit = TEST_TARGET;
+        let query = [&unit[..], &unit[..60], &unit[10..]].concat();
+        let seeder = MinimizerSeeder::new(4);
+        let selected = seeder.selected_patterns(&query, 5);
+        assert_eq!(selected.len(), query.len() / 5);
+        assert!(selected.windows(4).all(|window| window.iter().any(|v| *v)));
+        assert!(selected.iter().filter(|v| **v).count() < selected.len() / 2);
+        // Same selection of the shared patterns in the shifted query
+        let shifted = seeder.selected_patterns(&query[10..], 5);
+        assert_eq!(&selected[5..], &shifted[3..]);
+        // Exhaustive
+        assert!(MinimizerSeeder::new(1).selected_patterns(&query, 5).iter().all(|v| *v));
+        // Shorter than a window
+        assert_eq!(seeder.selected_patterns(&unit[..10], 5).iter().filter(|v| **v).count(), 1);
+        assert!(seeder.selected_patterns(&unit[..4], 5).is_empty());
+    }
+}
@@ -1,6 +1,16 @@
 //! Alignment regulators
 pub mod pattern_size;
 pub use pattern_size::calculate_max_pattern_size;
+mod low_complexity;
+pub use low_complexity::LowComplexityFilter;
+mod seed_pattern;
+pub use seed_pattern::SeedPattern;
+mod minimizer;
+pub use minimizer::MinimizerSeeder;
+mod seeding;
+pub use seeding::SeedingOptions;
+
+use crate::results::{AlignmentOperation, AlignmentOperations};
 
 pub const PREC_SCALE: u32 = 100_000; // Ensuring accuracy to the fourth decimal place.
 
@@ -9,12 +19,149 @@ pub struct Penalty {
     pub x: u32,
     pub o: u32,
     pub e: u32,
+    /// Substitution with the ambiguous base (N) in either sequence
+    pub n: u32,
+    /// Discount of the gap-open penalty for the gap extending a homopolymer (0 if not discounted)
+    ///  - Not larger than the gap-open penalty.
+    pub h: u32,
+    /// Gap-open and gap-extend penalties of the second piece of the gap penalty (same as `o` and `e` if single-piece)
+    ///  - A gap is penalized by the cheaper of the two pieces (`min(o + e * l, o2 + e2 * l)`).
+    ///  - The homopolymer gap discount is applied only to the first piece.
+    pub o2: u32,
+    pub e2: u32,
+}
+
+impl Penalty {
+    /// Penalty of aligning two bases (0 if matched)
+    #[inline]
+    pub fn of_substitution(&self, query_base: u8, target_base: u8) -> u32 {
+        if query_base == target_base {
+            0
+        } else if is_ambiguous_base(query_base) || is_ambiguous_base(target_base) {
+            self.n
+        } else {
+            self.x
+        }
+    }
+    /// Penalty of a gap of the length
+    ///  - The cheaper of the two pieces of the gap penalty.
+    #[inline]
+    pub fn of_gap(&self, length: u32) -> u32 {
+        (self.o + self.e * length).min(self.o2 + self.e2 * length)
+    }
+    /// Gap-open and gap-extend penalties of the second piece, if different from the first
+    #[inline]
+    pub fn second_gap_piece(&self) -> Option<(u32, u32)> {
+        if (self.o2, self.e2) != (self.o, self.e) {
+            Some((self.o2, self.e2))
+        } else {
+            None
+        }
+    }
+    /// Penalty of the operations without the sequences
+    ///  - Each substitution is penalized `x`, since the ambiguous bases are not known from the operations.
+    ///  - Consecutive runs of the same gap (e.g., two deletion runs in a row) are one gap opened once.
+    ///    The insertion next to the deletion is another gap.
+    ///  - The homopolymer gap discount is not applied, and the clipped bases are not penalized.
+    pub fn of_operations(&self, operations: &[AlignmentOperations]) -> u32 {
+        let mut penalty = 0;
+        // Operation and length of the gap not penalized yet
+        let mut gap: Option<(&AlignmentOperation, u32)> = None;
+        for operations in operations.iter().filter(|operations| operations.count != 0) {
+            let operation = &operations.operation;
+            match operation {
+                AlignmentOperation::Insertion | AlignmentOperation::Deletion => match &mut gap {
+                    Some((gap_operation, length)) if *gap_operation == operation => {
+                        *length += operations.count;
+                    },
+                    _ => {
+                        penalty += gap.map_or(0, |(_, length)| self.of_gap(length));
+                        gap = Some((operation, operations.count));
+                    },
+                },
+                AlignmentOperation::Match | AlignmentOperation::Subst => {
+                    penalty += gap.take().map_or(0, |(_, length)| self.of_gap(length));
+                    if *operation == AlignmentOperation::Subst {
+                        penalty += self.x * operations.count;
+                    }
+                },
+            }
+        }
+        penalty + gap.map_or(0, |(_, length)| self.of_gap(length))
+    }
+    /// Penalties to bound the alignments (pattern size and spare penalties)
+    ///  - The cheaper of the substitutions is regarded as the mismatch.
+    ///  - The discounted gap-open penalty is regarded as the gap-open penalty.
+    ///  - The cheaper of the two pieces of the gap penalty is taken for each of the gap-open and the gap-extend penalties,
+    ///    so the bounds are single-piece and hold for the gaps of any length.
+    pub fn for_bounds(&self) -> Self {
+        let o = (self.o - self.h).min(self.o2);
+        let e = self.e.min(self.e2);
+        Self {
+            x: self.x.min(self.n),
+            o,
+            e,
+            n: self.n,
+            h: 0,
+            o2: o,
+            e2: e,
+        }
+    }
+}
+
+#[inline(always)]
+pub fn is_ambiguous_base(base: u8) -> bool {
+    matches!(base, b'N' | b'n')
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Cutoff {
     pub minimum_length: u32,
     pub maximum_scaled_penalty_per_length: u32,
+    pub length_mode: LengthMode,
+    /// Hard limit of the penalty regardless of the length (divided by the GCD)
+    pub maximum_penalty: Option<u32>,
+}
+
+/// The length compared with the minimum length of the cutoff.
+///  - The maximum penalty per length is always checked with the `Total` length.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LengthMode {
+    /// Length of the alignment including the gaps (= the sum of the operation counts).
+    #[default]
+    Total,
+    /// Length of the aligned range of the query.
+    QuerySpan,
+    /// Length of the aligned range of the target.
+    TargetSpan,
+}
+
+impl Cutoff {
+    #[inline]
+    pub fn is_long_enough(&self, alignment_length: u32, query_span: u32, target_span: u32) -> bool {
+        let length = match self.length_mode {
+            LengthMode::Total => alignment_length,
+            LengthMode::QuerySpan => query_span,
+            LengthMode::TargetSpan => target_span,
+        };
+        length >= self.minimum_length
+    }
+    #[inline]
+    pub fn is_under_maximum_penalty(&self, penalty: u32) -> bool {
+        match self.maximum_penalty {
+            Some(maximum_penalty) => penalty <= maximum_penalty,
+            None => true,
+        }
+    }
+    /// Limit the spare penalty of the extension to the maximum penalty
+    ///  - `used_penalty`: penalty already used by the other side of the extension
+    #[inline]
+    pub fn cap_spare_penalty(&self, spare_penalty: u32, used_penalty: u32) -> u32 {
+        match self.maximum_penalty {
+            Some(maximum_penalty) => spare_penalty.min(maximum_penalty.saturating_sub(used_penalty)),
+            None => spare_penalty,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -235,7 +235,7 @@ fn penalty_and_length_are_not_touch_the_cutoff_line(
     length: u32,
     cutoff: &Cutoff,
 ) -> bool {
-    if penalty * PREC_SCALE > cutoff.maximum_scaled_penalty_per_length * length {
+    if penalty as u64 * PREC_SCALE as u64 > cutoff.maximum_scaled_penalty_per_length as u64 * length as u64 {
         true
     } else {
         false
@@ -273,9 +273,9 @@ fn calculate_max_pattern_size_without_panic() {
             for &pe in pe.iter() {
                 for &minl in minl.iter() {
                     for &maxp in maxp.iter() {
-                        let penalties = Penalty { x: px, o: po, e: pe };
+                        let penalties = Penalty { x: px, o: po, e: pe, n: px, h: 0, o2: po, e2: pe };
                         let min_penalty_for_pattern = MinPenaltyForPattern::new(&penalties);
-                        let cutoff = Cutoff { minimum_length: minl, maximum_scaled_penalty_per_length: (maxp * PREC_SCALE as f32) as u32 };
+                        let cutoff = Cutoff { minimum_length: minl, maximum_scaled_penalty_per_length: (maxp * PREC_SCALE as f32) as u32, length_mode: super::LengthMode::Total, maximum_penalty: None };
                         let _ = calculate_max_pattern_size(
                             &penalties,
                             &cutoff, &min_penalty_for_pattern,
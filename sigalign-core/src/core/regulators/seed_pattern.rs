@@ -0,0 +1,88 @@
+/// Spaced seed: mask of the pattern with the don't-care positions.
+///  - The mask is aligned to the start of each pattern. The positions beyond the mask must match.
+///  - The patterns are also located with any base at the don't-care positions,
+///    so the patterns with mismatches at the fixed positions (e.g., the third bases of the codons) can be found.
+///  - The patterns located only by the spaced seed are not anchors, since the extension needs the exact matches on the patterns.
+///    They count toward the minimum anchor count, like the seeds of the `seed_stride`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SeedPattern {
+    // Bit `i` is set if the position `i` is don't-care
+    dont_care_mask: u32,
+}
+
+impl SeedPattern {
+    /// Maximum length of the mask.
+    pub const MAX_MASK_LENGTH: usize = 32;
+    /// Maximum count of the don't-care positions (each pattern is located `4^count` times).
+    pub const MAX_DONT_CARE_COUNT: u32 = 4;
+
+    /// Make a spaced seed from the mask (e.g., `"110110"`).
+    ///  - `1` is the position to match, and `0` is don't-care.
+    ///  - None if the mask has other characters, is longer than `MAX_MASK_LENGTH`,
+    ///    or has no don't-care position or more than `MAX_DONT_CARE_COUNT`.
+    pub fn new(mask: &str) -> Option<Self> {
+        if mask.len() > Self::MAX_MASK_LENGTH {
+            return None;
+        }
+        let mut dont_care_mask: u32 = 0;
+        for (position, character) in mask.chars().enumerate() {
+            match character {
+                '1' => {},
+                '0' => dont_care_mask |= 1 << position,
+                _ => return None,
+            }
+        }
+        let dont_care_count = dont_care_mask.count_ones();
+        if dont_care_count == 0 || dont_care_count > Self::MAX_DONT_CARE_COUNT {
+            return None;
+        }
+        Some(Self { dont_care_mask })
+    }
+    /// Get the don't-care positions in the pattern.
+    pub fn dont_care_positions(&self, pattern_size: u32) -> Vec<usize> {
+        (0..(pattern_size as usize).min(Self::MAX_MASK_LENGTH)).filter(|position| {
+            self.dont_care_mask & (1 << position) != 0
+        }).collect()
+    }
+    /// Get the variants of the pattern with the other bases at the don't-care positions.
+    ///  - The pattern itself is not included.
+    pub fn variants(&self, pattern: &[u8]) -> Vec<Vec<u8>> {
+        const BASES: [u8; 4] = [b'A', b'C', b'G', b'T'];
+        let dont_care_positions = self.dont_care_positions(pattern.len() as u32);
+        let mut variants = vec![pattern.to_vec()];
+        dont_care_positions.iter().for_each(|&position| {
+            variants = variants.iter().flat_map(|variant| {
+                BASES.iter().map(move |&base| {
+                    let mut new_variant = variant.clone();
+                    new_variant[position] = base;
+                    new_variant
+                })
+            }).collect();
+        });
+        variants.retain(|variant| variant != pattern);
+        variants
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variants_differ_only_at_dont_care_positions() {
+        let seed_pattern = SeedPattern::new("1101").unwrap();
+        assert_eq!(seed_pattern.dont_care_positions(6), vec![2]);
+        // Mask longer than the pattern
+        assert_eq!(seed_pattern.dont_care_positions(2), Vec::<usize>::new());
+
+        let variants = seed_pattern.variants(b"ACGTAC");
+        assert_eq!(variants, vec![b"ACATAC".to_vec(), b"ACCTAC".to_vec(), b"ACTTAC".to_vec()]);
+        let variants = SeedPattern::new("0110").unwrap().variants(b"ACGTAC");
+        assert_eq!(variants.len(), 15);
+
+        assert!(SeedPattern::new("1111").is_none());
+        assert!(SeedPattern::new("1121").is_none());
+        assert!(SeedPattern::new("1000001").is_none());
+        assert!(SeedPattern::new(&"10".repeat(17)).is_none());
+    }
+}
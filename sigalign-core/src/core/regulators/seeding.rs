@@ -0,0 +1,39 @@
+use super::{LowComplexityFilter, SeedPattern, MinimizerSeeder};
+
+/// Options to locate the anchors of a query and to select the anchors to be extended.
+///  - Owned by `AlignmentRegulator` and set by its setters (e.g., `set_minimum_anchor_count`).
+///  - The default locates every pattern and extends every anchor.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SeedingOptions {
+    // Before the target is buffered
+    pub minimum_anchor_count: u32,
+    pub minimum_chain_score: Option<u32>,
+    pub low_complexity_filter: Option<LowComplexityFilter>,
+    pub seed_stride: Option<u32>,
+    pub seed_pattern: Option<SeedPattern>,
+    pub minimizer_seeder: Option<MinimizerSeeder>,
+    pub max_locations_per_pattern: Option<u32>,
+    // After the target is buffered
+    pub seed_extension_check: u32,
+    pub max_merge_mismatches: u32,
+    pub minimum_anchor_size: u32,
+    pub exact_chain_middle_size: u32,
+}
+
+impl Default for SeedingOptions {
+    fn default() -> Self {
+        Self {
+            minimum_anchor_count: 1,
+            minimum_chain_score: None,
+            low_complexity_filter: None,
+            seed_stride: None,
+            seed_pattern: None,
+            minimizer_seeder: None,
+            max_locations_per_pattern: None,
+            seed_extension_check: 0,
+            max_merge_mismatches: 0,
+            minimum_anchor_size: 0,
+            exact_chain_middle_size: 0,
+        }
+    }
+}
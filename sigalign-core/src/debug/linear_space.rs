@@ -0,0 +1,308 @@
+use crate::results::{AlignmentOperation, AlignmentOperations};
+use super::push_operation;
+
+/// Align the sequences as `wave_front_align_debug`, but with the memory linear to the sequence lengths.
+///  - The end point is found by one pass of the dynamic programming keeping only a row of the scores.
+///  - The operations to the end point are found by divide and conquer
+///    (Hirschberg's algorithm for the affine gap penalties by Myers and Miller):
+///    the optimal crossing of the middle row is found from both ends, and the halves are aligned recursively.
+///  - The memory is O(n + m), while the filled wave front holds O(s^2) components for the penalty s.
+///    Instead, the time is O(nm) regardless of the penalty.
+///  - Returns the penalty and the operations. None if the penalty exceeds `max_penalty`.
+pub fn linear_space_align_debug(
+    target: &[u8],
+    query: &[u8],
+    mismatch_penalty: u32,
+    gap_open_penalty: u32,
+    gap_extend_penalty: u32,
+    max_penalty: u32,
+) -> Option<(u32, Vec<AlignmentOperations>)> {
+    let costs = Costs {
+        x: mismatch_penalty as i64,
+        o: gap_open_penalty as i64,
+        e: gap_extend_penalty as i64,
+    };
+    let (penalty, target_end, query_end) = find_end_point(target, query, &costs);
+    if penalty > max_penalty as i64 {
+        return None;
+    }
+    let mut operations = Vec::new();
+    let mut buffers = Buffers::new(query_end);
+    diff(
+        &target[..target_end],
+        &query[..query_end],
+        costs.o,
+        costs.o,
+        &costs,
+        &mut buffers,
+        &mut operations,
+    );
+    Some((penalty as u32, operations))
+}
+
+struct Costs {
+    x: i64,
+    o: i64,
+    e: i64,
+}
+
+impl Costs {
+    fn substitution(&self, target_base: u8, query_base: u8) -> i64 {
+        if target_base == query_base { 0 } else { self.x }
+    }
+    fn gap(&self, length: usize) -> i64 {
+        if length == 0 { 0 } else { self.o + self.e * length as i64 }
+    }
+}
+
+// Scores of a row: the best (cc) and the best ending with a deletion (dd)
+struct Buffers {
+    cc: Vec<i64>,
+    dd: Vec<i64>,
+    rr: Vec<i64>,
+    ss: Vec<i64>,
+}
+
+impl Buffers {
+    fn new(query_length: usize) -> Self {
+        Self {
+            cc: vec![0; query_length + 1],
+            dd: vec![0; query_length + 1],
+            rr: vec![0; query_length + 1],
+            ss: vec![0; query_length + 1],
+        }
+    }
+}
+
+// (penalty, target end, query end) of the lowest penalty to reach the end of either sequence
+//  - The target is the rows and the query is the columns.
+fn find_end_point(target: &[u8], query: &[u8], costs: &Costs) -> (i64, usize, usize) {
+    let (n, m) = (target.len(), query.len());
+    let mut cc: Vec<i64> = (0..=m).map(|j| costs.gap(j)).collect();
+    let mut dd: Vec<i64> = cc.iter().map(|v| v + costs.o).collect();
+    let mut best = (cc[m], 0, m);
+    if n == 0 {
+        best = (0, 0, 0);
+    }
+    for i in 1..=n {
+        let mut s = cc[0];
+        let mut c = costs.gap(i);
+        let mut e = c + costs.o;
+        cc[0] = c;
+        for j in 1..=m {
+            e = e.min(c + costs.o) + costs.e;
+            dd[j] = dd[j].min(cc[j] + costs.o) + costs.e;
+            c = dd[j].min(e).min(s + costs.substitution(target[i - 1], query[j - 1]));
+            s = cc[j];
+            cc[j] = c;
+        }
+        if cc[m] < best.0 {
+            best = (cc[m], i, m);
+        }
+        if i == n {
+            cc.iter().enumerate().for_each(|(j, &c)| {
+                if c < best.0 {
+                    best = (c, n, j);
+                }
+            });
+        }
+    }
+    best
+}
+
+// Push the operations of the global alignment of the sequences
+//  - `tb`, `te`: the gap-open penalties of the deletions at the start and the end
+//    (0 if the deletion continues from the outside).
+fn diff(
+    target: &[u8],
+    query: &[u8],
+    tb: i64,
+    te: i64,
+    costs: &Costs,
+    buffers: &mut Buffers,
+    operations: &mut Vec<AlignmentOperations>,
+) {
+    let (n, m) = (target.len(), query.len());
+    if m == 0 {
+        push_operation(operations, AlignmentOperation::Deletion, n as u32);
+        return;
+    }
+    if n == 0 {
+        push_operation(operations, AlignmentOperation::Insertion, m as u32);
+        return;
+    }
+    if n == 1 {
+        // Delete the base and insert all, or substitute one of the query bases
+        let mut min_cost = tb.min(te) + costs.e + costs.gap(m);
+        let mut mid_j = 0;
+        for j in 1..=m {
+            let cost = costs.gap(j - 1) + costs.substitution(target[0], query[j - 1]) + costs.gap(m - j);
+            if cost < min_cost {
+                min_cost = cost;
+                mid_j = j;
+            }
+        }
+        if mid_j == 0 {
+            push_operation(operations, AlignmentOperation::Deletion, 1);
+            push_operation(operations, AlignmentOperation::Insertion, m as u32);
+        } else {
+            let operation = if target[0] == query[mid_j - 1] {
+                AlignmentOperation::Match
+            } else {
+                AlignmentOperation::Subst
+            };
+            push_operation(operations, AlignmentOperation::Insertion, mid_j as u32 - 1);
+            push_operation(operations, operation, 1);
+            push_operation(operations, AlignmentOperation::Insertion, (m - mid_j) as u32);
+        }
+        return;
+    }
+
+    let mid_i = n / 2;
+    // (1) Forward scores of the middle row
+    {
+        let Buffers { cc, dd, .. } = buffers;
+        cc[0] = 0;
+        for j in 1..=m {
+            cc[j] = costs.gap(j);
+            dd[j] = cc[j] + costs.o;
+        }
+        let mut t = tb;
+        for i in 1..=mid_i {
+            let mut s = cc[0];
+            t += costs.e;
+            let mut c = t;
+            cc[0] = c;
+            let mut e = t + costs.o;
+            for j in 1..=m {
+                e = e.min(c + costs.o) + costs.e;
+                dd[j] = dd[j].min(cc[j] + costs.o) + costs.e;
+                c = dd[j].min(e).min(s + costs.substitution(target[i - 1], query[j - 1]));
+                s = cc[j];
+                cc[j] = c;
+            }
+        }
+        dd[0] = cc[0];
+    }
+    // (2) Reverse scores of the middle row
+    {
+        let Buffers { rr, ss, .. } = buffers;
+        rr[m] = 0;
+        for j in (0..m).rev() {
+            rr[j] = costs.gap(m - j);
+            ss[j] = rr[j] + costs.o;
+        }
+        let mut t = te;
+        for i in (mid_i..n).rev() {
+            let mut s = rr[m];
+            t += costs.e;
+            let mut c = t;
+            rr[m] = c;
+            let mut e = t + costs.o;
+            for j in (0..m).rev() {
+                e = e.min(c + costs.o) + costs.e;
+                ss[j] = ss[j].min(rr[j] + costs.o) + costs.e;
+                c = ss[j].min(e).min(s + costs.substitution(target[i], query[j]));
+                s = rr[j];
+                rr[j] = c;
+            }
+        }
+        ss[m] = rr[m];
+    }
+    // (3) Find the crossing point: through a match state (type 1) or inside a deletion (type 2)
+    let mut min_cost = buffers.cc[0] + buffers.rr[0];
+    let mut mid_j = 0;
+    let mut in_deletion = false;
+    for j in 0..=m {
+        let cost = buffers.cc[j] + buffers.rr[j];
+        if cost < min_cost {
+            min_cost = cost;
+            mid_j = j;
+            in_deletion = false;
+        }
+        let cost = buffers.dd[j] + buffers.ss[j] - costs.o;
+        if cost < min_cost {
+            min_cost = cost;
+            mid_j = j;
+            in_deletion = true;
+        }
+    }
+    // (4) Align the halves
+    if in_deletion {
+        diff(&target[..mid_i - 1], &query[..mid_j], tb, 0, costs, buffers, operations);
+        push_operation(operations, AlignmentOperation::Deletion, 2);
+        diff(&target[mid_i + 1..], &query[mid_j..], 0, te, costs, buffers, operations);
+    } else {
+        diff(&target[..mid_i], &query[..mid_j], tb, costs.o, costs, buffers, operations);
+        diff(&target[mid_i..], &query[mid_j..], costs.o, te, costs, buffers, operations);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        aligner::AlignmentRegulator,
+        results::{Alignment, AlignmentPosition},
+        debug::wave_front_align_debug,
+    };
+    use crate::test_utils::{SEED, Xorshift};
+
+    fn alignment_of_operations(penalty: u32, operations: Vec<AlignmentOperations>) -> Alignment {
+        let (query_length, target_length, length) = operations.iter().fold((0, 0, 0), |(q, t, l), op| {
+            match op.operation {
+                AlignmentOperation::Match | AlignmentOperation::Subst => (q + op.count, t + op.count, l + op.count),
+                AlignmentOperation::Insertion => (q + op.count, t, l + op.count),
+                AlignmentOperation::Deletion => (q, t + op.count, l + op.count),
+            }
+        });
+        Alignment {
+            penalty,
+            length,
+            position: AlignmentPosition { query: (0, query_length), target: (0, target_length) },
+            operations,
+        }
+    }
+
+    #[test]
+    fn linear_space_backtrace_is_same_as_the_wave_front() {
+        let mut rng = Xorshift::new(SEED);
+        let regulator = AlignmentRegulator::new(4, 6, 2, 50, 0.5).unwrap();
+        for is_sparse in [true, false] {
+            for _ in 0..20 {
+                let target: Vec<u8> = (0..600).map(|_| b"ACGT"[rng.gen_range(4) as usize]).collect();
+                let mut query = Vec::new();
+                target.iter().enumerate().for_each(|(index, &base)| {
+                    // Sparse edits have the unique optimal alignment
+                    let edit = if !is_sparse {
+                        rng.gen_range(30)
+                    } else if index % 60 == 30 {
+                        rng.gen_range(3)
+                    } else {
+                        3
+                    };
+                    match edit {
+                        0 => query.push(b"ACGT"[(b"ACGT".iter().position(|v| *v == base).unwrap() + 1) % 4]),
+                        1 => {},
+                        2 => query.extend_from_slice(&[base, b"ACGT"[rng.gen_range(4) as usize]]),
+                        _ => query.push(base),
+                    }
+                });
+
+                let (penalty, operations) = wave_front_align_debug(&target, &query, 4, 6, 2, 1000).alignment.unwrap();
+                let (linear_space_penalty, linear_space_operations) = linear_space_align_debug(&target, &query, 4, 6, 2, 1000).unwrap();
+                assert_eq!(linear_space_penalty, penalty);
+                let mut alignment = alignment_of_operations(penalty, operations);
+                let mut linear_space_alignment = alignment_of_operations(linear_space_penalty, linear_space_operations);
+                assert_eq!(linear_space_alignment.verify(&target, &query, &regulator), Ok(()));
+                if is_sparse {
+                    // The same alignment after the gaps in the repeats are placed at the same position
+                    alignment.left_align_gaps(&target, &query);
+                    linear_space_alignment.left_align_gaps(&target, &query);
+                    assert_eq!(linear_space_alignment.to_cigar(true), alignment.to_cigar(true));
+                }
+            }
+        }
+        assert_eq!(linear_space_align_debug(b"ACGTACGT", b"TTTTTTTT", 4, 6, 2, 10), None);
+    }
+}
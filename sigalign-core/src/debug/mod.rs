@@ -0,0 +1,400 @@
+/*!
+Debugging tools to inspect the internal state of the algorithm (enabled with the `debug` feature).
+
+The wave front can be filled for a pair of sequences without the anchor pipeline.
+The penalties are used as given (not compressed by GCD as in `AlignmentRegulator`).
+The same alignment can be found in linear memory (`linear_space_align_debug`) to compare the backtraces.
+
+The aligners can also report how the anchors of a query are consumed (`AlignmentDiagnostics`),
+how long each extension of the anchors takes (`AlignmentProfile`),
+and which patterns of the query are located in the targets (`AnchorExistence`).
+The ungapped anchors can be merged without the aligners (`UnmergedAnchors`) to measure the merging.
+*/
+use std::time::{Duration, Instant};
+
+use crate::{
+    core::regulators::Penalty,
+    results::{AlignmentOperation, AlignmentOperations},
+    algorithm::{AnchorIndex, AnchorTable, ExtensionRecorder},
+};
+mod linear_space;
+pub use linear_space::linear_space_align_debug;
+pub use crate::algorithm::wave_front::{
+    WaveFront,
+    WaveEndPoint,
+    WaveFrontScore,
+    Components,
+    Component,
+    BackTraceMarker,
+};
+
+/// Counts of the anchors consumed while aligning a query.
+///  - Returned by `align_with_diagnostics` of `LocalAligner` and `SemiGlobalAligner`.
+///  - `extended_anchor_count` = `alignment_count` + `dropped_anchor_count`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AlignmentDiagnostics {
+    /// Number of targets having enough anchors to be extended.
+    pub target_count: u32,
+    /// Number of anchors in the targets (after ungapped anchors are merged).
+    pub anchor_count: u32,
+    /// Number of anchors not extended because they are already traversed by other extension.
+    pub skipped_anchor_count: u32,
+    /// Number of anchors extended with the wave front.
+    pub extended_anchor_count: u32,
+    /// Number of extended anchors that do not result in an alignment.
+    pub dropped_anchor_count: u32,
+    /// Number of alignments in the results.
+    pub alignment_count: u32,
+}
+
+impl AlignmentDiagnostics {
+    pub(crate) fn add_target(&mut self, anchor_count: u32, skipped_anchor_count: u32, alignment_count: u32) {
+        let extended_anchor_count = anchor_count - skipped_anchor_count;
+        self.target_count += 1;
+        self.anchor_count += anchor_count;
+        self.skipped_anchor_count += skipped_anchor_count;
+        self.extended_anchor_count += extended_anchor_count;
+        self.dropped_anchor_count += extended_anchor_count - alignment_count;
+        self.alignment_count += alignment_count;
+    }
+}
+
+/// Elapsed time and the size of the wave front of an extension of an anchor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionProfile {
+    pub target_index: u32,
+    /// (pattern index, index of the anchor in the pattern)
+    pub anchor_index: (u32, u32),
+    pub elapsed: Duration,
+    /// Penalty of the alignment if aligned, or the penalty of the wave front where the extension is dropped.
+    ///  - For the local alignment, the dropped penalty is the sum of the left and right wave fronts.
+    pub wave_front_penalty: u32,
+    /// Whether the extension results in an alignment.
+    pub is_aligned: bool,
+}
+
+/// Profile of the extensions while aligning a query.
+///  - Returned by `align_with_profile` of `LocalAligner` and `SemiGlobalAligner`.
+///  - Only the extensions with the wave front are recorded (empty for the ungapped alignment).
+///  - Without profiling, the extensions are not timed at all.
+#[derive(Debug, Clone, Default)]
+pub struct AlignmentProfile {
+    pub extensions: Vec<ExtensionProfile>,
+    current_target_index: u32,
+    started: Option<Instant>,
+}
+
+impl AlignmentProfile {
+    /// Total time spent in the extensions.
+    pub fn total_elapsed(&self) -> Duration {
+        self.extensions.iter().map(|v| v.elapsed).sum()
+    }
+    /// Number of the extensions that do not result in an alignment.
+    pub fn dropped_count(&self) -> usize {
+        self.extensions.iter().filter(|v| !v.is_aligned).count()
+    }
+    /// The extension taking the longest time.
+    pub fn slowest(&self) -> Option<&ExtensionProfile> {
+        self.extensions.iter().max_by_key(|v| v.elapsed)
+    }
+    /// The largest wave front penalty of the extensions.
+    pub fn max_wave_front_penalty(&self) -> Option<u32> {
+        self.extensions.iter().map(|v| v.wave_front_penalty).max()
+    }
+    pub(crate) fn scale_penalties(&mut self, gcd: u32) {
+        self.extensions.iter_mut().for_each(|v| v.wave_front_penalty *= gcd);
+    }
+}
+
+impl ExtensionRecorder for AlignmentProfile {
+    fn start_target(&mut self, target_index: u32) {
+        self.current_target_index = target_index;
+    }
+    fn start_extension(&mut self) {
+        self.started = Some(Instant::now());
+    }
+    fn end_extension(&mut self, anchor_index: AnchorIndex, wave_front_penalty: u32, is_aligned: bool) {
+        let elapsed = self.started.take().map(|v| v.elapsed()).unwrap_or_default();
+        self.extensions.push(ExtensionProfile {
+            target_index: self.current_target_index,
+            anchor_index,
+            elapsed,
+            wave_front_penalty,
+            is_aligned,
+        });
+    }
+}
+
+/// Whether each pattern of the query is located in any target.
+///  - Returned by `get_anchor_existence` of `LocalAligner` and `SemiGlobalAligner`.
+///  - The pattern of index `i` is the query range of `i * pattern_size..(i + 1) * pattern_size`.
+///  - The alignments can not be started in the regions without the anchors (dark regions),
+///    so they explain the missing alignments of the divergent queries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnchorExistence {
+    pub pattern_size: u32,
+    pub existence: Vec<bool>,
+}
+
+impl AnchorExistence {
+    /// Query ranges of the consecutive patterns without any anchor.
+    ///  - The remainder of the query shorter than the pattern size is not included.
+    pub fn dark_query_ranges(&self) -> Vec<(u32, u32)> {
+        let mut ranges: Vec<(u32, u32)> = Vec::new();
+        self.existence.iter().enumerate().filter(|(_, exists)| !**exists).for_each(|(pattern_index, _)| {
+            let start = pattern_index as u32 * self.pattern_size;
+            let end = start + self.pattern_size;
+            match ranges.last_mut() {
+                Some(last) if last.1 == start => last.1 = end,
+                _ => ranges.push((start, end)),
+            }
+        });
+        ranges
+    }
+}
+
+/// Anchors of a target before the ungapped anchors are merged.
+///  - Merged by the same method used in the aligners.
+#[derive(Debug, Clone)]
+pub struct UnmergedAnchors(AnchorTable);
+
+impl UnmergedAnchors {
+    /// The anchors of each pattern from its sorted target positions.
+    pub fn new(sorted_positions_list: Vec<Vec<u32>>) -> Self {
+        Self(AnchorTable::from_sorted_positions_list(sorted_positions_list))
+    }
+    /// Merge the anchors of the consecutive patterns located without gap.
+    pub fn merge(&mut self, pattern_size: u32) {
+        self.0.merge_ungapped_anchors(pattern_size);
+    }
+    /// (target position, pattern count) of the anchors of each pattern.
+    pub fn anchors(&self) -> Vec<Vec<(u32, u32)>> {
+        self.0.0.iter().map(|anchors| {
+            anchors.iter().map(|anchor| (anchor.target_position, anchor.pattern_count)).collect()
+        }).collect()
+    }
+}
+
+/// The result of `wave_front_align_debug`.
+#[derive(Debug, Clone)]
+pub struct WaveFrontDebugResult {
+    /// Filled wave front.
+    pub wave_front: WaveFront,
+    /// Penalty and operations to the end point (from the start of sequences).
+    /// None if the end of either sequence is not reached within the maximum penalty.
+    pub alignment: Option<(u32, Vec<AlignmentOperations>)>,
+}
+
+/// Fill the wave front from the start of both sequences until the end of either sequence is reached.
+pub fn wave_front_align_debug(
+    target: &[u8],
+    query: &[u8],
+    mismatch_penalty: u32,
+    gap_open_penalty: u32,
+    gap_extend_penalty: u32,
+    max_penalty: u32,
+) -> WaveFrontDebugResult {
+    let penalties = Penalty {
+        x: mismatch_penalty,
+        o: gap_open_penalty,
+        e: gap_extend_penalty,
+        n: mismatch_penalty,
+        h: 0,
+        o2: gap_open_penalty,
+        e2: gap_extend_penalty,
+    };
+    let mut wave_front = WaveFront::new_allocated(&penalties, max_penalty as usize);
+    wave_front.align_right_to_end_point(target, query, &penalties, max_penalty);
+
+    let alignment = wave_front.backtrace_from_end_point(&penalties);
+
+    WaveFrontDebugResult {
+        wave_front,
+        alignment,
+    }
+}
+
+fn push_operation(
+    operations: &mut Vec<AlignmentOperations>,
+    operation: AlignmentOperation,
+    count: u32,
+) {
+    if count == 0 {
+        return
+    }
+    match operations.last_mut() {
+        Some(last) if last.operation == operation => {
+            last.count += count;
+        },
+        _ => {
+            operations.push(AlignmentOperations { operation, count });
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aligner::{AlignmentRegulator, local::LocalAligner, semi_global::SemiGlobalAligner};
+    use crate::test_utils::{TEST_TARGET, NaiveLocator, NaiveBuffer};
+
+    #[test]
+    fn dark_query_ranges_are_merged() {
+        let anchor_existence = AnchorExistence {
+            pattern_size: 10,
+            existence: vec![true, false, false, true, false],
+        };
+        assert_eq!(anchor_existence.dark_query_ranges(), vec![(10, 30), (40, 50)]);
+    }
+
+    #[test]
+    fn ungapped_anchors_are_merged_to_the_leftmost() {
+        let mut anchors = UnmergedAnchors::new(vec![
+            vec![0, 50],
+            vec![10, 70],
+            vec![20, 80],
+        ]);
+        anchors.merge(10);
+        assert_eq!(anchors.anchors(), vec![
+            vec![(0, 3), (50, 1)],
+            vec![(70, 2)],
+            vec![],
+        ]);
+    }
+
+    #[test]
+    fn wave_front_gives_penalty_and_operations() {
+        let target = b"ACGTACGTTTACGTACGT";
+        let query = b"ACGTACGTACGAACGT";
+
+        let result = wave_front_align_debug(target, query, 4, 6, 2, 30);
+        let (penalty, operations) = result.alignment.unwrap();
+
+        let mut calculated_penalty = 0;
+        let (mut query_length, mut target_length) = (0, 0);
+        operations.iter().for_each(|op| {
+            match op.operation {
+                AlignmentOperation::Match => {
+                    query_length += op.count;
+                    target_length += op.count;
+                },
+                AlignmentOperation::Subst => {
+                    calculated_penalty += 4 * op.count;
+                    query_length += op.count;
+                    target_length += op.count;
+                },
+                AlignmentOperation::Deletion => {
+                    calculated_penalty += 6 + 2 * op.count;
+                    target_length += op.count;
+                },
+                AlignmentOperation::Insertion => {
+                    calculated_penalty += 6 + 2 * op.count;
+                    query_length += op.count;
+                },
+            }
+        });
+        assert_eq!(penalty, calculated_penalty);
+        assert!(query_length as usize == query.len() || target_length as usize == target.len());
+        assert_eq!(&target[..8], &query[..8]);
+        assert_eq!(result.wave_front.wave_front_scores.len(), 31);
+    }
+
+    #[test]
+    fn diagnostics_count_all_anchors() {
+        let unit = TEST_TARGET;
+        let mut query = unit.to_vec();
+        query[35] = b'G';
+        let locator = NaiveLocator::new(vec![
+            unit.repeat(2),
+            unit[..40].to_vec(),
+            b"GGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGG".to_vec(),
+        ]);
+        let mut buffer = NaiveBuffer(Vec::new());
+        let regulator = AlignmentRegulator::new(4, 6, 2, 50, 0.2).unwrap();
+
+        let mut local_aligner = LocalAligner::new(regulator.clone());
+        let mut semi_global_aligner = SemiGlobalAligner::new(regulator);
+        let results = [
+            (
+                local_aligner.align(&query, &locator, &mut buffer, &[0, 1, 2]),
+                local_aligner.align_with_diagnostics(&query, &locator, &mut buffer, &[0, 1, 2]),
+            ),
+            (
+                semi_global_aligner.align(&query, &locator, &mut buffer, &[0, 1, 2]),
+                semi_global_aligner.align_with_diagnostics(&query, &locator, &mut buffer, &[0, 1, 2]),
+            ),
+        ];
+        for (expected, (result, diagnostics)) in results {
+            assert_eq!(result.to_json(), expected.to_json());
+            assert_eq!(diagnostics.target_count, 2);
+            assert_eq!(diagnostics.alignment_count as usize, result.0.iter().map(|v| v.alignments.len()).sum::<usize>());
+            assert_eq!(diagnostics.anchor_count, diagnostics.skipped_anchor_count + diagnostics.extended_anchor_count);
+            assert_eq!(diagnostics.extended_anchor_count, diagnostics.alignment_count + diagnostics.dropped_anchor_count);
+            assert!(diagnostics.alignment_count > 0);
+            assert!(diagnostics.dropped_anchor_count > 0);
+        }
+
+        // All extended anchors are profiled
+        let results = [
+            (
+                local_aligner.align_with_diagnostics(&query, &locator, &mut buffer, &[0, 1, 2]),
+                local_aligner.align_with_profile(&query, &locator, &mut buffer, &[0, 1, 2]),
+            ),
+            (
+                semi_global_aligner.align_with_diagnostics(&query, &locator, &mut buffer, &[0, 1, 2]),
+                semi_global_aligner.align_with_profile(&query, &locator, &mut buffer, &[0, 1, 2]),
+            ),
+        ];
+        for ((expected, diagnostics), (result, profile)) in results {
+            assert_eq!(result.to_json(), expected.to_json());
+            assert_eq!(profile.extensions.len() as u32, diagnostics.extended_anchor_count);
+            assert_eq!(profile.dropped_count() as u32, diagnostics.dropped_anchor_count);
+            assert!(profile.extensions.iter().all(|v| v.target_index == 0 || v.target_index == 1));
+            profile.extensions.iter().filter(|v| v.is_aligned).for_each(|extension| {
+                let alignments = &result.0.iter().find(|v| v.index == extension.target_index).unwrap().alignments;
+                assert!(alignments.iter().any(|v| v.penalty == extension.wave_front_penalty));
+            });
+            assert_eq!(profile.max_wave_front_penalty().is_some(), profile.slowest().is_some());
+            assert!(profile.total_elapsed() >= profile.slowest().unwrap().elapsed);
+        }
+
+        // Only the pattern with the mismatch is not located
+        let anchor_existence = local_aligner.get_anchor_existence(&query, &locator, &[0, 1, 2]);
+        assert_eq!(anchor_existence, semi_global_aligner.get_anchor_existence(&query, &locator, &[0, 1, 2]));
+        let pattern_size = anchor_existence.pattern_size;
+        assert_eq!(anchor_existence.existence.len(), query.len() / pattern_size as usize);
+        let dark_pattern_index = 35 / pattern_size;
+        assert_eq!(
+            anchor_existence.dark_query_ranges(),
+            vec![(dark_pattern_index * pattern_size, (dark_pattern_index + 1) * pattern_size)],
+        );
+    }
+    #[test]
+    fn fully_divergent_query_is_dropped_before_extension() {
+        let unit = TEST_TARGET;
+        let locator = NaiveLocator::new(vec![
+            unit.repeat(2),
+            unit[..40].to_vec(),
+            b"GGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGG".to_vec(),
+        ]);
+        let mut buffer = NaiveBuffer(Vec::new());
+        let regulator = AlignmentRegulator::new(4, 6, 2, 50, 0.2).unwrap();
+        let mut local_aligner = LocalAligner::new(regulator.clone());
+        let mut semi_global_aligner = SemiGlobalAligner::new(regulator);
+
+        // The complement has all mismatches
+        let complement: Vec<u8> = unit.iter().map(|base| match base {
+            b'A' => b'T',
+            b'C' => b'G',
+            b'G' => b'C',
+            _ => b'A',
+        }).collect();
+        let estimate = local_aligner.regulator().estimate_work(&complement, &locator, &[0, 1, 2]);
+        assert_eq!(estimate, crate::aligner::WorkEstimate::default());
+        let (result, diagnostics) = local_aligner.align_with_diagnostics(&complement, &locator, &mut buffer, &[0, 1, 2]);
+        assert!(result.0.is_empty());
+        assert_eq!(diagnostics, AlignmentDiagnostics::default());
+        let (_, profile) = semi_global_aligner.align_with_profile(&complement, &locator, &mut buffer, &[0, 1, 2]);
+        assert!(profile.extensions.is_empty());
+    }
+}
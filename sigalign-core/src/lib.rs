@@ -6,6 +6,11 @@ The purpose of `sigalign-core` is to hide the complexity of the detailed impleme
 - `sigalign-core` can be used to perform more optimized alignment for specific tasks.
     - Implementations for traits such as `SequenceStorage` and `PatternIndex` are required (manually or by using `sigalign-impl`).
 - `sigalign-core` does not have user-friendly interfaces implemented in `sigalign`.
+
+The steps of the alignment are logged through the `log` facade (enabled by any logger, e.g., `env_logger`).
+- `debug`: anchors located for each query.
+- `trace`: anchors of each target, and the result of extending each anchor.
+- The penalties in the messages are divided by the GCD of the penalties (see `AlignmentRegulator`).
 */
 
 mod core;
@@ -14,3 +19,10 @@ mod algorithm;
 pub mod results;
 pub mod reference;
 pub mod aligner;
+
+#[cfg(feature = "debug")]
+pub mod debug;
+
+#[cfg(any(test, feature = "test-utils"))]
+#[doc(hidden)]
+pub mod test_utils;
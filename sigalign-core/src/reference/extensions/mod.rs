@@ -17,3 +17,9 @@ pub use io::{
 mod label;
 pub use label::LabelStorage;
 mod clone;
+mod pattern_location_cache;
+pub use pattern_location_cache::PatternLocationCache;
+mod target_regions;
+pub use target_regions::TargetRegionLocator;
+mod position_blacklist;
+pub use position_blacklist::PositionBlacklistLocator;
@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use ahash::AHashMap;
+
+use crate::core::{BufferedPatternLocator, PatternLocation};
+
+type CacheKey = (Vec<u8>, Vec<u32>);
+
+/// A pattern locator reusing the results of previously located patterns.
+///  - Wraps any `BufferedPatternLocator` (e.g., `Reference`), and can be passed to the aligners in place of it.
+///  - The results are cached by the pattern and the target indices, and the least recently used one is evicted when `capacity` is exceeded.
+///  - Thread-safe: one cache can be shared by the aligners of multiple threads.
+///  - Beneficial when many queries share the patterns (e.g., amplicon sequencing).
+///    Otherwise, the cost of the bookkeeping can exceed the cost of the locating.
+pub struct PatternLocationCache<'a, L: BufferedPatternLocator> {
+    locator: &'a L,
+    capacity: usize,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    last_used: u64,
+    entries: AHashMap<CacheKey, (u64, Vec<PatternLocation>)>,
+    keys_by_last_used: BTreeMap<u64, CacheKey>,
+}
+
+impl<'a, L: BufferedPatternLocator> PatternLocationCache<'a, L> {
+    /// Make a new cache holding at most `capacity` patterns (nothing is cached if 0).
+    pub fn new(locator: &'a L, capacity: usize) -> Self {
+        Self {
+            locator,
+            capacity,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+    /// Get the count of the currently cached patterns.
+    pub fn cached_pattern_count(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.entries.clear();
+        state.keys_by_last_used.clear();
+    }
+}
+
+impl CacheState {
+    fn get(&mut self, key: &CacheKey) -> Option<Vec<PatternLocation>> {
+        self.last_used += 1;
+        let last_used = self.last_used;
+        let (used, pattern_locations) = self.entries.get_mut(key)?;
+        let key = self.keys_by_last_used.remove(used).unwrap();
+        *used = last_used;
+        let pattern_locations = pattern_locations.clone();
+        self.keys_by_last_used.insert(last_used, key);
+        Some(pattern_locations)
+    }
+    fn insert(&mut self, key: CacheKey, pattern_locations: Vec<PatternLocation>, capacity: usize) {
+        // Can be already inserted by the other thread
+        if self.entries.contains_key(&key) {
+            return
+        }
+        while self.entries.len() >= capacity {
+            let Some(&oldest) = self.keys_by_last_used.keys().next() else {
+                break
+            };
+            let least_recently_used = self.keys_by_last_used.remove(&oldest).unwrap();
+            self.entries.remove(&least_recently_used);
+        }
+        self.last_used += 1;
+        self.keys_by_last_used.insert(self.last_used, key.clone());
+        self.entries.insert(key, (self.last_used, pattern_locations));
+    }
+}
+
+impl<'a, L: BufferedPatternLocator> BufferedPatternLocator for PatternLocationCache<'a, L> {
+    type Buffer = L::Buffer;
+
+    fn locate(&self, pattern: &[u8], sorted_target_indices: &[u32]) -> Vec<PatternLocation> {
+        if self.capacity == 0 {
+            return self.locator.locate(pattern, sorted_target_indices)
+        }
+        let key = (pattern.to_vec(), sorted_target_indices.to_vec());
+        if let Some(pattern_locations) = self.state.lock().unwrap().get(&key) {
+            return pattern_locations
+        }
+        // Not locked during locating
+        let pattern_locations = self.locator.locate(pattern, sorted_target_indices);
+        self.state.lock().unwrap().insert(key, pattern_locations.clone(), self.capacity);
+        pattern_locations
+    }
+    fn fill_buffer(&self, target_index: u32, buffer: &mut Self::Buffer) {
+        self.locator.fill_buffer(target_index, buffer)
+    }
+    fn is_seedable(&self, target_index: u32, position: u32, pattern_size: u32) -> bool {
+        self.locator.is_seedable(target_index, position, pattern_size)
+    }
+}
+
+impl<'a, L: BufferedPatternLocator> std::fmt::Debug for PatternLocationCache<'a, L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PatternLocationCache")
+            .field("capacity", &self.capacity)
+            .field("cached_pattern_count", &self.cached_pattern_count())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use super::*;
+    use crate::core::SequenceBuffer;
+    use crate::test_utils::NaiveBuffer;
+
+    struct CountingLocator {
+        target: Vec<u8>,
+        located: Cell<usize>,
+    }
+    impl BufferedPatternLocator for CountingLocator {
+        type Buffer = NaiveBuffer;
+
+        fn locate(&self, pattern: &[u8], sorted_target_indices: &[u32]) -> Vec<PatternLocation> {
+            self.located.set(self.located.get() + 1);
+            sorted_target_indices.iter().map(|&target_index| {
+                let sorted_positions = self.target
+                    .windows(pattern.len())
+                    .enumerate()
+                    .filter(|(_, window)| *window == pattern)
+                    .map(|(position, _)| position as u32)
+                    .collect();
+                PatternLocation { target_index, sorted_positions }
+            }).collect()
+        }
+        fn fill_buffer(&self, _target_index: u32, buffer: &mut Self::Buffer) {
+            buffer.0 = self.target.clone();
+        }
+    }
+
+    #[test]
+    fn least_recently_used_pattern_is_evicted() {
+        let locator = CountingLocator {
+            target: b"ACGTACGTTTGA".to_vec(),
+            located: Cell::new(0),
+        };
+        let cache = PatternLocationCache::new(&locator, 2);
+
+        let expected = locator.locate(b"ACG", &[0]);
+        assert_eq!(expected[0].sorted_positions, vec![0, 4]);
+        locator.located.set(0);
+
+        assert_eq!(cache.locate(b"ACG", &[0]), expected);
+        assert_eq!(cache.locate(b"ACG", &[0]), expected);
+        assert_eq!(locator.located.get(), 1);
+        // Different target indices are not the same entry
+        cache.locate(b"ACG", &[0, 1]);
+        assert_eq!(locator.located.get(), 2);
+        // "ACG" with [0] is used more recently than with [0, 1]
+        cache.locate(b"ACG", &[0]);
+        cache.locate(b"TTG", &[0]);
+        assert_eq!(locator.located.get(), 3);
+        assert_eq!(cache.cached_pattern_count(), 2);
+        cache.locate(b"ACG", &[0]);
+        assert_eq!(locator.located.get(), 3);
+        cache.locate(b"ACG", &[0, 1]);
+        assert_eq!(locator.located.get(), 4);
+
+        cache.clear();
+        assert_eq!(cache.cached_pattern_count(), 0);
+        let mut buffer = NaiveBuffer(Vec::new());
+        cache.fill_buffer(0, &mut buffer);
+        assert_eq!(buffer.buffered_sequence(), b"ACGTACGTTTGA");
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::HashSet;
+
+use crate::core::{BufferedPatternLocator, PatternLocation};
+
+/// A pattern locator not seeding the anchors at the blacklisted positions of the targets (e.g., known artifact loci).
+///  - Wraps any `BufferedPatternLocator` (e.g., `Reference`), and can be passed to the aligners in place of it.
+///  - The blacklist is a set of `(target index, position)`, and can be changed for each call without rebuilding the index.
+///  - The patterns covering any blacklisted position are not seedable, and the anchors made only of them are not extended.
+///  - Only the seeding is affected: the patterns are still located, so an alignment from a neighboring anchor
+///    can extend through the blacklisted positions.
+pub struct PositionBlacklistLocator<'a, L: BufferedPatternLocator> {
+    locator: &'a L,
+    position_blacklist: &'a HashSet<(u32, u32)>,
+}
+
+impl<'a, L: BufferedPatternLocator> PositionBlacklistLocator<'a, L> {
+    pub fn new(locator: &'a L, position_blacklist: &'a HashSet<(u32, u32)>) -> Self {
+        Self {
+            locator,
+            position_blacklist,
+        }
+    }
+    /// Whether the position of the target is blacklisted.
+    pub fn is_blacklisted(&self, target_index: u32, position: u32) -> bool {
+        self.position_blacklist.contains(&(target_index, position))
+    }
+}
+
+impl<'a, L: BufferedPatternLocator> BufferedPatternLocator for PositionBlacklistLocator<'a, L> {
+    type Buffer = L::Buffer;
+
+    fn locate(&self, pattern: &[u8], sorted_target_indices: &[u32]) -> Vec<PatternLocation> {
+        self.locator.locate(pattern, sorted_target_indices)
+    }
+    fn fill_buffer(&self, target_index: u32, buffer: &mut Self::Buffer) {
+        self.locator.fill_buffer(target_index, buffer)
+    }
+    fn is_seedable(&self, target_index: u32, position: u32, pattern_size: u32) -> bool {
+        (position..position + pattern_size).all(|v| !self.is_blacklisted(target_index, v))
+        && self.locator.is_seedable(target_index, position, pattern_size)
+    }
+}
+
+impl<'a, L: BufferedPatternLocator> std::fmt::Debug for PositionBlacklistLocator<'a, L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PositionBlacklistLocator")
+            .field("blacklisted_position_count", &self.position_blacklist.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aligner::{AlignmentRegulator, local::LocalAligner};
+    use crate::test_utils::{TEST_TARGET, NaiveLocator, NaiveBuffer};
+
+    #[test]
+    fn blacklisted_positions_are_not_seeded() {
+        let unit = TEST_TARGET;
+        let spacer = [b'G'; 30];
+        // Two copies of the unit in the first target
+        let locator = NaiveLocator::new(vec![[&unit[..], &spacer[..], &unit[..]].concat()]);
+        let regulator = AlignmentRegulator::new(4, 6, 2, 50, 0.2).unwrap();
+        let mut aligner = LocalAligner::new(regulator);
+        let mut buffer = NaiveBuffer(Vec::new());
+        let target_positions = |aligner: &mut LocalAligner, locator: &PositionBlacklistLocator<NaiveLocator>, buffer: &mut NaiveBuffer| {
+            let result = aligner.align(unit, locator, buffer, &[0]);
+            let mut positions: Vec<_> = result.0.iter().flat_map(|v| v.alignments.iter().map(|v| v.position.target)).collect();
+            positions.sort_unstable();
+            positions
+        };
+
+        let empty_blacklist = HashSet::new();
+        let blacklist_locator = PositionBlacklistLocator::new(&locator, &empty_blacklist);
+        assert_eq!(target_positions(&mut aligner, &blacklist_locator, &mut buffer), vec![(0, 70), (100, 170)]);
+
+        // One position of the first copy: the alignment passes through it from the other anchors
+        let blacklist: HashSet<(u32, u32)> = [(0, 15)].into_iter().collect();
+        let blacklist_locator = PositionBlacklistLocator::new(&locator, &blacklist);
+        assert!(blacklist_locator.is_blacklisted(0, 15));
+        assert!(!blacklist_locator.is_seedable(0, 10, 10));
+        assert!(blacklist_locator.is_seedable(0, 20, 10));
+        assert_eq!(target_positions(&mut aligner, &blacklist_locator, &mut buffer), vec![(0, 70), (100, 170)]);
+
+        // Every pattern of the first copy
+        let blacklist: HashSet<(u32, u32)> = (0..7).map(|v| (0, v * 10 + 5)).collect();
+        let blacklist_locator = PositionBlacklistLocator::new(&locator, &blacklist);
+        assert_eq!(target_positions(&mut aligner, &blacklist_locator, &mut buffer), vec![(100, 170)]);
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::BTreeMap;
+
+use crate::core::{BufferedPatternLocator, PatternLocation};
+
+/// A pattern locator restricted to the regions of the targets (e.g., exome targets of a genome).
+///  - Wraps any `BufferedPatternLocator` (e.g., `Reference`), and can be passed to the aligners in place of it.
+///  - For the targets with the registered regions, only the patterns located entirely inside of a region
+///    are returned, so the anchors outside of the regions are never extended.
+///    The other targets are not restricted.
+///  - The extension from an anchor can still reach out of the regions.
+///  - The positions are not changed, so the alignments are in the coordinates of the whole target.
+pub struct TargetRegionLocator<'a, L: BufferedPatternLocator> {
+    locator: &'a L,
+    // Sorted and merged (start, end) of each target
+    regions_by_target: BTreeMap<u32, Vec<(u32, u32)>>,
+}
+
+impl<'a, L: BufferedPatternLocator> TargetRegionLocator<'a, L> {
+    pub fn new(locator: &'a L) -> Self {
+        Self {
+            locator,
+            regions_by_target: BTreeMap::new(),
+        }
+    }
+    /// Register the regions of the target as half-open intervals `(start, end)`.
+    ///  - Added to the regions already registered. Overlapping or adjacent regions are merged.
+    ///  - Empty regions (`start >= end`) are ignored.
+    pub fn add_regions(&mut self, target_index: u32, regions: &[(u32, u32)]) {
+        let target_regions = self.regions_by_target.entry(target_index).or_default();
+        target_regions.extend(regions.iter().filter(|(start, end)| start < end));
+        target_regions.sort_unstable();
+        let mut merged: Vec<(u32, u32)> = Vec::with_capacity(target_regions.len());
+        target_regions.iter().for_each(|&(start, end)| {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => {
+                    last.1 = last.1.max(end);
+                },
+                _ => merged.push((start, end)),
+            }
+        });
+        *target_regions = merged;
+    }
+    /// Get the regions of the target. None if not registered.
+    pub fn get_regions(&self, target_index: u32) -> Option<&[(u32, u32)]> {
+        self.regions_by_target.get(&target_index).map(|v| v.as_slice())
+    }
+    /// Get the sorted indices of the targets with the registered regions.
+    ///  - Pass to the aligners to align only to the regions.
+    pub fn registered_target_indices(&self) -> Vec<u32> {
+        self.regions_by_target.keys().copied().collect()
+    }
+}
+
+impl<'a, L: BufferedPatternLocator> BufferedPatternLocator for TargetRegionLocator<'a, L> {
+    type Buffer = L::Buffer;
+
+    fn locate(&self, pattern: &[u8], sorted_target_indices: &[u32]) -> Vec<PatternLocation> {
+        let pattern_size = pattern.len() as u32;
+        let mut pattern_locations = self.locator.locate(pattern, sorted_target_indices);
+        pattern_locations.iter_mut().for_each(|pattern_location| {
+            if let Some(regions) = self.regions_by_target.get(&pattern_location.target_index) {
+                pattern_location.sorted_positions.retain(|&position| {
+                    // The last region starting at or before the position
+                    let region_index = regions.partition_point(|(start, _)| *start <= position);
+                    region_index != 0 && position + pattern_size <= regions[region_index - 1].1
+                });
+            }
+        });
+        pattern_locations.retain(|pattern_location| !pattern_location.sorted_positions.is_empty());
+        pattern_locations
+    }
+    fn fill_buffer(&self, target_index: u32, buffer: &mut Self::Buffer) {
+        self.locator.fill_buffer(target_index, buffer)
+    }
+    fn is_seedable(&self, target_index: u32, position: u32, pattern_size: u32) -> bool {
+        self.locator.is_seedable(target_index, position, pattern_size)
+    }
+}
+
+impl<'a, L: BufferedPatternLocator> std::fmt::Debug for TargetRegionLocator<'a, L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TargetRegionLocator")
+            .field("regions_by_target", &self.regions_by_target)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aligner::{AlignmentRegulator, local::LocalAligner};
+    use crate::test_utils::{TEST_TARGET, NaiveLocator, NaiveBuffer};
+
+    #[test]
+    fn only_anchors_in_the_regions_are_extended() {
+        let unit = TEST_TARGET;
+        let spacer = [b'G'; 30];
+        // Two copies of the unit in the first target
+        let locator = NaiveLocator::new(vec![
+            [&unit[..], &spacer[..], &unit[..]].concat(),
+            unit.to_vec(),
+        ]);
+        let mut region_locator = TargetRegionLocator::new(&locator);
+        region_locator.add_regions(0, &[(110, 150), (100, 120), (150, 170), (10, 10)]);
+        assert_eq!(region_locator.get_regions(0), Some(&[(100, 170)][..]));
+        assert_eq!(region_locator.get_regions(1), None);
+        assert_eq!(region_locator.registered_target_indices(), vec![0]);
+
+        let pattern = &unit[..10];
+        let located = region_locator.locate(pattern, &[0, 1]);
+        assert_eq!(located.len(), 2);
+        assert_eq!(located[0].sorted_positions, vec![100]);
+        assert_eq!(located[1].sorted_positions, vec![0]);
+
+        let regulator = AlignmentRegulator::new(4, 6, 2, 50, 0.2).unwrap();
+        let mut aligner = LocalAligner::new(regulator);
+        let mut buffer = NaiveBuffer(Vec::new());
+        let result = aligner.align(unit, &region_locator, &mut buffer, &[0]);
+        let positions: Vec<_> = result.0[0].alignments.iter().map(|v| v.position.target).collect();
+        assert_eq!(positions, vec![(100, 170)]);
+        let result = aligner.align(unit, &locator, &mut buffer, &[0]);
+        assert_eq!(result.0[0].alignments.len(), 2);
+    }
+}
@@ -30,7 +30,7 @@ pub mod extensions;
 
 pub use pattern_index::PatternIndex;
 pub use sequence_storage::SequenceStorage;
-pub use crate::core::{PatternLocation, SequenceBuffer};
+pub use crate::core::{BufferedPatternLocator, PatternLocation, SequenceBuffer};
 
 /// A database for multiple target sequences.
 #[derive(Debug)]
@@ -0,0 +1,93 @@
+use super::{
+    TargetAlignment,
+    Alignment,
+    AlignmentOperation,
+};
+
+const GAP: u8 = b'-';
+
+/// Aligned target and query sequences of an alignment with gaps (`-`).
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AlignedSequences {
+    pub target: Vec<u8>,
+    pub query: Vec<u8>,
+}
+
+impl TargetAlignment {
+    /// Get the aligned sequences of all alignments, in the order of `alignments`.
+    ///  - `target` is the whole sequence of this target.
+    pub fn get_aligned_sequences(&self, target: &[u8], query: &[u8]) -> Vec<AlignedSequences> {
+        self.alignments.iter().map(|alignment| {
+            alignment.get_aligned_sequences(target, query)
+        }).collect()
+    }
+}
+
+impl Alignment {
+    /// Get the aligned target and query sequences with gaps (`-`).
+    pub fn get_aligned_sequences(&self, target: &[u8], query: &[u8]) -> AlignedSequences {
+        let mut aligned_target = Vec::with_capacity(self.length as usize);
+        let mut aligned_query = Vec::with_capacity(self.length as usize);
+        let mut target_index = self.position.target.0 as usize;
+        let mut query_index = self.position.query.0 as usize;
+
+        self.operations.iter().for_each(|operations| {
+            let count = operations.count as usize;
+            match operations.operation {
+                AlignmentOperation::Match | AlignmentOperation::Subst => {
+                    aligned_target.extend_from_slice(&target[target_index..target_index+count]);
+                    aligned_query.extend_from_slice(&query[query_index..query_index+count]);
+                    target_index += count;
+                    query_index += count;
+                },
+                AlignmentOperation::Deletion => {
+                    aligned_target.extend_from_slice(&target[target_index..target_index+count]);
+                    aligned_query.resize(aligned_query.len() + count, GAP);
+                    target_index += count;
+                },
+                AlignmentOperation::Insertion => {
+                    aligned_target.resize(aligned_target.len() + count, GAP);
+                    aligned_query.extend_from_slice(&query[query_index..query_index+count]);
+                    query_index += count;
+                },
+            }
+        });
+
+        AlignedSequences {
+            target: aligned_target,
+            query: aligned_query,
+        }
+    }
+    /// Get the region of the query before the alignment (clipped on the left).
+    ///  - `query` is the whole query sequence. Empty if the alignment starts at the first base.
+    pub fn clipped_query_prefix<'a>(&self, query: &'a [u8]) -> &'a [u8] {
+        &query[..self.position.query.0 as usize]
+    }
+    /// Get the region of the query after the alignment (clipped on the right).
+    ///  - `query` is the whole query sequence. Empty if the alignment ends at the last base.
+    pub fn clipped_query_suffix<'a>(&self, query: &'a [u8]) -> &'a [u8] {
+        &query[self.position.query.1 as usize..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::{AlignmentOperations, AlignmentPosition};
+
+    #[test]
+    fn clipped_regions_of_query_are_outside_the_alignment() {
+        let adapter = b"AGATCGGAAG";
+        let insert = b"ACACAGATCGCAAACTCACAATTGTATTTC";
+        let query = [&adapter[..], &insert[..], &adapter[..]].concat();
+        let alignment = Alignment {
+            penalty: 0,
+            length: 30,
+            position: AlignmentPosition { query: (10, 40), target: (100, 130) },
+            operations: vec![AlignmentOperations { operation: AlignmentOperation::Match, count: 30 }],
+        };
+        assert_eq!(alignment.clipped_query_prefix(&query), adapter);
+        assert_eq!(alignment.clipped_query_suffix(&query), adapter);
+        assert!(alignment.clipped_query_suffix(&query[..40]).is_empty());
+    }
+}
@@ -0,0 +1,379 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+use super::{
+    QueryAlignment,
+    TargetAlignment,
+    Alignment,
+    AlignmentOperation,
+};
+
+/// Policy to choose among the alignments with the same lowest penalty in a target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Keep all alignments with the lowest penalty.
+    AllEqual,
+    /// Keep the alignment starting at the leftmost position of the target.
+    LeftmostTarget,
+    /// Keep the longest alignment.
+    LongestAlignment,
+    /// Keep the alignment with the fewest gaps (gap-opens, then gap length).
+    FewestGaps,
+}
+
+impl QueryAlignment {
+    /// Take the best alignment of each target (see `TargetAlignment::into_best_alignment`).
+    pub fn into_best_per_target(self) -> Vec<(u32, Alignment)> {
+        self.0.into_iter().filter_map(|v| {
+            let index = v.index;
+            v.into_best_alignment().map(|alignment| (index, alignment))
+        }).collect()
+    }
+    /// Get the target having the best alignment among all targets.
+    ///  - The best alignment has the lowest penalty (longer one if penalties are the same).
+    ///  - If the best alignments are the same, the target with the lower index is chosen.
+    pub fn best_target(&self) -> Option<(u32, &Alignment)> {
+        self.0.iter().filter_map(|v| {
+            v.best_alignment().map(|alignment| (v.index, alignment))
+        }).min_by(|(index_a, a), (index_b, b)| {
+            cmp_alignment_by_penalty(a, b).then(index_a.cmp(index_b))
+        })
+    }
+    /// Get the `k` best alignments among all targets with their target indices, from the best.
+    ///  - Ranked by `Alignment::rank_key`, then by the target index and the position.
+    ///  - Selected with a heap of size `k`, so the cost is O(n log k) without sorting all alignments.
+    pub fn top_k(&self, k: usize) -> Vec<(u32, &Alignment)> {
+        if k == 0 {
+            return Vec::new();
+        }
+        // Max-heap of the best `k` so far: the worst one is on the top
+        let mut heap: BinaryHeap<(TopKey, usize, usize)> = BinaryHeap::with_capacity(k + 1);
+        self.0.iter().enumerate().for_each(|(target_order, target_alignment)| {
+            target_alignment.alignments.iter().enumerate().for_each(|(alignment_order, alignment)| {
+                let key = top_key(target_alignment.index, alignment);
+                if heap.len() < k {
+                    heap.push((key, target_order, alignment_order));
+                } else if let Some(mut worst) = heap.peek_mut() {
+                    if key < worst.0 {
+                        *worst = (key, target_order, alignment_order);
+                    }
+                }
+            });
+        });
+        heap.into_sorted_vec().into_iter().map(|(_, target_order, alignment_order)| {
+            let target_alignment = &self.0[target_order];
+            (target_alignment.index, &target_alignment.alignments[alignment_order])
+        }).collect()
+    }
+    /// Get the primary alignment among all targets with its target index.
+    ///  - The primary alignment has the lowest penalty. If there are multiple, it is chosen by `tie_break`.
+    ///  - Remaining ties (and all ties of `TieBreak::AllEqual`) are resolved by the lower target index
+    ///    and the leftmost target and query position.
+    ///  - The other alignments are secondary (see `primary_flags`).
+    pub fn primary_alignment(&self, tie_break: TieBreak) -> Option<(u32, &Alignment)> {
+        self.primary_order(tie_break).map(|(target_order, alignment_order)| {
+            let target_alignment = &self.0[target_order];
+            (target_alignment.index, &target_alignment.alignments[alignment_order])
+        })
+    }
+    /// Whether each alignment is the primary (see `primary_alignment`) or secondary.
+    ///  - In the same layout as the alignments: `flags[i][j]` is for `self.0[i].alignments[j]`.
+    ///  - Only one alignment of the query is flagged as primary, e.g., to be written without the SAM secondary flag.
+    pub fn primary_flags(&self, tie_break: TieBreak) -> Vec<Vec<bool>> {
+        let mut flags: Vec<Vec<bool>> = self.0.iter().map(|v| vec![false; v.alignments.len()]).collect();
+        if let Some((target_order, alignment_order)) = self.primary_order(tie_break) {
+            flags[target_order][alignment_order] = true;
+        }
+        flags
+    }
+    // (target order, alignment order) of the primary alignment
+    fn primary_order(&self, tie_break: TieBreak) -> Option<(usize, usize)> {
+        self.0.iter().enumerate().flat_map(|(target_order, target_alignment)| {
+            target_alignment.alignments.iter().enumerate().map(move |(alignment_order, alignment)| {
+                (target_order, alignment_order, target_alignment.index, alignment)
+            })
+        }).min_by(|(_, _, index_a, a), (_, _, index_b, b)| {
+            a.penalty.cmp(&b.penalty)
+                .then_with(|| cmp_alignment_by_tie_break(a, b, tie_break))
+                .then(index_a.cmp(index_b))
+                .then_with(|| cmp_alignment_by_position(a, b))
+        }).map(|(target_order, alignment_order, _, _)| (target_order, alignment_order))
+    }
+    /// Sort the alignments of each target from the best (see `TargetAlignment::sort_by_rank`).
+    pub fn sort_by_rank(&mut self) {
+        self.0.iter_mut().for_each(|v| v.sort_by_rank());
+    }
+    /// Keep only the alignments with the lowest penalty in each target (see `TargetAlignment::retain_best`).
+    pub fn retain_best(&mut self, tie_break: TieBreak) {
+        self.0.iter_mut().for_each(|v| v.retain_best(tie_break));
+    }
+    /// Keep only the alignments with the lowest penalty including the clips in each target
+    /// (see `TargetAlignment::retain_best_with_clip_penalty`).
+    pub fn retain_best_with_clip_penalty(&mut self, tie_break: TieBreak, query_length: u32, clip_penalty: u32) {
+        self.0.iter_mut().for_each(|v| v.retain_best_with_clip_penalty(tie_break, query_length, clip_penalty));
+    }
+}
+
+impl TargetAlignment {
+    /// Get the alignment with the lowest penalty (longer one if penalties are the same).
+    pub fn best_alignment(&self) -> Option<&Alignment> {
+        self.alignments.iter().min_by(|a, b| cmp_alignment_by_penalty(a, b))
+    }
+    /// Take the alignment with the lowest penalty (longer one if penalties are the same).
+    pub fn into_best_alignment(self) -> Option<Alignment> {
+        self.alignments.into_iter().min_by(cmp_alignment_by_penalty)
+    }
+    /// Sort the alignments from the best (see `Alignment::rank_key`).
+    ///  - The sort is stable: the alignments with the same key keep their order.
+    pub fn sort_by_rank(&mut self) {
+        self.alignments.sort_by_key(Alignment::rank_key);
+    }
+    /// Keep only the alignments with the lowest penalty.
+    ///  - If there are multiple, they are chosen by `tie_break`.
+    ///  - Remaining ties are resolved by the leftmost target and query position.
+    pub fn retain_best(&mut self, tie_break: TieBreak) {
+        self.retain_best_with_clip_penalty(tie_break, 0, 0)
+    }
+    /// Keep only the alignments with the lowest penalty including the clips (see `Alignment::penalty_with_clips`).
+    ///  - Longer alignments are preferred over the shorter ones with a slightly lower penalty.
+    ///  - Same as `retain_best` if `clip_penalty` is 0.
+    pub fn retain_best_with_clip_penalty(&mut self, tie_break: TieBreak, query_length: u32, clip_penalty: u32) {
+        let min_penalty = match self.alignments.iter().map(|v| {
+            v.penalty_with_clips(query_length, clip_penalty)
+        }).min() {
+            Some(v) => v,
+            None => return,
+        };
+        self.alignments.retain(|v| v.penalty_with_clips(query_length, clip_penalty) == min_penalty);
+        if tie_break != TieBreak::AllEqual {
+            let best = std::mem::take(&mut self.alignments).into_iter().min_by(|a, b| {
+                cmp_alignment_by_tie_break(a, b, tie_break)
+                    .then_with(|| cmp_alignment_by_position(a, b))
+            });
+            self.alignments.extend(best);
+        }
+    }
+}
+
+// (rank key, target index, target start, query start)
+type TopKey = ((u32, Reverse<u32>), u32, u32, u32);
+fn top_key(target_index: u32, alignment: &Alignment) -> TopKey {
+    (alignment.rank_key(), target_index, alignment.position.target.0, alignment.position.query.0)
+}
+
+fn cmp_alignment_by_penalty(
+    a: &Alignment,
+    b: &Alignment,
+) -> Ordering {
+    a.rank_key().cmp(&b.rank_key())
+}
+
+fn cmp_alignment_by_tie_break(
+    a: &Alignment,
+    b: &Alignment,
+    tie_break: TieBreak,
+) -> Ordering {
+    match tie_break {
+        TieBreak::AllEqual => Ordering::Equal,
+        TieBreak::LeftmostTarget => cmp_alignment_by_position(a, b),
+        TieBreak::LongestAlignment => b.length.cmp(&a.length),
+        TieBreak::FewestGaps => a.count_gaps().cmp(&b.count_gaps()),
+    }
+}
+
+fn cmp_alignment_by_position(
+    a: &Alignment,
+    b: &Alignment,
+) -> Ordering {
+    a.position.target.0.cmp(&b.position.target.0)
+        .then(a.position.query.0.cmp(&b.position.query.0))
+}
+
+impl Alignment {
+    /// Get the key to rank the alignments: the smaller key is the better alignment.
+    ///  - Lower penalty first, then longer length.
+    ///  - e.g., `alignments.sort_by_key(Alignment::rank_key)` sorts from the best.
+    pub fn rank_key(&self) -> (u32, Reverse<u32>) {
+        (self.penalty, Reverse(self.length))
+    }
+    /// Compare by `rank_key`: `Ordering::Less` if this alignment is better.
+    pub fn cmp_by_rank(&self, other: &Self) -> Ordering {
+        cmp_alignment_by_penalty(self, other)
+    }
+    /// Get the penalty adding `clip_penalty` for each base of the query outside the alignment.
+    pub fn penalty_with_clips(&self, query_length: u32, clip_penalty: u32) -> u32 {
+        if clip_penalty == 0 {
+            return self.penalty
+        }
+        let aligned_query_length = self.position.query.1 - self.position.query.0;
+        let clipped_length = query_length.saturating_sub(aligned_query_length);
+        self.penalty + clip_penalty * clipped_length
+    }
+    // Return (gap-open count, gap length)
+    fn count_gaps(&self) -> (u32, u32) {
+        self.operations.iter().fold((0, 0), |(open, length), op| {
+            match op.operation {
+                AlignmentOperation::Deletion | AlignmentOperation::Insertion => (open + 1, length + op.count),
+                _ => (open, length),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::{AlignmentPosition, AlignmentOperations};
+    use crate::test_utils::{SEED, Xorshift};
+
+    fn alignment(penalty: u32, length: u32, target_start: u32, operations: &[(AlignmentOperation, u32)]) -> Alignment {
+        Alignment {
+            penalty,
+            length,
+            position: AlignmentPosition { query: (0, length), target: (target_start, target_start + length) },
+            operations: operations.iter().map(|(operation, count)| {
+                AlignmentOperations { operation: operation.clone(), count: *count }
+            }).collect(),
+        }
+    }
+
+    #[test]
+    fn tie_break_policies_choose_among_the_lowest_penalty() {
+        use AlignmentOperation::*;
+        let target_alignment = TargetAlignment {
+            index: 0,
+            alignments: vec![
+                alignment(12, 100, 300, &[(Match, 50), (Insertion, 1), (Match, 20), (Deletion, 1), (Match, 28)]),
+                alignment(12, 102, 200, &[(Match, 50), (Insertion, 3), (Match, 49)]),
+                alignment(12, 98, 400, &[(Match, 50), (Subst, 3), (Match, 45)]),
+                alignment(4, 90, 500, &[(Match, 50), (Subst, 1), (Match, 39)]),
+                alignment(4, 90, 100, &[(Match, 50), (Subst, 1), (Match, 39)]),
+                alignment(4, 95, 600, &[(Match, 50), (Subst, 1), (Match, 44)]),
+            ],
+        };
+        let start_positions = |tie_break| {
+            let mut v = target_alignment.clone();
+            v.retain_best(tie_break);
+            v.alignments.iter().map(|v| v.position.target.0).collect::<Vec<_>>()
+        };
+        assert_eq!(start_positions(TieBreak::AllEqual), vec![500, 100, 600]);
+        assert_eq!(start_positions(TieBreak::LeftmostTarget), vec![100]);
+        assert_eq!(start_positions(TieBreak::LongestAlignment), vec![600]);
+        assert_eq!(start_positions(TieBreak::FewestGaps), vec![100]);
+
+        // Fewest gaps among the gapped ties
+        let mut gapped = target_alignment.clone();
+        gapped.alignments.truncate(3);
+        gapped.retain_best(TieBreak::FewestGaps);
+        assert_eq!(gapped.alignments[0].position.target.0, 400);
+        let mut gapped = target_alignment.clone();
+        gapped.alignments.truncate(2);
+        gapped.retain_best(TieBreak::FewestGaps);
+        assert_eq!(gapped.alignments[0].position.target.0, 200);
+    }
+    #[test]
+    fn clip_penalty_prefers_the_longer_alignments() {
+        use AlignmentOperation::*;
+        let target_alignment = TargetAlignment {
+            index: 0,
+            alignments: vec![
+                alignment(12, 102, 200, &[(Match, 50), (Insertion, 3), (Match, 49)]),
+                alignment(4, 95, 600, &[(Match, 50), (Subst, 1), (Match, 44)]),
+                alignment(0, 60, 700, &[(Match, 60)]),
+            ],
+        };
+        let start_positions = |clip_penalty| {
+            let mut v = target_alignment.clone();
+            v.retain_best_with_clip_penalty(TieBreak::AllEqual, 102, clip_penalty);
+            v.alignments.iter().map(|v| v.position.target.0).collect::<Vec<_>>()
+        };
+        assert_eq!(start_positions(0), vec![700]);
+        assert_eq!(start_positions(1), vec![600]);
+        assert_eq!(start_positions(2), vec![200]);
+        assert_eq!(target_alignment.alignments[1].penalty_with_clips(102, 3), 25);
+    }
+    #[test]
+    fn alignments_are_ranked_by_penalty_and_length() {
+        let mut target_alignment = TargetAlignment {
+            index: 0,
+            alignments: vec![
+                alignment(8, 100, 100, &[]),
+                alignment(4, 90, 200, &[]),
+                alignment(4, 95, 300, &[]),
+                alignment(8, 100, 400, &[]),
+            ],
+        };
+        assert!(target_alignment.alignments[2].rank_key() < target_alignment.alignments[1].rank_key());
+        assert_eq!(
+            target_alignment.alignments[1].cmp_by_rank(&target_alignment.alignments[0]),
+            Ordering::Less,
+        );
+        target_alignment.sort_by_rank();
+        let start_positions: Vec<_> = target_alignment.alignments.iter().map(|v| v.position.target.0).collect();
+        assert_eq!(start_positions, vec![300, 200, 100, 400]);
+    }
+    #[test]
+    fn top_k_is_same_as_the_sorted_prefix() {
+        let mut rng = Xorshift::new(SEED);
+        let query_alignment = QueryAlignment((0..20).map(|index| {
+            TargetAlignment {
+                index,
+                alignments: (0..rng.gen_range(10)).map(|_| {
+                    alignment(rng.gen_range(10), 90 + rng.gen_range(10), rng.gen_range(1000), &[])
+                }).collect(),
+            }
+        }).collect());
+        let mut sorted: Vec<(u32, &Alignment)> = query_alignment.0.iter().flat_map(|v| {
+            v.alignments.iter().map(move |alignment| (v.index, alignment))
+        }).collect();
+        sorted.sort_by_key(|(index, alignment)| top_key(*index, alignment));
+
+        for k in [0, 1, 5, 30, sorted.len(), sorted.len() + 10] {
+            let top = query_alignment.top_k(k);
+            assert_eq!(top, sorted[..k.min(sorted.len())].to_vec());
+        }
+        assert_eq!(
+            query_alignment.top_k(1).first().map(|(index, alignment)| (*index, alignment.penalty)),
+            query_alignment.best_target().map(|(index, alignment)| (index, alignment.penalty)),
+        );
+    }
+    #[test]
+    fn only_one_alignment_is_primary() {
+        use AlignmentOperation::*;
+        let query_alignment = QueryAlignment(vec![
+            TargetAlignment {
+                index: 3,
+                alignments: vec![
+                    alignment(8, 100, 100, &[(Match, 100)]),
+                    alignment(4, 90, 500, &[(Match, 40), (Insertion, 1), (Match, 49)]),
+                ],
+            },
+            TargetAlignment {
+                index: 1,
+                alignments: vec![
+                    alignment(4, 80, 700, &[(Match, 80)]),
+                    alignment(4, 80, 300, &[(Match, 80)]),
+                ],
+            },
+        ]);
+        let primary = |tie_break| {
+            query_alignment.primary_alignment(tie_break).map(|(index, v)| (index, v.position.target.0))
+        };
+        assert_eq!(primary(TieBreak::AllEqual), Some((1, 300)));
+        assert_eq!(primary(TieBreak::LeftmostTarget), Some((1, 300)));
+        assert_eq!(primary(TieBreak::LongestAlignment), Some((3, 500)));
+        assert_eq!(primary(TieBreak::FewestGaps), Some((1, 300)));
+        assert_eq!(
+            query_alignment.primary_flags(TieBreak::LongestAlignment),
+            vec![vec![false, true], vec![false, false]],
+        );
+        assert_eq!(
+            query_alignment.primary_flags(TieBreak::AllEqual),
+            vec![vec![false, false], vec![false, true]],
+        );
+
+        let empty = QueryAlignment(vec![TargetAlignment { index: 0, alignments: Vec::new() }]);
+        assert_eq!(empty.primary_alignment(TieBreak::AllEqual), None);
+        assert_eq!(empty.primary_flags(TieBreak::AllEqual), vec![Vec::<bool>::new()]);
+    }
+}
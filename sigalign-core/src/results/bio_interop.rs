@@ -0,0 +1,71 @@
+//! Conversion to and from the alignment operation of `bio` crate (enabled with the `bio-interop` feature).
+use bio_types::alignment::AlignmentOperation as BioAlignmentOperation;
+use thiserror::Error;
+
+use super::{
+    Alignment,
+    AlignmentOperation,
+};
+
+/// Error for the `bio` operation that has no counterpart in `AlignmentOperation`.
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("Clipping operation ({0:?}) is not supported.")]
+pub struct UnsupportedBioOperation(pub BioAlignmentOperation);
+
+impl From<AlignmentOperation> for BioAlignmentOperation {
+    fn from(value: AlignmentOperation) -> Self {
+        match value {
+            AlignmentOperation::Match => Self::Match,
+            AlignmentOperation::Subst => Self::Subst,
+            AlignmentOperation::Deletion => Self::Del,
+            AlignmentOperation::Insertion => Self::Ins,
+        }
+    }
+}
+
+impl TryFrom<BioAlignmentOperation> for AlignmentOperation {
+    type Error = UnsupportedBioOperation;
+
+    fn try_from(value: BioAlignmentOperation) -> Result<Self, Self::Error> {
+        match value {
+            BioAlignmentOperation::Match => Ok(Self::Match),
+            BioAlignmentOperation::Subst => Ok(Self::Subst),
+            BioAlignmentOperation::Del => Ok(Self::Deletion),
+            BioAlignmentOperation::Ins => Ok(Self::Insertion),
+            clip => Err(UnsupportedBioOperation(clip)),
+        }
+    }
+}
+
+impl Alignment {
+    /// Get the operations of each base in the form of `bio` crate.
+    pub fn to_bio_operations(&self) -> Vec<BioAlignmentOperation> {
+        self.operations.iter().flat_map(|v| {
+            let bio_operation = BioAlignmentOperation::from(v.operation.clone());
+            (0..v.count).map(move |_| bio_operation)
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn supported_operations_are_round_tripped() {
+        for operation in [
+            AlignmentOperation::Match,
+            AlignmentOperation::Subst,
+            AlignmentOperation::Deletion,
+            AlignmentOperation::Insertion,
+        ] {
+            let bio_operation = BioAlignmentOperation::from(operation.clone());
+            assert_eq!(AlignmentOperation::try_from(bio_operation), Ok(operation));
+        }
+        assert_eq!(
+            AlignmentOperation::try_from(BioAlignmentOperation::Xclip(3)),
+            Err(UnsupportedBioOperation(BioAlignmentOperation::Xclip(3))),
+        );
+        assert!(AlignmentOperation::try_from(BioAlignmentOperation::Yclip(1)).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+/*!
+CIGAR and the text records (SAM and PAF) written directly from the run-length encoded operations.
+  - The operations are never expanded to the bases, so the memory does not grow with the alignment length.
+  - `extended`: `=` and `X` for the matches and the substitutions; otherwise, both are merged to `M`.
+*/
+use std::fmt::{self, Write};
+
+use super::{
+    Alignment,
+    AlignmentOperation,
+};
+
+impl Alignment {
+    /// Write the CIGAR string of the operations to the `writer`.
+    pub fn write_cigar<W: Write>(&self, writer: &mut W, extended: bool) -> fmt::Result {
+        let mut pending: Option<(char, u32)> = None;
+        for operations in self.operations.iter() {
+            let code = cigar_code(&operations.operation, extended);
+            match pending.as_mut() {
+                // Matches and substitutions in a row are one `M`
+                Some((pending_code, count)) if *pending_code == code => {
+                    *count += operations.count;
+                },
+                _ => {
+                    if let Some((pending_code, count)) = pending {
+                        write!(writer, "{}{}", count, pending_code)?;
+                    }
+                    pending = Some((code, operations.count));
+                },
+            }
+        }
+        if let Some((pending_code, count)) = pending {
+            write!(writer, "{}{}", count, pending_code)?;
+        }
+        Ok(())
+    }
+    /// Get the CIGAR string of the operations (see `write_cigar`).
+    pub fn to_cigar(&self, extended: bool) -> String {
+        let mut cigar = String::new();
+        // Writing to the `String` never fails
+        let _ = self.write_cigar(&mut cigar, extended);
+        cigar
+    }
+    /// Write a SAM record of this alignment (without the newline) to the `writer`.
+    ///  - The unaligned ends of the query are soft clipped; `query_length` is the length of the aligned query.
+    ///  - `is_forward`: if false, the record is flagged as reverse complemented (0x10).
+    ///  - The mapping quality, the mate and the sequence are missing (`255`, `*`, `*`).
+    pub fn write_sam_record<W: Write>(
+        &self,
+        writer: &mut W,
+        query_name: &str,
+        query_length: u32,
+        target_name: &str,
+        is_forward: bool,
+    ) -> fmt::Result {
+        let flag: u16 = if is_forward { 0 } else { 16 };
+        write!(writer, "{}\t{}\t{}\t{}\t255\t", query_name, flag, target_name, self.position.target.0 + 1)?;
+        let (query_start, query_end) = self.position.query;
+        if query_start != 0 {
+            write!(writer, "{}S", query_start)?;
+        }
+        self.write_cigar(writer, true)?;
+        if query_end < query_length {
+            write!(writer, "{}S", query_length - query_end)?;
+        }
+        write!(writer, "\t*\t0\t0\t*\t*")
+    }
+    /// Write a PAF record of this alignment (without the newline) to the `writer`.
+    ///  - If not `is_forward`, the alignment is of the reverse complement of the query,
+    ///    and the query range is converted to the coordinates of the original query.
+    ///  - The mapping quality is missing (`255`), and the CIGAR is in the `cg:Z` tag.
+    pub fn write_paf_record<W: Write>(
+        &self,
+        writer: &mut W,
+        query_name: &str,
+        query_length: u32,
+        target_name: &str,
+        target_length: u32,
+        is_forward: bool,
+    ) -> fmt::Result {
+        let (query_start, query_end) = if is_forward {
+            self.position.query
+        } else {
+            (query_length - self.position.query.1, query_length - self.position.query.0)
+        };
+        write!(
+            writer,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t255\tcg:Z:",
+            query_name,
+            query_length,
+            query_start,
+            query_end,
+            if is_forward { '+' } else { '-' },
+            target_name,
+            target_length,
+            self.position.target.0,
+            self.position.target.1,
+            self.count_matches(),
+            self.length,
+        )?;
+        self.write_cigar(writer, false)
+    }
+}
+
+fn cigar_code(operation: &AlignmentOperation, extended: bool) -> char {
+    match operation {
+        AlignmentOperation::Match => if extended { '=' } else { 'M' },
+        AlignmentOperation::Subst => if extended { 'X' } else { 'M' },
+        AlignmentOperation::Insertion => 'I',
+        AlignmentOperation::Deletion => 'D',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::{AlignmentOperations, AlignmentPosition};
+
+    fn alignment() -> Alignment {
+        Alignment {
+            penalty: 12,
+            length: 22,
+            position: AlignmentPosition { query: (2, 22), target: (100, 120) },
+            operations: vec![
+                AlignmentOperations { operation: AlignmentOperation::Match, count: 5 },
+                AlignmentOperations { operation: AlignmentOperation::Subst, count: 1 },
+                AlignmentOperations { operation: AlignmentOperation::Match, count: 6 },
+                AlignmentOperations { operation: AlignmentOperation::Insertion, count: 2 },
+                AlignmentOperations { operation: AlignmentOperation::Match, count: 6 },
+                AlignmentOperations { operation: AlignmentOperation::Deletion, count: 2 },
+            ],
+        }
+    }
+
+    #[test]
+    fn cigar_is_written_from_runs() {
+        let alignment = alignment();
+        assert_eq!(alignment.to_cigar(true), "5=1X6=2I6=2D");
+        assert_eq!(alignment.to_cigar(false), "12M2I6M2D");
+
+        let mut sam_record = String::new();
+        alignment.write_sam_record(&mut sam_record, "read", 25, "chr1", false).unwrap();
+        assert_eq!(sam_record, "read\t16\tchr1\t101\t255\t2S5=1X6=2I6=2D3S\t*\t0\t0\t*\t*");
+
+        let mut paf_record = String::new();
+        alignment.write_paf_record(&mut paf_record, "read", 25, "chr1", 1000, false).unwrap();
+        assert_eq!(paf_record, "read\t25\t3\t23\t-\tchr1\t1000\t100\t120\t17\t22\t255\tcg:Z:12M2I6M2D");
+    }
+}
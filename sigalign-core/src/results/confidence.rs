@@ -0,0 +1,51 @@
+use crate::core::regulators::PREC_SCALE;
+use super::{
+    QueryAlignment,
+    Alignment,
+};
+
+impl QueryAlignment {
+    /// Whether each alignment is low-confidence (see `Alignment::is_low_confidence`).
+    ///  - In the same layout as the alignments: `flags[i][j]` is for `self.0[i].alignments[j]`.
+    pub fn low_confidence_flags(&self, warning_penalty_per_length: f32) -> Vec<Vec<bool>> {
+        self.0.iter().map(|target_alignment| {
+            target_alignment.alignments.iter().map(|alignment| {
+                alignment.is_low_confidence(warning_penalty_per_length)
+            }).collect()
+        }).collect()
+    }
+}
+
+impl Alignment {
+    /// Whether the penalty per length exceeds `warning_penalty_per_length`,
+    /// a softer threshold than the cutoff of the aligner.
+    ///  - The alignments are pruned and reported by the cutoff, so the ones between the two thresholds
+    ///    are kept to be flagged, not dropped.
+    ///  - Compared in the same precision as the cutoff.
+    pub fn is_low_confidence(&self, warning_penalty_per_length: f32) -> bool {
+        let scaled_warning = (warning_penalty_per_length * PREC_SCALE as f32) as u64;
+        self.penalty as u64 * PREC_SCALE as u64 > scaled_warning * self.length as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::{TargetAlignment, AlignmentPosition};
+
+    #[test]
+    fn alignments_over_the_warning_are_flagged() {
+        let alignment_of = |penalty, length| Alignment {
+            penalty,
+            length,
+            position: AlignmentPosition { query: (0, length), target: (0, length) },
+            operations: Vec::new(),
+        };
+        let query_alignment = QueryAlignment(vec![
+            TargetAlignment { index: 0, alignments: vec![alignment_of(0, 100), alignment_of(10, 100)] },
+            TargetAlignment { index: 3, alignments: vec![alignment_of(11, 100)] },
+        ]);
+        assert_eq!(query_alignment.low_confidence_flags(0.1), vec![vec![false, false], vec![true]]);
+        assert_eq!(query_alignment.low_confidence_flags(0.0), vec![vec![false, true], vec![true]]);
+    }
+}
@@ -7,9 +7,18 @@ impl QueryAlignment {
     pub fn count_alignments(&self) -> usize {
         self.0.iter().map(|x| x.count_alignments()).sum()
     }
+    /// Whether no alignment is in any target.
+    ///  - The query without any anchor or without any alignment passing the cutoff has the same empty result
+    ///    (`QueryAlignment::default()`), since the targets without alignments are not included.
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|x| x.is_empty())
+    }
 }
 impl TargetAlignment {
     pub fn count_alignments(&self) -> usize {
         self.alignments.len()
     }
+    pub fn is_empty(&self) -> bool {
+        self.alignments.is_empty()
+    }
 }
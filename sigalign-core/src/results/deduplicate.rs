@@ -1,6 +1,6 @@
 use std::cmp::Ordering;
 
-use ahash::AHashSet;
+use ahash::{AHashMap, AHashSet};
 
 use super::{
     QueryAlignment,
@@ -12,11 +12,19 @@ use super::{
 impl QueryAlignment {
     /// Deduplicate the alignments by connected (Match or Subst) base pairs positions.
     pub fn deduplicated(self) -> Self {
-        let mut paths = AHashSet::new();
+        self.deduplicated_with_option(false)
+    }
+    /// Deduplicate the alignments, optionally reporting all equal-best alignments of each group.
+    ///  - The alignments sharing the connected base pairs positions are grouped,
+    ///    and the longest alignment (by query length) of each group is kept.
+    ///  - If `report_all_in_group` is true, the other alignments in the group
+    ///    with the same penalty as the kept one are also reported.
+    pub fn deduplicated_with_option(self, report_all_in_group: bool) -> Self {
+        let mut paths = AHashMap::new();
 
         Self(
             self.0.into_iter().map(|v| {
-                v.deduplicated_with_paths_buffer(&mut paths)
+                v.deduplicated_with_paths_buffer(&mut paths, report_all_in_group)
             }).collect()
         )
     }
@@ -24,10 +32,18 @@ impl QueryAlignment {
 
 impl TargetAlignment {
     pub fn deduplicated(self) -> Self {
-        let mut paths = AHashSet::new();
-        self.deduplicated_with_paths_buffer(&mut paths)
+        self.deduplicated_with_option(false)
+    }
+    pub fn deduplicated_with_option(self, report_all_in_group: bool) -> Self {
+        let mut paths = AHashMap::new();
+        self.deduplicated_with_paths_buffer(&mut paths, report_all_in_group)
     }
-    fn deduplicated_with_paths_buffer(mut self, paths: &mut AHashSet<(u32, u32)>) -> Self {
+    // Paths buffer: base pair position -> penalty of the alignment kept first at the position
+    fn deduplicated_with_paths_buffer(
+        mut self,
+        paths: &mut AHashMap<(u32, u32), u32>,
+        report_all_in_group: bool,
+    ) -> Self {
         paths.clear();
 
         self.alignments.sort_unstable_by(|a, b| {
@@ -44,12 +60,18 @@ impl TargetAlignment {
         
         self.alignments = temporary_vec.into_iter().filter(|v| {
             let path = v.get_path();
-            if paths.is_disjoint(&path) {
-                paths.extend(path);
-                true
-            } else {
-                false
+            let to_keep = path.iter().all(|position| {
+                match paths.get(position) {
+                    None => true,
+                    Some(penalty) => report_all_in_group && *penalty == v.penalty,
+                }
+            });
+            if to_keep {
+                path.into_iter().for_each(|position| {
+                    paths.entry(position).or_insert(v.penalty);
+                });
             }
+            to_keep
         }).collect();
         self
     }
@@ -97,3 +119,48 @@ impl Alignment {
         paths
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::AlignmentOperations;
+
+    fn alignment(penalty: u32, query: (u32, u32), target: (u32, u32), operations: &[(AlignmentOperation, u32)]) -> Alignment {
+        Alignment {
+            penalty,
+            length: operations.iter().map(|v| v.1).sum(),
+            position: AlignmentPosition { query, target },
+            operations: operations.iter().map(|(operation, count)| AlignmentOperations { operation: operation.clone(), count: *count }).collect(),
+        }
+    }
+
+    #[test]
+    fn equal_best_alignments_in_group_are_reported_optionally() {
+        use AlignmentOperation::{Match, Insertion, Subst};
+        let target_alignment = TargetAlignment {
+            index: 0,
+            alignments: vec![
+                // Two equal-best alignments sharing the first four base pairs
+                alignment(8, (0, 10), (0, 9), &[(Match, 5), (Insertion, 1), (Match, 4)]),
+                alignment(8, (0, 10), (0, 9), &[(Match, 4), (Insertion, 1), (Match, 5)]),
+                // Worse alignment in the same group
+                alignment(4, (1, 9), (1, 9), &[(Match, 3), (Subst, 1), (Match, 4)]),
+                // Another group
+                alignment(0, (20, 30), (20, 30), &[(Match, 10)]),
+            ],
+        };
+        let query_positions = |v: &TargetAlignment| {
+            let mut positions: Vec<_> = v.alignments.iter().map(|v| (v.position.query, v.operations[0].count)).collect();
+            positions.sort();
+            positions
+        };
+
+        let collapsed = target_alignment.clone().deduplicated();
+        assert_eq!(query_positions(&collapsed).len(), 2);
+        let all_best = target_alignment.deduplicated_with_option(true);
+        assert_eq!(
+            query_positions(&all_best),
+            vec![((0, 10), 4), ((0, 10), 5), ((20, 30), 10)],
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use super::{
+    QueryAlignment,
+    Alignment,
+    AlignmentPosition,
+    AlignmentOperation,
+};
+
+/// A difference between the alignments of two results of the same query (see `QueryAlignment::diff`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CigarDiff {
+    /// The alignment is only in the first result.
+    OnlyInSelf {
+        target_index: u32,
+        position: AlignmentPosition,
+    },
+    /// The alignment is only in the second result.
+    OnlyInOther {
+        target_index: u32,
+        position: AlignmentPosition,
+    },
+    /// The paired alignments diverge from this base.
+    ///  - `query_position` and `target_position` are where the first alignment is at the divergence.
+    ///    If the alignments start at different positions, they diverge at the start of the first alignment.
+    ///  - The operation is `None` if that alignment has ended.
+    Diverged {
+        target_index: u32,
+        query_position: u32,
+        target_position: u32,
+        self_operation: Option<AlignmentOperation>,
+        other_operation: Option<AlignmentOperation>,
+    },
+}
+
+impl QueryAlignment {
+    /// Compare the alignments with the `other` result, e.g., of another aligner, by the operations.
+    ///  - The alignments of the same target are paired if their target ranges overlap,
+    ///    in order of the alignments in `self`. Unpaired alignments are reported as only in one result.
+    ///  - Only the first divergence of each pair is reported.
+    ///  - Empty if both results have the same operations at the same positions.
+    pub fn diff(&self, other: &Self) -> Vec<CigarDiff> {
+        let mut diffs = Vec::new();
+        let mut is_paired: Vec<Vec<bool>> = other.0.iter().map(|v| vec![false; v.alignments.len()]).collect();
+        for target_alignment in self.0.iter() {
+            let target_index = target_alignment.index;
+            let other_order = other.0.iter().position(|v| v.index == target_index);
+            for alignment in target_alignment.alignments.iter() {
+                let paired = other_order.and_then(|other_order| {
+                    let other_alignments = &other.0[other_order].alignments;
+                    let alignment_order = other_alignments.iter().enumerate().position(|(alignment_order, other_alignment)| {
+                        !is_paired[other_order][alignment_order]
+                            && are_target_ranges_overlapped(alignment, other_alignment)
+                    })?;
+                    is_paired[other_order][alignment_order] = true;
+                    Some(&other_alignments[alignment_order])
+                });
+                match paired {
+                    Some(other_alignment) => {
+                        if let Some(diff) = first_divergence(target_index, alignment, other_alignment) {
+                            diffs.push(diff);
+                        }
+                    },
+                    None => diffs.push(CigarDiff::OnlyInSelf {
+                        target_index,
+                        position: alignment.position.clone(),
+                    }),
+                }
+            }
+        }
+        other.0.iter().zip(is_paired).for_each(|(target_alignment, is_paired)| {
+            target_alignment.alignments.iter().zip(is_paired).filter(|(_, is_paired)| !is_paired).for_each(|(alignment, _)| {
+                diffs.push(CigarDiff::OnlyInOther {
+                    target_index: target_alignment.index,
+                    position: alignment.position.clone(),
+                });
+            });
+        });
+        diffs
+    }
+}
+
+fn are_target_ranges_overlapped(a: &Alignment, b: &Alignment) -> bool {
+    a.position.target.0 < b.position.target.1 && b.position.target.0 < a.position.target.1
+}
+
+fn first_divergence(target_index: u32, alignment: &Alignment, other: &Alignment) -> Option<CigarDiff> {
+    let (mut query_position, mut target_position) = (alignment.position.query.0, alignment.position.target.0);
+    let is_same_start = (query_position, target_position) == (other.position.query.0, other.position.target.0);
+    let mut operations = expanded_operations(alignment);
+    let mut other_operations = expanded_operations(other);
+    loop {
+        let (operation, other_operation) = (operations.next(), other_operations.next());
+        if !is_same_start || operation != other_operation {
+            return Some(CigarDiff::Diverged {
+                target_index,
+                query_position,
+                target_position,
+                self_operation: operation.cloned(),
+                other_operation: other_operation.cloned(),
+            });
+        }
+        match operation {
+            Some(AlignmentOperation::Match | AlignmentOperation::Subst) => {
+                query_position += 1;
+                target_position += 1;
+            },
+            Some(AlignmentOperation::Insertion) => query_position += 1,
+            Some(AlignmentOperation::Deletion) => target_position += 1,
+            None => return None,
+        }
+    }
+}
+
+// The operation of each base
+fn expanded_operations(alignment: &Alignment) -> impl Iterator<Item = &AlignmentOperation> {
+    alignment.operations.iter().flat_map(|v| (0..v.count).map(move |_| &v.operation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::{TargetAlignment, AlignmentOperations};
+
+    #[test]
+    fn shifted_gap_is_the_divergence() {
+        use AlignmentOperation::*;
+        let alignment_of = |operations: Vec<(AlignmentOperation, u32)>, target_start| Alignment {
+            penalty: 8,
+            length: 31,
+            position: AlignmentPosition { query: (0, 30), target: (target_start, target_start + 31) },
+            operations: operations.into_iter().map(|(operation, count)| AlignmentOperations { operation, count }).collect(),
+        };
+        let left_gap = alignment_of(vec![(Match, 10), (Deletion, 1), (Match, 20)], 0);
+        let right_gap = alignment_of(vec![(Match, 12), (Deletion, 1), (Match, 18)], 0);
+        let other_target = alignment_of(vec![(Match, 30)], 100);
+        let result = QueryAlignment(vec![
+            TargetAlignment { index: 0, alignments: vec![left_gap.clone()] },
+        ]);
+        let other_result = QueryAlignment(vec![
+            TargetAlignment { index: 0, alignments: vec![right_gap] },
+            TargetAlignment { index: 2, alignments: vec![other_target.clone()] },
+        ]);
+
+        assert!(result.diff(&result).is_empty());
+        assert_eq!(result.diff(&other_result), vec![
+            CigarDiff::Diverged {
+                target_index: 0,
+                query_position: 10,
+                target_position: 10,
+                self_operation: Some(Deletion),
+                other_operation: Some(Match),
+            },
+            CigarDiff::OnlyInOther { target_index: 2, position: other_target.position.clone() },
+        ]);
+        assert_eq!(other_result.diff(&result)[1], CigarDiff::OnlyInSelf {
+            target_index: 2,
+            position: other_target.position,
+        });
+
+        // A longer alignment diverges at the end of the shorter one
+        let mut extended = left_gap.clone();
+        extended.operations[2].count += 5;
+        extended.position.query.1 += 5;
+        extended.position.target.1 += 5;
+        let extended_result = QueryAlignment(vec![TargetAlignment { index: 0, alignments: vec![extended] }]);
+        assert_eq!(result.diff(&extended_result), vec![CigarDiff::Diverged {
+            target_index: 0,
+            query_position: 30,
+            target_position: 31,
+            self_operation: None,
+            other_operation: Some(Match),
+        }]);
+    }
+}
@@ -0,0 +1,44 @@
+use super::{
+    QueryAlignment,
+    TargetAlignment,
+    Alignment,
+    AlignmentOperation,
+};
+
+impl QueryAlignment {
+    /// Remove the alignments having a gap longer than `maximum_gap_length` (see `TargetAlignment::without_long_gaps`).
+    ///  - Targets without remaining alignments are removed.
+    pub fn without_long_gaps(self, maximum_gap_length: u32) -> Self {
+        Self(
+            self.0.into_iter().filter_map(|v| {
+                let target_alignment = v.without_long_gaps(maximum_gap_length);
+                if target_alignment.alignments.is_empty() {
+                    None
+                } else {
+                    Some(target_alignment)
+                }
+            }).collect()
+        )
+    }
+}
+
+impl TargetAlignment {
+    /// Remove the alignments having a single insertion or deletion longer than `maximum_gap_length`.
+    ///  - The alignments are dropped, not clipped at the gap.
+    pub fn without_long_gaps(mut self, maximum_gap_length: u32) -> Self {
+        self.alignments.retain(|v| v.get_longest_gap_length() <= maximum_gap_length);
+        self
+    }
+}
+
+impl Alignment {
+    /// Get the length of the longest insertion or deletion (0 if ungapped).
+    pub fn get_longest_gap_length(&self) -> u32 {
+        self.operations.iter().filter_map(|v| {
+            match v.operation {
+                AlignmentOperation::Deletion | AlignmentOperation::Insertion => Some(v.count),
+                _ => None,
+            }
+        }).max().unwrap_or(0)
+    }
+}
@@ -0,0 +1,79 @@
+use super::{
+    QueryAlignment,
+    TargetAlignment,
+    Alignment,
+};
+
+impl QueryAlignment {
+    /// Remove the alignments with the identity lower than `minimum_identity` (see `Alignment::identity`).
+    ///  - Targets without remaining alignments are removed.
+    pub fn without_low_identity(self, minimum_identity: f32) -> Self {
+        Self(
+            self.0.into_iter().filter_map(|v| {
+                let target_alignment = v.without_low_identity(minimum_identity);
+                if target_alignment.alignments.is_empty() {
+                    None
+                } else {
+                    Some(target_alignment)
+                }
+            }).collect()
+        )
+    }
+}
+
+impl TargetAlignment {
+    /// Remove the alignments with the identity lower than `minimum_identity` (see `Alignment::identity`).
+    pub fn without_low_identity(mut self, minimum_identity: f32) -> Self {
+        self.alignments.retain(|v| v.identity() >= minimum_identity);
+        self
+    }
+}
+
+impl Alignment {
+    /// Get the fraction of the matched bases in the alignment: `matches / length`.
+    ///  - Not equivalent to the penalty per length under the affine gap penalties:
+    ///    a long gap lowers the identity by its length, but adds only one gap-open penalty.
+    ///  - 0 if the length is 0.
+    pub fn identity(&self) -> f32 {
+        if self.length == 0 {
+            return 0.0;
+        }
+        self.count_matches() as f32 / self.length as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::{AlignmentOperations, AlignmentOperation, AlignmentPosition};
+
+    #[test]
+    fn identity_is_fraction_of_matches() {
+        let alignment = |operations: Vec<(AlignmentOperation, u32)>| Alignment {
+            penalty: 0,
+            length: operations.iter().map(|v| v.1).sum(),
+            position: AlignmentPosition { query: (0, 0), target: (0, 0) },
+            operations: operations.into_iter().map(|(operation, count)| {
+                AlignmentOperations { operation, count }
+            }).collect(),
+        };
+        let with_subst = alignment(vec![
+            (AlignmentOperation::Match, 45),
+            (AlignmentOperation::Subst, 5),
+        ]);
+        let with_gap = alignment(vec![
+            (AlignmentOperation::Match, 20),
+            (AlignmentOperation::Deletion, 8),
+            (AlignmentOperation::Match, 22),
+        ]);
+        assert_eq!(with_subst.identity(), 0.9);
+        assert_eq!(with_gap.identity(), 0.84);
+
+        let target_alignment = TargetAlignment {
+            index: 0,
+            alignments: vec![with_subst.clone(), with_gap],
+        };
+        assert_eq!(target_alignment.clone().without_low_identity(0.85).alignments, vec![with_subst]);
+        assert!(QueryAlignment(vec![target_alignment]).without_low_identity(0.95).0.is_empty());
+    }
+}
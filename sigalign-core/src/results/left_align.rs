@@ -0,0 +1,151 @@
+use super::{
+    TargetAlignment,
+    Alignment,
+    AlignmentOperations,
+    AlignmentOperation,
+};
+
+impl TargetAlignment {
+    /// Left-align the gaps of all alignments (see `Alignment::left_align_gaps`).
+    ///  - `target` is the whole sequence of this target.
+    pub fn left_align_gaps(&mut self, target: &[u8], query: &[u8]) {
+        self.alignments.iter_mut().for_each(|alignment| {
+            alignment.left_align_gaps(target, query);
+        });
+    }
+}
+
+impl Alignment {
+    /// Shift each gap to the leftmost of its equivalent positions (left-aligned indels, as `bcftools norm`).
+    ///  - `target` and `query` are the whole sequences (the position is applied).
+    ///  - A gap in a tandem repeat has many representations with the same penalty, and the extension can report any of them.
+    ///    After this, the same alignment always has the same operations.
+    ///  - A gap is shifted over the preceding matches while the base before the gap is the same as the last base of the gap,
+    ///    so the penalty and the length are not changed. It is not shifted over the substitutions or the other gaps,
+    ///    and at least one match is kept between the gaps and at the start.
+    ///  - The aligned sequences are filled again if they were filled.
+    pub fn left_align_gaps(&mut self, target: &[u8], query: &[u8]) {
+        let mut query_index = self.position.query.0 as usize;
+        let mut target_index = self.position.target.0 as usize;
+        let mut operation_index = 0;
+        while operation_index < self.operations.len() {
+            let count = self.operations[operation_index].count as usize;
+            let sequence = match self.operations[operation_index].operation {
+                AlignmentOperation::Match | AlignmentOperation::Subst => {
+                    query_index += count;
+                    target_index += count;
+                    operation_index += 1;
+                    continue;
+                },
+                AlignmentOperation::Insertion => query,
+                AlignmentOperation::Deletion => target,
+            };
+            let gap_start = match self.operations[operation_index].operation {
+                AlignmentOperation::Insertion => query_index,
+                _ => target_index,
+            };
+            let max_shift = self.max_shift_of_gap(operation_index);
+            let mut shift = 0;
+            while shift < max_shift && sequence[gap_start - shift - 1] == sequence[gap_start - shift - 1 + count] {
+                shift += 1;
+            }
+            if shift != 0 {
+                self.operations[operation_index - 1].count -= shift as u32;
+                match self.operations.get_mut(operation_index + 1) {
+                    Some(next) if next.operation == AlignmentOperation::Match => {
+                        next.count += shift as u32;
+                    },
+                    _ => {
+                        self.operations.insert(operation_index + 1, AlignmentOperations {
+                            operation: AlignmentOperation::Match,
+                            count: shift as u32,
+                        });
+                    },
+                }
+                if self.operations[operation_index - 1].count == 0 {
+                    self.operations.remove(operation_index - 1);
+                    operation_index -= 1;
+                }
+                query_index -= shift;
+                target_index -= shift;
+            }
+            match self.operations[operation_index].operation {
+                AlignmentOperation::Insertion => query_index += count,
+                _ => target_index += count,
+            }
+            operation_index += 1;
+        }
+    }
+    // The count of the matches that the gap can be shifted over
+    fn max_shift_of_gap(&self, operation_index: usize) -> usize {
+        if operation_index == 0 {
+            return 0;
+        }
+        let previous = &self.operations[operation_index - 1];
+        if previous.operation != AlignmentOperation::Match {
+            return 0;
+        }
+        // All matches can be passed only if a substitution is before them
+        let is_after_substitution = operation_index >= 2
+            && self.operations[operation_index - 2].operation == AlignmentOperation::Subst;
+        if is_after_substitution {
+            previous.count as usize
+        } else {
+            previous.count as usize - 1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aligner::AlignmentRegulator;
+    use crate::results::AlignmentPosition;
+
+    fn operations(runs: &[(AlignmentOperation, u32)]) -> Vec<AlignmentOperations> {
+        runs.iter().map(|(operation, count)| AlignmentOperations { operation: operation.clone(), count: *count }).collect()
+    }
+
+    #[test]
+    fn gaps_in_tandem_repeats_are_left_aligned() {
+        use AlignmentOperation::*;
+        let regulator = AlignmentRegulator::new(4, 6, 2, 10, 0.5).unwrap();
+        // STR expansion: one more "CA" unit in the query
+        let target = b"GGTTCACACACAGGTT";
+        let query = b"GGTTCACACACACAGGTT";
+        // Right-aligned insertion
+        //   GGTTCACACACA--GGTT
+        //   GGTTCACACACACAGGTT
+        let mut alignment = Alignment {
+            penalty: 10,
+            length: 18,
+            position: AlignmentPosition { query: (0, 18), target: (0, 16) },
+            operations: operations(&[(Match, 12), (Insertion, 2), (Match, 4)]),
+        };
+        assert_eq!(alignment.verify(target, query, &regulator), Ok(()));
+
+        alignment.left_align_gaps(target, query);
+        // GGTT--CACACACAGGTT
+        assert_eq!(alignment.to_cigar(true), "4=2I12=");
+        assert_eq!(alignment.get_aligned_sequences(target, query).target, &b"GGTT--CACACACAGGTT"[..]);
+        assert_eq!(alignment.verify(target, query, &regulator), Ok(()));
+        // Already left-aligned
+        let left_aligned = alignment.clone();
+        alignment.left_align_gaps(target, query);
+        assert_eq!(alignment, left_aligned);
+
+        // Deletion (contraction of the repeat) after a substitution
+        let target = b"GATCACACAGG";
+        let query = b"GTTCACAGG";
+        let mut alignment = Alignment {
+            penalty: 14,
+            length: 11,
+            position: AlignmentPosition { query: (0, 9), target: (0, 11) },
+            operations: operations(&[(Match, 1), (Subst, 1), (Match, 5), (Deletion, 2), (Match, 2)]),
+        };
+        assert_eq!(alignment.verify(target, query, &regulator), Ok(()));
+        alignment.left_align_gaps(target, query);
+        assert_eq!(alignment.to_cigar(true), "1=1X1=2D6=");
+        assert_eq!(alignment.verify(target, query, &regulator), Ok(()));
+    }
+}
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    QueryAlignment,
+    Alignment,
+};
+
+/// Coordinates and penalty of an alignment, without the operations.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize)]
+pub struct MappingResult {
+    pub target_index: u32,
+    pub target_range: (u32, u32),
+    pub query_range: (u32, u32),
+    pub penalty: u32,
+}
+
+impl QueryAlignment {
+    /// Convert to the mapping results, in order of the targets and the alignments.
+    pub fn into_mapping_results(self) -> Vec<MappingResult> {
+        self.0.into_iter().flat_map(|target_alignment| {
+            let target_index = target_alignment.index;
+            target_alignment.alignments.into_iter().map(move |alignment| {
+                alignment.to_mapping_result(target_index)
+            })
+        }).collect()
+    }
+}
+
+impl Alignment {
+    pub fn to_mapping_result(&self, target_index: u32) -> MappingResult {
+        MappingResult {
+            target_index,
+            target_range: self.position.target,
+            query_range: self.position.query,
+            penalty: self.penalty,
+        }
+    }
+}
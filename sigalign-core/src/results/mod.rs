@@ -42,7 +42,7 @@ Example in JSON format:
 */ 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[cfg_attr(feature = "short_key", serde(rename = "QryAln"))]
 pub struct QueryAlignment(
     pub Vec<TargetAlignment>
@@ -110,3 +110,32 @@ mod to_json;
 // Features
 mod count_alignments;
 mod deduplicate;
+mod non_overlapping;
+mod aligned_sequences;
+pub use aligned_sequences::AlignedSequences;
+mod pretty;
+mod best_alignment;
+pub use best_alignment::TieBreak;
+mod gap_length;
+mod identity;
+mod confidence;
+mod score;
+mod significance;
+pub use significance::SignificanceModel;
+mod overlap;
+pub use overlap::{Overlap, OverlapOrientation};
+mod mapping;
+pub use mapping::MappingResult;
+mod cigar;
+mod left_align;
+mod quality_clip;
+mod terminal_extend;
+mod stitch;
+mod diff;
+pub use diff::CigarDiff;
+mod verify;
+pub use verify::AlignmentVerificationError;
+#[cfg(feature = "bio-interop")]
+mod bio_interop;
+#[cfg(feature = "bio-interop")]
+pub use bio_interop::UnsupportedBioOperation;
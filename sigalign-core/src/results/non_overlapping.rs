@@ -0,0 +1,89 @@
+use std::cmp::Ordering;
+
+use super::{
+    QueryAlignment,
+    TargetAlignment,
+    Alignment,
+};
+
+impl QueryAlignment {
+    /// Keep only the non-overlapping alignments in each target (see `TargetAlignment::non_overlapping`).
+    pub fn non_overlapping(self, minimum_gap: u32) -> Self {
+        Self(
+            self.0.into_iter().map(|v| {
+                v.non_overlapping(minimum_gap)
+            }).collect()
+        )
+    }
+}
+
+impl TargetAlignment {
+    /// Keep only the maximal alignments that do not overlap on the target.
+    ///  - Alignments are ranked by `Alignment::cmp_by_rank` (lower penalty, then longer length),
+    ///    and then by the target and query positions.
+    ///  - An alignment is reported when its target interval is apart from
+    ///    the intervals of all higher-ranked reported alignments by at least `minimum_gap`.
+    ///  - The reported alignments are sorted by the rank.
+    pub fn non_overlapping(mut self, minimum_gap: u32) -> Self {
+        self.alignments.sort_unstable_by(cmp_alignment_by_rank);
+
+        let mut reported_intervals: Vec<(u32, u32)> = Vec::with_capacity(self.alignments.len());
+        self.alignments.retain(|alignment| {
+            let interval = alignment.position.target;
+            let is_separated = reported_intervals.iter().all(|&(start, end)| {
+                interval.0 >= end.saturating_add(minimum_gap)
+                || start >= interval.1.saturating_add(minimum_gap)
+            });
+            if is_separated {
+                reported_intervals.push(interval);
+            }
+            is_separated
+        });
+        self
+    }
+}
+
+fn cmp_alignment_by_rank(
+    a: &Alignment,
+    b: &Alignment,
+) -> Ordering {
+    a.cmp_by_rank(b)
+        .then(a.position.target.0.cmp(&b.position.target.0))
+        .then(a.position.query.0.cmp(&b.position.query.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::AlignmentPosition;
+
+    fn alignment(penalty: u32, length: u32, target: (u32, u32)) -> Alignment {
+        Alignment {
+            penalty,
+            length,
+            position: AlignmentPosition { query: (0, length), target },
+            operations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn overlapping_alignments_are_removed_by_rank() {
+        let target_alignment = TargetAlignment {
+            index: 0,
+            alignments: vec![
+                alignment(4, 80, (50, 130)),
+                alignment(0, 100, (0, 100)),
+                alignment(2, 100, (300, 400)),
+                alignment(0, 50, (105, 155)),
+            ],
+        };
+
+        let result = target_alignment.clone().non_overlapping(0);
+        let targets: Vec<(u32, u32)> = result.alignments.iter().map(|v| v.position.target).collect();
+        assert_eq!(targets, vec![(0, 100), (105, 155), (300, 400)]);
+
+        let result = target_alignment.non_overlapping(10);
+        let targets: Vec<(u32, u32)> = result.alignments.iter().map(|v| v.position.target).collect();
+        assert_eq!(targets, vec![(0, 100), (300, 400)]);
+    }
+}
@@ -0,0 +1,93 @@
+use super::Alignment;
+
+/// Orientation of the overlap (dovetail) alignment between the ends of the query and the target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OverlapOrientation {
+    /// The suffix of the query is aligned to the prefix of the target.
+    /// ```text
+    /// QUERY : -------------
+    ///             |||||||||
+    /// TARGET:     -------------
+    /// ```
+    QuerySuffixToTargetPrefix,
+    /// The prefix of the query is aligned to the suffix of the target.
+    /// ```text
+    /// QUERY :     -------------
+    ///             |||||||||
+    /// TARGET: -------------
+    /// ```
+    QueryPrefixToTargetSuffix,
+}
+
+/// An alignment between the ends of the query and the target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overlap {
+    pub target_index: u32,
+    pub orientation: OverlapOrientation,
+    /// Length of the overlapped region of the query
+    pub query_overlap_length: u32,
+    /// Length of the overlapped region of the target
+    pub target_overlap_length: u32,
+    pub alignment: Alignment,
+}
+
+impl Alignment {
+    /// Get the orientation if this alignment is an overlap of the query and the target.
+    ///  - None if the alignment does not reach the ends, or one sequence is contained in the other
+    ///    (both sides of the query or the target are aligned).
+    pub fn get_overlap_orientation(&self, query_length: u32, target_length: u32) -> Option<OverlapOrientation> {
+        let (query_start, query_end) = self.position.query;
+        let (target_start, target_end) = self.position.target;
+        let query_overhangs = (query_start != 0, query_end != query_length);
+        let target_overhangs = (target_start != 0, target_end != target_length);
+        match (query_overhangs, target_overhangs) {
+            ((true, false), (false, true)) => Some(OverlapOrientation::QuerySuffixToTargetPrefix),
+            ((false, true), (true, false)) => Some(OverlapOrientation::QueryPrefixToTargetSuffix),
+            _ => None,
+        }
+    }
+    /// Make `Overlap` if this alignment is an overlap (see `get_overlap_orientation`).
+    pub fn into_overlap(self, target_index: u32, query_length: u32, target_length: u32) -> Option<Overlap> {
+        let orientation = self.get_overlap_orientation(query_length, target_length)?;
+        Some(Overlap {
+            target_index,
+            orientation,
+            query_overlap_length: self.position.query.1 - self.position.query.0,
+            target_overlap_length: self.position.target.1 - self.position.target.0,
+            alignment: self,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::AlignmentPosition;
+
+    fn alignment_at(query: (u32, u32), target: (u32, u32)) -> Alignment {
+        Alignment {
+            penalty: 0,
+            length: query.1 - query.0,
+            position: AlignmentPosition { query, target },
+            operations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn only_dovetails_are_overlaps() {
+        // Query length: 100, target length: 200
+        let overlap = alignment_at((40, 100), (0, 60)).into_overlap(3, 100, 200).unwrap();
+        assert_eq!(overlap.orientation, OverlapOrientation::QuerySuffixToTargetPrefix);
+        assert_eq!((overlap.target_index, overlap.query_overlap_length, overlap.target_overlap_length), (3, 60, 60));
+        assert_eq!(
+            alignment_at((0, 70), (130, 200)).get_overlap_orientation(100, 200),
+            Some(OverlapOrientation::QueryPrefixToTargetSuffix),
+        );
+        // Contained
+        assert_eq!(alignment_at((0, 100), (50, 150)).get_overlap_orientation(100, 200), None);
+        assert_eq!(alignment_at((0, 100), (0, 100)).get_overlap_orientation(100, 200), None);
+        assert_eq!(alignment_at((0, 100), (0, 100)).get_overlap_orientation(100, 100), None);
+        // Not reaching the ends
+        assert_eq!(alignment_at((40, 90), (0, 50)).get_overlap_orientation(100, 200), None);
+    }
+}
@@ -0,0 +1,97 @@
+use super::{
+    Alignment,
+    AlignmentOperation,
+    AlignedSequences,
+};
+
+const MATCH: u8 = b'|';
+const SUBST: u8 = b'.';
+const GAP: u8 = b' ';
+
+impl Alignment {
+    /// Render the alignment in three lines (target, match bars, query) for human inspection.
+    ///  - `target` and `query` are the whole sequences (the position is applied).
+    ///  - In the middle line, `|` is a match, `.` is a substitution, and the space is a gap.
+    ///  - The lines are wrapped every `width` columns (not wrapped if 0), and each block is separated by an empty line.
+    ///  - Each line starts with the 1-based position of its first base and ends with the position of its last base.
+    pub fn pretty_print(&self, target: &[u8], query: &[u8], width: usize) -> String {
+        let AlignedSequences { target: aligned_target, query: aligned_query } = self.get_aligned_sequences(target, query);
+        let match_bars: Vec<u8> = self.operations.iter().flat_map(|operations| {
+            let symbol = match operations.operation {
+                AlignmentOperation::Match => MATCH,
+                AlignmentOperation::Subst => SUBST,
+                AlignmentOperation::Insertion | AlignmentOperation::Deletion => GAP,
+            };
+            (0..operations.count).map(move |_| symbol)
+        }).collect();
+
+        let width = if width == 0 { aligned_target.len().max(1) } else { width };
+        let position_width = self.position.target.1.max(self.position.query.1).to_string().len();
+        let mut target_position = self.position.target.0;
+        let mut query_position = self.position.query.0;
+        let blocks: Vec<String> = aligned_target.chunks(width)
+            .zip(aligned_query.chunks(width))
+            .zip(match_bars.chunks(width))
+            .map(|((target_block, query_block), match_block)| {
+                let target_start = target_position + 1;
+                let query_start = query_position + 1;
+                target_position += target_block.iter().filter(|base| **base != b'-').count() as u32;
+                query_position += query_block.iter().filter(|base| **base != b'-').count() as u32;
+                format!(
+                    "Target {:>pw$} {} {}\n       {:>pw$} {}\nQuery  {:>pw$} {} {}\n",
+                    target_start, String::from_utf8_lossy(target_block), target_position,
+                    "", String::from_utf8_lossy(match_block),
+                    query_start, String::from_utf8_lossy(query_block), query_position,
+                    pw = position_width,
+                )
+            }).collect();
+        blocks.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::{AlignmentOperations, AlignmentPosition};
+
+    #[test]
+    fn alignment_is_printed_in_wrapped_blocks() {
+        let target = b"TTACGTACGTACGTACGT";
+        let query = b"ACGTTACGTCCGT";
+        // ACGT-ACGTACGTA
+        // ACGTTACGTCCGT-
+        let alignment = Alignment {
+            penalty: 20,
+            length: 14,
+            position: AlignmentPosition { query: (0, 13), target: (2, 15) },
+            operations: vec![
+                AlignmentOperations { operation: AlignmentOperation::Match, count: 4 },
+                AlignmentOperations { operation: AlignmentOperation::Insertion, count: 1 },
+                AlignmentOperations { operation: AlignmentOperation::Match, count: 4 },
+                AlignmentOperations { operation: AlignmentOperation::Subst, count: 1 },
+                AlignmentOperations { operation: AlignmentOperation::Match, count: 3 },
+                AlignmentOperations { operation: AlignmentOperation::Deletion, count: 1 },
+            ],
+        };
+        assert_eq!(
+            alignment.pretty_print(target, query, 0),
+            concat!(
+                "Target  3 ACGT-ACGTACGTA 15\n",
+                "          |||| ||||.||| \n",
+                "Query   1 ACGTTACGTCCGT- 13\n",
+            ),
+        );
+        assert_eq!(
+            alignment.pretty_print(target, query, 8),
+            concat!(
+                "Target  3 ACGT-ACG 9\n",
+                "          |||| |||\n",
+                "Query   1 ACGTTACG 8\n",
+                "\n",
+                "Target 10 TACGTA 15\n",
+                "          |.||| \n",
+                "Query   9 TCCGT- 13\n",
+            ),
+        );
+    }
+}
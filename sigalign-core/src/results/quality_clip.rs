@@ -0,0 +1,300 @@
+use crate::aligner::AlignmentRegulator;
+use crate::core::regulators::{Penalty, is_ambiguous_base};
+use super::{
+    TargetAlignment,
+    Alignment,
+    AlignmentOperations,
+    AlignmentOperation,
+};
+
+impl TargetAlignment {
+    /// Clip the ends of all alignments by the quality of the query (see `Alignment::clip_ends_by_quality`).
+    ///  - `target` is the whole sequence of this target.
+    pub fn clip_ends_by_quality(
+        &mut self,
+        target: &[u8],
+        query: &[u8],
+        query_quality: &[u8],
+        regulator: &AlignmentRegulator,
+        clip_penalty: u32,
+    ) {
+        self.alignments.iter_mut().for_each(|alignment| {
+            alignment.clip_ends_by_quality(target, query, query_quality, regulator, clip_penalty);
+        });
+    }
+}
+
+impl Alignment {
+    /// Choose the boundary between the aligned and the clipped bases at each end with the quality of the query.
+    ///  - `target` and `query` are the whole sequences (the position is applied),
+    ///    and `query_quality` is the quality of each base of the query (any offset, e.g., Phred+33).
+    ///  - The candidate boundaries are the ends of the match runs near each end.
+    ///    The cost of a boundary is the penalty of the aligned part plus `clip_penalty` per clipped base of the query.
+    ///  - Of the boundaries with the lowest cost, the one with the highest mean quality of the aligned query bases is chosen,
+    ///    so the bases are clipped where the quality drops. The current end is kept if it is as good as the others.
+    ///  - The alignment is not clipped shorter than the minimum length of the `regulator`.
+    ///    With the homopolymer gap discount, the gaps are never clipped, since their penalties depend on the direction.
+    ///  - The aligned sequences are filled again if they were filled.
+    pub fn clip_ends_by_quality(
+        &mut self,
+        target: &[u8],
+        query: &[u8],
+        query_quality: &[u8],
+        regulator: &AlignmentRegulator,
+        clip_penalty: u32,
+    ) {
+        let penalties = EndPenalties::new(regulator);
+        let minimum_length = regulator.get_minimum_length();
+
+        // Right end
+        let (query_end, target_end) = (self.position.query.1 as usize, self.position.target.1 as usize);
+        let candidates = clip_candidates(
+            self.operations.iter().rev(),
+            |query_offset, target_offset| (
+                target[target_end - 1 - target_offset],
+                query[query_end - 1 - query_offset],
+                query_quality[query_end - 1 - query_offset],
+            ),
+            &penalties,
+        );
+        if let Some(clip) = self.best_clip(candidates, query_quality, clip_penalty, minimum_length) {
+            self.operations.truncate(self.operations.len() - clip.operation_count);
+            self.position.query.1 -= clip.query_length;
+            self.position.target.1 -= clip.target_length;
+            self.apply_clip(&clip);
+        }
+
+        // Left end
+        let (query_start, target_start) = (self.position.query.0 as usize, self.position.target.0 as usize);
+        let candidates = clip_candidates(
+            self.operations.iter(),
+            |query_offset, target_offset| (
+                target[target_start + target_offset],
+                query[query_start + query_offset],
+                query_quality[query_start + query_offset],
+            ),
+            &penalties,
+        );
+        if let Some(clip) = self.best_clip(candidates, query_quality, clip_penalty, minimum_length) {
+            self.operations.drain(..clip.operation_count);
+            self.position.query.0 += clip.query_length;
+            self.position.target.0 += clip.target_length;
+            self.apply_clip(&clip);
+        }
+    }
+    // The clip with the lowest cost, and then the highest mean quality of the aligned query bases.
+    //  - None if no clip is better than the current end.
+    fn best_clip(
+        &self,
+        candidates: Vec<ClipCandidate>,
+        query_quality: &[u8],
+        clip_penalty: u32,
+        minimum_length: u32,
+    ) -> Option<ClipCandidate> {
+        let (query_start, query_end) = self.position.query;
+        let quality_sum: u64 = query_quality[query_start as usize..query_end as usize]
+            .iter().map(|v| *v as u64).sum();
+        let query_length = (query_end - query_start) as u64;
+        // (cost, sum of the quality, count of the query bases) of the aligned part
+        let score_of = |clip: &ClipCandidate| (
+            self.penalty.saturating_sub(clip.penalty) + clip_penalty * clip.query_length,
+            quality_sum - clip.quality_sum,
+            query_length - clip.query_length as u64,
+        );
+
+        let mut best: Option<ClipCandidate> = None;
+        let mut best_score = (self.penalty, quality_sum, query_length);
+        for clip in candidates {
+            if self.length - clip.length < minimum_length {
+                break;
+            }
+            let score = score_of(&clip);
+            // Compare the mean qualities without the division
+            let is_better = score.0 < best_score.0
+                || (score.0 == best_score.0 && score.1 * best_score.2 > best_score.1 * score.2);
+            if is_better {
+                best = Some(clip);
+                best_score = score;
+            }
+        }
+        best
+    }
+    fn apply_clip(&mut self, clip: &ClipCandidate) {
+        self.penalty = self.penalty.saturating_sub(clip.penalty);
+        self.length -= clip.length;
+    }
+}
+
+// The operations clipped from an end, and their penalty and lengths
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ClipCandidate {
+    operation_count: usize,
+    penalty: u32,
+    query_length: u32,
+    target_length: u32,
+    length: u32,
+    quality_sum: u64,
+}
+
+struct EndPenalties {
+    mismatch: u32,
+    n: u32,
+    gaps: Penalty,
+    are_gaps_clippable: bool,
+}
+
+impl EndPenalties {
+    fn new(regulator: &AlignmentRegulator) -> Self {
+        Self {
+            mismatch: regulator.get_mismatch_penalty(),
+            n: regulator.get_n_penalty(),
+            gaps: regulator.decompressed_penalties(),
+            are_gaps_clippable: regulator.get_homopolymer_gap_discount().is_none(),
+        }
+    }
+}
+
+// The clips leaving a match at the end, from the nearest to the end
+//  - `operations` are iterated from the end.
+//  - `bases_at` gets the (target base, query base, quality) at the offsets of (query, target) from the end.
+fn clip_candidates<'a, I, F>(
+    operations: I,
+    bases_at: F,
+    penalties: &EndPenalties,
+) -> Vec<ClipCandidate> where
+    I: Iterator<Item = &'a AlignmentOperations>,
+    F: Fn(usize, usize) -> (u8, u8, u8),
+{
+    let mut candidates = Vec::new();
+    let mut clip = ClipCandidate {
+        operation_count: 0,
+        penalty: 0,
+        query_length: 0,
+        target_length: 0,
+        length: 0,
+        quality_sum: 0,
+    };
+    let mut operations = operations.peekable();
+    while let Some(operations_to_clip) = operations.next() {
+        // At least one match is left
+        let Some(next_operations) = operations.peek() else {
+            break;
+        };
+        let count = operations_to_clip.count;
+        match operations_to_clip.operation {
+            AlignmentOperation::Match | AlignmentOperation::Subst => {
+                for offset in 0..count {
+                    let (target_base, query_base, quality) = bases_at(
+                        (clip.query_length + offset) as usize,
+                        (clip.target_length + offset) as usize,
+                    );
+                    if operations_to_clip.operation == AlignmentOperation::Subst {
+                        clip.penalty += if is_ambiguous_base(query_base) || is_ambiguous_base(target_base) {
+                            penalties.n
+                        } else {
+                            penalties.mismatch
+                        };
+                    }
+                    clip.quality_sum += quality as u64;
+                }
+                clip.query_length += count;
+                clip.target_length += count;
+            },
+            AlignmentOperation::Insertion => {
+                if !penalties.are_gaps_clippable {
+                    break;
+                }
+                for offset in 0..count {
+                    clip.quality_sum += bases_at((clip.query_length + offset) as usize, 0).2 as u64;
+                }
+                clip.penalty += penalties.gaps.of_gap(count);
+                clip.query_length += count;
+            },
+            AlignmentOperation::Deletion => {
+                if !penalties.are_gaps_clippable {
+                    break;
+                }
+                clip.penalty += penalties.gaps.of_gap(count);
+                clip.target_length += count;
+            },
+        }
+        clip.length += count;
+        clip.operation_count += 1;
+        if next_operations.operation == AlignmentOperation::Match {
+            candidates.push(clip.clone());
+        }
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::AlignmentPosition;
+    use crate::test_utils::TEST_TARGET;
+
+    #[test]
+    fn quality_decides_between_equal_penalty_clips() {
+        use AlignmentOperation::*;
+        let regulator = AlignmentRegulator::new(4, 6, 2, 50, 0.2).unwrap();
+        let target = TEST_TARGET.to_vec();
+        // Substitutions at the second base and the second last base
+        let mut query = target.clone();
+        query[1] = b'G';
+        query[68] = b'A';
+        let alignment = Alignment {
+            penalty: 8,
+            length: 70,
+            position: AlignmentPosition { query: (0, 70), target: (0, 70) },
+            operations: vec![
+                AlignmentOperations { operation: Match, count: 1 },
+                AlignmentOperations { operation: Subst, count: 1 },
+                AlignmentOperations { operation: Match, count: 66 },
+                AlignmentOperations { operation: Subst, count: 1 },
+                AlignmentOperations { operation: Match, count: 1 },
+            ],
+        };
+        assert_eq!(alignment.verify(&target, &query, &regulator), Ok(()));
+
+        // With the clip penalty 2, clipping two bases costs the same as the substitution (4)
+        let high_quality = vec![b'I'; 70];
+        let mut clipped = alignment.clone();
+        clipped.clip_ends_by_quality(&target, &query, &high_quality, &regulator, 2);
+        assert_eq!(clipped, alignment);
+
+        // Quality drops at the right end
+        let mut low_quality_end = high_quality.clone();
+        low_quality_end[68..].copy_from_slice(b"##");
+        let mut clipped = alignment.clone();
+        clipped.clip_ends_by_quality(&target, &query, &low_quality_end, &regulator, 2);
+        assert_eq!(clipped.to_cigar(true), "1=1X66=");
+        assert_eq!(clipped.position, AlignmentPosition { query: (0, 68), target: (0, 68) });
+        assert_eq!((clipped.penalty, clipped.length), (4, 68));
+        assert_eq!(clipped.get_aligned_sequences(&target, &query).query, &query[..68]);
+        assert_eq!(clipped.verify(&target, &query, &regulator), Ok(()));
+
+        // Quality drops at the left end
+        let mut low_quality_start = high_quality.clone();
+        low_quality_start[..2].copy_from_slice(b"##");
+        let mut clipped = alignment.clone();
+        clipped.clip_ends_by_quality(&target, &query, &low_quality_start, &regulator, 2);
+        assert_eq!(clipped.to_cigar(true), "66=1X1=");
+        assert_eq!(clipped.position, AlignmentPosition { query: (2, 70), target: (2, 70) });
+        assert_eq!(clipped.verify(&target, &query, &regulator), Ok(()));
+
+        // The penalty decides if not tied
+        let mut clipped = alignment.clone();
+        clipped.clip_ends_by_quality(&target, &query, &high_quality, &regulator, 1);
+        assert_eq!(clipped.to_cigar(true), "66=");
+        assert_eq!(clipped.penalty, 0);
+        let mut clipped = alignment.clone();
+        clipped.clip_ends_by_quality(&target, &query, &low_quality_end, &regulator, 3);
+        assert_eq!(clipped, alignment);
+
+        // Not shorter than the minimum length
+        let regulator = AlignmentRegulator::new(4, 6, 2, 69, 0.2).unwrap();
+        let mut clipped = alignment.clone();
+        clipped.clip_ends_by_quality(&target, &query, &high_quality, &regulator, 1);
+        assert_eq!(clipped, alignment);
+    }
+}
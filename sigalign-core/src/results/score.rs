@@ -0,0 +1,49 @@
+use super::{
+    Alignment,
+    AlignmentOperation,
+};
+
+impl Alignment {
+    /// Get the number of matched bases.
+    pub fn count_matches(&self) -> u32 {
+        self.operations.iter().filter_map(|v| {
+            match v.operation {
+                AlignmentOperation::Match => Some(v.count),
+                _ => None,
+            }
+        }).sum()
+    }
+    /// Get the conventional (higher-is-better) score: `match_reward * matches - penalty`.
+    ///  - The aligner minimizes the penalty, where a match costs 0.
+    ///    This score is a post-processed view of the same result, so the alignment is not re-optimized for it:
+    ///    among the alignments of one query, the order by score can differ from the order by penalty.
+    ///  - `match_reward` can be zero or negative; the score is then non-positive.
+    pub fn score(&self, match_reward: i32) -> i64 {
+        match_reward as i64 * self.count_matches() as i64 - self.penalty as i64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::{AlignmentOperations, AlignmentPosition};
+
+    #[test]
+    fn score_is_reward_of_matches_minus_penalty() {
+        let alignment = Alignment {
+            penalty: 10,
+            length: 13,
+            position: AlignmentPosition { query: (0, 12), target: (0, 11) },
+            operations: vec![
+                AlignmentOperations { operation: AlignmentOperation::Match, count: 5 },
+                AlignmentOperations { operation: AlignmentOperation::Subst, count: 1 },
+                AlignmentOperations { operation: AlignmentOperation::Insertion, count: 1 },
+                AlignmentOperations { operation: AlignmentOperation::Match, count: 6 },
+            ],
+        };
+        assert_eq!(alignment.count_matches(), 11);
+        assert_eq!(alignment.score(2), 12);
+        assert_eq!(alignment.score(0), -10);
+        assert_eq!(alignment.score(-1), -21);
+    }
+}
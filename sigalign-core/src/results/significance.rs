@@ -0,0 +1,137 @@
+use super::Alignment;
+
+/// Karlin-Altschul style model to estimate the significance of alignments.
+///
+/// This is a rough estimate to rank the alignments, not an exact statistic:
+///  - The score is `match_reward * matches - penalty` (see `Alignment::score`), so the gap penalties are included in the score.
+///  - Bases are assumed to be independent and uniformly distributed (probability of match: 1/4).
+///  - `lambda` is solved for the ungapped scores (`match_reward` and `-mismatch_penalty`).
+///    The gapped `lambda` is smaller, so alignments with gaps can look more significant than they are.
+///  - `k` is not derived from the scores but given as a constant (default: `0.1`).
+///  - The lengths of the sequences are not corrected for the edge effect.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignificanceModel {
+    match_reward: u32,
+    lambda: f64,
+    k: f64,
+}
+
+impl SignificanceModel {
+    pub const DEFAULT_K: f64 = 0.1;
+
+    /// Make a new model. None if the expected score of a random base pair is not negative.
+    pub fn new(match_reward: u32, mismatch_penalty: u32) -> Option<Self> {
+        let lambda = solve_lambda(match_reward as f64, mismatch_penalty as f64)?;
+        Some(Self {
+            match_reward,
+            lambda,
+            k: Self::DEFAULT_K,
+        })
+    }
+    /// Set the constant `k` of the model.
+    pub fn set_k(&mut self, k: f64) {
+        self.k = k;
+    }
+    pub fn get_match_reward(&self) -> u32 {
+        self.match_reward
+    }
+    pub fn get_lambda(&self) -> f64 {
+        self.lambda
+    }
+    pub fn get_k(&self) -> f64 {
+        self.k
+    }
+}
+
+impl Alignment {
+    /// Get the expected number of alignments scoring at least as this alignment by chance (like the E-value).
+    ///  - Lower is more significant.
+    ///  - `reference_total_length`: total length of the targets searched.
+    ///  - `query_length`: length of the whole query, not only the aligned part.
+    pub fn significance(
+        &self,
+        model: &SignificanceModel,
+        reference_total_length: u64,
+        query_length: u32,
+    ) -> f64 {
+        let score = self.score(model.match_reward as i32) as f64;
+        model.k * reference_total_length as f64 * query_length as f64 * (-model.lambda * score).exp()
+    }
+    /// Get the score normalized by the model in bits: `(lambda * score - ln(k)) / ln(2)`.
+    ///  - Higher is more significant, and it does not depend on the lengths of the sequences.
+    pub fn bit_score(&self, model: &SignificanceModel) -> f64 {
+        let score = self.score(model.match_reward as i32) as f64;
+        (model.lambda * score - model.k.ln()) / std::f64::consts::LN_2
+    }
+}
+
+// Positive root of `p * exp(lambda * match_reward) + q * exp(-lambda * mismatch_penalty) = 1`
+fn solve_lambda(match_reward: f64, mismatch_penalty: f64) -> Option<f64> {
+    const MATCH_PROBABILITY: f64 = 0.25;
+    const MISMATCH_PROBABILITY: f64 = 0.75;
+    // The root exists only if the expected score is negative
+    if MATCH_PROBABILITY * match_reward - MISMATCH_PROBABILITY * mismatch_penalty >= 0.0 {
+        return None;
+    }
+    let f = |lambda: f64| {
+        MATCH_PROBABILITY * (lambda * match_reward).exp()
+        + MISMATCH_PROBABILITY * (-lambda * mismatch_penalty).exp()
+        - 1.0
+    };
+    // `f` is negative right after zero, and positive after the root
+    let mut upper = 1.0;
+    while f(upper) <= 0.0 {
+        upper *= 2.0;
+    }
+    let mut lower = 0.0;
+    for _ in 0..100 {
+        let middle = (lower + upper) / 2.0;
+        if f(middle) > 0.0 {
+            upper = middle;
+        } else {
+            lower = middle;
+        }
+    }
+    Some((lower + upper) / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::{AlignmentOperation, AlignmentOperations, AlignmentPosition};
+
+    fn alignment_with(matches: u32, mismatches: u32, mismatch_penalty: u32) -> Alignment {
+        Alignment {
+            penalty: mismatches * mismatch_penalty,
+            length: matches + mismatches,
+            position: AlignmentPosition { query: (0, matches + mismatches), target: (0, matches + mismatches) },
+            operations: vec![
+                AlignmentOperations { operation: AlignmentOperation::Match, count: matches },
+                AlignmentOperations { operation: AlignmentOperation::Subst, count: mismatches },
+            ],
+        }
+    }
+
+    #[test]
+    fn lambda_is_same_as_known_value() {
+        // Ungapped lambda of +1/-3 for the uniform bases
+        let model = SignificanceModel::new(1, 3).unwrap();
+        assert!((model.get_lambda() - 1.374).abs() < 0.001);
+        // Expected score is not negative
+        assert!(SignificanceModel::new(3, 1).is_none());
+        assert!(SignificanceModel::new(1, 0).is_none());
+    }
+    #[test]
+    fn longer_and_fewer_mismatches_are_more_significant() {
+        let model = SignificanceModel::new(1, 4).unwrap();
+        let significance = |matches, mismatches| {
+            alignment_with(matches, mismatches, 4).significance(&model, 1_000_000, 150)
+        };
+        assert!(significance(100, 0) < significance(50, 0));
+        assert!(significance(100, 0) < significance(100, 2));
+        let alignment = alignment_with(100, 2, 4);
+        assert!(
+            alignment.significance(&model, 1_000_000, 150) < alignment.significance(&model, 2_000_000, 150)
+        );
+    }
+}
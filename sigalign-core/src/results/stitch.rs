@@ -0,0 +1,229 @@
+use crate::aligner::AlignmentRegulator;
+use crate::core::regulators::Penalty;
+use super::{
+    Alignment,
+    AlignmentOperation,
+    AlignmentOperations,
+};
+
+impl Alignment {
+    /// Join this alignment with the next one on the same path (e.g., of the overlapping windows of a query).
+    ///  - `target` and `query` are the whole sequences (the position is applied).
+    ///  - The alignments are cut at the first match of the same query and target positions in both,
+    ///    and the operations of this alignment before the cut and of `next` from the cut are joined,
+    ///    so the overlapped part is counted once.
+    ///  - None if `next` does not start inside this alignment, or no match is shared (not co-linear).
+    ///  - If `next` does not end after this alignment, it is a part of this alignment, and this alignment is returned.
+    ///  - The penalty is recalculated from the joined operations without the homopolymer gap discount.
+    ///    The cutoff is not checked again.
+    pub fn stitch(
+        &self,
+        next: &Self,
+        target: &[u8],
+        query: &[u8],
+        regulator: &AlignmentRegulator,
+    ) -> Option<Self> {
+        let (query_start, query_end) = self.position.query;
+        if next.position.query.0 < query_start || query_end <= next.position.query.0 {
+            return None;
+        }
+        let (cut_query, cut_target) = self.first_shared_match(next)?;
+        if next.position.query.1 <= query_end {
+            return Some(self.clone());
+        }
+
+        let mut operations = split_operations_at_match(
+            &self.operations, self.position.query.0, self.position.target.0, cut_query, cut_target,
+        ).0;
+        let suffix = split_operations_at_match(
+            &next.operations, next.position.query.0, next.position.target.0, cut_query, cut_target,
+        ).1;
+        suffix.into_iter().for_each(|operations_to_add| {
+            match operations.last_mut() {
+                Some(last) if last.operation == operations_to_add.operation => last.count += operations_to_add.count,
+                _ => operations.push(operations_to_add),
+            }
+        });
+
+        let mut stitched = Self {
+            penalty: 0,
+            length: operations.iter().map(|v| v.count).sum(),
+            position: super::AlignmentPosition {
+                query: (query_start, next.position.query.1),
+                target: (self.position.target.0, next.position.target.1),
+            },
+            operations,
+        };
+        stitched.penalty = stitched.penalty_of_operations(target, query, regulator);
+        Some(stitched)
+    }
+    // (query, target) of the first match of `next` that is also a match of this alignment
+    fn first_shared_match(&self, next: &Self) -> Option<(u32, u32)> {
+        // Match positions of this alignment from the start of `next`: (query, target) sorted by the query
+        let mut matches: Vec<(u32, u32)> = Vec::new();
+        let (mut query_index, mut target_index) = (self.position.query.1, self.position.target.1);
+        for operations in self.operations.iter().rev() {
+            if query_index <= next.position.query.0 {
+                break;
+            }
+            match operations.operation {
+                AlignmentOperation::Match => {
+                    (0..operations.count).for_each(|_| {
+                        query_index -= 1;
+                        target_index -= 1;
+                        matches.push((query_index, target_index));
+                    });
+                },
+                AlignmentOperation::Subst => {
+                    query_index -= operations.count;
+                    target_index -= operations.count;
+                },
+                AlignmentOperation::Insertion => query_index -= operations.count,
+                AlignmentOperation::Deletion => target_index -= operations.count,
+            }
+        }
+        matches.reverse();
+
+        let (mut query_index, mut target_index) = (next.position.query.0, next.position.target.0);
+        for operations in next.operations.iter() {
+            if query_index >= self.position.query.1 {
+                break;
+            }
+            match operations.operation {
+                AlignmentOperation::Match => {
+                    let shared = (0..operations.count).map(|offset| {
+                        (query_index + offset, target_index + offset)
+                    }).find(|cell| {
+                        matches!(matches.binary_search_by_key(&cell.0, |v| v.0), Ok(index) if matches[index].1 == cell.1)
+                    });
+                    if shared.is_some() {
+                        return shared;
+                    }
+                    query_index += operations.count;
+                    target_index += operations.count;
+                },
+                AlignmentOperation::Subst => {
+                    query_index += operations.count;
+                    target_index += operations.count;
+                },
+                AlignmentOperation::Insertion => query_index += operations.count,
+                AlignmentOperation::Deletion => target_index += operations.count,
+            }
+        }
+        None
+    }
+    fn penalty_of_operations(&self, target: &[u8], query: &[u8], regulator: &AlignmentRegulator) -> u32 {
+        let penalties = Penalty { h: 0, ..regulator.decompressed_penalties() };
+        // Substitutions are penalized by the bases below
+        let gap_penalty = Penalty { x: 0, ..penalties.clone() }.of_operations(&self.operations);
+        let (mut query_index, mut target_index) = (self.position.query.0 as usize, self.position.target.0 as usize);
+        let substitution_penalty: u32 = self.operations.iter().map(|operations| {
+            let count = operations.count as usize;
+            let penalty = match operations.operation {
+                AlignmentOperation::Subst => (0..count).map(|offset| {
+                    penalties.of_substitution(query[query_index + offset], target[target_index + offset])
+                }).sum(),
+                _ => 0,
+            };
+            match operations.operation {
+                AlignmentOperation::Match | AlignmentOperation::Subst => {
+                    query_index += count;
+                    target_index += count;
+                },
+                AlignmentOperation::Insertion => query_index += count,
+                AlignmentOperation::Deletion => target_index += count,
+            }
+            penalty
+        }).sum();
+        gap_penalty + substitution_penalty
+    }
+}
+
+// Split the operations before the match at (cut_query, cut_target)
+fn split_operations_at_match(
+    operations: &[AlignmentOperations],
+    query_start: u32,
+    target_start: u32,
+    cut_query: u32,
+    cut_target: u32,
+) -> (Vec<AlignmentOperations>, Vec<AlignmentOperations>) {
+    let (mut query_index, mut target_index) = (query_start, target_start);
+    for (index, operations_of_run) in operations.iter().enumerate() {
+        let count = operations_of_run.count;
+        match operations_of_run.operation {
+            AlignmentOperation::Match => {
+                if query_index <= cut_query && cut_query < query_index + count && cut_query - query_index == cut_target - target_index {
+                    let offset = cut_query - query_index;
+                    let mut prefix = operations[..index].to_vec();
+                    if offset != 0 {
+                        prefix.push(AlignmentOperations { operation: AlignmentOperation::Match, count: offset });
+                    }
+                    let mut suffix = vec![AlignmentOperations { operation: AlignmentOperation::Match, count: count - offset }];
+                    suffix.extend_from_slice(&operations[index + 1..]);
+                    return (prefix, suffix);
+                }
+                query_index += count;
+                target_index += count;
+            },
+            AlignmentOperation::Subst => {
+                query_index += count;
+                target_index += count;
+            },
+            AlignmentOperation::Insertion => query_index += count,
+            AlignmentOperation::Deletion => target_index += count,
+        }
+    }
+    (operations.to_vec(), Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::AlignmentPosition;
+    use crate::test_utils::TEST_TARGET;
+
+    fn alignment_of(query: (u32, u32), target: (u32, u32), runs: &[(AlignmentOperation, u32)]) -> Alignment {
+        let operations: Vec<AlignmentOperations> = runs.iter().map(|(operation, count)| {
+            AlignmentOperations { operation: operation.clone(), count: *count }
+        }).collect();
+        Alignment {
+            penalty: 0,
+            length: operations.iter().map(|v| v.count).sum(),
+            position: AlignmentPosition { query, target },
+            operations,
+        }
+    }
+
+    #[test]
+    fn overlapped_part_is_counted_once() {
+        let unit = TEST_TARGET;
+        let mut query = unit.to_vec();
+        query[20] = b'G';
+        query.remove(50);
+        let regulator = AlignmentRegulator::new(4, 6, 2, 10, 0.3).unwrap();
+        // Windows of 0..45 and 30..69 of the query
+        let left = alignment_of((0, 45), (0, 45), &[
+            (AlignmentOperation::Match, 20), (AlignmentOperation::Subst, 1), (AlignmentOperation::Match, 24),
+        ]);
+        let right = alignment_of((30, 69), (30, 70), &[
+            (AlignmentOperation::Match, 20), (AlignmentOperation::Deletion, 1), (AlignmentOperation::Match, 19),
+        ]);
+        let stitched = left.stitch(&right, unit, &query, &regulator).unwrap();
+        assert_eq!(stitched.position, AlignmentPosition { query: (0, 69), target: (0, 70) });
+        assert_eq!(stitched.operations, alignment_of((0, 0), (0, 0), &[
+            (AlignmentOperation::Match, 20), (AlignmentOperation::Subst, 1), (AlignmentOperation::Match, 29),
+            (AlignmentOperation::Deletion, 1), (AlignmentOperation::Match, 19),
+        ]).operations);
+        assert_eq!((stitched.penalty, stitched.length), (4 + 8, 70));
+        assert_eq!(stitched.verify(unit, &query, &regulator), Ok(()));
+
+        // Part of the alignment
+        let inner = alignment_of((30, 40), (30, 40), &[(AlignmentOperation::Match, 10)]);
+        assert_eq!(left.stitch(&inner, unit, &query, &regulator), Some(left.clone()));
+        // Not on the same diagonal
+        let shifted = alignment_of((30, 40), (31, 41), &[(AlignmentOperation::Match, 10)]);
+        assert_eq!(left.stitch(&shifted, unit, &query, &regulator), None);
+        // Not overlapped
+        assert_eq!(right.stitch(&left, unit, &query, &regulator), None);
+    }
+}
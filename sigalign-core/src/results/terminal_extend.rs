@@ -0,0 +1,169 @@
+use crate::aligner::AlignmentRegulator;
+use crate::core::regulators::is_ambiguous_base;
+use super::{
+    TargetAlignment,
+    Alignment,
+    AlignmentOperations,
+    AlignmentOperation,
+};
+
+impl TargetAlignment {
+    /// Extend the ends of all alignments through the terminal matches (see `Alignment::extend_terminal_matches`).
+    ///  - `target` is the whole sequence of this target.
+    pub fn extend_terminal_matches(
+        &mut self,
+        target: &[u8],
+        query: &[u8],
+        regulator: &AlignmentRegulator,
+        max_bases: u32,
+    ) {
+        self.alignments.iter_mut().for_each(|alignment| {
+            alignment.extend_terminal_matches(target, query, regulator, max_bases);
+        });
+    }
+}
+
+impl Alignment {
+    /// Extend each end through at most `max_bases` bases without gaps, if the extended bases have more matches than mismatches.
+    ///  - `target` and `query` are the whole sequences (the position is applied).
+    ///  - The end is clipped by the cutoff of the penalty per length, so a terminal region matching except for
+    ///    a single mismatch next to the end can be left out. Of the extensions ending with a match,
+    ///    the one with the most matches minus mismatches is taken (the shortest of the ties).
+    ///  - The extended alignment is not checked with the cutoff again: it can exceed the maximum penalty per length.
+    ///  - The aligned sequences are filled again if they were filled.
+    pub fn extend_terminal_matches(
+        &mut self,
+        target: &[u8],
+        query: &[u8],
+        regulator: &AlignmentRegulator,
+        max_bases: u32,
+    ) {
+        let mismatch_penalty = regulator.get_mismatch_penalty();
+        let n_penalty = regulator.get_n_penalty();
+
+        // Right end
+        let (query_end, target_end) = (self.position.query.1 as usize, self.position.target.1 as usize);
+        let bases = query[query_end..].iter().zip(&target[target_end..]).map(|(q, t)| (*q, *t));
+        if let Some(extension) = best_extension(bases, max_bases, mismatch_penalty, n_penalty) {
+            self.position.query.1 += extension.length;
+            self.position.target.1 += extension.length;
+            self.apply_extension(&extension);
+            extension.operations.into_iter().for_each(|operation| push_operation(&mut self.operations, operation));
+        }
+
+        // Left end
+        let (query_start, target_start) = (self.position.query.0 as usize, self.position.target.0 as usize);
+        let bases = query[..query_start].iter().rev().zip(target[..target_start].iter().rev()).map(|(q, t)| (*q, *t));
+        if let Some(extension) = best_extension(bases, max_bases, mismatch_penalty, n_penalty) {
+            self.position.query.0 -= extension.length;
+            self.position.target.0 -= extension.length;
+            self.apply_extension(&extension);
+            let mut operations = Vec::with_capacity(self.operations.len() + extension.operations.len());
+            extension.operations.into_iter().rev().for_each(|operation| push_operation(&mut operations, operation));
+            self.operations.drain(..).for_each(|operations_to_push| {
+                push_operations(&mut operations, operations_to_push);
+            });
+            self.operations = operations;
+        }
+    }
+    fn apply_extension(&mut self, extension: &TerminalExtension) {
+        self.penalty += extension.penalty;
+        self.length += extension.length;
+    }
+}
+
+// The bases added to an end, from the nearest to the end
+struct TerminalExtension {
+    length: u32,
+    penalty: u32,
+    operations: Vec<AlignmentOperation>,
+}
+
+// The extension with the most matches minus mismatches, if positive
+//  - `bases` are the (query base, target base) from the end.
+fn best_extension<I>(
+    bases: I,
+    max_bases: u32,
+    mismatch_penalty: u32,
+    n_penalty: u32,
+) -> Option<TerminalExtension> where
+    I: Iterator<Item = (u8, u8)>,
+{
+    let mut operations = Vec::new();
+    let (mut net_matches, mut penalty) = (0_i64, 0);
+    let mut best: Option<(usize, u32)> = None;
+    let mut best_net_matches = 0;
+    for (query_base, target_base) in bases.take(max_bases as usize) {
+        if query_base == target_base {
+            operations.push(AlignmentOperation::Match);
+            net_matches += 1;
+            if net_matches > best_net_matches {
+                best = Some((operations.len(), penalty));
+                best_net_matches = net_matches;
+            }
+        } else {
+            operations.push(AlignmentOperation::Subst);
+            net_matches -= 1;
+            penalty += if is_ambiguous_base(query_base) || is_ambiguous_base(target_base) {
+                n_penalty
+            } else {
+                mismatch_penalty
+            };
+        }
+    }
+    best.map(|(length, penalty)| {
+        operations.truncate(length);
+        TerminalExtension { length: length as u32, penalty, operations }
+    })
+}
+
+fn push_operation(operations: &mut Vec<AlignmentOperations>, operation: AlignmentOperation) {
+    push_operations(operations, AlignmentOperations { operation, count: 1 });
+}
+fn push_operations(operations: &mut Vec<AlignmentOperations>, operations_to_push: AlignmentOperations) {
+    match operations.last_mut() {
+        Some(last) if last.operation == operations_to_push.operation => last.count += operations_to_push.count,
+        _ => operations.push(operations_to_push),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::AlignmentPosition;
+    use crate::test_utils::TEST_TARGET;
+
+    #[test]
+    fn terminal_mismatches_followed_by_matches_are_included() {
+        use AlignmentOperation::*;
+        let regulator = AlignmentRegulator::new(4, 6, 2, 50, 0.2).unwrap();
+        let target = TEST_TARGET.to_vec();
+        // Single mismatches before the last base and after the first three bases
+        let mut query = target.clone();
+        query[3] = b'G';
+        query[68] = b'G';
+        let alignment = Alignment {
+            penalty: 0,
+            length: 64,
+            position: AlignmentPosition { query: (4, 68), target: (4, 68) },
+            operations: vec![AlignmentOperations { operation: Match, count: 64 }],
+        };
+
+        // Net matches: 0 at the right end, and 2 at the left end
+        let mut extended = alignment.clone();
+        extended.extend_terminal_matches(&target, &query, &regulator, 4);
+        assert_eq!(extended.to_cigar(true), "3=1X64=");
+        assert_eq!(extended.position, AlignmentPosition { query: (0, 68), target: (0, 68) });
+        assert_eq!((extended.penalty, extended.length), (4, 68));
+        assert_eq!(extended.get_aligned_sequences(&target, &query).query, &query[..68]);
+        assert_eq!(extended.verify(&target, &query, &regulator), Ok(()));
+
+        // Not through the mismatch only
+        let mut extended = alignment.clone();
+        extended.extend_terminal_matches(&target, &query, &regulator, 1);
+        assert_eq!(extended, alignment);
+        let mut extended = alignment.clone();
+        extended.extend_terminal_matches(&target, &query, &regulator, 0);
+        assert_eq!(extended, alignment);
+    }
+}
@@ -0,0 +1,261 @@
+use thiserror::Error;
+
+use crate::aligner::AlignmentRegulator;
+use crate::core::regulators::{Penalty, is_ambiguous_base};
+use super::{
+    Alignment,
+    AlignmentOperation,
+};
+
+/// Error for the alignment inconsistent with the sequences or the penalties.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum AlignmentVerificationError {
+    #[error("Position (query: {query:?}, target: {target:?}) is out of the sequences.")]
+    OutOfSequences { query: (u32, u32), target: (u32, u32) },
+    #[error("Operations consume {consumed:?} bases of (query, target), but the position spans {spanned:?}.")]
+    SpanMismatch { consumed: (u32, u32), spanned: (u32, u32) },
+    #[error("Match operation at unequal bases (query: {query_index}, target: {target_index}).")]
+    UnequalMatch { query_index: u32, target_index: u32 },
+    #[error("Substitution operation at equal bases (query: {query_index}, target: {target_index}).")]
+    EqualSubstitution { query_index: u32, target_index: u32 },
+    #[error("Length is {stored}, but the operations have {computed}.")]
+    LengthMismatch { stored: u32, computed: u32 },
+    #[error("Penalty is {stored}, but the operations are penalized {computed}.")]
+    PenaltyMismatch { stored: u32, computed: u32 },
+}
+
+impl Alignment {
+    /// Check if the operations are consistent with the sequences and the penalties of the `regulator`.
+    ///  - `target` and `query` are the whole sequences (the position is applied).
+    ///  - The bases of the match operations must be the same, and the bases of the substitutions must differ.
+    ///  - The length and the penalty are recalculated from the operations and compared with the stored values.
+    ///  - With the homopolymer gap discount, the gaps extending the homopolymer from either side may or may not be discounted
+    ///    (the discount depends on the direction of the extension), so the penalty is accepted in both cases.
+    pub fn verify(
+        &self,
+        target: &[u8],
+        query: &[u8],
+        regulator: &AlignmentRegulator,
+    ) -> Result<(), AlignmentVerificationError> {
+        let (query_start, query_end) = self.position.query;
+        let (target_start, target_end) = self.position.target;
+        if query_start > query_end || target_start > target_end
+            || query_end as usize > query.len() || target_end as usize > target.len()
+        {
+            return Err(AlignmentVerificationError::OutOfSequences {
+                query: self.position.query,
+                target: self.position.target,
+            });
+        }
+        let consumed = self.operations.iter().fold((0, 0), |(query_count, target_count), operations| {
+            match operations.operation {
+                AlignmentOperation::Match | AlignmentOperation::Subst => (query_count + operations.count, target_count + operations.count),
+                AlignmentOperation::Insertion => (query_count + operations.count, target_count),
+                AlignmentOperation::Deletion => (query_count, target_count + operations.count),
+            }
+        });
+        let spanned = (query_end - query_start, target_end - target_start);
+        if consumed != spanned {
+            return Err(AlignmentVerificationError::SpanMismatch { consumed, spanned });
+        }
+
+        let mismatch_penalty = regulator.get_mismatch_penalty();
+        let n_penalty = regulator.get_n_penalty();
+        let homopolymer_gap_discount = regulator.get_homopolymer_gap_discount().unwrap_or(0);
+
+        // Substitutions are penalized by the bases below
+        let gap_penalties = Penalty { x: 0, n: 0, h: 0, ..regulator.decompressed_penalties() };
+
+        let mut query_index = query_start;
+        let mut target_index = target_start;
+        let mut length = 0;
+        let mut penalty = gap_penalties.of_operations(&self.operations);
+        // Number of the gaps that can be discounted
+        let mut homopolymer_gap_count = 0;
+        for operations in self.operations.iter() {
+            length += operations.count;
+            match operations.operation {
+                AlignmentOperation::Match | AlignmentOperation::Subst => {
+                    for _ in 0..operations.count {
+                        let query_base = query[query_index as usize];
+                        let target_base = target[target_index as usize];
+                        let is_match = operations.operation == AlignmentOperation::Match;
+                        if is_match && query_base != target_base {
+                            return Err(AlignmentVerificationError::UnequalMatch { query_index, target_index });
+                        } else if !is_match && query_base == target_base {
+                            return Err(AlignmentVerificationError::EqualSubstitution { query_index, target_index });
+                        } else if !is_match {
+                            penalty += if is_ambiguous_base(query_base) || is_ambiguous_base(target_base) {
+                                n_penalty
+                            } else {
+                                mismatch_penalty
+                            };
+                        }
+                        query_index += 1;
+                        target_index += 1;
+                    }
+                },
+                AlignmentOperation::Insertion => {
+                    if is_homopolymer_gap(query, query_index, operations.count) {
+                        homopolymer_gap_count += 1;
+                    }
+                    query_index += operations.count;
+                },
+                AlignmentOperation::Deletion => {
+                    if is_homopolymer_gap(target, target_index, operations.count) {
+                        homopolymer_gap_count += 1;
+                    }
+                    target_index += operations.count;
+                },
+            }
+        }
+        if length != self.length {
+            return Err(AlignmentVerificationError::LengthMismatch { stored: self.length, computed: length });
+        }
+        let is_penalty_matched = (0..=homopolymer_gap_count).any(|discounted_gap_count| {
+            penalty == self.penalty + homopolymer_gap_discount * discounted_gap_count
+        });
+        if !is_penalty_matched {
+            return Err(AlignmentVerificationError::PenaltyMismatch { stored: self.penalty, computed: penalty });
+        }
+        Ok(())
+    }
+}
+
+// If the first gapped base is the same as the previous base, or the last gapped base is the same as the next base
+fn is_homopolymer_gap(sequence: &[u8], start_index: u32, count: u32) -> bool {
+    let start_index = start_index as usize;
+    let end_index = start_index + count as usize;
+    count != 0 && (
+        (start_index != 0 && sequence[start_index] == sequence[start_index - 1])
+        || (end_index < sequence.len() && sequence[end_index - 1] == sequence[end_index])
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::{AlignmentOperations, AlignmentPosition};
+    use crate::test_utils::TEST_TARGET;
+
+    #[test]
+    fn operations_are_verified_with_the_sequences() {
+        let target = b"ACGTACGTACGTACGT";
+        let query = b"ACGTTACGTCCGT";
+        let regulator = AlignmentRegulator::new(4, 6, 2, 10, 0.5).unwrap();
+        // ACGT-ACGTACGTA
+        // ACGTTACGTCCGT-
+        let mut alignment = Alignment {
+            penalty: 0,
+            length: 14,
+            position: AlignmentPosition { query: (0, 13), target: (0, 13) },
+            operations: vec![
+                AlignmentOperations { operation: AlignmentOperation::Match, count: 4 },
+                AlignmentOperations { operation: AlignmentOperation::Insertion, count: 1 },
+                AlignmentOperations { operation: AlignmentOperation::Match, count: 4 },
+                AlignmentOperations { operation: AlignmentOperation::Subst, count: 1 },
+                AlignmentOperations { operation: AlignmentOperation::Match, count: 3 },
+                AlignmentOperations { operation: AlignmentOperation::Deletion, count: 1 },
+            ],
+        };
+        assert_eq!(
+            alignment.verify(target, query, &regulator),
+            Err(AlignmentVerificationError::PenaltyMismatch { stored: 0, computed: 20 }),
+        );
+        alignment.penalty = 20;
+        assert_eq!(alignment.verify(target, query, &regulator), Ok(()));
+
+        let mut wrong = alignment.clone();
+        wrong.length = 15;
+        assert_eq!(
+            wrong.verify(target, query, &regulator),
+            Err(AlignmentVerificationError::LengthMismatch { stored: 15, computed: 14 }),
+        );
+        let mut wrong = alignment.clone();
+        wrong.operations[3].operation = AlignmentOperation::Match;
+        assert_eq!(
+            wrong.verify(target, query, &regulator),
+            Err(AlignmentVerificationError::UnequalMatch { query_index: 9, target_index: 8 }),
+        );
+        let mut wrong = alignment.clone();
+        wrong.operations[4].operation = AlignmentOperation::Subst;
+        assert_eq!(
+            wrong.verify(target, query, &regulator),
+            Err(AlignmentVerificationError::EqualSubstitution { query_index: 10, target_index: 9 }),
+        );
+        let mut wrong = alignment.clone();
+        wrong.operations[5] = AlignmentOperations { operation: AlignmentOperation::Match, count: 1 };
+        assert_eq!(
+            wrong.verify(target, query, &regulator),
+            Err(AlignmentVerificationError::SpanMismatch { consumed: (14, 13), spanned: (13, 13) }),
+        );
+        wrong.position.query.1 = 14;
+        assert!(matches!(
+            wrong.verify(target, query, &regulator),
+            Err(AlignmentVerificationError::OutOfSequences { .. }),
+        ));
+    }
+    #[test]
+    fn penalty_is_recomputed_from_operations() {
+        let penalties = Penalty { x: 4, o: 6, e: 2, n: 1, h: 0, o2: 6, e2: 2 };
+        let operations = |runs: &[(AlignmentOperation, u32)]| -> Vec<AlignmentOperations> {
+            runs.iter().map(|(operation, count)| AlignmentOperations { operation: operation.clone(), count: *count }).collect()
+        };
+        // Two separate deletions and a substitution
+        let two_deletions = operations(&[
+            (AlignmentOperation::Match, 10),
+            (AlignmentOperation::Deletion, 2),
+            (AlignmentOperation::Match, 5),
+            (AlignmentOperation::Subst, 1),
+            (AlignmentOperation::Match, 5),
+            (AlignmentOperation::Deletion, 3),
+            (AlignmentOperation::Match, 10),
+        ]);
+        assert_eq!(penalties.of_operations(&two_deletions), (6 + 2 * 2) + 4 + (6 + 2 * 3));
+        // Deletion split into two runs is one gap
+        let split_deletion = operations(&[
+            (AlignmentOperation::Match, 10),
+            (AlignmentOperation::Deletion, 2),
+            (AlignmentOperation::Deletion, 3),
+            (AlignmentOperation::Match, 10),
+        ]);
+        assert_eq!(penalties.of_operations(&split_deletion), 6 + 2 * 5);
+        // Insertion next to the deletion is another gap
+        let adjacent_gaps = operations(&[
+            (AlignmentOperation::Match, 10),
+            (AlignmentOperation::Deletion, 2),
+            (AlignmentOperation::Insertion, 1),
+            (AlignmentOperation::Match, 10),
+        ]);
+        assert_eq!(penalties.of_operations(&adjacent_gaps), (6 + 2 * 2) + (6 + 2));
+        // Long gap is penalized by the second piece
+        let two_piece = Penalty { o2: 24, e2: 1, ..penalties.clone() };
+        let long_deletion = operations(&[
+            (AlignmentOperation::Match, 10),
+            (AlignmentOperation::Deletion, 30),
+            (AlignmentOperation::Match, 10),
+        ]);
+        assert_eq!(penalties.of_operations(&long_deletion), 6 + 2 * 30);
+        assert_eq!(two_piece.of_operations(&long_deletion), 24 + 30);
+        assert_eq!(two_piece.of_operations(&two_deletions), penalties.of_operations(&two_deletions));
+
+        // Checked in the verification
+        let unit = TEST_TARGET;
+        let query = [&unit[..20], &unit[22..50], &unit[53..]].concat();
+        let regulator = AlignmentRegulator::new(4, 6, 2, 50, 0.2).unwrap();
+        let alignment = Alignment {
+            penalty: (6 + 2 * 2) + (6 + 2 * 3),
+            length: 70,
+            position: AlignmentPosition { query: (0, 65), target: (0, 70) },
+            operations: operations(&[
+                (AlignmentOperation::Match, 20),
+                (AlignmentOperation::Deletion, 2),
+                (AlignmentOperation::Match, 28),
+                (AlignmentOperation::Deletion, 3),
+                (AlignmentOperation::Match, 17),
+            ]),
+        };
+        assert_eq!(penalties.of_operations(&alignment.operations), alignment.penalty);
+        assert_eq!(alignment.verify(unit, &query, &regulator), Ok(()));
+    }
+}
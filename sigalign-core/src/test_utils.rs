@@ -0,0 +1,87 @@
+//! Fixtures shared by the unit tests (also of `sigalign` with the `test-utils` feature).
+use crate::core::{BufferedPatternLocator, PatternLocation, SequenceBuffer};
+
+/// Sequence of 70 bp used as the target (or its unit) in the tests.
+pub const TEST_TARGET: &[u8; 70] = b"ACACAGATCGCAAACTCACAATTGTATTTCTTTGCCACCTGGGCATATACTTTTTGCGCCCCCTCATTTA";
+
+/// Seed of the `Xorshift` in the tests.
+pub const SEED: u32 = 2463534242;
+
+/// Simple xorshift to avoid the dependency for the tests.
+pub struct Xorshift(u32);
+
+impl Xorshift {
+    pub fn new(seed: u32) -> Self {
+        Self(seed)
+    }
+    pub fn next_u32(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+    pub fn gen_range(&mut self, max: u32) -> u32 {
+        self.next_u32() % max
+    }
+    pub fn dna(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| b"ACGT"[self.gen_range(4) as usize]).collect()
+    }
+}
+
+/// Random-like DNA sequence of `len` bases.
+pub fn random_dna(seed: u32, len: usize) -> Vec<u8> {
+    Xorshift::new(seed).dna(len)
+}
+
+/// Pattern locator searching the targets without the index.
+pub struct NaiveLocator {
+    targets: Vec<Vec<u8>>,
+    reversed: bool,
+}
+
+/// Buffer of the `NaiveLocator`.
+pub struct NaiveBuffer(pub Vec<u8>);
+
+impl NaiveLocator {
+    pub fn new(targets: Vec<Vec<u8>>) -> Self {
+        Self { targets, reversed: false }
+    }
+    /// Return the positions in descending order like the unsorted index.
+    pub fn with_reversed_positions(mut self, reversed: bool) -> Self {
+        self.reversed = reversed;
+        self
+    }
+}
+
+impl SequenceBuffer for NaiveBuffer {
+    fn buffered_sequence(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl BufferedPatternLocator for NaiveLocator {
+    type Buffer = NaiveBuffer;
+
+    fn locate(&self, pattern: &[u8], sorted_target_indices: &[u32]) -> Vec<PatternLocation> {
+        sorted_target_indices.iter().filter_map(|&target_index| {
+            let mut sorted_positions: Vec<u32> = self.targets[target_index as usize]
+                .windows(pattern.len())
+                .enumerate()
+                .filter(|(_, window)| *window == pattern)
+                .map(|(position, _)| position as u32)
+                .collect();
+            if self.reversed {
+                sorted_positions.reverse();
+            }
+            if sorted_positions.is_empty() {
+                None
+            } else {
+                Some(PatternLocation { target_index, sorted_positions })
+            }
+        }).collect()
+    }
+    fn fill_buffer(&self, target_index: u32, buffer: &mut Self::Buffer) {
+        buffer.0.clear();
+        buffer.0.extend_from_slice(&self.targets[target_index as usize]);
+    }
+}
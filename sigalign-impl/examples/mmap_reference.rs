@@ -0,0 +1,54 @@
+//! Align queries to the targets of a memory-mapped FASTA file without copying the targets.
+//!
+//! ```sh
+//! cargo run --release --example mmap_reference -- <FASTA with single-line sequences> <QUERY>
+//! ```
+use std::fs::File;
+
+use memmap2::Mmap;
+use sigalign_core::{
+    aligner::{AlignmentRegulator, local::LocalAligner},
+    reference::{Reference, SequenceStorage},
+};
+use sigalign_impl::{
+    pattern_index::dynamic_lfi::{DynamicLfi, DynamicLfiOption},
+    sequence_storage::borrowed::BorrowedStorage,
+};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let (Some(fasta_path), Some(query)) = (args.next(), args.next()) else {
+        eprintln!("Usage: mmap_reference <FASTA> <QUERY>");
+        std::process::exit(1);
+    };
+
+    // The mapped bytes outlive the reference borrowing them
+    let file = File::open(&fasta_path).expect("Failed to open the FASTA file");
+    let mmap = unsafe { Mmap::map(&file) }.expect("Failed to map the FASTA file");
+    let mut sequence_storage = BorrowedStorage::new();
+    if let Err(line_index) = sequence_storage.add_single_line_fasta(&mmap) {
+        eprintln!("Invalid record at line {}", line_index + 1);
+        std::process::exit(1);
+    }
+    let pattern_index_option = DynamicLfiOption {
+        suffix_array_sampling_ratio: 1,
+        lookup_table_max_bytes_size: 200 * 1024 * 1024,
+        use_safe_guard: true,
+    };
+    let reference: Reference<DynamicLfi, BorrowedStorage> = Reference::new(
+        sequence_storage,
+        pattern_index_option,
+    ).expect("Failed to build the pattern index");
+
+    let regulator = AlignmentRegulator::new(4, 6, 2, 50, 0.2).unwrap();
+    let mut aligner = LocalAligner::new(regulator);
+    let mut sequence_buffer = reference.get_sequence_storage().get_buffer();
+    let sorted_target_indices: Vec<u32> = (0..reference.num_targets()).collect();
+    let result = aligner.align(query.as_bytes(), &reference, &mut sequence_buffer, &sorted_target_indices);
+    for target_alignment in result.0 {
+        let label = reference.label_of_target_unchecked(target_alignment.index);
+        for alignment in target_alignment.alignments {
+            println!("{}\t{:?}\t{}", label, alignment.position, alignment.penalty);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use sigalign_core::reference::{
+    SequenceStorage,
+    SequenceBuffer,
+    extensions::LabelStorage,
+};
+
+/// `SequenceStorage` that borrows the sequences, e.g., from a memory-mapped file.
+///
+/// - The target sequences are not copied: the buffer is filled with the borrowed slice of each target,
+///   and the alignment reads it in place (in both directions).
+/// - The pattern index is still built from a concatenated copy of the targets,
+///   since the index owns its text (see `SequenceStorage::get_concatenated_sequence_with_boundaries_of_targets`).
+/// - The bytes must outlive the `Reference` built from this storage, which the lifetime ensures.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BorrowedStorage<'a> {
+    sequences: Vec<&'a [u8]>,
+    labels: Vec<&'a str>,
+}
+
+/// `SequenceBuffer` for `BorrowedStorage`.
+#[derive(Debug, Clone)]
+pub struct BorrowedBuffer<'a> {
+    sequence: &'a [u8],
+}
+
+impl<'a> SequenceStorage for BorrowedStorage<'a> {
+    type Buffer = BorrowedBuffer<'a>;
+
+    fn num_targets(&self) -> u32 {
+        self.sequences.len() as u32
+    }
+    fn get_buffer(&self) -> Self::Buffer {
+        BorrowedBuffer { sequence: &[] }
+    }
+    fn fill_buffer(&self, target_index: u32, buffer: &mut Self::Buffer) {
+        buffer.sequence = self.sequences[target_index as usize];
+    }
+}
+impl<'a> SequenceBuffer for BorrowedBuffer<'a> {
+    fn buffered_sequence(&self) -> &[u8] {
+        self.sequence
+    }
+}
+impl<'a> LabelStorage for BorrowedStorage<'a> {
+    fn label_of_target_unchecked(&self, target_index: u32) -> String {
+        self.labels[target_index as usize].to_string()
+    }
+}
+
+impl<'a> BorrowedStorage<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn add_target(
+        &mut self,
+        label: &'a str,
+        sequence: &'a [u8],
+    ) {
+        self.sequences.push(sequence);
+        self.labels.push(label);
+    }
+    /// Add the records of the FASTA formatted bytes without copying the sequences.
+    ///  - Each sequence must be in one line (e.g., a FASTA written with no line wrapping),
+    ///    since the sequences are borrowed as they are. Returns the index of the first line
+    ///    of a record that is not, or of a label that is not valid UTF-8, as the error.
+    ///  - Carriage returns at the ends of the lines are ignored.
+    pub fn add_single_line_fasta(&mut self, fasta: &'a [u8]) -> Result<(), usize> {
+        let mut lines = fasta.split(|v| *v == b'\n')
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty())
+            .peekable();
+        while let Some((line_index, header)) = lines.next() {
+            let label = header.strip_prefix(b">").ok_or(line_index)?;
+            let label = std::str::from_utf8(label).map_err(|_| line_index)?;
+            let label = label.split_whitespace().next().unwrap_or("");
+            let (_, sequence) = lines.next().ok_or(line_index)?;
+            if let Some((_, line)) = lines.peek() {
+                if !line.starts_with(b">") {
+                    return Err(line_index);
+                }
+            }
+            self.add_target(label, sequence);
+        }
+        Ok(())
+    }
+    pub fn get_sequence(&self, target_index: u32) -> Option<&'a [u8]> {
+        self.sequences.get(target_index as usize).copied()
+    }
+    pub fn get_total_length(&self) -> u64 {
+        self.sequences.iter().map(|v| v.len() as u64).sum()
+    }
+}
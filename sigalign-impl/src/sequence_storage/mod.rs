@@ -5,6 +5,9 @@ Implementations for the trait `sigalign_core::reference::SequenceStorage`.
 - `in_memory`: Stores the sequences in memory.
   - Generally most fast.
   - Requires enough memory to store all sequences.
+- `borrowed`: Borrows the sequences (e.g., from a memory-mapped file).
+  - The sequences are not copied, except for building the pattern index.
 */
 
-pub mod in_memory;
\ No newline at end of file
+pub mod in_memory;
+pub mod borrowed;
\ No newline at end of file
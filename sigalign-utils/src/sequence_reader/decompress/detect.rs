@@ -0,0 +1,140 @@
+use std::io::{Read, BufReader, Chain, Cursor, Result};
+#[cfg(feature = "flate2")]
+use flate2::read::MultiGzDecoder;
+
+const GZIP_MAGIC_BYTES: [u8; 2] = [0x1f, 0x8b];
+
+/// Reader that decompresses the gzip stream transparently.
+///  - Whether the stream is compressed is decided by the magic bytes.
+///  - Decompression is streamed. The whole file is not loaded into memory.
+///  - Without the `flate2` feature, the gzip stream is an error (`ErrorKind::Unsupported`).
+pub struct MaybeGzipReader<R: Read> {
+    inner: Inner<R>,
+}
+
+// The magic bytes read for the detection are put back in front of the stream
+type Prefixed<R> = Chain<Cursor<Vec<u8>>, R>;
+
+enum Inner<R: Read> {
+    Plain(BufReader<Prefixed<R>>),
+    #[cfg(feature = "flate2")]
+    Gzip(MultiGzDecoder<BufReader<Prefixed<R>>>),
+}
+
+impl<R: Read> MaybeGzipReader<R> {
+    pub fn new(mut reader: R) -> Result<Self> {
+        let magic_bytes = Self::read_magic_bytes(&mut reader)?;
+        let is_gzip = magic_bytes == GZIP_MAGIC_BYTES;
+        let buf_reader = BufReader::new(Cursor::new(magic_bytes).chain(reader));
+        let inner = if is_gzip {
+            #[cfg(feature = "flate2")]
+            { Inner::Gzip(MultiGzDecoder::new(buf_reader)) }
+            #[cfg(not(feature = "flate2"))]
+            {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    "gzip input requires the flate2 feature",
+                ));
+            }
+        } else {
+            Inner::Plain(buf_reader)
+        };
+        Ok(Self { inner })
+    }
+    pub fn is_gzip(&self) -> bool {
+        match self.inner {
+            Inner::Plain(_) => false,
+            #[cfg(feature = "flate2")]
+            Inner::Gzip(_) => true,
+        }
+    }
+    // A read can return fewer bytes than the magic bytes before the end of the stream (e.g., from a pipe),
+    //  so the bytes are read until the magic bytes are filled or the stream ends.
+    fn read_magic_bytes(reader: &mut R) -> Result<Vec<u8>> {
+        let mut magic_bytes = Vec::with_capacity(GZIP_MAGIC_BYTES.len());
+        reader.by_ref().take(GZIP_MAGIC_BYTES.len() as u64).read_to_end(&mut magic_bytes)?;
+        Ok(magic_bytes)
+    }
+}
+
+impl<R: Read> Read for MaybeGzipReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        match &mut self.inner {
+            Inner::Plain(v) => v.read(buf),
+            #[cfg(feature = "flate2")]
+            Inner::Gzip(v) => v.read(buf),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "flate2"))]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use flate2::{write::GzEncoder, Compression};
+
+    #[test]
+    fn gzip_stream_is_detected_and_decompressed() {
+        let plain = b">seq_1\nACGT\n>seq_2\nTTGCA\n".to_vec();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&plain).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut plain_reader = MaybeGzipReader::new(&plain[..]).unwrap();
+        let mut gzip_reader = MaybeGzipReader::new(&compressed[..]).unwrap();
+        assert!(!plain_reader.is_gzip());
+        assert!(gzip_reader.is_gzip());
+
+        let mut plain_buf = Vec::new();
+        let mut gzip_buf = Vec::new();
+        plain_reader.read_to_end(&mut plain_buf).unwrap();
+        gzip_reader.read_to_end(&mut gzip_buf).unwrap();
+        assert_eq!(plain_buf, plain);
+        assert_eq!(gzip_buf, plain);
+    }
+    // Returns one byte per read, like a pipe
+    struct OneByteReader<'a>(&'a [u8]);
+    impl Read for OneByteReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            if self.0.is_empty() || buf.is_empty() {
+                return Ok(0);
+            }
+            buf[0] = self.0[0];
+            self.0 = &self.0[1..];
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn gzip_stream_is_detected_from_the_short_reads() {
+        let plain = b">seq_1\nACGT\n".to_vec();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&plain).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut gzip_reader = MaybeGzipReader::new(OneByteReader(&compressed)).unwrap();
+        assert!(gzip_reader.is_gzip());
+        let mut gzip_buf = Vec::new();
+        gzip_reader.read_to_end(&mut gzip_buf).unwrap();
+        assert_eq!(gzip_buf, plain);
+
+        // Shorter than the magic bytes
+        let mut plain_reader = MaybeGzipReader::new(OneByteReader(b">")).unwrap();
+        assert!(!plain_reader.is_gzip());
+        let mut plain_buf = Vec::new();
+        plain_reader.read_to_end(&mut plain_buf).unwrap();
+        assert_eq!(plain_buf, b">");
+    }
+}
+
+#[cfg(all(test, not(feature = "flate2")))]
+mod tests_without_flate2 {
+    use super::*;
+
+    #[test]
+    fn gzip_stream_is_an_error() {
+        let error = MaybeGzipReader::new(&[0x1f, 0x8b, 0x08, 0x00][..]).err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::Unsupported);
+        assert!(!MaybeGzipReader::new(&b">seq_1\nACGT\n"[..]).unwrap().is_gzip());
+    }
+}
@@ -1,5 +1,12 @@
+#[cfg(feature = "flate2")]
 mod gzip;
+#[cfg(feature = "flate2")]
 pub use gzip::get_gzip_decoder;
 
+#[cfg(feature = "flate2")]
 mod zlib;
-pub use zlib::get_zlib_decoder;
\ No newline at end of file
+#[cfg(feature = "flate2")]
+pub use zlib::get_zlib_decoder;
+
+mod detect;
+pub use detect::MaybeGzipReader;
@@ -0,0 +1,117 @@
+use std::{
+    io::{BufRead, BufReader, Read, Error, ErrorKind},
+    fs::File,
+    path::Path,
+};
+
+use super::super::decompress::MaybeGzipReader;
+
+/// The reader of FASTA formatted file, reading one record at a time into the reusable buffers.
+///  - The ID (before the first whitespace of the header) and the sequence lines joined
+///    are lent until the next record is read.
+///  - Only one record is in memory, so the targets of a large file can be added one by one
+///    (e.g., with `ReferenceBuilder::add_target`).
+///  - Both LF and CRLF line endings are accepted, and the last line may not end with a newline.
+pub struct BufferedFastaReader<R: Read> {
+    reader: BufReader<R>,
+    line: Vec<u8>,
+    // Header line of the next record, if already read
+    next_header: Option<Vec<u8>>,
+    id: String,
+    seq: Vec<u8>,
+}
+
+impl<R: Read> BufferedFastaReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            line: Vec::new(),
+            next_header: None,
+            id: String::new(),
+            seq: Vec::new(),
+        }
+    }
+    /// Read the next record and get its (ID, sequence). None at the end of the file.
+    ///  - Error if the file is not started with a header, or the ID is invalid UTF8.
+    pub fn next_record(&mut self) -> Result<Option<(&str, &[u8])>, Error> {
+        let header = match self.next_header.take() {
+            Some(header) => header,
+            None => {
+                // The first record: skip the empty lines before the header
+                loop {
+                    if !self.read_line()? {
+                        return Ok(None);
+                    }
+                    if !self.line.is_empty() {
+                        break;
+                    }
+                }
+                if self.line[0] != b'>' {
+                    return Err(Error::new(ErrorKind::InvalidData, "FASTA record does not start with '>'"));
+                }
+                std::mem::take(&mut self.line)
+            },
+        };
+        let id = header[1..].split(|v| v.is_ascii_whitespace()).next().unwrap_or_default();
+        self.id.clear();
+        self.id.push_str(
+            std::str::from_utf8(id).map_err(|e| Error::new(ErrorKind::InvalidData, e))?
+        );
+
+        self.seq.clear();
+        while self.read_line()? {
+            if self.line.first() == Some(&b'>') {
+                self.next_header = Some(std::mem::take(&mut self.line));
+                break;
+            }
+            self.seq.extend_from_slice(&self.line);
+        }
+        Ok(Some((&self.id, &self.seq)))
+    }
+    // Read a line into `self.line` without the line ending. False at the end of the file.
+    fn read_line(&mut self) -> Result<bool, Error> {
+        self.line.clear();
+        if self.reader.read_until(b'\n', &mut self.line)? == 0 {
+            return Ok(false);
+        }
+        if self.line.last() == Some(&b'\n') {
+            self.line.pop();
+        }
+        if self.line.last() == Some(&b'\r') {
+            self.line.pop();
+        }
+        Ok(true)
+    }
+}
+impl BufferedFastaReader<MaybeGzipReader<File>> {
+    /// Open the file. Gzip compressed file is decompressed transparently.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = File::open(path)?;
+        Ok(Self::new(MaybeGzipReader::new(file)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_are_read_with_any_line_ending() {
+        let fasta = b"\n>target_1 description\r\nACGT\r\nacgt\r\n>target_2\n\nGGGG\nCC\n>empty\n>target_3\nTTTT";
+        let mut reader = BufferedFastaReader::new(&fasta[..]);
+        let mut records = Vec::new();
+        while let Some((id, seq)) = reader.next_record().unwrap() {
+            records.push((id.to_string(), seq.to_vec()));
+        }
+        assert_eq!(records, vec![
+            ("target_1".to_string(), b"ACGTacgt".to_vec()),
+            ("target_2".to_string(), b"GGGGCC".to_vec()),
+            ("empty".to_string(), Vec::new()),
+            ("target_3".to_string(), b"TTTT".to_vec()),
+        ]);
+        assert!(reader.next_record().unwrap().is_none());
+
+        let mut reader = BufferedFastaReader::new(&b"ACGT\n>target\nACGT\n"[..]);
+        assert_eq!(reader.next_record().unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+}
@@ -6,11 +6,15 @@ use seq_io::fasta::{
 };
 
 use super::{
+    decompress::MaybeGzipReader,
     SeqRecord,
     IdRecord,
     IdRefRecord,
 };
 
+mod buffered;
+pub use buffered::BufferedFastaReader;
+
 /// The reader of FASTA formatted file
 pub struct FastaReader<R: Read> {
     reader: SeqIoReader<R>,
@@ -45,6 +49,16 @@ impl FastaReader<File> {
         })
     }
 }
+impl FastaReader<MaybeGzipReader<File>> {
+    /// Open the file. Gzip compressed file is decompressed transparently.
+    pub fn from_path_maybe_gzip<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = File::open(path)?;
+        let reader = SeqIoReader::new(MaybeGzipReader::new(file)?);
+        Ok(Self {
+            reader
+        })
+    }
+}
 
 impl<'a> SeqRecord for FastaRecord<'a> {
     fn extend_seq_buf(&mut self, buf: &mut Vec<u8>) {
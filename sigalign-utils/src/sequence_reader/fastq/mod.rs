@@ -11,6 +11,7 @@ use seq_io::fastq::{
 };
 
 use super::{
+    decompress::MaybeGzipReader,
     SeqRecord,
     SeqRefRecord,
     IdRecord,
@@ -51,6 +52,16 @@ impl FastqReader<File> {
         })
     }
 }
+impl FastqReader<MaybeGzipReader<File>> {
+    /// Open the file. Gzip compressed file is decompressed transparently.
+    pub fn from_path_maybe_gzip<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
+        let file = File::open(path)?;
+        let reader = SeqIoReader::new(MaybeGzipReader::new(file)?);
+        Ok(Self {
+            reader
+        })
+    }
+}
 impl<'a> FastqReader<&'a [u8]> {
     pub fn from_bytes(bytes: &'a [u8]) -> Self {
         Self::new(bytes)
@@ -8,7 +8,7 @@ use crate::{
     reference::DefaultSequenceBuffer,
     results::QueryAlignment,
 };
-use super::{Algorithm, ParamsError, check_pattern_size};
+use super::{Algorithm, ParamsError, WithRegulator, check_pattern_size};
 
 // Structs
 #[derive(Clone)]
@@ -66,6 +66,22 @@ impl SemiGlobal {
     }
 }
 
+// Rebuild with the new regulator
+impl WithRegulator for Local {
+    fn with_regulator(self, regulator: AlignmentRegulator) -> Self {
+        Self {
+            inner: LocalAligner::new(regulator),
+        }
+    }
+}
+impl WithRegulator for SemiGlobal {
+    fn with_regulator(self, regulator: AlignmentRegulator) -> Self {
+        Self {
+            inner: SemiGlobalAligner::new(regulator),
+        }
+    }
+}
+
 // Implement Algorithm
 impl Algorithm for Local {
     fn align(
@@ -84,6 +100,7 @@ impl Algorithm for Local {
     fn regulator(&self) -> &AlignmentRegulator {
         self.inner.regulator()
     }
+    forward_option_setters!(inner);
 }
 
 impl Algorithm for SemiGlobal {
@@ -103,6 +120,7 @@ impl Algorithm for SemiGlobal {
     fn regulator(&self) -> &AlignmentRegulator {
         self.inner.regulator()
     }
+    forward_option_setters!(inner);
 }
 
 // Debug
@@ -7,6 +7,10 @@ pub enum ParamsError {
     InvalidValue(String),
     #[error("Operation inhibited due to low efficiency: {0}")]
     InhibitedLowEfficiency(String),
+    #[error("Invalid parameters file: {0}")]
+    InvalidFormat(#[from] serde_json::Error),
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
 }
 
 impl From::<RegulatorError> for ParamsError  {
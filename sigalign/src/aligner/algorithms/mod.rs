@@ -76,14 +76,53 @@ use super::{
 
 mod error;
 pub use error::ParamsError;
+pub use sigalign_core::aligner::{LengthMode, LowComplexityFilter, SeedPattern, MinimizerSeeder};
 use error::check_pattern_size;
 
+// Forward the options of the `Algorithm`
+//  - `inner`: to the aligner of the `sigalign-core`
+//  - `dispatch`: to the algorithm of the `DynamicAlgorithm`
+macro_rules! forward_option_setters {
+    ($target:ident) => {
+        forward_option_setters!(@setters $target;
+            set_minimum_anchor_count(count: u32),
+            set_minimum_chain_score(minimum_chain_score: Option<u32>),
+            set_low_complexity_filter(low_complexity_filter: Option<$crate::algorithms::LowComplexityFilter>),
+            set_seed_stride(seed_stride: Option<u32>),
+            set_seed_pattern(seed_pattern: Option<$crate::algorithms::SeedPattern>),
+            set_minimizer_seeder(minimizer_seeder: Option<$crate::algorithms::MinimizerSeeder>),
+            set_max_locations_per_pattern(max_locations_per_pattern: Option<u32>),
+            set_seed_extension_check(seed_extension_check: u32),
+            set_max_merge_mismatches(max_merge_mismatches: u32),
+            set_minimum_anchor_size(minimum_anchor_size: u32),
+            set_exact_chain_middle_size(exact_chain_middle_size: u32),
+            set_minimum_aligned_fraction(minimum_aligned_fraction: Option<f32>),
+            set_length_mode(length_mode: $crate::algorithms::LengthMode),
+            set_maximum_penalty(maximum_penalty: Option<u32>),
+            set_ungapped(ungapped: bool)
+        );
+    };
+    (@setters $target:ident; $($setter:ident($value:ident: $type:ty)),*) => { $(
+        fn $setter(&mut self, $value: $type) {
+            forward_option_setters!(@forward $target, self, $setter, $value)
+        }
+    )* };
+    (@forward inner, $self:ident, $setter:ident, $value:ident) => {
+        $self.inner.$setter($value)
+    };
+    (@forward dispatch, $self:ident, $setter:ident, $value:ident) => {
+        dispatch!($self, algorithm => algorithm.$setter($value))
+    };
+}
+
 mod basic;
 mod with_limit;
 mod with_chunk;
 pub use basic::{Local, SemiGlobal};
 pub use with_limit::{LocalWithLimit, SemiGlobalWithLimit};
 pub use with_chunk::{LocalWithChunk, SemiGlobalWithChunk};
+mod params;
+pub use params::{AlgorithmParams, AlignmentMode, DynamicAlgorithm};
 
 /// An alignment algorithm.
 pub trait Algorithm: std::fmt::Debug + Clone {
@@ -96,4 +135,229 @@ pub trait Algorithm: std::fmt::Debug + Clone {
     ) -> QueryAlignment;
     // Can access the regulator
     fn regulator(&self) -> &AlignmentRegulator;
+    // Can change the options used at each query
+    //  - The penalties and the cutoff define the workspace, so they are changed by rebuilding the algorithm.
+    fn set_minimum_anchor_count(&mut self, count: u32);
+    fn set_minimum_chain_score(&mut self, minimum_chain_score: Option<u32>);
+    fn set_low_complexity_filter(&mut self, low_complexity_filter: Option<LowComplexityFilter>);
+    fn set_seed_stride(&mut self, seed_stride: Option<u32>);
+    fn set_seed_pattern(&mut self, seed_pattern: Option<SeedPattern>);
+    fn set_minimizer_seeder(&mut self, minimizer_seeder: Option<MinimizerSeeder>);
+    fn set_max_locations_per_pattern(&mut self, max_locations_per_pattern: Option<u32>);
+    fn set_seed_extension_check(&mut self, seed_extension_check: u32);
+    fn set_max_merge_mismatches(&mut self, max_merge_mismatches: u32);
+    fn set_minimum_anchor_size(&mut self, minimum_anchor_size: u32);
+    fn set_exact_chain_middle_size(&mut self, exact_chain_middle_size: u32);
+    fn set_minimum_aligned_fraction(&mut self, minimum_aligned_fraction: Option<f32>);
+    fn set_length_mode(&mut self, length_mode: LengthMode);
+    fn set_maximum_penalty(&mut self, maximum_penalty: Option<u32>);
+    fn set_ungapped(&mut self, ungapped: bool);
+}
+
+// The penalties define the workspace of the aligner, so the algorithm is created again from the new regulator
+trait WithRegulator: Algorithm {
+    // Keep the other options (e.g., the limit or the chunk size)
+    fn with_regulator(self, regulator: AlignmentRegulator) -> Self;
+}
+fn rebuilt_with_regulator<A: WithRegulator>(
+    algorithm: A,
+    regulator: AlignmentRegulator,
+) -> Result<A, ParamsError> {
+    check_pattern_size(&regulator)?;
+    Ok(algorithm.with_regulator(regulator))
+}
+
+// Options of the penalties shared by all algorithms
+macro_rules! impl_penalty_options {
+    ($($algorithm:ty),*) => { $(
+        impl $algorithm {
+            /// Set the penalty of the substitution with the ambiguous base (N) (default: the mismatch penalty).
+            ///  - The lower penalty than the mismatch penalty makes the pattern size smaller.
+            ///  - Error if the penalty is 0, or the pattern size gets too small.
+            pub fn with_n_penalty(self, n_penalty: u32) -> Result<Self, ParamsError> {
+                let regulator = self.regulator().clone().with_n_penalty(n_penalty)?;
+                rebuilt_with_regulator(self, regulator)
+            }
+            /// Set the discount of the gap-open penalty for the gaps extending the homopolymers (default: `None`).
+            ///  - The discounted gap-open penalty makes the pattern size smaller.
+            ///  - Error if the discount is larger than the gap-open penalty, or the pattern size gets too small.
+            pub fn with_homopolymer_gap_discount(self, discount: Option<u32>) -> Result<Self, ParamsError> {
+                let regulator = self.regulator().clone().with_homopolymer_gap_discount(discount)?;
+                rebuilt_with_regulator(self, regulator)
+            }
+            /// Set the second piece of the gap penalty (default: single-piece).
+            ///  - A gap of length `l` is penalized by `min(o + e * l, o2 + e2 * l)`, so the long gaps are penalized by the lower slope.
+            ///  - The lower gap-extend penalty makes the pattern size smaller.
+            ///  - Error if the second piece is not costlier for the gap of length 1, its gap-extend penalty is not lower than the first,
+            ///    or the pattern size gets too small.
+            pub fn with_two_piece_gap(self, gap_open_penalty_2: u32, gap_extend_penalty_2: u32) -> Result<Self, ParamsError> {
+                let regulator = self.regulator().clone().with_two_piece_gap(gap_open_penalty_2, gap_extend_penalty_2)?;
+                rebuilt_with_regulator(self, regulator)
+            }
+        }
+    )* };
+}
+impl_penalty_options!(Local, SemiGlobal, LocalWithLimit, SemiGlobalWithLimit, LocalWithChunk, SemiGlobalWithChunk);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Aligner,
+        ReferenceBuilder,
+        algorithms::{Algorithm, Local, SemiGlobal, LocalWithChunk, SemiGlobalWithLimit},
+    };
+    use crate::test_utils::{TEST_TARGET, SEED, Xorshift, random_dna};
+
+    #[test]
+    fn n_penalty_is_applied_to_the_ambiguous_bases() {
+        let unit = TEST_TARGET;
+        let reference = ReferenceBuilder::new().add_target("target", unit).build().unwrap();
+        let mut query = unit.to_vec();
+        [10, 35, 60].iter().for_each(|&i| query[i] = b'N');
+        let penalty = |alignment: crate::results::QueryAlignment| alignment.0[0].alignments[0].penalty;
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        assert_eq!(aligner.get_n_penalty(), 4);
+        assert_eq!(penalty(aligner.align(&query, &reference)), 12);
+        // N penalty not dividable by the GCD of the other penalties
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap().with_n_penalty(1).unwrap());
+        assert_eq!(aligner.get_n_penalty(), 1);
+        assert_eq!(aligner.get_mismatch_penalty(), 4);
+        assert_eq!(penalty(aligner.align(&query, &reference)), 3);
+        assert!(Local::new(4, 6, 2, 50, 0.2).unwrap().with_n_penalty(0).is_err());
+        // The other options are kept
+        let algorithm = LocalWithChunk::new(4, 6, 2, 50, 0.2, 100, 50).unwrap().with_n_penalty(1).unwrap();
+        assert_eq!(algorithm.regulator().get_n_penalty(), 1);
+        assert_eq!(format!("{:?}", algorithm), format!("{:?}", LocalWithChunk::new(4, 6, 2, 50, 0.2, 100, 50).unwrap()));
+        let algorithm = SemiGlobalWithLimit::new(4, 6, 2, 50, 0.2, 3).unwrap().with_n_penalty(1).unwrap();
+        assert_eq!(format!("{:?}", algorithm), format!("{:?}", SemiGlobalWithLimit::new(4, 6, 2, 50, 0.2, 3).unwrap()));
+        let mut aligner = Aligner::new(SemiGlobal::new(4, 6, 2, 50, 0.2).unwrap().with_n_penalty(2).unwrap());
+        assert_eq!(penalty(aligner.align(&query, &reference)), 6);
+        aligner.set_ungapped(true);
+        assert_eq!(penalty(aligner.align(&query, &reference)), 6);
+        // N in the target
+        let mut target = unit.to_vec();
+        target[35] = b'N';
+        let reference = ReferenceBuilder::new().add_target("target", &target).build().unwrap();
+        let mut aligner = Aligner::new(SemiGlobal::new(4, 6, 2, 50, 0.2).unwrap().with_n_penalty(2).unwrap());
+        let alignment = aligner.align(unit, &reference).0[0].alignments[0].clone();
+        assert_eq!(alignment.penalty, 2);
+        assert_eq!(alignment.to_cigar(true), "35=1X34=");
+    }
+    #[test]
+    fn homopolymer_gap_is_discounted() {
+        use crate::{Reference, algorithms::Algorithm, results::{Alignment, AlignmentOperation}};
+
+        let unit = TEST_TARGET;
+        let reference = ReferenceBuilder::new().add_target("target", unit).build().unwrap();
+        // A deletion in the poly-T run (TTTTT -> TTTT)
+        let poly_t_start = unit.windows(5).position(|v| v == b"TTTTT").unwrap();
+        let mut query = unit.to_vec();
+        query.remove(poly_t_start + 2);
+        // A deletion out of the homopolymers (GCATA -> GATA)
+        let mut other_query = unit.to_vec();
+        other_query.remove(44);
+        fn alignment<A: Algorithm>(aligner: &mut Aligner<A>, query: &[u8], reference: &Reference) -> Alignment {
+            let query_alignment = aligner.align(query, reference);
+            assert_eq!(aligner.verify(query, reference, &query_alignment), Ok(()));
+            query_alignment.0[0].alignments[0].clone()
+        }
+
+        let mut aligner = Aligner::new(SemiGlobal::new(4, 6, 2, 50, 0.2).unwrap());
+        assert_eq!(aligner.get_homopolymer_gap_discount(), None);
+        assert_eq!(alignment(&mut aligner, &query, &reference).penalty, 8);
+
+        let mut aligner = Aligner::new(SemiGlobal::new(4, 6, 2, 50, 0.2).unwrap().with_homopolymer_gap_discount(Some(5)).unwrap());
+        assert_eq!(aligner.get_homopolymer_gap_discount(), Some(5));
+        assert_eq!(aligner.get_gap_open_penalty(), 6);
+        let discounted = alignment(&mut aligner, &query, &reference);
+        assert_eq!(discounted.penalty, 3);
+        assert_eq!(discounted.position.target, (0, 70));
+        assert_eq!(discounted.operations.iter().filter(|v| v.operation == AlignmentOperation::Deletion).count(), 1);
+        assert_eq!(alignment(&mut aligner, &other_query, &reference).penalty, 8);
+
+        // Not larger than the gap-open penalty
+        assert!(Local::new(4, 6, 2, 50, 0.2).unwrap().with_homopolymer_gap_discount(Some(10)).is_err());
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap().with_homopolymer_gap_discount(Some(6)).unwrap());
+        assert_eq!(aligner.get_homopolymer_gap_discount(), Some(6));
+        assert_eq!(alignment(&mut aligner, &query, &reference).penalty, 2);
+        assert_eq!(alignment(&mut aligner, &other_query, &reference).penalty, 8);
+    }
+    #[test]
+    fn linear_gap_penalty_is_same_as_edit_distance() {
+        // Random-like sequence
+        let mut rng = Xorshift::new(SEED);
+        let target: Vec<u8> = (0..300).map(|_| b"ACGT"[rng.gen_range(4) as usize]).collect();
+        let reference = ReferenceBuilder::new().add_target("target", &target).build().unwrap();
+        // Smallest penalty of aligning the whole query to any region of the target
+        let infix_distance = |query: &[u8], mismatch_penalty: u32, gap_penalty: u32| -> u32 {
+            let mut previous = vec![0; target.len() + 1];
+            for (query_index, query_base) in query.iter().enumerate() {
+                let mut current = vec![(query_index as u32 + 1) * gap_penalty; target.len() + 1];
+                for (target_index, target_base) in target.iter().enumerate() {
+                    let substitution = if query_base == target_base { 0 } else { mismatch_penalty };
+                    current[target_index + 1] = (previous[target_index] + substitution)
+                        .min(previous[target_index + 1] + gap_penalty)
+                        .min(current[target_index] + gap_penalty);
+                }
+                previous = current;
+            }
+            *previous.iter().min().unwrap()
+        };
+        // Edit distance (1, 0, 1), a gap cheaper than the mismatch (3, 0, 2),
+        // and a mismatch more expensive than two gaps (5, 0, 1)
+        for (mismatch_penalty, gap_extend_penalty, maximum_penalty_per_length) in [(1, 1, 0.1), (3, 2, 0.1), (5, 1, 0.15)] {
+            let mut aligner = Aligner::new(
+                SemiGlobal::new(mismatch_penalty, 0, gap_extend_penalty, 100, maximum_penalty_per_length).unwrap()
+            );
+            assert_eq!(aligner.get_gap_open_penalty(), 0);
+            for _ in 0..20 {
+                let mut query = target[50..250].to_vec();
+                // Edits at least 20 bases apart
+                (0..4).for_each(|i| {
+                    let position = 30 + i * 40 + rng.gen_range(20) as usize;
+                    match rng.gen_range(3) {
+                        0 => query[position] = if query[position] == b'A' { b'C' } else { b'A' },
+                        1 => { query.insert(position, b'G'); },
+                        _ => { query.remove(position); },
+                    }
+                });
+                let expected = infix_distance(&query, mismatch_penalty, gap_extend_penalty);
+                let result = aligner.align(&query, &reference);
+                let alignment = result.0[0].best_alignment().unwrap();
+                assert_eq!(alignment.position.query, (0, query.len() as u32));
+                assert_eq!(alignment.penalty, expected);
+            }
+        }
+    }
+    #[test]
+    fn long_gap_is_aligned_with_the_two_piece_gap() {
+        use crate::results::AlignmentOperation;
+
+        let target = random_dna(SEED, 300);
+        let reference = ReferenceBuilder::new().add_target("target", &target).build().unwrap();
+        // A deletion of 30 bases: 6 + 2 * 30 = 66 (single-piece) or 24 + 1 * 30 = 54 (two-piece)
+        let query = [&target[..150], &target[180..]].concat();
+
+        let mut aligner = Aligner::new(SemiGlobal::new(4, 6, 2, 100, 0.2).unwrap());
+        assert_eq!(aligner.get_two_piece_gap_penalties(), None);
+        assert!(aligner.align(&query, &reference).0.is_empty());
+
+        let algorithm = SemiGlobal::new(4, 6, 2, 100, 0.2).unwrap().with_two_piece_gap(24, 1).unwrap();
+        let mut aligner = Aligner::new(algorithm);
+        assert_eq!(aligner.get_two_piece_gap_penalties(), Some((24, 1)));
+        let query_alignment = aligner.align(&query, &reference);
+        assert_eq!(aligner.verify(&query, &reference, &query_alignment), Ok(()));
+        let alignment = &query_alignment.0[0].alignments[0];
+        assert_eq!(alignment.penalty, 54);
+        assert_eq!(alignment.position.target, (0, 300));
+        assert!(alignment.operations.iter().any(|v| v.operation == AlignmentOperation::Deletion && v.count == 30));
+        // Same for the local alignment
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 100, 0.2).unwrap().with_two_piece_gap(24, 1).unwrap());
+        let query_alignment = aligner.align(&query, &reference);
+        assert!(query_alignment.0[0].alignments.iter().any(|v| v.penalty == 54 && v.position.target == (0, 300)));
+
+        assert!(Local::new(4, 6, 2, 100, 0.2).unwrap().with_two_piece_gap(24, 2).is_err());
+        assert!(Local::new(4, 6, 2, 100, 0.2).unwrap().with_two_piece_gap(6, 1).is_err());
+    }
 }
@@ -0,0 +1,256 @@
+use std::io::Read;
+use serde::{Deserialize, Serialize};
+use sigalign_core::aligner::AlignmentRegulator;
+
+use crate::{
+    Reference,
+    reference::DefaultSequenceBuffer,
+    results::QueryAlignment,
+};
+use super::{
+    Algorithm, ParamsError, WithRegulator, rebuilt_with_regulator,
+    Local, SemiGlobal,
+    LocalWithLimit, SemiGlobalWithLimit,
+    LocalWithChunk, SemiGlobalWithChunk,
+};
+
+/// Parameters of the algorithm to be stored in a file (e.g., to version the parameters of a pipeline).
+///  - Written in JSON with the same names as the fields. The fields with `Option` or the default can be omitted.
+///  - Loaded with the same validation as `new` of the algorithms, so the invalid parameters return the same errors.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlgorithmParams {
+    /// Local or semi-global (default: local)
+    #[serde(default)]
+    pub mode: AlignmentMode,
+    pub mismatch_penalty: u32,
+    pub gap_open_penalty: u32,
+    pub gap_extend_penalty: u32,
+    pub minimum_length: u32,
+    pub maximum_penalty_per_length: f32,
+    /// The limit of the number of alignments (see `LocalWithLimit` and `SemiGlobalWithLimit`)
+    #[serde(default)]
+    pub limit: Option<u32>,
+    /// The segment size and the sliding size of the chunks (see `LocalWithChunk` and `SemiGlobalWithChunk`)
+    ///  - Can not be set with the limit.
+    #[serde(default)]
+    pub chunk: Option<(u32, u32)>,
+    /// See `with_n_penalty` of the algorithms
+    #[serde(default)]
+    pub n_penalty: Option<u32>,
+    /// See `with_homopolymer_gap_discount` of the algorithms
+    #[serde(default)]
+    pub homopolymer_gap_discount: Option<u32>,
+    /// See `with_two_piece_gap` of the algorithms (gap-open and gap-extend penalties of the second piece)
+    #[serde(default)]
+    pub two_piece_gap: Option<(u32, u32)>,
+}
+
+/// Alignment mode of `AlgorithmParams` (see the module documentation).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlignmentMode {
+    #[default]
+    Local,
+    SemiGlobal,
+}
+
+/// The algorithm chosen by `AlgorithmParams` at runtime.
+#[derive(Debug, Clone)]
+pub enum DynamicAlgorithm {
+    Local(Local),
+    SemiGlobal(SemiGlobal),
+    LocalWithLimit(LocalWithLimit),
+    SemiGlobalWithLimit(SemiGlobalWithLimit),
+    LocalWithChunk(LocalWithChunk),
+    SemiGlobalWithChunk(SemiGlobalWithChunk),
+}
+
+impl AlgorithmParams {
+    /// Load the parameters from JSON, checking that they make a valid algorithm.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, ParamsError> {
+        let params: Self = serde_json::from_reader(reader)?;
+        params.to_algorithm()?;
+        Ok(params)
+    }
+    /// Load the parameters from the JSON file, checking that they make a valid algorithm.
+    pub fn from_path<P: AsRef<std::path::Path>>(path: P) -> Result<Self, ParamsError> {
+        let file = std::fs::File::open(path)?;
+        Self::from_reader(std::io::BufReader::new(file))
+    }
+    /// Save the parameters in JSON.
+    pub fn to_json(&self) -> Result<String, ParamsError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+    /// Make the algorithm of the mode, with the limit or the chunk if set.
+    pub fn to_algorithm(&self) -> Result<DynamicAlgorithm, ParamsError> {
+        let (x, o, e, minl, maxp) = (
+            self.mismatch_penalty,
+            self.gap_open_penalty,
+            self.gap_extend_penalty,
+            self.minimum_length,
+            self.maximum_penalty_per_length,
+        );
+        let algorithm = match (self.mode, self.limit, self.chunk) {
+            (_, Some(_), Some(_)) => {
+                return Err(ParamsError::InvalidValue("Limit and chunk can not be set together.".to_string()));
+            },
+            (AlignmentMode::Local, None, None) => DynamicAlgorithm::Local(
+                self.with_options(Local::new(x, o, e, minl, maxp)?)?
+            ),
+            (AlignmentMode::SemiGlobal, None, None) => DynamicAlgorithm::SemiGlobal(
+                self.with_options(SemiGlobal::new(x, o, e, minl, maxp)?)?
+            ),
+            (AlignmentMode::Local, Some(limit), None) => DynamicAlgorithm::LocalWithLimit(
+                self.with_options(LocalWithLimit::new(x, o, e, minl, maxp, limit)?)?
+            ),
+            (AlignmentMode::SemiGlobal, Some(limit), None) => DynamicAlgorithm::SemiGlobalWithLimit(
+                self.with_options(SemiGlobalWithLimit::new(x, o, e, minl, maxp, limit)?)?
+            ),
+            (AlignmentMode::Local, None, Some((segment_size, sliding_size))) => DynamicAlgorithm::LocalWithChunk(
+                self.with_options(LocalWithChunk::new(x, o, e, minl, maxp, segment_size, sliding_size)?)?
+            ),
+            (AlignmentMode::SemiGlobal, None, Some((segment_size, sliding_size))) => DynamicAlgorithm::SemiGlobalWithChunk(
+                self.with_options(SemiGlobalWithChunk::new(x, o, e, minl, maxp, segment_size, sliding_size)?)?
+            ),
+        };
+        Ok(algorithm)
+    }
+    // Apply the optional penalties in the same order as chaining the `with_*` methods
+    fn with_options<A: WithRegulator>(&self, algorithm: A) -> Result<A, ParamsError> {
+        let mut regulator = algorithm.regulator().clone();
+        if let Some(n_penalty) = self.n_penalty {
+            regulator = regulator.with_n_penalty(n_penalty)?;
+        }
+        if self.homopolymer_gap_discount.is_some() {
+            regulator = regulator.with_homopolymer_gap_discount(self.homopolymer_gap_discount)?;
+        }
+        if let Some((gap_open_penalty_2, gap_extend_penalty_2)) = self.two_piece_gap {
+            regulator = regulator.with_two_piece_gap(gap_open_penalty_2, gap_extend_penalty_2)?;
+        }
+        rebuilt_with_regulator(algorithm, regulator)
+    }
+}
+
+// Implement Algorithm
+macro_rules! dispatch {
+    ($self:ident, $algorithm:ident => $expression:expr) => {
+        match $self {
+            DynamicAlgorithm::Local($algorithm) => $expression,
+            DynamicAlgorithm::SemiGlobal($algorithm) => $expression,
+            DynamicAlgorithm::LocalWithLimit($algorithm) => $expression,
+            DynamicAlgorithm::SemiGlobalWithLimit($algorithm) => $expression,
+            DynamicAlgorithm::LocalWithChunk($algorithm) => $expression,
+            DynamicAlgorithm::SemiGlobalWithChunk($algorithm) => $expression,
+        }
+    };
+}
+impl Algorithm for DynamicAlgorithm {
+    fn align(
+        &mut self,
+        query: &[u8],
+        reference: &Reference,
+        sequence_buffer: &mut DefaultSequenceBuffer,
+    ) -> QueryAlignment {
+        dispatch!(self, algorithm => algorithm.align(query, reference, sequence_buffer))
+    }
+    fn regulator(&self) -> &AlignmentRegulator {
+        dispatch!(self, algorithm => algorithm.regulator())
+    }
+    forward_option_setters!(dispatch);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithms::{Algorithm, AlgorithmParams, AlignmentMode, DynamicAlgorithm, ParamsError, Local, SemiGlobalWithChunk};
+
+    #[test]
+    fn algorithm_is_loaded_from_the_params_file() {
+        let json = r#"{
+            "mismatch_penalty": 4,
+            "gap_open_penalty": 6,
+            "gap_extend_penalty": 2,
+            "minimum_length": 50,
+            "maximum_penalty_per_length": 0.1,
+            "n_penalty": 1
+        }"#;
+        let params = AlgorithmParams::from_reader(json.as_bytes()).unwrap();
+        assert_eq!(params.homopolymer_gap_discount, None);
+        assert_eq!(params.two_piece_gap, None);
+        assert_eq!(params.mode, AlignmentMode::Local);
+        let algorithm = params.to_algorithm().unwrap();
+        assert!(matches!(algorithm, DynamicAlgorithm::Local(_)));
+        let expected = Local::new(4, 6, 2, 50, 0.1).unwrap().with_n_penalty(1).unwrap();
+        assert_eq!(format!("{:?}", algorithm.regulator()), format!("{:?}", expected.regulator()));
+        // Round trip
+        assert_eq!(AlgorithmParams::from_reader(params.to_json().unwrap().as_bytes()).unwrap(), params);
+        let path = std::env::temp_dir().join("sigalign_test_algorithm_params.json");
+        std::fs::write(&path, params.to_json().unwrap()).unwrap();
+        assert_eq!(AlgorithmParams::from_path(&path).unwrap(), params);
+        std::fs::remove_file(&path).unwrap();
+        // Two-piece gap as the pair of penalties
+        let two_piece_json = json.replace("\"n_penalty\": 1", "\"two_piece_gap\": [24, 1]");
+        let params = AlgorithmParams::from_reader(two_piece_json.as_bytes()).unwrap();
+        assert_eq!(params.two_piece_gap, Some((24, 1)));
+        let expected = Local::new(4, 6, 2, 50, 0.1).unwrap().with_two_piece_gap(24, 1).unwrap();
+        assert_eq!(format!("{:?}", params.to_algorithm().unwrap().regulator()), format!("{:?}", expected.regulator()));
+        assert_eq!(AlgorithmParams::from_reader(params.to_json().unwrap().as_bytes()).unwrap(), params);
+
+        // Same errors as `new`
+        let invalid_json = json.replace("\"maximum_penalty_per_length\": 0.1", "\"maximum_penalty_per_length\": 0.9");
+        assert_eq!(
+            AlgorithmParams::from_reader(invalid_json.as_bytes()).unwrap_err().to_string(),
+            Local::new(4, 6, 2, 50, 0.9).unwrap_err().to_string(),
+        );
+        let invalid_json = json.replace("\"mismatch_penalty\": 4", "\"mismatch_penalty\": 0");
+        assert_eq!(
+            AlgorithmParams::from_reader(invalid_json.as_bytes()).unwrap_err().to_string(),
+            Local::new(0, 6, 2, 50, 0.1).unwrap_err().to_string(),
+        );
+        // Unknown or missing fields
+        let unknown_field = json.replace("\"n_penalty\"", "\"pattern_size\"");
+        assert!(matches!(AlgorithmParams::from_reader(unknown_field.as_bytes()), Err(ParamsError::InvalidFormat(_))));
+        assert!(matches!(AlgorithmParams::from_reader(&b"{}"[..]), Err(ParamsError::InvalidFormat(_))));
+    }
+    #[test]
+    fn algorithm_of_the_mode_is_made_from_the_params() {
+        let json = r#"{
+            "mode": "semi_global",
+            "mismatch_penalty": 4,
+            "gap_open_penalty": 6,
+            "gap_extend_penalty": 2,
+            "minimum_length": 50,
+            "maximum_penalty_per_length": 0.1,
+            "chunk": [100, 50],
+            "n_penalty": 1
+        }"#;
+        let params = AlgorithmParams::from_reader(json.as_bytes()).unwrap();
+        assert_eq!(params.mode, AlignmentMode::SemiGlobal);
+        let algorithm = params.to_algorithm().unwrap();
+        let expected = SemiGlobalWithChunk::new(4, 6, 2, 50, 0.1, 100, 50).unwrap().with_n_penalty(1).unwrap();
+        match &algorithm {
+            DynamicAlgorithm::SemiGlobalWithChunk(algorithm) => assert_eq!(format!("{:?}", algorithm), format!("{:?}", expected)),
+            _ => panic!("Unexpected algorithm: {:?}", algorithm),
+        }
+        assert_eq!(format!("{:?}", algorithm.regulator()), format!("{:?}", expected.regulator()));
+        assert_eq!(AlgorithmParams::from_reader(params.to_json().unwrap().as_bytes()).unwrap(), params);
+
+        let limit_json = json.replace("\"chunk\": [100, 50]", "\"limit\": 3").replace("semi_global", "local");
+        let params = AlgorithmParams::from_reader(limit_json.as_bytes()).unwrap();
+        let mut algorithm = params.to_algorithm().unwrap();
+        assert!(matches!(algorithm, DynamicAlgorithm::LocalWithLimit(_)));
+        // The options are changed in the algorithm of the mode
+        algorithm.set_maximum_penalty(Some(12));
+        assert_eq!(algorithm.regulator().get_maximum_penalty(), Some(12));
+        // Same errors as `new`, and only one of the limit and the chunk
+        let invalid_json = json.replace("[100, 50]", "[0, 50]");
+        assert_eq!(
+            AlgorithmParams::from_reader(invalid_json.as_bytes()).unwrap_err().to_string(),
+            SemiGlobalWithChunk::new(4, 6, 2, 50, 0.1, 0, 50).unwrap_err().to_string(),
+        );
+        let invalid_json = json.replace("\"n_penalty\": 1", "\"limit\": 3");
+        assert!(matches!(AlgorithmParams::from_reader(invalid_json.as_bytes()), Err(ParamsError::InvalidValue(_))));
+        let unknown_mode = json.replace("semi_global", "global");
+        assert!(matches!(AlgorithmParams::from_reader(unknown_mode.as_bytes()), Err(ParamsError::InvalidFormat(_))));
+    }
+}
@@ -8,7 +8,7 @@ use crate::{
     reference::DefaultSequenceBuffer,
     results::QueryAlignment,
 };
-use super::{Algorithm, ParamsError, check_pattern_size};
+use super::{Algorithm, ParamsError, WithRegulator, check_pattern_size};
 
 // Structs
 #[derive(Clone)]
@@ -89,6 +89,24 @@ impl SemiGlobalWithChunk {
     }
 }
 
+// Rebuild with the new regulator
+impl WithRegulator for LocalWithChunk {
+    fn with_regulator(self, regulator: AlignmentRegulator) -> Self {
+        Self {
+            inner: LocalAligner::new(regulator),
+            ..self
+        }
+    }
+}
+impl WithRegulator for SemiGlobalWithChunk {
+    fn with_regulator(self, regulator: AlignmentRegulator) -> Self {
+        Self {
+            inner: SemiGlobalAligner::new(regulator),
+            ..self
+        }
+    }
+}
+
 // Implement Algorithm
 impl Algorithm for LocalWithChunk {
     fn align(
@@ -119,6 +137,7 @@ impl Algorithm for LocalWithChunk {
     fn regulator(&self) -> &AlignmentRegulator {
         self.inner.regulator()
     }
+    forward_option_setters!(inner);
 }
 
 impl Algorithm for SemiGlobalWithChunk {
@@ -150,6 +169,7 @@ impl Algorithm for SemiGlobalWithChunk {
     fn regulator(&self) -> &AlignmentRegulator {
         self.inner.regulator()
     }
+    forward_option_setters!(inner);
 }
 
 fn adjust_positions(
@@ -8,7 +8,7 @@ use crate::{
     reference::DefaultSequenceBuffer,
     results::QueryAlignment,
 };
-use super::{Algorithm, ParamsError, check_pattern_size};
+use super::{Algorithm, ParamsError, WithRegulator, check_pattern_size};
 
 // Structs
 #[derive(Clone)]
@@ -68,6 +68,22 @@ impl SemiGlobalWithLimit {
     }
 }
 
+// Rebuild with the new regulator
+impl WithRegulator for LocalWithLimit {
+    fn with_regulator(self, regulator: AlignmentRegulator) -> Self {
+        Self {
+            inner: LocalWithLimitAligner::new(regulator, self.inner.limit()),
+        }
+    }
+}
+impl WithRegulator for SemiGlobalWithLimit {
+    fn with_regulator(self, regulator: AlignmentRegulator) -> Self {
+        Self {
+            inner: SemiGlobalWithLimitAligner::new(regulator, self.inner.limit()),
+        }
+    }
+}
+
 // Implement Algorithm
 impl Algorithm for LocalWithLimit {
     fn align(
@@ -86,6 +102,7 @@ impl Algorithm for LocalWithLimit {
     fn regulator(&self) -> &AlignmentRegulator {
         self.inner.regulator()
     }
+    forward_option_setters!(inner);
 }
 
 impl Algorithm for SemiGlobalWithLimit {
@@ -105,6 +122,7 @@ impl Algorithm for SemiGlobalWithLimit {
     fn regulator(&self) -> &AlignmentRegulator {
         self.inner.regulator()
     }
+    forward_option_setters!(inner);
 }
 
 // Debug
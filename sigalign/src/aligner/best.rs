@@ -0,0 +1,156 @@
+use crate::{
+    results::{Alignment, TieBreak},
+    reference::Reference,
+};
+use super::{Aligner, algorithms::Algorithm};
+
+impl<A: Algorithm> Aligner<A> {
+    /// Set the policy to report only the alignments with the lowest penalty in each target (default: `None`).
+    ///  - `None`: all alignments are reported.
+    ///  - `Some(TieBreak)`: only the lowest penalty alignments are kept, and the ties are resolved by the policy.
+    pub fn set_tie_break(&mut self, tie_break: Option<TieBreak>) {
+        self.tie_break = tie_break;
+    }
+    /// Set the penalty for each base of the query outside the alignment (default: 0).
+    ///  - Used only to choose the best alignments by the tie-break (see `set_tie_break`),
+    ///    so that a longer alignment can be chosen over a shorter one with a slightly lower penalty.
+    ///  - The penalties of the reported alignments are not changed.
+    pub fn set_clip_penalty(&mut self, clip_penalty: u32) {
+        self.clip_penalty = clip_penalty;
+    }
+    /// Align a query to a reference and keep only the best alignment of each target.
+    ///  - The best alignment has the lowest penalty (longer one if penalties are the same).
+    ///  - All targets in the reference are returned in order, with `None` if no alignment is found.
+    pub fn align_best_per_target(&mut self, query: &[u8], reference: &Reference) -> Vec<(u32, Option<Alignment>)> {
+        let mut best_per_target: Vec<(u32, Option<Alignment>)> = (0..reference.get_num_targets()).map(|target_index| {
+            (target_index, None)
+        }).collect();
+        self.align(query, reference).into_best_per_target().into_iter().for_each(|(target_index, alignment)| {
+            best_per_target[target_index as usize].1 = Some(alignment);
+        });
+        best_per_target
+    }
+    /// Align a query to a reference and get the target having the best alignment.
+    ///  - Useful to choose one of the alternative sequences (e.g., linear paths of a graph labeled by path IDs).
+    ///  - See `QueryAlignment::best_target` for the criteria.
+    pub fn align_best_target(&mut self, query: &[u8], reference: &Reference) -> Option<(u32, Alignment)> {
+        self.align(query, reference).best_target().map(|(target_index, alignment)| {
+            (target_index, alignment.clone())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Aligner,
+        ReferenceBuilder,
+        algorithms::Local,
+    };
+    use crate::test_utils::TEST_TARGET;
+
+    #[test]
+    fn best_alignment_is_selected_for_each_target() {
+        let fasta = br#">target_1
+ACACAGATCGCAAACTCACAATTGTATTTCTTTGCCACCTGGGCATATACTTTTTGCGCCCCCTCATTTA
+>target_2
+GGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGG
+>target_3
+TCTGGGGCCATTGTATTTCTTTGCCAGCTGGGGCATATACTTTTTCCGCCCCCTCATTTACGCTCATCAC"#;
+        let reference = ReferenceBuilder::new()
+            .add_fasta(&fasta[..]).unwrap()
+            .build().unwrap();
+        let query = b"CAAACTCACAATTGTATTTCTTTGCCAGCTGGGCATATACTTTTTCCGCCCCCTCATTTAACTTCTTGGA";
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        let all_results = aligner.align(query, &reference);
+        let best_per_target = aligner.align_best_per_target(query, &reference);
+
+        assert_eq!(best_per_target.len(), 3);
+        assert!(best_per_target[1].1.is_none());
+        for target_alignment in all_results.0 {
+            let best = best_per_target[target_alignment.index as usize].1.as_ref().unwrap();
+            assert!(target_alignment.alignments.iter().all(|v| v.penalty >= best.penalty));
+        }
+    }
+    #[test]
+    fn tie_break_keeps_the_lowest_penalty_alignments() {
+        use crate::results::TieBreak;
+
+        let unit = TEST_TARGET;
+        let spacer = b"GGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGG";
+        let target = [&unit[..], spacer, unit].concat();
+        let reference = ReferenceBuilder::new()
+            .add_target("target", &target)
+            .build().unwrap();
+        let query = &unit[..];
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        assert_eq!(aligner.get_tie_break(), None);
+        let all = aligner.align(query, &reference);
+        let min_penalty = all.0[0].alignments.iter().map(|v| v.penalty).min().unwrap();
+
+        aligner.set_tie_break(Some(TieBreak::AllEqual));
+        let result = aligner.align(query, &reference);
+        assert_eq!(result.0[0].alignments.len(), 2);
+        assert!(result.0[0].alignments.iter().all(|v| v.penalty == min_penalty));
+
+        aligner.set_tie_break(Some(TieBreak::LeftmostTarget));
+        let result = aligner.align(query, &reference);
+        assert_eq!(result.0[0].alignments.len(), 1);
+        assert_eq!(result.0[0].alignments[0].position.target.0, 0);
+    }
+    #[test]
+    fn best_target_is_chosen_among_alternative_paths() {
+        let unit = TEST_TARGET;
+        let mut path_with_one_snp = unit.to_vec();
+        path_with_one_snp[40] = b'A';
+        let mut path_with_two_snps = path_with_one_snp.clone();
+        path_with_two_snps[20] = b'C';
+        let reference = ReferenceBuilder::new()
+            .add_target("path_2snps", &path_with_two_snps)
+            .add_target("path_ref", unit)
+            .add_target("path_1snp", &path_with_one_snp)
+            .build().unwrap();
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        let (target_index, alignment) = aligner.align_best_target(&path_with_one_snp, &reference).unwrap();
+        assert_eq!(reference.get_label(target_index).unwrap(), "path_1snp");
+        assert_eq!(alignment.penalty, 0);
+        let (target_index, alignment) = aligner.align_best_target(unit, &reference).unwrap();
+        assert_eq!(reference.get_label(target_index).unwrap(), "path_ref");
+        assert_eq!(alignment.penalty, 0);
+
+        let query_alignment = aligner.align(unit, &reference);
+        assert_eq!(query_alignment.0.len(), 3);
+        assert!(aligner.align_best_target(b"GGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGG", &reference).is_none());
+    }
+    #[test]
+    fn clip_penalty_chooses_the_longer_alignment() {
+        use crate::results::TieBreak;
+
+        let unit = TEST_TARGET;
+        let mut with_mismatches = unit.to_vec();
+        with_mismatches[10] = b'G';
+        with_mismatches[35] = b'G';
+        with_mismatches[60] = b'G';
+        let spacer = b"GGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGG";
+        let target = [&with_mismatches[..], spacer, &unit[..52], spacer].concat();
+        let reference = ReferenceBuilder::new()
+            .add_target("target", &target)
+            .build().unwrap();
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        aligner.set_tie_break(Some(TieBreak::LeftmostTarget));
+        assert_eq!(aligner.get_clip_penalty(), 0);
+        let result = aligner.align(unit, &reference);
+        let shorter = &result.0[0].alignments[0];
+        assert!(shorter.position.target.0 > 0);
+
+        aligner.set_clip_penalty(1);
+        let result = aligner.align(unit, &reference);
+        let longer = &result.0[0].alignments[0];
+        assert_eq!(longer.position.target, (0, 70));
+        assert!(longer.penalty > shorter.penalty);
+    }
+}
@@ -0,0 +1,125 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use thiserror::Error;
+
+use crate::{
+    results::{QueryAlignment, MappingResult},
+    reference::{Reference, ReferenceSet},
+};
+use super::{
+    Aligner,
+    QueryError,
+    algorithms::{Algorithm, ParamsError},
+};
+
+/// Error for the alignment, to handle all failures of the alignment as values.
+///  - Returned by the `try_` methods of the `Aligner`.
+///  - Bases absent from the reference are not errors; they are aligned as mismatches.
+#[derive(Error, Debug)]
+pub enum AlignmentError {
+    #[error("Invalid parameters: {0}")]
+    InvalidParams(#[from] ParamsError),
+    #[error("Query is empty")]
+    EmptyQuery,
+    #[error("Query length {length} is over the maximum {maximum_length} of the algorithm")]
+    TooLongQuery { length: usize, maximum_length: u32 },
+    #[error(transparent)]
+    InvalidQuery(#[from] QueryError),
+    #[error("Internal error during the alignment: {0}")]
+    Internal(String),
+}
+
+/// Alignment returning the `AlignmentError` instead of panicking (e.g., in a service).
+///  - The query is checked before the alignment, and these queries are rejected,
+///    though the methods without `try_` accept them:
+///     - An empty query: `AlignmentError::EmptyQuery`.
+///     - A query with any byte that is not an ASCII letter (`A-Z`, `a-z`), e.g., a digit, a space, `-`, `*`, `.`
+///       or a non-ASCII byte: `AlignmentError::InvalidQuery` with the first such byte and its position.
+///     - A query longer than the maximum query length of the algorithm (see `get_maximum_query_length`),
+///       where the penalties can overflow: `AlignmentError::TooLongQuery`.
+///  - For the other queries, the results are the same as the methods without `try_`.
+///  - A panic during the alignment is caught and returned as `AlignmentError::Internal`.
+///    The buffers of the aligner may be left inconsistent, so the aligner should be dropped after this error.
+impl<A: Algorithm> Aligner<A> {
+    /// Align a query to a reference.
+    pub fn try_align(&mut self, query: &[u8], reference: &Reference) -> Result<QueryAlignment, AlignmentError> {
+        checked_query(query, self.get_maximum_query_length())?;
+        catch_internal_error(|| self.align(query, reference))
+    }
+    /// Align a query to a reference and get only the coordinates and the penalties.
+    pub fn try_map(&mut self, query: &[u8], reference: &Reference) -> Result<Vec<MappingResult>, AlignmentError> {
+        checked_query(query, self.get_maximum_query_length())?;
+        catch_internal_error(|| self.map(query, reference))
+    }
+    /// Align a query to all references of the set.
+    pub fn try_align_to_reference_set(
+        &mut self,
+        query: &[u8],
+        reference_set: &ReferenceSet,
+    ) -> Result<QueryAlignment, AlignmentError> {
+        checked_query(query, self.get_maximum_query_length())?;
+        catch_internal_error(|| self.align_to_reference_set(query, reference_set))
+    }
+}
+
+fn checked_query(query: &[u8], maximum_length: u32) -> Result<(), AlignmentError> {
+    if query.is_empty() {
+        return Err(AlignmentError::EmptyQuery);
+    } else if query.len() > maximum_length as usize {
+        return Err(AlignmentError::TooLongQuery { length: query.len(), maximum_length });
+    }
+    match query.iter().position(|base| !base.is_ascii_alphabetic()) {
+        Some(position) => Err(QueryError::InvalidCharacter {
+            character: query[position] as char,
+            position,
+        }.into()),
+        None => Ok(()),
+    }
+}
+
+fn catch_internal_error<T, F: FnOnce() -> T>(f: F) -> Result<T, AlignmentError> {
+    catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "unknown panic".to_string()
+        };
+        AlignmentError::Internal(message)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ReferenceBuilder, algorithms::Local};
+    use crate::test_utils::TEST_TARGET;
+
+    #[test]
+    fn alignment_errors_are_returned_as_values() {
+        let unit = TEST_TARGET;
+        let reference = ReferenceBuilder::new().add_target("target", unit).build().unwrap();
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+
+        let expected = aligner.align(unit, &reference);
+        assert_eq!(aligner.try_align(unit, &reference).unwrap().to_json(), expected.to_json());
+        assert_eq!(aligner.try_map(unit, &reference).unwrap().len(), 1);
+        assert!(matches!(aligner.try_align(b"", &reference), Err(AlignmentError::EmptyQuery)));
+        assert!(matches!(
+            aligner.try_align(b"ACGT ACGT", &reference),
+            Err(AlignmentError::InvalidQuery(QueryError::InvalidCharacter { character: ' ', position: 4 })),
+        ));
+
+        let maximum_length = aligner.get_maximum_query_length();
+        let too_long_query = vec![b'A'; maximum_length as usize + 1];
+        assert!(matches!(
+            aligner.try_align(&too_long_query, &reference),
+            Err(AlignmentError::TooLongQuery { length, maximum_length: maximum }) if length == too_long_query.len() && maximum == maximum_length,
+        ));
+
+        let params_error: AlignmentError = Local::new(4, 6, 2, 50, 1.0).unwrap_err().into();
+        assert!(matches!(params_error, AlignmentError::InvalidParams(_)));
+        let internal_error = catch_internal_error(|| -> u32 { panic!("broken invariant") });
+        assert!(matches!(internal_error, Err(AlignmentError::Internal(message)) if message == "broken invariant"));
+    }
+}
@@ -0,0 +1,56 @@
+use crate::results::QueryAlignment;
+use super::{Aligner, algorithms::Algorithm};
+
+impl<A: Algorithm> Aligner<A> {
+    /// Set the penalty per length to flag the alignments as low-confidence (default: `None`, no flag).
+    ///  - A softer threshold than the cutoff: the alignments are still pruned and dropped by the cutoff,
+    ///    and the ones over this threshold are reported but flagged by `low_confidence_flags`.
+    pub fn set_warning_penalty_per_length(&mut self, warning_penalty_per_length: Option<f32>) {
+        self.warning_penalty_per_length = warning_penalty_per_length;
+    }
+    /// Whether each alignment is low-confidence (see `set_warning_penalty_per_length`).
+    ///  - In the same layout as the alignments: `flags[i][j]` is for `query_alignment.0[i].alignments[j]`.
+    ///  - All false if the warning threshold is not set.
+    pub fn low_confidence_flags(&self, query_alignment: &QueryAlignment) -> Vec<Vec<bool>> {
+        match self.warning_penalty_per_length {
+            Some(warning_penalty_per_length) => query_alignment.low_confidence_flags(warning_penalty_per_length),
+            None => query_alignment.0.iter().map(|v| vec![false; v.alignments.len()]).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Aligner,
+        ReferenceBuilder,
+        algorithms::Local,
+    };
+    use crate::test_utils::TEST_TARGET;
+
+    #[test]
+    fn alignments_over_the_warning_are_reported_with_flags() {
+        let unit = TEST_TARGET;
+        let mut with_mismatches = unit.to_vec();
+        with_mismatches[10] = b'G';
+        with_mismatches[35] = b'G';
+        with_mismatches[60] = b'G';
+        let reference = ReferenceBuilder::new()
+            .add_target("exact", unit)
+            .add_target("borderline", &with_mismatches)
+            .build().unwrap();
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        let expected = aligner.align(unit, &reference);
+        assert!(aligner.low_confidence_flags(&expected).iter().flatten().all(|is_low| !is_low));
+
+        aligner.set_warning_penalty_per_length(Some(0.1));
+        assert_eq!(aligner.get_warning_penalty_per_length(), Some(0.1));
+        let result = aligner.align(unit, &reference);
+        assert_eq!(result.count_alignments(), expected.count_alignments());
+        let flags = aligner.low_confidence_flags(&result);
+        result.0.iter().zip(flags).for_each(|(target_alignment, flags)| {
+            assert_eq!(flags, vec![target_alignment.index == 1; target_alignment.alignments.len()]);
+        });
+    }
+}
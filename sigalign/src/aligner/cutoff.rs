@@ -0,0 +1,123 @@
+use super::{
+    Aligner,
+    algorithms::{Algorithm, LengthMode},
+};
+
+impl<A: Algorithm> Aligner<A> {
+    /// Set the minimum length as a fraction of the query length (default: `None`).
+    ///  - e.g., `Some(0.8)` requires 80% of the query length to be aligned.
+    ///  - Only raises the minimum length of the algorithm for each query, so set the minimum length for the shortest queries.
+    pub fn set_minimum_aligned_fraction(&mut self, minimum_aligned_fraction: Option<f32>) {
+        self.algorithm.set_minimum_aligned_fraction(minimum_aligned_fraction);
+    }
+    /// Set the maximum penalty of an alignment regardless of the length (default: `None`, no limit).
+    ///  - The extension stops at this penalty, so the alignments are not longer than it allows.
+    ///  - If the penalties have a GCD, the maximum penalty is rounded down to its multiple.
+    pub fn set_maximum_penalty(&mut self, maximum_penalty: Option<u32>) {
+        self.algorithm.set_maximum_penalty(maximum_penalty);
+    }
+    /// Set the length compared with the minimum length of the cutoff (default: `LengthMode::Total`).
+    ///  - `Total`: length of the alignment including the gaps.
+    ///  - `QuerySpan` or `TargetSpan`: length of the aligned range of the query or the target.
+    pub fn set_length_mode(&mut self, length_mode: LengthMode) {
+        self.algorithm.set_length_mode(length_mode);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Aligner,
+        ReferenceBuilder,
+        algorithms::{Local, SemiGlobal},
+    };
+    use crate::test_utils::{TEST_TARGET, SEED, Xorshift};
+
+    #[test]
+    fn minimum_length_is_compared_with_the_selected_length() {
+        use crate::algorithms::{Algorithm, LengthMode};
+
+        let target = TEST_TARGET;
+        let reference = ReferenceBuilder::new()
+            .add_target("target", target)
+            .build().unwrap();
+        // Deletion of 3 bases: the query span is 67, and the others are 70.
+        let query = [&target[..35], &target[38..]].concat();
+
+        fn aligned_lengths<A: Algorithm>(mut aligner: Aligner<A>, query: &[u8], reference: &crate::Reference) -> Vec<Option<u32>> {
+            assert_eq!(aligner.get_length_mode(), LengthMode::Total);
+            [LengthMode::Total, LengthMode::QuerySpan, LengthMode::TargetSpan].into_iter().map(|length_mode| {
+                aligner.set_length_mode(length_mode);
+                let result = aligner.align(query, reference);
+                result.0.first().map(|v| v.alignments[0].length)
+            }).collect()
+        }
+        let expected = vec![Some(70), None, Some(70)];
+        assert_eq!(aligned_lengths(Aligner::new(Local::new(4, 6, 2, 68, 0.2).unwrap()), &query, &reference), expected);
+        assert_eq!(aligned_lengths(Aligner::new(SemiGlobal::new(4, 6, 2, 68, 0.2).unwrap()), &query, &reference), expected);
+    }
+    #[test]
+    fn maximum_penalty_limits_the_alignments() {
+        use crate::algorithms::Algorithm;
+
+        fn capped_penalties<A: Algorithm>(mut aligner: Aligner<A>, query: &[u8], reference: &crate::Reference) -> (u32, Vec<u32>) {
+            assert_eq!(aligner.get_maximum_penalty(), None);
+            let penalty_without_cap = aligner.align(query, reference).0[0].alignments[0].penalty;
+            aligner.set_maximum_penalty(Some(9));
+            assert_eq!(aligner.get_maximum_penalty(), Some(8));
+            let penalties = aligner.align(query, reference).0.iter()
+                .flat_map(|v| v.alignments.iter().map(|v| v.penalty))
+                .collect();
+            (penalty_without_cap, penalties)
+        }
+
+        let unit = TEST_TARGET;
+        let reference = ReferenceBuilder::new().add_target("target", unit).build().unwrap();
+        let mut query = unit.to_vec();
+        [10, 35, 60].iter().for_each(|&i| query[i] = if query[i] == b'A' { b'C' } else { b'A' });
+
+        // Semi-global: the whole query can not be aligned under the cap
+        let (penalty_without_cap, penalties) = capped_penalties(
+            Aligner::new(SemiGlobal::new(4, 6, 2, 50, 0.2).unwrap()), &query, &reference,
+        );
+        assert_eq!(penalty_without_cap, 12);
+        assert!(penalties.is_empty());
+        // Local: shorter alignments under the cap
+        let (penalty_without_cap, penalties) = capped_penalties(
+            Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap()), &query, &reference,
+        );
+        assert_eq!(penalty_without_cap, 12);
+        assert!(!penalties.is_empty());
+        assert!(penalties.iter().all(|&v| v <= 8));
+    }
+    #[test]
+    fn minimum_length_is_derived_from_the_query_length() {
+        let mut rng = Xorshift::new(SEED);
+        let target = rng.dna(1000);
+        let reference = ReferenceBuilder::new().add_target("target", &target).build().unwrap();
+        // (aligned length, unrelated length)
+        let queries: Vec<(usize, Vec<u8>)> = [(55, 5), (70, 30), (170, 30), (120, 80)].iter().map(|&(aligned, unrelated)| {
+            (aligned, [&target[100..100 + aligned], &rng.dna(unrelated)[..]].concat())
+        }).collect();
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        assert_eq!(aligner.get_minimum_aligned_fraction(), None);
+        let is_aligned = |aligner: &mut Aligner<Local>, query: &[u8]| {
+            let query_alignment = aligner.align(query, &reference);
+            query_alignment.0.iter().flat_map(|v| v.alignments.iter()).any(|alignment| {
+                alignment.position.target.0 == 100
+            })
+        };
+        assert!(queries.iter().all(|(_, query)| is_aligned(&mut aligner, query)));
+
+        aligner.set_minimum_aligned_fraction(Some(0.8));
+        assert_eq!(aligner.get_minimum_aligned_fraction(), Some(0.8));
+        // The minimum length is not lowered below 50 for the short query
+        let expected: Vec<bool> = queries.iter().map(|(aligned, query)| {
+            *aligned as f32 >= (0.8 * query.len() as f32).max(50.0)
+        }).collect();
+        assert_eq!(expected, vec![true, false, true, false]);
+        let result: Vec<bool> = queries.iter().map(|(_, query)| is_aligned(&mut aligner, query)).collect();
+        assert_eq!(result, expected);
+    }
+}
@@ -1,8 +1,9 @@
 use std::fmt::Debug;
 
+use crate::results::TieBreak;
 use super::{
     Aligner,
-    algorithms::Algorithm,
+    algorithms::{Algorithm, LengthMode, LowComplexityFilter, SeedPattern, MinimizerSeeder},
 };
 
 impl<A: Algorithm> Debug for Aligner<A> {
@@ -10,6 +11,13 @@ impl<A: Algorithm> Debug for Aligner<A> {
         f.debug_struct("Aligner")
             .field("algorithm", &self.algorithm)
             .field("sequence_buffer", &"InMemorySequenceBuffer")
+            .field("uppercase_query", &self.uppercase_query)
+            .field("maximum_gap_length", &self.maximum_gap_length)
+            .field("minimum_identity", &self.minimum_identity)
+            .field("tie_break", &self.tie_break)
+            .field("clip_penalty", &self.clip_penalty)
+            .field("warning_penalty_per_length", &self.warning_penalty_per_length)
+            .field("terminal_match_extend", &self.terminal_match_extend)
             .finish()
     }
 }
@@ -19,6 +27,10 @@ impl<A: Algorithm> Aligner<A> {
     pub fn get_mismatch_penalty(&self) -> u32 {
         self.algorithm.regulator().get_mismatch_penalty()
     }
+    /// Get the penalty of the substitution with the ambiguous base (N)
+    pub fn get_n_penalty(&self) -> u32 {
+        self.algorithm.regulator().get_n_penalty()
+    }
     /// Get gap-open penalty
     pub fn get_gap_open_penalty(&self) -> u32 {
         self.algorithm.regulator().get_gap_open_penalty()
@@ -27,6 +39,14 @@ impl<A: Algorithm> Aligner<A> {
     pub fn get_gap_extend_penalty(&self) -> u32 {
         self.algorithm.regulator().get_gap_extend_penalty()
     }
+    /// Get the discount of the gap-open penalty for the gaps extending the homopolymers
+    pub fn get_homopolymer_gap_discount(&self) -> Option<u32> {
+        self.algorithm.regulator().get_homopolymer_gap_discount()
+    }
+    /// Get the gap-open and gap-extend penalties of the second piece of the gap penalty
+    pub fn get_two_piece_gap_penalties(&self) -> Option<(u32, u32)> {
+        self.algorithm.regulator().get_two_piece_gap_penalties()
+    }
     /// Get minimum length
     pub fn get_minimum_length(&self) -> u32 {
         self.algorithm.regulator().get_minimum_length()
@@ -39,4 +59,105 @@ impl<A: Algorithm> Aligner<A> {
     pub fn get_pattern_size(&self) -> u32 {
         self.algorithm.regulator().get_pattern_size()
     }
+    /// Get the maximum length of the query to be aligned at once
+    ///  - The longer query can be aligned in the windows (see `align_long_query`).
+    pub fn get_maximum_query_length(&self) -> u32 {
+        self.algorithm.regulator().get_maximum_query_length()
+    }
+    /// Get the GCD of penalties (penalties are divided by it inside the algorithm)
+    pub fn get_gcd_for_compression(&self) -> u32 {
+        self.algorithm.regulator().get_gcd_for_compression()
+    }
+    /// Get the penalties used inside the algorithm as (mismatch, gap-open, gap-extend)
+    pub fn get_compressed_penalties(&self) -> (u32, u32, u32) {
+        self.algorithm.regulator().get_compressed_penalties()
+    }
+    /// Get minimum number of anchors in a target to be extended
+    pub fn get_minimum_anchor_count(&self) -> u32 {
+        self.algorithm.regulator().get_minimum_anchor_count()
+    }
+    /// Get the filter of the low-complexity patterns of the query
+    pub fn get_low_complexity_filter(&self) -> Option<LowComplexityFilter> {
+        self.algorithm.regulator().get_low_complexity_filter()
+    }
+    /// Get the stride of the seeds between the patterns
+    pub fn get_seed_stride(&self) -> Option<u32> {
+        self.algorithm.regulator().get_seed_stride()
+    }
+    /// Get the spaced seed of the patterns
+    pub fn get_seed_pattern(&self) -> Option<SeedPattern> {
+        self.algorithm.regulator().get_seed_pattern()
+    }
+    /// Get the minimizer sketch of the patterns
+    pub fn get_minimizer_seeder(&self) -> Option<MinimizerSeeder> {
+        self.algorithm.regulator().get_minimizer_seeder()
+    }
+    /// Get the maximum number of locations of a pattern to be used as anchors
+    pub fn get_max_locations_per_pattern(&self) -> Option<u32> {
+        self.algorithm.regulator().get_max_locations_per_pattern()
+    }
+    /// Get the number of the flanking bases to verify the anchors of a single pattern
+    pub fn get_seed_extension_check(&self) -> u32 {
+        self.algorithm.regulator().get_seed_extension_check()
+    }
+    /// Get the maximum number of mismatches between two anchors to be merged
+    pub fn get_max_merge_mismatches(&self) -> u32 {
+        self.algorithm.regulator().get_max_merge_mismatches()
+    }
+    /// Get the minimum length of the exact match around an anchor to be extended
+    pub fn get_minimum_anchor_size(&self) -> u32 {
+        self.algorithm.regulator().get_minimum_anchor_size()
+    }
+    /// Get the maximum size of the middle between two chained anchors to be aligned exactly
+    pub fn get_exact_chain_middle_size(&self) -> u32 {
+        self.algorithm.regulator().get_exact_chain_middle_size()
+    }
+    /// Get the minimum length as a fraction of the query length
+    pub fn get_minimum_aligned_fraction(&self) -> Option<f32> {
+        self.algorithm.regulator().get_minimum_aligned_fraction()
+    }
+    /// Get the maximum penalty of an alignment regardless of the length
+    pub fn get_maximum_penalty(&self) -> Option<u32> {
+        self.algorithm.regulator().get_maximum_penalty()
+    }
+    /// Get the minimum score of the anchor chains to be extended
+    pub fn get_minimum_chain_score(&self) -> Option<u32> {
+        self.algorithm.regulator().get_minimum_chain_score()
+    }
+    /// Get the length compared with the minimum length of the cutoff
+    pub fn get_length_mode(&self) -> LengthMode {
+        self.algorithm.regulator().get_length_mode()
+    }
+    /// Get whether the anchors are extended without gaps
+    pub fn get_ungapped(&self) -> bool {
+        self.algorithm.regulator().get_ungapped()
+    }
+    /// Get whether the query is converted to uppercase before alignment
+    pub fn get_uppercase_query(&self) -> bool {
+        self.uppercase_query
+    }
+    /// Get the maximum length of a single gap
+    pub fn get_maximum_gap_length(&self) -> Option<u32> {
+        self.maximum_gap_length
+    }
+    /// Get the minimum identity of the alignments
+    pub fn get_minimum_identity(&self) -> Option<f32> {
+        self.minimum_identity
+    }
+    /// Get the tie-breaking policy for the alignments with the same lowest penalty
+    pub fn get_tie_break(&self) -> Option<TieBreak> {
+        self.tie_break
+    }
+    /// Get the penalty for each clipped base of the query used by the tie-break
+    pub fn get_clip_penalty(&self) -> u32 {
+        self.clip_penalty
+    }
+    /// Get the penalty per length to flag the alignments as low-confidence
+    pub fn get_warning_penalty_per_length(&self) -> Option<f32> {
+        self.warning_penalty_per_length
+    }
+    /// Get the maximum number of bases to extend each end through the terminal matches
+    pub fn get_terminal_match_extend(&self) -> u32 {
+        self.terminal_match_extend
+    }
 }
@@ -0,0 +1,133 @@
+use sigalign_core::reference::{
+    SequenceStorage as _,
+    SequenceBuffer as _,
+};
+use crate::{
+    results::{QueryAlignment, Alignment},
+    reference::Reference,
+};
+use super::{Aligner, algorithms::Algorithm};
+
+impl<A: Algorithm> Aligner<A> {
+    /// Set the maximum number of bases to extend each end of the alignments through the terminal matches (default: 0, not extended).
+    ///  - The end is extended without gaps if the extended bases have more matches than mismatches
+    ///    (see `Alignment::extend_terminal_matches`), so a single mismatch near the end does not clip the matches after it.
+    ///  - The extended alignments can exceed the cutoff of the penalty per length.
+    ///  - Extended before the other filters of the aligner and the clip by the quality.
+    pub fn set_terminal_match_extend(&mut self, terminal_match_extend: u32) {
+        self.terminal_match_extend = terminal_match_extend;
+    }
+    /// Align a query with the quality of each base, and clip the ends of the alignments where the quality drops.
+    ///  - At each end, of the clip boundaries with the same penalty including the clip penalty (see `set_clip_penalty`),
+    ///    the one with the highest mean quality of the aligned bases is chosen (see `Alignment::clip_ends_by_quality`).
+    ///  - Clipped before the other filters of the aligner, so the filters and the tie-break see the clipped alignments.
+    ///  - Not clipped if the clip penalty is 0, since any penalized end would be clipped.
+    ///  - Panics if the lengths of the query and the quality are different.
+    pub fn align_with_quality(&mut self, query: &[u8], query_quality: &[u8], reference: &Reference) -> QueryAlignment {
+        assert_eq!(query.len(), query_quality.len(), "The quality must have the same length as the query");
+        self.align_with_predicate(query, Some(query_quality), reference, None::<fn(u32, &Alignment) -> bool>)
+    }
+    // The query is already converted to uppercase if `uppercase_query` is set (in `align_with_predicate`).
+    pub(super) fn extend_terminal_matches(&mut self, query_alignment: &mut QueryAlignment, query: &[u8], reference: &Reference) {
+        let sequence_storage = reference.as_ref().get_sequence_storage();
+        let regulator = self.algorithm.regulator();
+        query_alignment.0.iter_mut().for_each(|target_alignment| {
+            sequence_storage.fill_buffer(target_alignment.index, &mut self.sequence_buffer);
+            let target = self.sequence_buffer.buffered_sequence();
+            target_alignment.extend_terminal_matches(target, query, regulator, self.terminal_match_extend);
+        });
+    }
+    pub(super) fn clip_ends_by_quality(&mut self, query_alignment: &mut QueryAlignment, query: &[u8], query_quality: &[u8], reference: &Reference) {
+        let sequence_storage = reference.as_ref().get_sequence_storage();
+        let regulator = self.algorithm.regulator();
+        query_alignment.0.iter_mut().for_each(|target_alignment| {
+            sequence_storage.fill_buffer(target_alignment.index, &mut self.sequence_buffer);
+            let target = self.sequence_buffer.buffered_sequence();
+            target_alignment.clip_ends_by_quality(target, query, query_quality, regulator, self.clip_penalty);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Aligner,
+        ReferenceBuilder,
+        algorithms::Local,
+    };
+    use crate::test_utils::TEST_TARGET;
+
+    #[test]
+    fn terminal_mismatch_does_not_clip_the_matches_after_it() {
+        let unit = TEST_TARGET;
+        let reference = ReferenceBuilder::new()
+            .add_target("target", unit)
+            .build().unwrap();
+        let mut query = unit.to_vec();
+        for index in [3, 15, 27, 39, 64, 69] {
+            query[index] = if query[index] == b'A' { b'C' } else { b'A' };
+        }
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        let result = aligner.align(&query, &reference);
+        assert_eq!(result.0[0].alignments[0].position.query, (4, 64));
+
+        aligner.set_terminal_match_extend(5);
+        assert_eq!(aligner.get_terminal_match_extend(), 5);
+        let result = aligner.align(&query, &reference);
+        let alignment = &result.0[0].alignments[0];
+        assert_eq!(alignment.position.query, (0, 69));
+        assert_eq!(alignment.to_cigar(true), "3=1X11=1X11=1X11=1X24=1X4=");
+        assert_eq!(alignment.penalty, 20);
+        assert_eq!(aligner.get_aligned_sequences(&query, &reference, &result)[0][0].query, &query[..69]);
+    }
+    #[test]
+    fn ends_with_low_quality_are_clipped() {
+        let unit = TEST_TARGET;
+        let reference = ReferenceBuilder::new()
+            .add_target("target", unit)
+            .build().unwrap();
+        let mut query = unit.to_vec();
+        query[68] = b'A';
+        let mut quality = vec![b'I'; 70];
+        quality[68..].copy_from_slice(b"##");
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        let unclipped = aligner.align(&query, &reference);
+        assert_eq!(unclipped.0[0].alignments[0].position.query, (0, 70));
+        // Not clipped without the clip penalty
+        assert_eq!(aligner.align_with_quality(&query, &quality, &reference).0[0].alignments, unclipped.0[0].alignments);
+
+        // Clipping two bases costs the same as the substitution
+        aligner.set_clip_penalty(2);
+        let result = aligner.align_with_quality(&query, &quality, &reference);
+        let alignment = &result.0[0].alignments[0];
+        assert_eq!(alignment.position.query, (0, 68));
+        assert_eq!(alignment.penalty, 0);
+        assert_eq!(aligner.get_aligned_sequences(&query, &reference, &result)[0][0].query, &query[..68]);
+        assert_eq!(aligner.align_with_quality(&query, &[b'I'; 70], &reference).0[0].alignments, unclipped.0[0].alignments);
+    }
+    #[test]
+    fn lowercase_query_is_clipped_and_extended_as_uppercase_if_uppercase_query_is_set() {
+        let unit = TEST_TARGET;
+        let reference = ReferenceBuilder::new()
+            .add_target("target", unit)
+            .build().unwrap();
+        let mut query = unit.to_vec();
+        query[3] = if query[3] == b'A' { b'C' } else { b'A' };
+        query[68] = b'A';
+        let mut quality = vec![b'I'; 70];
+        quality[68..].copy_from_slice(b"##");
+        let lowercase_query = query.to_ascii_lowercase();
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        aligner.set_terminal_match_extend(5);
+        aligner.set_clip_penalty(2);
+        let expected = aligner.align_with_quality(&query, &quality, &reference);
+        assert_eq!(expected.0[0].alignments[0].position.query, (0, 68));
+
+        aligner.set_uppercase_query(true);
+        let result = aligner.align_with_quality(&lowercase_query, &quality, &reference);
+        assert_eq!(result.0[0].alignments, expected.0[0].alignments);
+    }
+}
@@ -0,0 +1,61 @@
+use crate::reference::Reference;
+use super::{Aligner, WorkEstimate, algorithms::Algorithm};
+
+impl<A: Algorithm> Aligner<A> {
+    /// Estimate the work to align a query without aligning it (see `WorkEstimate`).
+    ///  - Only the anchors are made from the query, so it is much cheaper than `align`.
+    ///  - The counts are the upper bounds to compare the queries (e.g., to find the pathological ones or to size the batches),
+    ///    not the exact costs.
+    pub fn estimate_cost(&self, query: &[u8], reference: &Reference) -> WorkEstimate {
+        let regulator = self.algorithm.regulator();
+        let sorted_target_indices = reference.get_full_sorted_target_indices();
+        if self.uppercase_query && query.iter().any(u8::is_ascii_lowercase) {
+            regulator.estimate_work(&query.to_ascii_uppercase(), reference.as_ref(), sorted_target_indices)
+        } else {
+            regulator.estimate_work(query, reference.as_ref(), sorted_target_indices)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Aligner,
+        ReferenceBuilder,
+        algorithms::Local,
+    };
+    use crate::test_utils::TEST_TARGET;
+
+    #[test]
+    fn cost_is_estimated_without_alignment() {
+        use crate::WorkEstimate;
+        let unit = TEST_TARGET;
+        let reference = ReferenceBuilder::new()
+            .add_target("single", unit)
+            .add_target("repeats", &unit.repeat(5))
+            .add_target("other", &[b'G'; 100])
+            .build().unwrap();
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+
+        let estimate = aligner.estimate_cost(unit, &reference);
+        assert_eq!(estimate.target_count, 2);
+        // Merged to one anchor in each copy
+        assert_eq!(estimate.anchor_count, 6);
+        assert!(estimate.estimated_wave_front_cells > 0);
+        aligner.set_uppercase_query(true);
+        assert_eq!(aligner.estimate_cost(&unit.to_ascii_lowercase(), &reference), estimate);
+
+        // More copies, more work
+        let half = aligner.estimate_cost(&unit[..60], &reference);
+        assert_eq!(half.anchor_count, 6);
+        assert!(half.estimated_wave_front_cells < estimate.estimated_wave_front_cells);
+
+        assert_eq!(aligner.estimate_cost(&[b'T'; 70], &reference), WorkEstimate::default());
+        assert_eq!(aligner.estimate_cost(b"ACGT", &reference), WorkEstimate::default());
+
+        aligner.set_ungapped(true);
+        let ungapped = aligner.estimate_cost(unit, &reference);
+        assert_eq!(ungapped.anchor_count, estimate.anchor_count);
+        assert_eq!(ungapped.estimated_wave_front_cells, 0);
+    }
+}
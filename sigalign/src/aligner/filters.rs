@@ -0,0 +1,167 @@
+use crate::{
+    results::{QueryAlignment, Alignment},
+    reference::Reference,
+};
+use super::{Aligner, algorithms::Algorithm};
+
+impl<A: Algorithm> Aligner<A> {
+    /// Set the maximum length of a single insertion or deletion (default: `None`, no limit).
+    ///  - Alignments with a longer gap are dropped rather than clipped.
+    pub fn set_maximum_gap_length(&mut self, maximum_gap_length: Option<u32>) {
+        self.maximum_gap_length = maximum_gap_length;
+    }
+    /// Set the minimum identity (matches / alignment length) of the alignments (default: `None`, no limit).
+    ///  - Alignments satisfying the cutoff of the penalty per length are dropped if less identical.
+    ///    They are not the same under the affine gap penalties, since a long gap is penalized less than its length.
+    pub fn set_minimum_identity(&mut self, minimum_identity: Option<f32>) {
+        self.minimum_identity = minimum_identity;
+    }
+    /// Align a query to a reference and keep only the alignments satisfying the `predicate`.
+    ///  - `predicate` takes the target index and the alignment, and the alignment is dropped if it returns `false`.
+    ///  - Called after the backtrace and the other filters of the aligner (e.g., `set_minimum_identity`),
+    ///    but before the tie-break and filling the aligned sequences, so the dropped alignments are never selected or filled.
+    ///  - The positions of the alignments crossing the origin of a circular target are not wrapped yet:
+    ///    the end can be larger than the length of the target.
+    pub fn align_filtered<F>(&mut self, query: &[u8], reference: &Reference, predicate: F) -> QueryAlignment where
+        F: FnMut(u32, &Alignment) -> bool,
+    {
+        self.align_with_predicate(query, None, reference, Some(predicate))
+    }
+    /// Find the query contained in the targets: keep only the alignments consuming the whole query.
+    ///  - The targets are clipped freely at both ends, and the query is not clipped.
+    ///  - e.g., to find where a read or a gene is in the genomes.
+    ///  - Same as `align` if the algorithm is semi-global and the query is shorter than the targets,
+    ///    since an alignment consumes the query unless it reaches the end of the target.
+    pub fn align_query_in_reference(&mut self, query: &[u8], reference: &Reference) -> QueryAlignment {
+        let query_length = query.len() as u32;
+        self.align_filtered(query, reference, |_, alignment| {
+            alignment.position.query == (0, query_length)
+        })
+    }
+    /// Find the targets contained in the query: keep only the alignments consuming the whole target.
+    ///  - The query is clipped freely at both ends, and the targets are not clipped.
+    ///  - e.g., to find the adapters, the primers, or the short genes of the reference in a long read.
+    ///  - A circular target is consumed if the alignment spans its length from any position.
+    pub fn align_reference_in_query(&mut self, query: &[u8], reference: &Reference) -> QueryAlignment {
+        self.align_filtered(query, reference, |target_index, alignment| {
+            let (target_start, target_end) = alignment.position.target;
+            let target_length = reference.get_length(target_index).unwrap_or(0);
+            if reference.is_circular(target_index) {
+                target_end - target_start == target_length
+            } else {
+                (target_start, target_end) == (0, target_length)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Aligner,
+        ReferenceBuilder,
+        algorithms::{Local, SemiGlobal},
+    };
+    use crate::test_utils::TEST_TARGET;
+
+    #[test]
+    fn alignments_with_long_gap_are_dropped() {
+        let unit = TEST_TARGET;
+        let target = [&unit[..], b"TTGAGCATCCGATAGC"].concat();
+        let reference = ReferenceBuilder::new()
+            .add_target("target", &target)
+            .build().unwrap();
+        // 4 bp deletion in the query
+        let query = [&target[..40], &target[44..]].concat();
+
+        let mut aligner = Aligner::new(SemiGlobal::new(4, 6, 2, 50, 0.2).unwrap());
+        let result = aligner.align(&query, &reference);
+        let longest_gap_length = result.0[0].alignments.iter().map(|v| v.get_longest_gap_length()).max().unwrap();
+        assert_eq!(longest_gap_length, 4);
+
+        aligner.set_maximum_gap_length(Some(4));
+        assert_eq!(aligner.align(&query, &reference).to_json(), result.to_json());
+        aligner.set_maximum_gap_length(Some(3));
+        let result = aligner.align(&query, &reference);
+        assert!(result.0.is_empty());
+    }
+    #[test]
+    fn alignments_with_low_identity_are_dropped() {
+        let unit = TEST_TARGET;
+        let target = [&unit[..], b"TTGAGCATCCGATAGC"].concat();
+        let reference = ReferenceBuilder::new()
+            .add_target("target", &target)
+            .build().unwrap();
+        // 4 bp deletion in the query: the penalty per length is 14 / 86 (< 0.2), and the identity is 82 / 86
+        let query = [&target[..40], &target[44..]].concat();
+
+        let mut aligner = Aligner::new(SemiGlobal::new(4, 6, 2, 50, 0.2).unwrap());
+        assert_eq!(aligner.get_minimum_identity(), None);
+        let result = aligner.align(&query, &reference);
+        let alignment = &result.0[0].alignments[0];
+        assert_eq!((alignment.penalty, alignment.length), (14, 86));
+
+        aligner.set_minimum_identity(Some(0.95));
+        assert_eq!(aligner.align(&query, &reference).to_json(), result.to_json());
+        aligner.set_minimum_identity(Some(0.96));
+        assert!(aligner.align(&query, &reference).0.is_empty());
+    }
+    #[test]
+    fn alignments_are_filtered_by_the_predicate() {
+        use crate::results::AlignmentOperation;
+
+        let unit = TEST_TARGET;
+        // The query has a deletion against the second target
+        let mut with_insertion = unit.to_vec();
+        with_insertion.insert(44, b'G');
+        let reference = ReferenceBuilder::new()
+            .add_target("exact", unit)
+            .add_target("with_insertion", &with_insertion)
+            .build().unwrap();
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        let query_alignment = aligner.align(unit, &reference);
+        assert_eq!(query_alignment.0.len(), 2);
+        let unfiltered = aligner.align_filtered(unit, &reference, |_, _| true);
+        assert_eq!(unfiltered.0.len(), 2);
+        assert!(unfiltered.0.iter().zip(query_alignment.0.iter()).all(|(a, b)| {
+            a.index == b.index && a.alignments == b.alignments
+        }));
+
+        let mut called_targets = Vec::new();
+        let filtered = aligner.align_filtered(unit, &reference, |target_index, alignment| {
+            called_targets.push(target_index);
+            alignment.operations.iter().any(|v| v.operation == AlignmentOperation::Deletion)
+        });
+        called_targets.sort_unstable();
+        assert_eq!(called_targets, vec![0, 1]);
+        assert_eq!(filtered.0.len(), 1);
+        assert_eq!(filtered.0[0].index, 1);
+        assert_eq!(filtered.0[0].alignments, query_alignment.0[1].alignments);
+        assert!(aligner.align_filtered(unit, &reference, |_, _| false).0.is_empty());
+    }
+    #[test]
+    fn containment_is_checked_on_the_chosen_side() {
+        let unit = TEST_TARGET;
+        let spacer = b"GGGGGGGGGGGGGGGGGGGG";
+        let reference = ReferenceBuilder::new()
+            .add_target("genome", &[&spacer[..], unit, spacer].concat())
+            .add_target("gene", &unit[5..65])
+            .build().unwrap();
+        let target_indices = |result: &crate::results::QueryAlignment| result.0.iter().map(|v| v.index).collect::<Vec<_>>();
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        // The query overhangs the gene
+        let query = &unit[..60];
+        assert_eq!(target_indices(&aligner.align(query, &reference)), vec![0, 1]);
+        let result = aligner.align_query_in_reference(query, &reference);
+        assert_eq!(target_indices(&result), vec![0]);
+        assert_eq!(result.0[0].alignments[0].position.query, (0, 60));
+
+        // The read contains the gene, but not the genome
+        let read = [&b"TTTTTTTTTT"[..], unit, b"TTTTTTTTTT"].concat();
+        let result = aligner.align_reference_in_query(&read, &reference);
+        assert_eq!(target_indices(&result), vec![1]);
+        assert_eq!(result.0[0].alignments[0].position.target, (0, 60));
+        assert_eq!(result.0[0].alignments[0].position.query, (15, 75));
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+
+use sigalign_core::reference::{
+    SequenceStorage as _,
+    SequenceBuffer as _,
+};
+use crate::{
+    results::{QueryAlignment, TargetAlignment, Alignment},
+    reference::Reference,
+};
+use super::{Aligner, algorithms::Algorithm};
+
+impl<A: Algorithm> Aligner<A> {
+    /// Align a very long query (e.g., a whole chromosome) in the overlapping windows.
+    ///  - The query is tiled into the windows of `window` bases, each starting `window - overlap` bases after the previous one.
+    ///    The last window ends at the end of the query.
+    ///  - Each window is aligned as `align`, and the co-linear alignments of the adjacent windows
+    ///    are stitched into one alignment (see `Alignment::stitch`), so the overlaps are counted once.
+    ///  - The overlap should be longer than the alignments to be stitched are clipped at the window ends.
+    ///  - The query is aligned at once, if it is not longer than the window or `overlap` is not smaller than `window`.
+    pub fn align_long_query(
+        &mut self,
+        query: &[u8],
+        reference: &Reference,
+        window: u32,
+        overlap: u32,
+    ) -> QueryAlignment {
+        let (window, overlap) = (window as usize, overlap as usize);
+        if query.len() <= window || window <= overlap {
+            return self.align(query, reference);
+        }
+        let step = window - overlap;
+
+        let mut alignments_by_target: BTreeMap<u32, Vec<Alignment>> = BTreeMap::new();
+        let mut window_start = 0;
+        loop {
+            let window_end = (window_start + window).min(query.len());
+            let query_alignment = self.align(&query[window_start..window_end], reference);
+            query_alignment.0.into_iter().for_each(|target_alignment| {
+                let alignments = alignments_by_target.entry(target_alignment.index).or_default();
+                alignments.extend(target_alignment.alignments.into_iter().map(|mut alignment| {
+                    alignment.position.query.0 += window_start as u32;
+                    alignment.position.query.1 += window_start as u32;
+                    alignment
+                }));
+            });
+            if window_end == query.len() {
+                break;
+            }
+            window_start += step;
+        }
+
+        let query = if self.uppercase_query {
+            query.to_ascii_uppercase()
+        } else {
+            query.to_vec()
+        };
+        let sequence_storage = reference.as_ref().get_sequence_storage();
+        let target_alignments = alignments_by_target.into_iter().map(|(target_index, mut alignments)| {
+            sequence_storage.fill_buffer(target_index, &mut self.sequence_buffer);
+            let target = self.sequence_buffer.buffered_sequence();
+            alignments.sort_by_key(|v| (v.position.query.0, v.position.target.0));
+            let mut stitched_alignments: Vec<Alignment> = Vec::with_capacity(alignments.len());
+            alignments.into_iter().for_each(|alignment| {
+                let stitched = stitched_alignments.iter_mut().find_map(|previous| {
+                    previous.stitch(&alignment, target, &query, self.algorithm.regulator()).map(|stitched| (previous, stitched))
+                });
+                match stitched {
+                    Some((previous, stitched)) => *previous = stitched,
+                    None => stitched_alignments.push(alignment),
+                }
+            });
+            TargetAlignment {
+                index: target_index,
+                alignments: stitched_alignments,
+            }
+        }).collect();
+        QueryAlignment(target_alignments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Aligner,
+        ReferenceBuilder,
+        algorithms::Local,
+    };
+    use crate::test_utils::{SEED, Xorshift};
+
+    #[test]
+    fn long_query_is_stitched_into_one_alignment() {
+        // Random-like sequence of 500 kb
+        let mut rng = Xorshift::new(SEED);
+        let target: Vec<u8> = (0..500_000).map(|_| b"ACGT"[rng.gen_range(4) as usize]).collect();
+        let reference = ReferenceBuilder::new().add_target("target", &target).build().unwrap();
+        // A substitution, an insertion or a deletion in every 5 kb
+        let mut query = target.clone();
+        let mut expected_penalty = 0;
+        (0..100).rev().for_each(|i| {
+            let position = i * 5_000 + 1_000 + rng.gen_range(3_000) as usize;
+            match i % 3 {
+                0 => {
+                    query[position] = if query[position] == b'A' { b'C' } else { b'A' };
+                    expected_penalty += 4;
+                },
+                1 => query.insert(position, if query[position] == b'G' { b'T' } else { b'G' }),
+                _ => { query.remove(position); },
+            }
+            if i % 3 != 0 {
+                expected_penalty += 6 + 2;
+            }
+        });
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 100, 0.1).unwrap());
+        let result = aligner.align_long_query(&query, &reference, 4_000, 1_000);
+        assert_eq!(result.0.len(), 1);
+        assert_eq!(result.0[0].alignments.len(), 1);
+        let alignment = &result.0[0].alignments[0];
+        assert_eq!(alignment.position.query, (0, query.len() as u32));
+        assert_eq!(alignment.position.target, (0, target.len() as u32));
+        assert_eq!(alignment.penalty, expected_penalty);
+        assert_eq!(aligner.verify(&query, &reference, &result), Ok(()));
+    }
+}
@@ -1,5 +1,12 @@
+use sigalign_core::reference::{
+    SequenceStorage as _,
+    SequenceBuffer as _,
+};
+use sigalign_utils::sequence_manipulation::reverse_complementary::reverse_complement_of_dna_sequence;
 use crate::{
-    results::QueryAlignment,
+    results::{
+        QueryAlignment, Alignment, AlignedSequences, TieBreak, MappingResult,
+    },
     reference::{
         Reference,
         DefaultSequenceBuffer,
@@ -10,12 +17,39 @@ pub mod algorithms;
 use algorithms::Algorithm;
 
 mod debug;
+mod str_query;
+pub use str_query::QueryError;
+mod checked;
+pub use checked::AlignmentError;
+pub use sigalign_core::aligner::WorkEstimate;
+mod paired;
+mod stranded;
+mod long_query;
+mod seeding;
+mod cutoff;
+mod ungapped;
+mod filters;
+mod best;
+mod ends;
+mod confidence;
+mod threaded;
+mod reference_set;
+mod overlaps;
+mod verify;
+mod estimate;
 
 /// An alignment executor.
 #[derive(Clone)]
 pub struct Aligner<A: Algorithm> {
     algorithm: A,
     sequence_buffer: DefaultSequenceBuffer,
+    uppercase_query: bool,
+    maximum_gap_length: Option<u32>,
+    minimum_identity: Option<f32>,
+    tie_break: Option<TieBreak>,
+    clip_penalty: u32,
+    warning_penalty_per_length: Option<f32>,
+    terminal_match_extend: u32,
 }
 
 impl<A: Algorithm> Aligner<A> {
@@ -23,9 +57,101 @@ impl<A: Algorithm> Aligner<A> {
     pub fn new(algorithm: A) -> Self {
         Self::from(algorithm)
     }
+    /// Set whether to convert the query to uppercase before alignment (default: false).
+    ///  - Use with the reference built in uppercase (default of `ReferenceBuilder`)
+    ///    to match the lowercase (soft-masked) bases of the query.
+    ///  - The aligned query sequence keeps the original case.
+    pub fn set_uppercase_query(&mut self, uppercase_query: bool) {
+        self.uppercase_query = uppercase_query;
+    }
     /// Align a query to a reference.
     pub fn align(&mut self, query: &[u8], reference: &Reference) -> QueryAlignment {
-        self.algorithm.align(query, reference, &mut self.sequence_buffer)
+        self.align_with_predicate(query, None, reference, None::<fn(u32, &Alignment) -> bool>)
+    }
+    fn align_with_predicate<F>(
+        &mut self,
+        query: &[u8],
+        query_quality: Option<&[u8]>,
+        reference: &Reference,
+        predicate: Option<F>,
+    ) -> QueryAlignment where
+        F: FnMut(u32, &Alignment) -> bool,
+    {
+        // The query compared with the target (the original case is kept only in the aligned query sequence)
+        let uppercase_query = if self.uppercase_query && query.iter().any(u8::is_ascii_lowercase) {
+            Some(query.to_ascii_uppercase())
+        } else {
+            None
+        };
+        let query_to_compare = uppercase_query.as_deref().unwrap_or(query);
+        let mut query_alignment = self.algorithm.align(query_to_compare, reference, &mut self.sequence_buffer);
+        if reference.has_circular_targets() {
+            reference.remove_duplicated_circular_alignments(&mut query_alignment);
+        }
+        if self.terminal_match_extend != 0 {
+            self.extend_terminal_matches(&mut query_alignment, query_to_compare, reference);
+        }
+        if let Some(query_quality) = query_quality.filter(|_| self.clip_penalty != 0) {
+            self.clip_ends_by_quality(&mut query_alignment, query_to_compare, query_quality, reference);
+        }
+        if let Some(maximum_gap_length) = self.maximum_gap_length {
+            query_alignment = query_alignment.without_long_gaps(maximum_gap_length);
+        }
+        if let Some(minimum_identity) = self.minimum_identity {
+            query_alignment = query_alignment.without_low_identity(minimum_identity);
+        }
+        if let Some(mut predicate) = predicate {
+            query_alignment.0.retain_mut(|target_alignment| {
+                let target_index = target_alignment.index;
+                target_alignment.alignments.retain(|alignment| predicate(target_index, alignment));
+                !target_alignment.alignments.is_empty()
+            });
+        }
+        if let Some(tie_break) = self.tie_break {
+            query_alignment.retain_best_with_clip_penalty(tie_break, query.len() as u32, self.clip_penalty);
+        }
+        if reference.has_circular_targets() {
+            reference.wrap_circular_positions(&mut query_alignment);
+        }
+        query_alignment
+    }
+    /// Align a query to a reference and get only the coordinates and the penalties.
+    ///  - The alignments are the same as `align`; the operations are dropped.
+    ///    The backtrace is not skipped, since it is also used to find the duplicated alignments.
+    pub fn map(&mut self, query: &[u8], reference: &Reference) -> Vec<MappingResult> {
+        self.align(query, reference).into_mapping_results()
+    }
+    /// Extend an alignment from its end to the end of either sequence, when more of the sequences are available.
+    ///  - `target` and `query` are the whole sequences including the new bases (see `AlignmentRegulator::extend_alignment`).
+    ///  - Returns `false` and leaves the alignment unchanged if the extended alignment does not satisfy the cutoff.
+    pub fn extend_alignment(&self, alignment: &mut Alignment, target: &[u8], query: &[u8]) -> bool {
+        self.algorithm.regulator().extend_alignment(alignment, target, query)
+    }
+    /// Get the aligned target and query sequences with gaps (`-`) of each alignment.
+    ///  - `query_alignment` is the result of `align` with the same `query` and `reference`.
+    ///  - In the order of the target alignments, and then of the alignments in each target.
+    ///  - The aligned query keeps the original case of `query`.
+    pub fn get_aligned_sequences(
+        &mut self,
+        query: &[u8],
+        reference: &Reference,
+        query_alignment: &QueryAlignment,
+    ) -> Vec<Vec<AlignedSequences>> {
+        let sequence_storage = reference.as_ref().get_sequence_storage();
+        query_alignment.0.iter().map(|target_alignment| {
+            sequence_storage.fill_buffer(target_alignment.index, &mut self.sequence_buffer);
+            let target = self.sequence_buffer.buffered_sequence();
+            target_alignment.get_aligned_sequences(target, query)
+        }).collect()
+    }
+    // Reverse complement of the query for the other strand
+    //  - Converted to uppercase first if `uppercase_query` is set, since the lowercase bases are not complemented.
+    fn reverse_complement_of_query(&self, query: &[u8]) -> Vec<u8> {
+        if self.uppercase_query {
+            reverse_complement_of_dna_sequence(&query.to_ascii_uppercase())
+        } else {
+            reverse_complement_of_dna_sequence(query)
+        }
     }
 }
 
@@ -34,6 +160,291 @@ impl<A: Algorithm> From<A> for Aligner<A> {
         Self {
             algorithm,
             sequence_buffer: Reference::get_sequence_buffer(),
+            uppercase_query: false,
+            maximum_gap_length: None,
+            minimum_identity: None,
+            tie_break: None,
+            clip_penalty: 0,
+            warning_penalty_per_length: None,
+            terminal_match_extend: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Aligner,
+        ReferenceBuilder,
+        algorithms::{Local, SemiGlobal},
+    };
+    use crate::test_utils::TEST_TARGET;
+
+    #[test]
+    fn results_are_in_the_same_order_for_every_run() {
+        let unit = TEST_TARGET;
+        let mut builder = ReferenceBuilder::new();
+        for i in 0..20 {
+            let mut sequence = unit.repeat(3);
+            sequence[i * 3] = b'T';
+            builder = builder.add_target(&format!("target_{}", i), &sequence);
+        }
+        let reference = builder.build().unwrap();
+        let query = &unit[5..];
+
+        let local_results: Vec<String> = (0..5).map(|_| {
+            let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+            aligner.align(query, &reference).to_json()
+        }).collect();
+        let semi_global_results: Vec<String> = (0..5).map(|_| {
+            let mut aligner = Aligner::new(SemiGlobal::new(4, 6, 2, 50, 0.2).unwrap());
+            aligner.align(query, &reference).to_json()
+        }).collect();
+        assert!(local_results.windows(2).all(|w| w[0] == w[1]));
+        assert!(semi_global_results.windows(2).all(|w| w[0] == w[1]));
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        let target_indices: Vec<u32> = aligner.align(query, &reference).0.iter().map(|v| v.index).collect();
+        assert_eq!(target_indices.len(), 20);
+        assert!(target_indices.windows(2).all(|w| w[0] < w[1]));
+    }
+    #[test]
+    fn aligned_sequences_match_the_alignments() {
+        let target = TEST_TARGET;
+        let query = b"CAAACTCACAATTGTATTTCTTTGCCAGCTGGGCATATACTTTTTCCGCCCCCTCATTTA";
+        let reference = ReferenceBuilder::new()
+            .add_target("target", target)
+            .build().unwrap();
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        let result = aligner.align(query, &reference);
+        let aligned_sequences = aligner.get_aligned_sequences(query, &reference, &result);
+        assert_eq!(aligned_sequences.len(), 1);
+        assert_eq!(aligned_sequences[0].len(), result.0[0].alignments.len());
+        result.0[0].alignments.iter().zip(&aligned_sequences[0]).for_each(|(alignment, aligned)| {
+            let aligned_target = &aligned.target;
+            let aligned_query = &aligned.query;
+            assert_eq!(aligned_target.len(), alignment.length as usize);
+            assert_eq!(aligned_query.len(), alignment.length as usize);
+
+            let (target_start, target_end) = alignment.position.target;
+            let (query_start, query_end) = alignment.position.query;
+            let ungapped_target: Vec<u8> = aligned_target.iter().filter(|&&c| c != b'-').cloned().collect();
+            let ungapped_query: Vec<u8> = aligned_query.iter().filter(|&&c| c != b'-').cloned().collect();
+            assert_eq!(ungapped_target, &target[target_start as usize..target_end as usize]);
+            assert_eq!(ungapped_query, &query[query_start as usize..query_end as usize]);
+        });
+    }
+    #[test]
+    fn custom_pattern_locator_gives_the_same_results() {
+        use sigalign_core::aligner::{AlignmentRegulator, local::LocalAligner};
+        use crate::test_utils::{NaiveLocator, NaiveBuffer};
+
+        // Locate patterns by scanning all targets
+        let targets: Vec<Vec<u8>> = vec![
+            TEST_TARGET.to_vec(),
+            b"TCTGGGGCCATTGTATTTCTTTGCCAGCTGGGGCATATACTTTTTCCGCCCCCTCATTTACGCTCATCAC".to_vec(),
+        ];
+        let query = b"CAAACTCACAATTGTATTTCTTTGCCAGCTGGGCATATACTTTTTCCGCCCCCTCATTTAACTTCTTGGA";
+        let reference = targets.iter().enumerate().fold(ReferenceBuilder::new(), |builder, (i, target)| {
+            builder.add_target(&i.to_string(), target)
+        }).build().unwrap();
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        let answer = aligner.align(query, &reference);
+
+        let mut core_aligner = LocalAligner::new(AlignmentRegulator::new(4, 6, 2, 50, 0.2).unwrap());
+        let result = core_aligner.align(
+            query,
+            &NaiveLocator::new(targets),
+            &mut NaiveBuffer(Vec::new()),
+            &[0, 1],
+        );
+        assert_eq!(result.to_json(), answer.to_json());
+    }
+    #[test]
+    fn queries_shorter_than_pattern_give_empty_result() {
+        use crate::algorithms::{LocalWithLimit, SemiGlobalWithLimit, LocalWithChunk, SemiGlobalWithChunk};
+
+        let target = TEST_TARGET;
+        let reference = ReferenceBuilder::new()
+            .add_target("target", target)
+            .build().unwrap();
+
+        fn assert_empty<A: crate::algorithms::Algorithm>(mut aligner: Aligner<A>, target: &[u8], reference: &crate::Reference) {
+            let pattern_size = aligner.get_pattern_size() as usize;
+            for query_length in [0, 1, pattern_size - 1] {
+                let result = aligner.align(&target[..query_length], reference);
+                assert!(result.0.is_empty());
+            }
+        }
+        assert_empty(Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap()), target, &reference);
+        assert_empty(Aligner::new(SemiGlobal::new(4, 6, 2, 50, 0.2).unwrap()), target, &reference);
+        assert_empty(Aligner::new(LocalWithLimit::new(4, 6, 2, 50, 0.2, 1).unwrap()), target, &reference);
+        assert_empty(Aligner::new(SemiGlobalWithLimit::new(4, 6, 2, 50, 0.2, 1).unwrap()), target, &reference);
+        assert_empty(Aligner::new(LocalWithChunk::new(4, 6, 2, 50, 0.2, 100, 50).unwrap()), target, &reference);
+        assert_empty(Aligner::new(SemiGlobalWithChunk::new(4, 6, 2, 50, 0.2, 100, 50).unwrap()), target, &reference);
+    }
+    #[test]
+    fn lowercase_query_is_matched_if_uppercase_query_is_set() {
+        let target = TEST_TARGET;
+        let soft_masked_target = [&target[..20], &target[20..50].to_ascii_lowercase(), &target[50..]].concat();
+        let reference = ReferenceBuilder::new()
+            .add_target("target", &soft_masked_target)
+            .build().unwrap();
+        let query = &soft_masked_target[5..];
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        let expected = aligner.align(&query.to_ascii_uppercase(), &reference);
+        assert_eq!(expected.0[0].alignments[0].penalty, 0);
+        let result = aligner.align(query, &reference);
+        assert_ne!(result.to_json(), expected.to_json());
+
+        aligner.set_uppercase_query(true);
+        let result = aligner.align(query, &reference);
+        assert_eq!(result.to_json(), expected.to_json());
+
+        let aligned_sequences = aligner.get_aligned_sequences(query, &reference, &result);
+        assert_eq!(aligned_sequences[0][0].query, query);
+    }
+    #[test]
+    fn operations_are_run_length_encoded() {
+        use crate::results::{AlignmentOperation, AlignmentOperations};
+
+        let unit = TEST_TARGET;
+        let target = unit.repeat(2);
+        let reference = ReferenceBuilder::new()
+            .add_target("target", &target)
+            .build().unwrap();
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        // Exact match over the anchors and both extended sides
+        let query = &target[20..120];
+        let result = aligner.align(query, &reference);
+        let alignment = result.0[0].alignments.iter().find(|v| v.penalty == 0).unwrap();
+        assert_eq!(alignment.operations, vec![
+            AlignmentOperations { operation: AlignmentOperation::Match, count: 100 },
+        ]);
+
+        // No consecutive operations of the same type
+        let mut query = query.to_vec();
+        query[10] = b'G';
+        query[11] = b'G';
+        query.remove(60);
+        let result = aligner.align(&query, &reference);
+        result.0[0].alignments.iter().for_each(|alignment| {
+            assert!(alignment.operations.windows(2).all(|w| w[0].operation != w[1].operation));
+        });
+    }
+    #[test]
+    fn substitution_in_the_flank_is_distinguished_from_match() {
+        use crate::results::{AlignmentOperation, AlignmentOperations};
+
+        let target = TEST_TARGET;
+        let reference = ReferenceBuilder::new()
+            .add_target("target", target)
+            .build().unwrap();
+        // SNP in the first pattern, that can not be an anchor
+        let mut query = target.to_vec();
+        query[3] = b'G';
+        let expected_operations = vec![
+            AlignmentOperations { operation: AlignmentOperation::Match, count: 3 },
+            AlignmentOperations { operation: AlignmentOperation::Subst, count: 1 },
+            AlignmentOperations { operation: AlignmentOperation::Match, count: target.len() as u32 - 4 },
+        ];
+
+        let mut aligner = Aligner::new(SemiGlobal::new(4, 6, 2, 50, 0.2).unwrap());
+        let result = aligner.align(&query, &reference);
+        assert_eq!(result.0[0].alignments.len(), 1);
+        assert_eq!(result.0[0].alignments[0].operations, expected_operations);
+        assert_eq!(result.0[0].alignments[0].penalty, 4);
+    }
+    #[test]
+    fn alignment_with_seed_passes_through_the_seed() {
+        use sigalign_core::aligner::{AlignmentRegulator, local::LocalAligner, semi_global::SemiGlobalAligner};
+
+        let unit = TEST_TARGET;
+        let target = [&unit[..], b"TTTTTTTTTT", unit].concat();
+        let reference = ReferenceBuilder::new()
+            .add_target("target", &target)
+            .build().unwrap();
+        let query = &unit[5..65];
+
+        let regulator = AlignmentRegulator::new(4, 6, 2, 50, 0.2).unwrap();
+        let mut local_aligner = LocalAligner::new(regulator.clone());
+        let mut semi_global_aligner = SemiGlobalAligner::new(regulator);
+        let expected = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap()).align(query, &reference);
+        assert_eq!(expected.0[0].alignments.len(), 2);
+
+        // Seed on the second copy of the unit
+        for aligner_alignments in [
+            local_aligner.align_with_seed(query, &target, (95, 10, 20)),
+            semi_global_aligner.align_with_seed(query, &target, (95, 10, 20)),
+        ] {
+            assert_eq!(aligner_alignments.len(), 1);
+            assert_eq!(aligner_alignments[0].position.target, (85, 145));
+            assert!(expected.0[0].alignments.contains(&aligner_alignments[0]));
+        }
+        // Seed with errors: the matched patterns are used
+        let mut query_with_error = query.to_vec();
+        query_with_error[12] = b'G';
+        let alignments = local_aligner.align_with_seed(&query_with_error, &target, (5, 0, 60));
+        assert_eq!(alignments.len(), 1);
+        assert_eq!(alignments[0].position.target, (5, 65));
+        // No matched pattern on the diagonal
+        assert!(local_aligner.align_with_seed(query, &target, (3, 0, 60)).is_empty());
+        assert!(local_aligner.align_with_seed(query, &target, (500, 0, 60)).is_empty());
+    }
+    #[test]
+    fn mapping_results_are_coordinates_of_alignments() {
+        let unit = TEST_TARGET;
+        let reference = ReferenceBuilder::new()
+            .add_target("other", b"GGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGG")
+            .add_target("target", &[&unit[..], b"TTTTTTTTTT", unit].concat())
+            .build().unwrap();
+        let query = &unit[5..65];
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        let alignment = aligner.align(query, &reference);
+        let mapping_results = aligner.map(query, &reference);
+        assert_eq!(mapping_results.len(), 2);
+        assert_eq!(mapping_results.len(), alignment.count_alignments());
+        alignment.0[0].alignments.iter().zip(mapping_results.iter()).for_each(|(alignment, mapping_result)| {
+            assert_eq!(mapping_result.target_index, 1);
+            assert_eq!(mapping_result.target_range, alignment.position.target);
+            assert_eq!(mapping_result.query_range, alignment.position.query);
+            assert_eq!(mapping_result.penalty, alignment.penalty);
+        });
+    }
+    #[test]
+    fn queries_without_hits_have_the_same_empty_result() {
+        use crate::results::QueryAlignment;
+        let unit = TEST_TARGET;
+        let reference = ReferenceBuilder::new()
+            .add_target("poly_g", &[b'G'; 100])
+            .add_target("unit", unit)
+            .add_target("poly_t", &[b'T'; 100])
+            .build().unwrap();
+        let empty = QueryAlignment::default();
+        assert!(empty.is_empty());
+        // No pattern is located, a pattern is located but not extended enough, and shorter than a pattern
+        let located_once = [&unit[..10], &[b'C'; 60][..]].concat();
+        let queries: [&[u8]; 3] = [&[b'C'; 80], &located_once, &unit[..5]];
+        let mut local_aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        let mut semi_global_aligner = Aligner::new(SemiGlobal::new(4, 6, 2, 50, 0.2).unwrap());
+        for query in queries {
+            for result in [local_aligner.align(query, &reference), semi_global_aligner.align(query, &reference)] {
+                assert!(result.is_empty());
+                assert_eq!(result.count_alignments(), 0);
+                assert!(result.best_target().is_none());
+                assert!(result.top_k(1).is_empty());
+                assert_eq!(result.to_json(), empty.to_json());
+            }
         }
+        // Targets with no alignments left are also empty
+        let mut result = local_aligner.align(unit, &reference);
+        assert!(!result.is_empty());
+        result.0.iter_mut().for_each(|target_alignment| target_alignment.alignments.clear());
+        assert!(result.is_empty());
     }
 }
@@ -0,0 +1,81 @@
+use sigalign_core::reference::{
+    SequenceStorage as _,
+    SequenceBuffer as _,
+};
+use crate::{
+    results::{Overlap, OverlapOrientation},
+    reference::Reference,
+};
+use super::{Aligner, algorithms::Algorithm};
+
+impl<A: Algorithm> Aligner<A> {
+    /// Align a query to a reference and get only the overlaps between the ends of the query and the targets.
+    ///  - For the overlap-layout-consensus assembly. Use with the semi-global algorithms,
+    ///    since the local alignments are reported only if they happen to reach the ends.
+    ///  - `orientation`: only the overlaps of this orientation are returned (`None`: both).
+    ///  - The alignments of the circular targets are not overlaps, since the targets have no ends.
+    pub fn align_overlaps(
+        &mut self,
+        query: &[u8],
+        reference: &Reference,
+        orientation: Option<OverlapOrientation>,
+    ) -> Vec<Overlap> {
+        let query_alignment = self.align(query, reference);
+        let sequence_storage = reference.as_ref().get_sequence_storage();
+        let mut overlaps = Vec::new();
+        query_alignment.0.into_iter().for_each(|target_alignment| {
+            let target_index = target_alignment.index;
+            if reference.is_circular(target_index) {
+                return;
+            }
+            sequence_storage.fill_buffer(target_index, &mut self.sequence_buffer);
+            let target_length = self.sequence_buffer.buffered_sequence().len() as u32;
+            overlaps.extend(target_alignment.alignments.into_iter().filter_map(|alignment| {
+                alignment.into_overlap(target_index, query.len() as u32, target_length)
+            }).filter(|overlap| {
+                orientation.map_or(true, |v| v == overlap.orientation)
+            }));
+        });
+        overlaps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Aligner,
+        ReferenceBuilder,
+        algorithms::SemiGlobal,
+    };
+    use crate::test_utils::TEST_TARGET;
+
+    #[test]
+    fn overlaps_are_found_by_orientation() {
+        use crate::results::OverlapOrientation;
+
+        let unit = TEST_TARGET;
+        let reference = ReferenceBuilder::new()
+            .add_target("prefix", &[&unit[..], b"TTTTTTTTTTTTTTTTTTTTTTTTTTTTTT"].concat())
+            .add_target("suffix", &[&b"GGGGGGGGGGGGGGGGGGGGGGGGGGGGGG"[..], unit].concat())
+            .add_target("container", &[&b"CCCCCCCCCCCCCCCCCCCC"[..], unit, b"CCCCCCCCCCCCCCCCCCCC"].concat())
+            .build().unwrap();
+        let query = [&b"AAAAAAAAAAAAAAAAAAAA"[..], unit, b"AAAAAAAAAAAAAAAAAAAA"].concat();
+        let mut aligner = Aligner::new(SemiGlobal::new(4, 6, 2, 50, 0.2).unwrap());
+        // Query contains the targets partially
+        let overlaps = aligner.align_overlaps(&query[20..], &reference, None);
+        assert_eq!(overlaps.len(), 1);
+        assert_eq!(overlaps[0].target_index, 1);
+        assert_eq!(overlaps[0].orientation, OverlapOrientation::QueryPrefixToTargetSuffix);
+        assert_eq!(overlaps[0].query_overlap_length, 70);
+
+        let overlaps = aligner.align_overlaps(&query[..90], &reference, None);
+        assert_eq!(overlaps.len(), 1);
+        assert_eq!(overlaps[0].target_index, 0);
+        assert_eq!(overlaps[0].orientation, OverlapOrientation::QuerySuffixToTargetPrefix);
+        assert_eq!(overlaps[0].target_overlap_length, 70);
+        assert!(aligner.align_overlaps(&query[..90], &reference, Some(OverlapOrientation::QueryPrefixToTargetSuffix)).is_empty());
+
+        // Contained in the target
+        assert!(aligner.align_overlaps(unit, &reference, None).iter().all(|v| v.target_index != 2));
+    }
+}
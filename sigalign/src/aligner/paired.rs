@@ -0,0 +1,109 @@
+use crate::{
+    results::{PairedAlignment, MateAlignment},
+    reference::Reference,
+};
+use super::{Aligner, algorithms::Algorithm};
+
+impl<A: Algorithm> Aligner<A> {
+    /// Align the paired-end reads and find the best concordant pair.
+    ///  - Each mate is aligned in both strands (the reverse complement of DNA).
+    ///  - Concordant: on the same target in the FR orientation
+    ///    (`read1` forward and `read2` reverse downstream, or vice versa),
+    ///    with the insert size in `minimum_insert_size..=maximum_insert_size`.
+    ///  - See `PairedAlignment::from_mates` for the pairing.
+    pub fn align_pair(
+        &mut self,
+        read1: &[u8],
+        read2: &[u8],
+        reference: &Reference,
+        minimum_insert_size: u32,
+        maximum_insert_size: u32,
+    ) -> PairedAlignment {
+        let read1_alignment = self.align_mate(read1, reference);
+        let read2_alignment = self.align_mate(read2, reference);
+        PairedAlignment::from_mates(
+            read1_alignment,
+            read2_alignment,
+            read1.len() as u32,
+            read2.len() as u32,
+            minimum_insert_size,
+            maximum_insert_size,
+        )
+    }
+    fn align_mate(&mut self, read: &[u8], reference: &Reference) -> MateAlignment {
+        MateAlignment {
+            forward: self.align(read, reference),
+            reverse: self.align(&self.reverse_complement_of_query(read), reference),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sigalign_utils::sequence_manipulation::reverse_complementary::reverse_complement_of_dna_sequence;
+    use crate::{
+        Aligner,
+        ReferenceBuilder,
+        algorithms::Local,
+        results::PairStatus,
+    };
+    use crate::test_utils::{SEED, random_dna};
+
+    #[test]
+    fn concordant_pair_is_found_within_insert_size() {
+        // Random-like sequence of 400 bp
+        let fragment = random_dna(SEED, 400);
+        let reference = ReferenceBuilder::new()
+            .add_target("other", &fragment[100..300])
+            .add_target("target", &[&b"GGGGGGGGGG"[..], &fragment].concat())
+            .build().unwrap();
+        let read1 = fragment[..100].to_vec();
+        let read2 = reverse_complement_of_dna_sequence(&fragment[300..]);
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+
+        let result = aligner.align_pair(&read1, &read2, &reference, 300, 500);
+        assert_eq!(result.status, PairStatus::Concordant);
+        let pair = result.best_pair.unwrap();
+        assert_eq!((pair.target_index, pair.is_forward, pair.insert_size), (1, true, 400));
+        assert_eq!(pair.read1.position.target, (10, 110));
+        assert_eq!(pair.read2.position.target, (310, 410));
+
+        // Fragment from the other strand
+        let result = aligner.align_pair(&read2, &read1, &reference, 300, 500);
+        let pair = result.best_pair.unwrap();
+        assert_eq!((pair.target_index, pair.is_forward, pair.insert_size), (1, false, 400));
+
+        // Out of the insert size range
+        let result = aligner.align_pair(&read1, &read2, &reference, 100, 300);
+        assert_eq!(result.status, PairStatus::Discordant);
+        assert!(result.best_pair.is_none());
+        // Same orientation
+        let result = aligner.align_pair(&read1, &fragment[300..], &reference, 300, 500);
+        assert_eq!(result.status, PairStatus::Discordant);
+        // Only one mate
+        let result = aligner.align_pair(&read1, &[b'C'; 100], &reference, 300, 500);
+        assert_eq!(result.status, PairStatus::Single);
+        assert!(result.read2.forward.0.is_empty() && result.read2.reverse.0.is_empty());
+    }
+    #[test]
+    fn lowercase_reads_are_paired_if_uppercase_query_is_set() {
+        let fragment = random_dna(SEED, 400);
+        let reference = ReferenceBuilder::new()
+            .add_target("target", &fragment)
+            .build().unwrap();
+        let read1 = fragment[..100].to_vec();
+        let read2 = reverse_complement_of_dna_sequence(&fragment[300..]);
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+        let expected = aligner.align_pair(&read1, &read2, &reference, 300, 500);
+        assert_eq!(expected.status, PairStatus::Concordant);
+
+        // Soft-masked reads: the reverse complement must be taken after the conversion to uppercase
+        aligner.set_uppercase_query(true);
+        let result = aligner.align_pair(&read1.to_ascii_lowercase(), &read2.to_ascii_lowercase(), &reference, 300, 500);
+        assert_eq!(result.status, PairStatus::Concordant);
+        let (pair, expected_pair) = (result.best_pair.unwrap(), expected.best_pair.unwrap());
+        assert_eq!(pair.read1.position, expected_pair.read1.position);
+        assert_eq!(pair.read2.position, expected_pair.read2.position);
+        assert_eq!(pair.insert_size, expected_pair.insert_size);
+    }
+}
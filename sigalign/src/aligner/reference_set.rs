@@ -0,0 +1,73 @@
+use crate::{
+    results::QueryAlignment,
+    reference::ReferenceSet,
+};
+use super::{Aligner, algorithms::Algorithm};
+
+impl<A: Algorithm> Aligner<A> {
+    /// Align a query to all references of the set.
+    ///  - The target indices of the result are the global indices of the set.
+    ///  - The options of the aligner are applied to each reference separately.
+    pub fn align_to_reference_set(&mut self, query: &[u8], reference_set: &ReferenceSet) -> QueryAlignment {
+        let mut target_alignments = Vec::new();
+        reference_set.iter_with_offsets().for_each(|(reference, target_index_offset)| {
+            let query_alignment = self.align(query, reference);
+            target_alignments.extend(query_alignment.0.into_iter().map(|mut v| {
+                v.index += target_index_offset;
+                v
+            }));
+        });
+        QueryAlignment(target_alignments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Aligner,
+        ReferenceBuilder,
+        ReferenceSet,
+        algorithms::Local,
+    };
+    use crate::test_utils::TEST_TARGET;
+
+    #[test]
+    fn reference_set_is_same_as_one_reference() {
+        let targets: [&[u8]; 4] = [
+            TEST_TARGET,
+            b"TTTTTTTTTTACACAGATCGCAAACTCACAATTGTATTTCTTTGCCACCTGGGCATATACTTTTTGCGC",
+            b"GGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGG",
+            b"ACACAGATCGCAAACTCACAATTGTATTTCTTTGCCACCTGGGCATATACTTTTTGCGCCCCC",
+        ];
+        let build = |targets: &[&[u8]], first_index: usize| {
+            let mut builder = ReferenceBuilder::new();
+            for (index, target) in targets.iter().enumerate() {
+                builder = builder.add_target(&(first_index + index).to_string(), target);
+            }
+            builder.build().unwrap()
+        };
+        let reference = build(&targets, 0);
+        let mut reference_set = ReferenceSet::new();
+        reference_set.push(build(&targets[..1], 0));
+        reference_set.push(build(&targets[1..3], 1));
+        reference_set.push(build(&targets[3..], 3));
+        assert_eq!(reference_set.get_num_targets(), 4);
+        assert_eq!(reference_set.get_total_length(), reference.get_total_length());
+        for target_index in 0..4 {
+            assert_eq!(reference_set.get_sequence(target_index), reference.get_sequence(target_index));
+            assert_eq!(reference_set.get_label(target_index), reference.get_label(target_index));
+        }
+        assert_eq!(reference_set.get_sequence(4), None);
+
+        let query = &targets[0][3..];
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        let expected = aligner.align(query, &reference);
+        assert_eq!(expected.0.len(), 3);
+        let result = aligner.align_to_reference_set(query, &reference_set);
+        assert_eq!(result.to_json(), expected.to_json());
+        assert_eq!(
+            reference_set.label_query_alignment(result).to_json(),
+            reference.label_query_alignment(expected).to_json(),
+        );
+    }
+}
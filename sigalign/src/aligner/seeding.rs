@@ -0,0 +1,281 @@
+use super::{
+    Aligner,
+    algorithms::{Algorithm, LowComplexityFilter, SeedPattern, MinimizerSeeder},
+};
+
+impl<A: Algorithm> Aligner<A> {
+    /// Set minimum number of anchors (exactly matched patterns) in a target to be extended (default: 1).
+    ///  - Targets with fewer anchors are skipped before the costly extension.
+    pub fn set_minimum_anchor_count(&mut self, count: u32) {
+        self.algorithm.set_minimum_anchor_count(count);
+    }
+    /// Set the minimum score of the co-linear anchor chains to be extended (default: `None`, no chaining).
+    ///  - The score is the length of the chained anchors minus the difference of the gaps in the query and the target.
+    ///  - Anchors out of the chains with enough score are not extended. This reduces the extensions
+    ///    for the targets with many spurious anchors, but the alignments found only from them are missed.
+    pub fn set_minimum_chain_score(&mut self, minimum_chain_score: Option<u32>) {
+        self.algorithm.set_minimum_chain_score(minimum_chain_score);
+    }
+    /// Set the filter to skip the patterns in the low-complexity regions of the query (default: `None`, no filter).
+    ///  - e.g., `Some(LowComplexityFilter::default())` masks the homopolymers like `dustmasker`.
+    ///  - Fewer anchors are located from the repetitive regions, but the alignments anchored only there are missed.
+    pub fn set_low_complexity_filter(&mut self, low_complexity_filter: Option<LowComplexityFilter>) {
+        self.algorithm.set_low_complexity_filter(low_complexity_filter);
+    }
+    /// Set the stride of the overlapping seeds counted toward the minimum anchor count (default: `None`).
+    ///  - Seeds starting at every `seed_stride` bases between the non-overlapping patterns are also located.
+    ///  - Reads with clustered errors keep enough seeds to pass `set_minimum_anchor_count`,
+    ///    at the cost of locating more seeds. No effect if the minimum anchor count is 1.
+    pub fn set_seed_stride(&mut self, seed_stride: Option<u32>) {
+        self.algorithm.set_seed_stride(seed_stride);
+    }
+    /// Set the spaced seed counted toward the minimum anchor count (default: `None`).
+    ///  - e.g., `SeedPattern::new("110110110")` also locates the patterns with mismatches at every third base.
+    ///  - Reads with periodic errors keep enough seeds to pass `set_minimum_anchor_count`,
+    ///    at the cost of locating the variants of each pattern. No effect if the minimum anchor count is 1.
+    pub fn set_seed_pattern(&mut self, seed_pattern: Option<SeedPattern>) {
+        self.algorithm.set_seed_pattern(seed_pattern);
+    }
+    /// Set the minimizer sketch to locate only the minimizers of the patterns (default: `None`, every pattern).
+    ///  - e.g., `MinimizerSeeder::new(5)` locates one in each five consecutive patterns at least, about a third of them.
+    ///  - Far fewer seeds on the large genomes, but the alignments having the exact patterns only in the unselected ones are missed.
+    ///  - Set to `None` to fall back to the exhaustive seeding.
+    pub fn set_minimizer_seeder(&mut self, minimizer_seeder: Option<MinimizerSeeder>) {
+        self.algorithm.set_minimizer_seeder(minimizer_seeder);
+    }
+    /// Set the maximum number of locations of a pattern to be used as anchors (default: `None`, no limit).
+    ///  - Patterns located in more positions across the targets (e.g., seeds from repeats) are skipped entirely.
+    ///  - Bounds the cost of the repetitive queries, but the alignments anchored only by those patterns are missed.
+    pub fn set_max_locations_per_pattern(&mut self, max_locations_per_pattern: Option<u32>) {
+        self.algorithm.set_max_locations_per_pattern(max_locations_per_pattern);
+    }
+    /// Set the number of the flanking bases to verify the anchors of a single pattern (default: 0, no verification).
+    ///  - The anchors not matching this number of bases on both sides are not extended.
+    ///  - Prunes the false seeds in the repetitive references, but the alignments anchored only next to the errors are missed.
+    pub fn set_seed_extension_check(&mut self, seed_extension_check: u32) {
+        self.algorithm.set_seed_extension_check(seed_extension_check);
+    }
+    /// Set the maximum number of mismatches between two anchors on the same diagonal to be merged (default: 0, not merged).
+    ///  - The anchors split by a few mismatches are extended once, from the left anchor.
+    pub fn set_max_merge_mismatches(&mut self, max_merge_mismatches: u32) {
+        self.algorithm.set_max_merge_mismatches(max_merge_mismatches);
+    }
+    /// Set the minimum length of the exact match around an anchor to be extended (default: 0, no limit).
+    ///  - The exact match is the patterns of the anchor extended by the matched bases on both sides.
+    ///  - Drops the isolated short seeds (e.g., on the short repeats), while the anchors in the longer matches are kept.
+    pub fn set_minimum_anchor_size(&mut self, minimum_anchor_size: u32) {
+        self.algorithm.set_minimum_anchor_size(minimum_anchor_size);
+    }
+    /// Set the maximum size of the middle between two chained anchors to be aligned exactly (default: 0, estimated).
+    ///  - Only used with `set_minimum_chain_score`, where the middle is estimated as a single gap by default.
+    ///  - The short middles are aligned to score the chains by their mismatches and matched bases,
+    ///    so the anchors around an indel are kept even if the estimate is below the minimum score.
+    pub fn set_exact_chain_middle_size(&mut self, exact_chain_middle_size: u32) {
+        self.algorithm.set_exact_chain_middle_size(exact_chain_middle_size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Aligner,
+        ReferenceBuilder,
+        algorithms::Local,
+    };
+    use crate::test_utils::{TEST_TARGET, SEED, random_dna};
+
+    #[test]
+    fn targets_with_fewer_anchors_are_skipped() {
+        let target = TEST_TARGET;
+        let query = b"CAAACTCACAATTGTATTTCTTTGCCAGCTGGGCATATACTTTTTCCGCCCCCTCATTTA";
+        let reference = ReferenceBuilder::new()
+            .add_target("target", target)
+            .build().unwrap();
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        assert_eq!(aligner.get_minimum_anchor_count(), 1);
+        let result = aligner.align(query, &reference);
+        assert_eq!(result.0.len(), 1);
+
+        let pattern_count = query.len() as u32 / aligner.get_pattern_size();
+        aligner.set_minimum_anchor_count(pattern_count + 1);
+        let result = aligner.align(query, &reference);
+        assert!(result.0.is_empty());
+    }
+    #[test]
+    fn anchors_out_of_the_chains_are_not_extended() {
+        let unit = TEST_TARGET;
+        let spacer = b"GGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGG";
+        let reference = ReferenceBuilder::new()
+            .add_target("full", unit)
+            .add_target("spurious", &[&unit[..15], spacer, &unit[40..55], spacer].concat())
+            .build().unwrap();
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        assert_eq!(aligner.get_minimum_chain_score(), None);
+        let expected = aligner.align(unit, &reference);
+
+        aligner.set_minimum_chain_score(Some(aligner.get_pattern_size() * 2));
+        let result = aligner.align(unit, &reference);
+        assert_eq!(result.to_json(), expected.to_json());
+
+        aligner.set_minimum_chain_score(Some(unit.len() as u32 + 1));
+        assert!(aligner.align(unit, &reference).0.is_empty());
+    }
+    #[test]
+    fn low_complexity_filter_keeps_the_alignments_of_the_complex_region() {
+        use crate::algorithms::LowComplexityFilter;
+
+        let unit = TEST_TARGET;
+        let poly_a = [b'A'; 300];
+        let reference = ReferenceBuilder::new()
+            .add_target("poly_a", &poly_a)
+            .add_target("target", &[&poly_a[..], unit].concat())
+            .build().unwrap();
+        let query = [&poly_a[..100], unit].concat();
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        assert_eq!(aligner.get_low_complexity_filter(), None);
+        aligner.set_low_complexity_filter(Some(LowComplexityFilter::default()));
+        let result = aligner.align(&query, &reference);
+        assert_eq!(result.0.len(), 1);
+        assert_eq!(result.0[0].index, 1);
+        assert!(result.0[0].alignments.iter().any(|v| v.position.query == (0, query.len() as u32)));
+    }
+    #[test]
+    fn seed_stride_keeps_the_reads_with_clustered_errors() {
+        // Random-like sequence
+        let target = random_dna(SEED, 300);
+        let reference = ReferenceBuilder::new().add_target("target", &target).build().unwrap();
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        let pattern_size = aligner.get_pattern_size() as usize;
+        // Pairs of mismatches across the boundaries of the patterns in the first half of the read
+        //  - Each pair breaks two patterns, but only one seed shifted by the half of the pattern size.
+        let mut read = target[50..50 + 20 * pattern_size].to_vec();
+        (0..5).for_each(|i| {
+            let boundary = (2 * i + 1) * pattern_size;
+            [boundary - 2, boundary + 2].into_iter().for_each(|position| {
+                read[position] = if read[position] == b'A' { b'C' } else { b'A' };
+            });
+        });
+        // 10 patterns are left, and 14 shifted seeds
+        aligner.set_minimum_anchor_count(20);
+        assert!(aligner.align(&read, &reference).0.is_empty());
+
+        assert_eq!(aligner.get_seed_stride(), None);
+        aligner.set_seed_stride(Some(pattern_size as u32 / 2));
+        let result = aligner.align(&read, &reference);
+        assert_eq!(result.0.len(), 1);
+        // Same as without the minimum anchor count
+        aligner.set_minimum_anchor_count(1);
+        assert_eq!(result.to_json(), aligner.align(&read, &reference).to_json());
+    }
+    #[test]
+    fn anchors_merged_across_a_mismatch_give_the_same_alignments() {
+        let unit = TEST_TARGET;
+        let reference = ReferenceBuilder::new()
+            .add_target("target", &unit.repeat(3))
+            .build().unwrap();
+        let mut query = unit.to_vec();
+        query[35] = b'G';
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        let expected = aligner.align(&query, &reference);
+        aligner.set_max_merge_mismatches(1);
+        assert_eq!(aligner.get_max_merge_mismatches(), 1);
+        let result = aligner.align(&query, &reference);
+        assert_eq!(result.0[0].alignments.len(), 3);
+        assert_eq!(result.0[0].alignments, expected.0[0].alignments);
+    }
+    #[test]
+    fn minimum_anchor_size_keeps_the_extended_anchors() {
+        let unit = TEST_TARGET;
+        let reference = ReferenceBuilder::new()
+            .add_target("target", &[&unit[..], b"TTTTTTTTTT", unit].concat())
+            .build().unwrap();
+        let mut query = unit.to_vec();
+        query[35] = b'G';
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        let expected = aligner.align(&query, &reference);
+        aligner.set_minimum_anchor_size(30);
+        assert_eq!(aligner.get_minimum_anchor_size(), 30);
+        let result = aligner.align(&query, &reference);
+        assert_eq!(result.0[0].alignments, expected.0[0].alignments);
+        // No exact match is as long as the query
+        aligner.set_minimum_anchor_size(40);
+        assert!(aligner.align(&query, &reference).0.is_empty());
+    }
+    #[test]
+    fn seed_pattern_keeps_the_reads_with_periodic_errors() {
+        use crate::algorithms::SeedPattern;
+        // Random-like sequence
+        let target = random_dna(SEED, 300);
+        let reference = ReferenceBuilder::new().add_target("target", &target).build().unwrap();
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        let pattern_size = aligner.get_pattern_size() as usize;
+        // A mismatch at the third base of every third pattern
+        let mut read = target[50..50 + 20 * pattern_size].to_vec();
+        (0..20).step_by(3).for_each(|pattern_index| {
+            let position = pattern_index * pattern_size + 2;
+            read[position] = if read[position] == b'A' { b'C' } else { b'A' };
+        });
+        // 13 patterns are left, and 7 patterns with the mismatches
+        aligner.set_minimum_anchor_count(20);
+        assert!(aligner.align(&read, &reference).0.is_empty());
+
+        assert_eq!(aligner.get_seed_pattern(), None);
+        aligner.set_seed_pattern(SeedPattern::new("110"));
+        let result = aligner.align(&read, &reference);
+        assert_eq!(result.0.len(), 1);
+        // Same as without the minimum anchor count
+        aligner.set_minimum_anchor_count(1);
+        assert_eq!(result.to_json(), aligner.align(&read, &reference).to_json());
+    }
+    #[test]
+    fn minimizer_seeder_keeps_the_alignment_of_the_read() {
+        use crate::algorithms::MinimizerSeeder;
+        // Random-like sequence
+        let target = random_dna(SEED, 3000);
+        let reference = ReferenceBuilder::new().add_target("target", &target).build().unwrap();
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+        // Read with a mismatch and a deletion
+        let mut read = target[1000..1500].to_vec();
+        read[100] = if read[100] == b'A' { b'C' } else { b'A' };
+        read.remove(300);
+        let exhaustive = aligner.align(&read, &reference);
+        assert_eq!(exhaustive.0.len(), 1);
+
+        assert_eq!(aligner.get_minimizer_seeder(), None);
+        aligner.set_minimizer_seeder(Some(MinimizerSeeder::new(5)));
+        assert_eq!(aligner.align(&read, &reference).to_json(), exhaustive.to_json());
+        aligner.set_minimizer_seeder(None);
+        assert_eq!(aligner.align(&read, &reference).to_json(), exhaustive.to_json());
+    }
+    #[test]
+    fn exact_chain_middle_keeps_the_anchors_around_an_indel() {
+        let target = random_dna(SEED, 200);
+        let reference = ReferenceBuilder::new()
+            .add_target("target", &target)
+            .build().unwrap();
+        // A 3 bp deletion and a mismatch next to it
+        let mut query = [&target[..100], &target[103..]].concat();
+        query[101] = if query[101] == b'A' { b'C' } else { b'A' };
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+        assert_eq!(aligner.get_exact_chain_middle_size(), 0);
+        let expected = aligner.align(&query, &reference);
+        assert_eq!(expected.0[0].alignments.len(), 1);
+
+        // At least a pattern is broken around the deletion, so the estimate is at most (anchored - pattern - 3).
+        // The exact middle adds its matched bases: (anchored - 5).
+        let pattern_size = aligner.get_pattern_size();
+        let anchored_length = query.len() as u32 / pattern_size * pattern_size;
+        aligner.set_minimum_chain_score(Some(anchored_length - pattern_size - 2));
+        assert!(aligner.align(&query, &reference).0.is_empty());
+
+        aligner.set_exact_chain_middle_size(100);
+        assert_eq!(aligner.get_exact_chain_middle_size(), 100);
+        assert_eq!(aligner.align(&query, &reference).to_json(), expected.to_json());
+    }
+}
@@ -0,0 +1,72 @@
+use thiserror::Error;
+
+use crate::{
+    results::{QueryAlignment, MappingResult},
+    reference::{Reference, ReferenceSet},
+};
+use super::{Aligner, algorithms::Algorithm};
+
+/// Error for the query given as a string.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum QueryError {
+    #[error("Invalid character '{character}' at position {position} of the query")]
+    InvalidCharacter { character: char, position: usize },
+}
+
+/// Alignment with the query of `&str` (e.g., from the scripts).
+///  - Only the ASCII letters are allowed as the sequence (e.g., "ACGT", "N", or the amino acids).
+///  - The results are the same as the byte slice of the string.
+impl<A: Algorithm> Aligner<A> {
+    /// Align a query string to a reference.
+    pub fn align_str(&mut self, query: &str, reference: &Reference) -> Result<QueryAlignment, QueryError> {
+        let query = checked_query(query)?;
+        Ok(self.align(query, reference))
+    }
+    /// Align a query string to a reference and get only the coordinates and the penalties.
+    pub fn map_str(&mut self, query: &str, reference: &Reference) -> Result<Vec<MappingResult>, QueryError> {
+        let query = checked_query(query)?;
+        Ok(self.map(query, reference))
+    }
+    /// Align a query string to all references of the set.
+    pub fn align_str_to_reference_set(
+        &mut self,
+        query: &str,
+        reference_set: &ReferenceSet,
+    ) -> Result<QueryAlignment, QueryError> {
+        let query = checked_query(query)?;
+        Ok(self.align_to_reference_set(query, reference_set))
+    }
+}
+
+fn checked_query(query: &str) -> Result<&[u8], QueryError> {
+    match query.char_indices().find(|(_, character)| !character.is_ascii_alphabetic()) {
+        Some((position, character)) => Err(QueryError::InvalidCharacter { character, position }),
+        None => Ok(query.as_bytes()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ReferenceBuilder, algorithms::Local};
+    use crate::test_utils::TEST_TARGET;
+
+    #[test]
+    fn string_query_is_same_as_bytes() {
+        let unit = std::str::from_utf8(TEST_TARGET).unwrap();
+        let reference = ReferenceBuilder::new().add_target("target", unit.as_bytes()).build().unwrap();
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+
+        let expected = aligner.align(unit.as_bytes(), &reference);
+        assert_eq!(aligner.align_str(unit, &reference).unwrap().to_json(), expected.to_json());
+        aligner.set_uppercase_query(true);
+        let lowercase_query: String = unit.to_lowercase();
+        assert_eq!(aligner.align_str(&lowercase_query, &reference).unwrap().to_json(), expected.to_json());
+
+        let query_with_digit = format!("{}1{}", &unit[..30], &unit[30..]);
+        let error = aligner.align_str(&query_with_digit, &reference).unwrap_err();
+        assert_eq!(error, QueryError::InvalidCharacter { character: '1', position: 30 });
+        assert_eq!(error.to_string(), "Invalid character '1' at position 30 of the query");
+        assert!(aligner.map_str("ACGT ACGT", &reference).is_err());
+    }
+}
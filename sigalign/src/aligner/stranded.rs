@@ -0,0 +1,87 @@
+use crate::{
+    results::StrandedAlignment,
+    reference::Reference,
+};
+use super::{Aligner, algorithms::Algorithm};
+
+impl<A: Algorithm> Aligner<A> {
+    /// Align a query in both strands (the reverse complement of DNA), reporting the strand of each alignment.
+    ///  - The alignments of the both strands at the same locus are collapsed to the better one
+    ///    (see `StrandedAlignment::from_strands`).
+    ///  - If `uppercase_query` is set, the query is converted to uppercase before the reverse complement.
+    pub fn align_both_strands(&mut self, query: &[u8], reference: &Reference) -> Vec<StrandedAlignment> {
+        let forward = self.align(query, reference);
+        let reverse = self.align(&self.reverse_complement_of_query(query), reference);
+        StrandedAlignment::from_strands(forward, reverse)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sigalign_utils::sequence_manipulation::reverse_complementary::reverse_complement_of_dna_sequence;
+    use crate::{
+        Aligner,
+        ReferenceBuilder,
+        algorithms::Local,
+        results::StrandedAlignment,
+    };
+    use crate::test_utils::TEST_TARGET;
+
+    #[test]
+    fn palindromic_query_is_reported_once() {
+        let unit = TEST_TARGET;
+        let reverse_unit = reverse_complement_of_dna_sequence(unit);
+        // The query is the same as its reverse complement
+        let palindrome = [&unit[..], &reverse_unit].concat();
+        assert_eq!(palindrome, reverse_complement_of_dna_sequence(&palindrome));
+        let flank = b"TTGAGCATCCGATAGC";
+        let reference = ReferenceBuilder::new()
+            .add_target("target", &[&b"GGGGGGGGGG"[..], &palindrome, flank].concat())
+            .build().unwrap();
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+        let forward = aligner.align(&palindrome, &reference);
+        let reverse = aligner.align(&reverse_complement_of_dna_sequence(&palindrome), &reference);
+        assert_eq!(forward.to_json(), reverse.to_json());
+
+        let result = aligner.align_both_strands(&palindrome, &reference);
+        assert_eq!(result.len(), 1);
+        assert!(result[0].is_forward);
+        assert_eq!(result[0].alignment.position.target, (10, 150));
+
+        // The better strand is kept: only one strand is extended to the flank
+        let query = [&palindrome[..], flank].concat();
+        let result = aligner.align_both_strands(&query, &reference);
+        assert_eq!(result.len(), 1);
+        assert!(result[0].is_forward);
+        assert_eq!(result[0].alignment.position.target, (10, 166));
+        let result = aligner.align_both_strands(&reverse_complement_of_dna_sequence(&query), &reference);
+        assert_eq!(result.len(), 1);
+        assert!(!result[0].is_forward);
+        assert_eq!(result[0].alignment.position.target, (10, 166));
+
+        // Alignments at the different loci are all kept
+        let reference = ReferenceBuilder::new()
+            .add_target("target", &[&unit[..], b"GGGGGGGGGG", &reverse_unit].concat())
+            .build().unwrap();
+        let result = aligner.align_both_strands(unit, &reference);
+        let strands: Vec<(bool, (u32, u32))> = result.iter().map(|v| (v.is_forward, v.alignment.position.target)).collect();
+        assert_eq!(strands, vec![(true, (0, 70)), (false, (80, 150))]);
+    }
+    #[test]
+    fn lowercase_query_is_aligned_in_both_strands_if_uppercase_query_is_set() {
+        let unit = TEST_TARGET;
+        let reference = ReferenceBuilder::new()
+            .add_target("target", &[&unit[..], b"GGGGGGGGGG", &reverse_complement_of_dna_sequence(unit)].concat())
+            .build().unwrap();
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+        let strands = |result: Vec<StrandedAlignment>| -> Vec<(bool, (u32, u32))> {
+            result.iter().map(|v| (v.is_forward, v.alignment.position.target)).collect()
+        };
+        let expected = strands(aligner.align_both_strands(unit, &reference));
+        assert_eq!(expected.len(), 2);
+
+        aligner.set_uppercase_query(true);
+        let result = strands(aligner.align_both_strands(&unit.to_ascii_lowercase(), &reference));
+        assert_eq!(result, expected);
+    }
+}
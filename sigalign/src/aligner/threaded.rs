@@ -0,0 +1,75 @@
+use crate::{
+    results::QueryAlignment,
+    reference::Reference,
+};
+use super::{Aligner, algorithms::Algorithm};
+
+impl<A: Algorithm> Aligner<A> {
+    /// Align multiple queries to a reference using scoped threads.
+    ///  - Queries are split into `num_threads` chunks, and each chunk is aligned by a clone of this aligner.
+    ///  - Results are returned in the order of the queries.
+    pub fn align_queries_threaded<Q>(
+        &self,
+        queries: &[Q],
+        reference: &Reference,
+        num_threads: usize,
+    ) -> Vec<QueryAlignment> where
+        Q: AsRef<[u8]> + Sync,
+        A: Send,
+    {
+        if queries.is_empty() {
+            return Vec::new();
+        }
+        let num_threads = num_threads.clamp(1, queries.len());
+        let chunk_size = (queries.len() + num_threads - 1) / num_threads;
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = queries.chunks(chunk_size).map(|chunk| {
+                let mut aligner = self.clone();
+                scope.spawn(move || {
+                    chunk.iter().map(|query| {
+                        aligner.align(query.as_ref(), reference)
+                    }).collect::<Vec<_>>()
+                })
+            }).collect();
+            handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Aligner,
+        ReferenceBuilder,
+        algorithms::SemiGlobal,
+    };
+    use crate::test_utils::TEST_TARGET;
+
+    #[test]
+    fn threaded_alignment_gives_the_same_results_in_order() {
+        let unit = TEST_TARGET;
+        let mut builder = ReferenceBuilder::new();
+        for i in 0..10 {
+            let mut sequence = unit.repeat(2);
+            sequence[i * 7] = b'T';
+            builder = builder.add_target(&format!("target_{}", i), &sequence);
+        }
+        let reference = builder.build().unwrap();
+        let queries: Vec<Vec<u8>> = (0..17).map(|i| {
+            let mut query = unit[i..].to_vec();
+            query[i % 10 + 20] = b'G';
+            query
+        }).collect();
+
+        let mut aligner = Aligner::new(SemiGlobal::new(4, 6, 2, 50, 0.2).unwrap());
+        let expected: Vec<String> = queries.iter().map(|query| {
+            aligner.align(query, &reference).to_json()
+        }).collect();
+        assert!(expected.iter().all(|v| v != "[]"));
+        for num_threads in [0, 1, 3, 8, 100] {
+            let results: Vec<String> = aligner.align_queries_threaded(&queries, &reference, num_threads)
+                .iter().map(|v| v.to_json()).collect();
+            assert_eq!(results, expected);
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use super::{Aligner, algorithms::Algorithm};
+
+impl<A: Algorithm> Aligner<A> {
+    /// Extend the anchors only with the mismatches, without gaps (default: false).
+    ///  - Much faster when gaps are rare (e.g., high-identity short reads).
+    ///  - Alignments that need gaps to satisfy the cutoff are not found.
+    pub fn set_ungapped(&mut self, ungapped: bool) {
+        self.algorithm.set_ungapped(ungapped);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Aligner,
+        ReferenceBuilder,
+        algorithms::{Local, SemiGlobal},
+    };
+    use crate::test_utils::{SEED, Xorshift};
+
+    #[test]
+    fn ungapped_alignment_is_same_as_gapped_without_gaps() {
+        use crate::algorithms::{Algorithm, SemiGlobalWithLimit};
+
+        let mut rng = Xorshift::new(SEED);
+        let targets: Vec<Vec<u8>> = (0..5).map(|_| {
+            (0..300).map(|_| b"ACGT"[rng.gen_range(4) as usize]).collect()
+        }).collect();
+        let mut builder = ReferenceBuilder::new();
+        for (index, target) in targets.iter().enumerate() {
+            builder = builder.add_target(&index.to_string(), target);
+        }
+        let reference = builder.build().unwrap();
+
+        let mut error_free_reads = Vec::new();
+        let mut substituted_reads = Vec::new();
+        for _ in 0..20 {
+            let target = &targets[rng.gen_range(5) as usize];
+            let start = rng.gen_range(150) as usize;
+            let read = target[start..start + 100].to_vec();
+            let mut substituted_read = read.clone();
+            for _ in 0..2 {
+                let position = rng.gen_range(100) as usize;
+                substituted_read[position] = if read[position] == b'A' { b'C' } else { b'A' };
+            }
+            error_free_reads.push(read);
+            substituted_reads.push(substituted_read);
+        }
+
+        fn assert_same<A: Algorithm>(mut aligner: Aligner<A>, reads: &[Vec<u8>], reference: &crate::Reference) {
+            for read in reads {
+                aligner.set_ungapped(false);
+                let expected = aligner.align(read, reference);
+                aligner.set_ungapped(true);
+                let result = aligner.align(read, reference);
+                assert!(!expected.0.is_empty());
+                assert_eq!(result.to_json(), expected.to_json());
+            }
+        }
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+        assert!(!aligner.get_ungapped());
+        aligner.set_ungapped(true);
+        assert!(aligner.get_ungapped());
+        assert_same(Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap()), &error_free_reads, &reference);
+        assert_same(Aligner::new(SemiGlobal::new(4, 6, 2, 50, 0.1).unwrap()), &error_free_reads, &reference);
+        assert_same(Aligner::new(SemiGlobalWithLimit::new(4, 6, 2, 50, 0.1, 1).unwrap()), &error_free_reads, &reference);
+        assert_same(Aligner::new(SemiGlobal::new(4, 6, 2, 50, 0.1).unwrap()), &substituted_reads, &reference);
+        assert_same(Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap()), &substituted_reads, &reference);
+
+        // The alignment with a gap is not found
+        let mut read_with_deletion = error_free_reads[0].clone();
+        read_with_deletion.remove(50);
+        let mut aligner = Aligner::new(SemiGlobal::new(4, 6, 2, 50, 0.1).unwrap());
+        assert!(aligner.align(&read_with_deletion, &reference).0.iter().any(|v| {
+            v.alignments.iter().any(|alignment| alignment.length == 100)
+        }));
+        aligner.set_ungapped(true);
+        assert!(aligner.align(&read_with_deletion, &reference).0.iter().all(|v| {
+            v.alignments.iter().all(|alignment| alignment.penalty % 4 == 0)
+        }));
+    }
+}
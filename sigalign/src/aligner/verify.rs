@@ -0,0 +1,81 @@
+use sigalign_core::reference::{
+    SequenceStorage as _,
+    SequenceBuffer as _,
+};
+use crate::{
+    results::{QueryAlignment, AlignmentVerificationError},
+    reference::Reference,
+};
+use super::{Aligner, algorithms::Algorithm};
+
+impl<A: Algorithm> Aligner<A> {
+    /// Check if the alignments of the query are consistent with the sequences and the penalties of this aligner.
+    ///  - For the quality control or the assertion at runtime (see `Alignment::verify`).
+    ///  - `query_alignment` is the result of `align` with the same `query` and `reference`.
+    ///  - Error with the target index of the first inconsistent alignment.
+    pub fn verify(
+        &mut self,
+        query: &[u8],
+        reference: &Reference,
+        query_alignment: &QueryAlignment,
+    ) -> Result<(), (u32, AlignmentVerificationError)> {
+        let query = if self.uppercase_query {
+            query.to_ascii_uppercase()
+        } else {
+            query.to_vec()
+        };
+        let sequence_storage = reference.as_ref().get_sequence_storage();
+        query_alignment.0.iter().try_for_each(|target_alignment| {
+            let target_index = target_alignment.index;
+            sequence_storage.fill_buffer(target_index, &mut self.sequence_buffer);
+            let target = self.sequence_buffer.buffered_sequence();
+            target_alignment.alignments.iter().try_for_each(|alignment| {
+                let result = if reference.is_circular(target_index) && alignment.position.target.1 <= alignment.position.target.0 {
+                    // Unwrap the end crossing the origin to the stored sequence
+                    let mut alignment = alignment.clone();
+                    alignment.position.target.1 += reference.get_length(target_index).unwrap_or(0);
+                    alignment.verify(target, &query, self.algorithm.regulator())
+                } else {
+                    alignment.verify(target, &query, self.algorithm.regulator())
+                };
+                result.map_err(|error| (target_index, error))
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Aligner,
+        ReferenceBuilder,
+        algorithms::Local,
+    };
+    use crate::test_utils::TEST_TARGET;
+
+    #[test]
+    fn alignments_are_consistent_with_the_sequences() {
+        let unit = TEST_TARGET;
+        let reference = ReferenceBuilder::new()
+            .add_target("target", unit)
+            .add_circular_target("circular", unit)
+            .build().unwrap();
+        // Substitution, N and insertion
+        let mut query = unit.to_vec();
+        query[10] = b'T';
+        query[20] = b'N';
+        query.insert(40, b'G');
+        let rotated_query = [&query[35..], &query[..35]].concat();
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap().with_n_penalty(1).unwrap());
+        for query in [&query, &rotated_query] {
+            let query_alignment = aligner.align(query, &reference);
+            assert!(!query_alignment.0.is_empty());
+            assert_eq!(aligner.verify(query, &reference, &query_alignment), Ok(()));
+        }
+
+        let mut query_alignment = aligner.align(&query, &reference);
+        query_alignment.0[0].alignments[0].penalty += 1;
+        assert!(matches!(aligner.verify(&query, &reference, &query_alignment), Err((0, _))));
+    }
+}
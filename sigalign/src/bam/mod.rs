@@ -0,0 +1,222 @@
+/*!
+BAM output (enabled with the `noodles` feature).
+
+The encoding (binary records, bins and BGZF compression) is done by `noodles`.
+
+- The header has the targets of the reference in order of the target index,
+  so the reference sequence IDs of the records are the target indices.
+- Targets without a label are named by their index.
+- The alignment with the lowest penalty is the primary record and the others are written as secondary
+  (see `QueryAlignment::primary_alignment`; the ties are resolved by `set_tie_break`).
+- A query without any alignment is written as an unmapped record.
+- The mapping quality is missing (255), and the `NM` tag has the edit distance.
+*/
+use std::io::{self, Write};
+use std::num::NonZeroUsize;
+
+use noodles_bam as bam;
+use noodles_bgzf as bgzf;
+use noodles_core::Position;
+use noodles_sam::{
+    self as sam,
+    alignment::{
+        io::Write as _,
+        record::{
+            Flags,
+            cigar::{op::Kind, Op},
+            data::field::Tag,
+        },
+        record_buf::{Cigar, Data, Sequence, data::field::Value},
+        RecordBuf,
+    },
+    header::record::value::{map::ReferenceSequence, Map},
+};
+
+use crate::{
+    Reference,
+    results::{QueryAlignment, Alignment, AlignmentOperation, TieBreak},
+};
+
+/// Writer of the alignments in BAM format.
+pub struct BamWriter<W: Write> {
+    header: sam::Header,
+    writer: bam::io::Writer<bgzf::io::Writer<W>>,
+    tie_break: TieBreak,
+}
+
+impl<W: Write> BamWriter<W> {
+    /// Make a new writer and write the header with the names and the lengths of the targets.
+    pub fn new(inner: W, reference: &Reference) -> io::Result<Self> {
+        let header = header_of_reference(reference)?;
+        let mut writer = bam::io::Writer::new(inner);
+        writer.write_header(&header)?;
+        Ok(Self { header, writer, tie_break: TieBreak::LeftmostTarget })
+    }
+    /// Set the policy to choose the primary record among the alignments with the same lowest penalty.
+    ///  - Default is `TieBreak::LeftmostTarget`.
+    pub fn set_tie_break(&mut self, tie_break: TieBreak) {
+        self.tie_break = tie_break;
+    }
+    /// Write the records of the query alignment.
+    ///  - `query` is the sequence that was aligned.
+    ///  - `is_forward`: if false, the records are flagged as reverse complemented.
+    pub fn write_query_alignment(
+        &mut self,
+        query_name: &str,
+        query: &[u8],
+        is_forward: bool,
+        query_alignment: &QueryAlignment,
+    ) -> io::Result<()> {
+        let strand_flags = if is_forward { Flags::empty() } else { Flags::REVERSE_COMPLEMENTED };
+        let primary_flags = query_alignment.primary_flags(self.tie_break);
+        let mut is_unmapped = true;
+        for (target_alignment, primary_flags) in query_alignment.0.iter().zip(primary_flags) {
+            for (alignment, is_primary) in target_alignment.alignments.iter().zip(primary_flags) {
+                let flags = if is_primary { strand_flags } else { strand_flags | Flags::SECONDARY };
+                is_unmapped = false;
+                let record = record_of_alignment(
+                    query_name,
+                    query,
+                    flags,
+                    target_alignment.index,
+                    alignment,
+                )?;
+                self.writer.write_alignment_record(&self.header, &record)?;
+            }
+        }
+        if is_unmapped {
+            let record = RecordBuf::builder()
+                .set_name(query_name)
+                .set_flags(Flags::UNMAPPED)
+                .set_sequence(Sequence::from(query.to_vec()))
+                .build();
+            self.writer.write_alignment_record(&self.header, &record)?;
+        }
+        Ok(())
+    }
+    /// Get the header written at the start.
+    pub fn get_header(&self) -> &sam::Header {
+        &self.header
+    }
+    /// Write the end of the BGZF stream and get the inner writer.
+    pub fn finish(self) -> io::Result<W> {
+        self.writer.into_inner().finish()
+    }
+}
+
+fn header_of_reference(reference: &Reference) -> io::Result<sam::Header> {
+    let mut builder = sam::Header::builder();
+    for target_index in 0..reference.get_num_targets() {
+        let label = reference.get_label(target_index).unwrap_or_default();
+        let name = if label.is_empty() { target_index.to_string() } else { label };
+        let length = reference.get_length(target_index).unwrap_or(0) as usize;
+        let length = NonZeroUsize::new(length).ok_or_else(|| io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("target {} is empty", target_index),
+        ))?;
+        builder = builder.add_reference_sequence(name, Map::<ReferenceSequence>::new(length));
+    }
+    Ok(builder.build())
+}
+
+fn record_of_alignment(
+    query_name: &str,
+    query: &[u8],
+    flags: Flags,
+    target_index: u32,
+    alignment: &Alignment,
+) -> io::Result<RecordBuf> {
+    let alignment_start = Position::try_from(alignment.position.target.0 as usize + 1)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let data: Data = [(Tag::EDIT_DISTANCE, Value::from(edit_distance(alignment)))]
+        .into_iter()
+        .collect();
+    Ok(RecordBuf::builder()
+        .set_name(query_name)
+        .set_flags(flags)
+        .set_reference_sequence_id(target_index as usize)
+        .set_alignment_start(alignment_start)
+        .set_cigar(cigar_with_soft_clips(alignment, query.len()))
+        .set_sequence(Sequence::from(query.to_vec()))
+        .set_data(data)
+        .build())
+}
+
+// The unaligned ends of the query are soft clipped
+fn cigar_with_soft_clips(alignment: &Alignment, query_length: usize) -> Cigar {
+    let (query_start, query_end) = alignment.position.query;
+    let mut ops = Vec::with_capacity(alignment.operations.len() + 2);
+    if query_start != 0 {
+        ops.push(Op::new(Kind::SoftClip, query_start as usize));
+    }
+    ops.extend(alignment.operations.iter().map(|v| {
+        let kind = match v.operation {
+            AlignmentOperation::Match => Kind::SequenceMatch,
+            AlignmentOperation::Subst => Kind::SequenceMismatch,
+            AlignmentOperation::Insertion => Kind::Insertion,
+            AlignmentOperation::Deletion => Kind::Deletion,
+        };
+        Op::new(kind, v.count as usize)
+    }));
+    if (query_end as usize) < query_length {
+        ops.push(Op::new(Kind::SoftClip, query_length - query_end as usize));
+    }
+    ops.into_iter().collect()
+}
+
+fn edit_distance(alignment: &Alignment) -> u32 {
+    alignment.operations.iter()
+        .filter(|v| v.operation != AlignmentOperation::Match)
+        .map(|v| v.count)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Aligner, ReferenceBuilder, algorithms::Local};
+    use crate::test_utils::TEST_TARGET;
+
+    #[test]
+    fn written_records_are_indexed_by_target() {
+        let unit = TEST_TARGET;
+        let reference = ReferenceBuilder::new()
+            .add_target("other", b"GGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGG")
+            .add_target("target", &[&b"TTTTTTTTTT"[..], unit].concat())
+            .build().unwrap();
+        let query = [&b"GTGTG"[..], &unit[5..65]].concat();
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        let query_alignment = aligner.align(&query, &reference);
+        let alignment = &query_alignment.0[0].alignments[0];
+        let (query_start, target_start) = (alignment.position.query.0, alignment.position.target.0);
+        assert!(query_start > 0);
+
+        let mut writer = BamWriter::new(Vec::new(), &reference).unwrap();
+        writer.write_query_alignment("read_1", &query, true, &query_alignment).unwrap();
+        writer.write_query_alignment("read_2", b"AAAAAAAAAAAAAAAAAAAAAAAAAAAAAA", false, &QueryAlignment(Vec::new())).unwrap();
+        let bam = writer.finish().unwrap();
+
+        let mut reader = bam::io::Reader::new(&bam[..]);
+        let header = reader.read_header().unwrap();
+        let names: Vec<_> = header.reference_sequences().keys().map(|v| v.to_string()).collect();
+        assert_eq!(names, vec!["other", "target"]);
+        assert_eq!(header.reference_sequences()[1].length().get(), 80);
+
+        let records: Vec<RecordBuf> = reader.record_bufs(&header).map(|v| v.unwrap()).collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].reference_sequence_id(), Some(1));
+        assert_eq!(records[0].alignment_start(), Position::new(target_start as usize + 1));
+        assert_eq!(records[0].flags(), Flags::empty());
+        assert_eq!(
+            records[0].cigar().alignment_span() as u32,
+            alignment.position.target.1 - alignment.position.target.0,
+        );
+        assert_eq!(
+            records[0].cigar().as_ref().first(),
+            Some(&Op::new(Kind::SoftClip, query_start as usize)),
+        );
+        assert_eq!(records[0].sequence().as_ref(), &query[..]);
+        assert!(records[1].flags().is_unmapped());
+        assert_eq!(records[1].reference_sequence_id(), None);
+    }
+}
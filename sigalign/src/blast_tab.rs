@@ -0,0 +1,164 @@
+/*!
+BLAST tabular output (the 12 columns of `-outfmt 6`).
+
+The columns are `qseqid`, `sseqid`, `pident`, `length`, `mismatch`, `gapopen`,
+`qstart`, `qend`, `sstart`, `send`, `evalue` and `bitscore`, separated by tabs.
+
+- Targets without a label are named by their index.
+- The positions are 1-based and inclusive. For the reverse complemented query,
+  the query positions are on the forward strand and `sstart` is greater than `send`, as in BLAST.
+- `pident` is the percentage of the matches in the alignment length (including the gaps).
+- `evalue` and `bitscore` are estimated by the `SignificanceModel` (see `set_significance_model`), or 0 without the model.
+- A query without any alignment writes no line.
+*/
+use std::io::{self, Write};
+
+use crate::{
+    Reference,
+    results::{QueryAlignment, Alignment, AlignmentOperation, SignificanceModel},
+};
+
+/// Writer of the alignments in BLAST tabular format.
+pub struct BlastTabWriter<W: Write> {
+    writer: W,
+    target_names: Vec<String>,
+    reference_total_length: u64,
+    significance_model: Option<SignificanceModel>,
+}
+
+impl<W: Write> BlastTabWriter<W> {
+    /// Make a new writer with the names of the targets.
+    pub fn new(writer: W, reference: &Reference) -> Self {
+        let mut reference_total_length = 0;
+        let target_names = (0..reference.get_num_targets()).map(|target_index| {
+            reference_total_length += reference.get_length(target_index).unwrap_or(0) as u64;
+            let label = reference.get_label(target_index).unwrap_or_default();
+            if label.is_empty() { target_index.to_string() } else { label }
+        }).collect();
+        Self {
+            writer,
+            target_names,
+            reference_total_length,
+            significance_model: None,
+        }
+    }
+    /// Set the model to estimate `evalue` and `bitscore`.
+    ///  - The search space is the total length of the targets and the length of the query.
+    pub fn set_significance_model(&mut self, model: SignificanceModel) {
+        self.significance_model = Some(model);
+    }
+    /// Write a line for each alignment of the query.
+    ///  - `query_length` is the length of the whole query.
+    ///  - `is_forward`: if false, the alignments are of the reverse complemented query.
+    pub fn write_query_alignment(
+        &mut self,
+        query_name: &str,
+        query_length: u32,
+        is_forward: bool,
+        query_alignment: &QueryAlignment,
+    ) -> io::Result<()> {
+        for target_alignment in query_alignment.0.iter() {
+            let target_name = self.target_names.get(target_alignment.index as usize).ok_or_else(|| io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("target {} is not in the reference", target_alignment.index),
+            ))?;
+            for alignment in target_alignment.alignments.iter() {
+                let (evalue, bit_score) = match &self.significance_model {
+                    Some(model) => (
+                        alignment.significance(model, self.reference_total_length, query_length),
+                        alignment.bit_score(model),
+                    ),
+                    None => (0.0, 0.0),
+                };
+                let (mismatches, gap_opens) = count_mismatches_and_gap_opens(alignment);
+                let pident = if alignment.length == 0 {
+                    0.0
+                } else {
+                    100.0 * alignment.count_matches() as f64 / alignment.length as f64
+                };
+                let (query_start, query_end) = alignment.position.query;
+                let (target_start, target_end) = alignment.position.target;
+                let (qstart, qend, sstart, send) = if is_forward {
+                    (query_start + 1, query_end, target_start + 1, target_end)
+                } else {
+                    (query_length - query_end + 1, query_length - query_start, target_end, target_start + 1)
+                };
+                writeln!(
+                    self.writer,
+                    "{}\t{}\t{:.3}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{:.2e}\t{:.1}",
+                    query_name, target_name, pident, alignment.length, mismatches, gap_opens,
+                    qstart, qend, sstart, send, evalue, bit_score,
+                )?;
+            }
+        }
+        Ok(())
+    }
+    /// Get the inner writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+fn count_mismatches_and_gap_opens(alignment: &Alignment) -> (u32, u32) {
+    alignment.operations.iter().fold((0, 0), |(mismatches, gap_opens), operations| {
+        match operations.operation {
+            AlignmentOperation::Match => (mismatches, gap_opens),
+            AlignmentOperation::Subst => (mismatches + operations.count, gap_opens),
+            AlignmentOperation::Insertion | AlignmentOperation::Deletion => (mismatches, gap_opens + 1),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Aligner, ReferenceBuilder, algorithms::Local};
+    use crate::test_utils::TEST_TARGET;
+
+    #[test]
+    fn lines_have_the_twelve_columns() {
+        let unit = TEST_TARGET;
+        let reference = ReferenceBuilder::new()
+            .add_target("other", b"GGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGG")
+            .add_target("target", &[&b"TTTTTTTTTT"[..], unit].concat())
+            .build().unwrap();
+        // A substitution and a deletion
+        let mut query = unit[..60].to_vec();
+        query[20] = b'G';
+        query.remove(40);
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        let query_alignment = aligner.align(&query, &reference);
+        let alignment = &query_alignment.0[0].alignments[0];
+
+        let mut writer = BlastTabWriter::new(Vec::new(), &reference);
+        writer.write_query_alignment("read_1", query.len() as u32, true, &query_alignment).unwrap();
+        writer.write_query_alignment("read_2", 30, true, &QueryAlignment(Vec::new())).unwrap();
+        writer.set_significance_model(SignificanceModel::new(1, 4).unwrap());
+        writer.write_query_alignment("read_3", query.len() as u32, false, &query_alignment).unwrap();
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        let lines: Vec<Vec<&str>> = output.lines().map(|line| line.split('\t').collect()).collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|columns| columns.len() == 12));
+
+        let (query_start, query_end) = alignment.position.query;
+        let (target_start, target_end) = alignment.position.target;
+        let pident = format!("{:.3}", 100.0 * alignment.count_matches() as f64 / alignment.length as f64);
+        assert_eq!(lines[0], vec![
+            "read_1", "target", &pident, &alignment.length.to_string(), "1", "1",
+            &(query_start + 1).to_string(), &query_end.to_string(),
+            &(target_start + 1).to_string(), &target_end.to_string(),
+            "0.00e0", "0.0",
+        ]);
+
+        // Reverse complemented query with the significance
+        let query_length = query.len() as u32;
+        assert_eq!(lines[1][6..10], [
+            (query_length - query_end + 1).to_string(), (query_length - query_start).to_string(),
+            target_end.to_string(), (target_start + 1).to_string(),
+        ]);
+        let evalue: f64 = lines[1][10].parse().unwrap();
+        let bit_score: f64 = lines[1][11].parse().unwrap();
+        assert!(evalue > 0.0 && evalue < 1e-3);
+        assert!(bit_score > 10.0);
+    }
+}
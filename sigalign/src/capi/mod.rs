@@ -0,0 +1,264 @@
+/*!
+C API for SigAlign (enabled with the `capi` feature).
+
+All types exposed here are either opaque (used only behind pointers) or `#[repr(C)]`,
+so the header can be generated with `cbindgen`.
+
+Ownership:
+- Every pointer returned by `sigalign_*_new` or `sigalign_align` is owned by the caller.
+- Free them with the matching `sigalign_*_free` or `sigalign_free_result` function.
+- A null pointer is returned when the input is invalid.
+- No panic unwinds across the API: a panic inside a call is caught and null is returned.
+*/
+use std::ffi::{c_char, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::{
+    Aligner,
+    Reference,
+    ReferenceBuilder,
+    algorithms::{Local, SemiGlobal},
+    results::QueryAlignment,
+};
+
+/// Opaque reference.
+pub struct SigAlignReference(Reference);
+
+/// Opaque aligner.
+pub struct SigAlignAligner(AlignerKind);
+
+enum AlignerKind {
+    Local(Aligner<Local>),
+    SemiGlobal(Aligner<SemiGlobal>),
+}
+
+/// One alignment.
+/// - Positions are zero-based and the end positions are exclusive.
+/// - `cigar_ptr` is a null-terminated string of `cigar_len` bytes (without the terminator).
+#[repr(C)]
+pub struct SigAlignHit {
+    pub ref_index: u32,
+    pub query_start: u32,
+    pub query_end: u32,
+    pub target_start: u32,
+    pub target_end: u32,
+    pub penalty: u32,
+    pub length: u32,
+    pub cigar_ptr: *mut c_char,
+    pub cigar_len: usize,
+}
+
+/// Flat array of alignments.
+#[repr(C)]
+pub struct SigAlignResult {
+    pub hits: *mut SigAlignHit,
+    pub hit_count: usize,
+}
+
+/* Reference */
+/// Build a reference from FASTA formatted bytes.
+/// Returns null if the bytes can not be parsed or no target is given.
+///
+/// # Safety
+/// `fasta_ptr` must be valid for reads of `fasta_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_reference_new_from_fasta(
+    fasta_ptr: *const u8,
+    fasta_len: usize,
+) -> *mut SigAlignReference {
+    if fasta_ptr.is_null() {
+        return std::ptr::null_mut()
+    }
+    let fasta = std::slice::from_raw_parts(fasta_ptr, fasta_len);
+    null_on_panic(|| {
+        let reference = ReferenceBuilder::new()
+            .add_fasta(fasta)
+            .and_then(|builder| builder.build());
+        match reference {
+            Ok(reference) => Box::into_raw(Box::new(SigAlignReference(reference))),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
+}
+/// Free the reference.
+///
+/// # Safety
+/// `reference` must be null or returned from `sigalign_reference_new_from_fasta`, and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_reference_free(reference: *mut SigAlignReference) {
+    if !reference.is_null() {
+        drop(Box::from_raw(reference));
+    }
+}
+
+/* Aligner */
+/// Create a local aligner. Returns null if the parameters are invalid.
+#[no_mangle]
+pub extern "C" fn sigalign_local_aligner_new(
+    mismatch_penalty: u32,
+    gap_open_penalty: u32,
+    gap_extend_penalty: u32,
+    minimum_length: u32,
+    maximum_penalty_per_length: f32,
+) -> *mut SigAlignAligner {
+    null_on_panic(|| {
+        match Local::new(mismatch_penalty, gap_open_penalty, gap_extend_penalty, minimum_length, maximum_penalty_per_length) {
+            Ok(algorithm) => Box::into_raw(Box::new(
+                SigAlignAligner(AlignerKind::Local(Aligner::new(algorithm)))
+            )),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
+}
+/// Create a semi-global aligner. Returns null if the parameters are invalid.
+#[no_mangle]
+pub extern "C" fn sigalign_semi_global_aligner_new(
+    mismatch_penalty: u32,
+    gap_open_penalty: u32,
+    gap_extend_penalty: u32,
+    minimum_length: u32,
+    maximum_penalty_per_length: f32,
+) -> *mut SigAlignAligner {
+    null_on_panic(|| {
+        match SemiGlobal::new(mismatch_penalty, gap_open_penalty, gap_extend_penalty, minimum_length, maximum_penalty_per_length) {
+            Ok(algorithm) => Box::into_raw(Box::new(
+                SigAlignAligner(AlignerKind::SemiGlobal(Aligner::new(algorithm)))
+            )),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
+}
+/// Free the aligner.
+///
+/// # Safety
+/// `aligner` must be null or returned from `sigalign_*_aligner_new`, and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_aligner_free(aligner: *mut SigAlignAligner) {
+    if !aligner.is_null() {
+        drop(Box::from_raw(aligner));
+    }
+}
+
+/* Alignment */
+/// Align the query to the reference.
+/// Returns null if any pointer is null, or the query is rejected by `Aligner::try_align`
+/// (empty, not ASCII letters, or longer than the maximum query length).
+/// After null is returned for a failure during the alignment, the aligner should be freed.
+///
+/// # Safety
+/// - `aligner` and `reference` must be valid pointers returned from this API.
+/// - `query_ptr` must be valid for reads of `query_len` bytes.
+/// - `aligner` must not be used from other threads during the call.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_align(
+    aligner: *mut SigAlignAligner,
+    reference: *const SigAlignReference,
+    query_ptr: *const u8,
+    query_len: usize,
+) -> *mut SigAlignResult {
+    if aligner.is_null() || reference.is_null() || query_ptr.is_null() {
+        return std::ptr::null_mut()
+    }
+    let aligner = &mut (*aligner).0;
+    let reference = &(*reference).0;
+    let query = std::slice::from_raw_parts(query_ptr, query_len);
+
+    null_on_panic(|| {
+        let query_alignment = match aligner {
+            AlignerKind::Local(v) => v.try_align(query, reference),
+            AlignerKind::SemiGlobal(v) => v.try_align(query, reference),
+        };
+        match query_alignment {
+            Ok(query_alignment) => Box::into_raw(Box::new(SigAlignResult::from(query_alignment))),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
+}
+/// Free the result and all CIGAR strings in it.
+///
+/// # Safety
+/// `result` must be null or returned from `sigalign_align`, and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_free_result(result: *mut SigAlignResult) {
+    if result.is_null() {
+        return
+    }
+    let result = Box::from_raw(result);
+    let hits = Box::from_raw(std::ptr::slice_from_raw_parts_mut(result.hits, result.hit_count));
+    hits.iter().for_each(|hit| {
+        drop(CString::from_raw(hit.cigar_ptr));
+    });
+}
+
+// Unwinding across `extern "C"` is undefined (or aborts), so the panic is returned as null
+fn null_on_panic<T, F: FnOnce() -> *mut T>(f: F) -> *mut T {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(std::ptr::null_mut())
+}
+
+impl From<QueryAlignment> for SigAlignResult {
+    fn from(query_alignment: QueryAlignment) -> Self {
+        let hits: Box<[SigAlignHit]> = query_alignment.0.into_iter().flat_map(|target_alignment| {
+            let ref_index = target_alignment.index;
+            target_alignment.alignments.into_iter().map(move |alignment| {
+                let cigar = alignment.to_cigar(true);
+                let cigar_len = cigar.len();
+                // CIGAR never contains a nul byte
+                let cigar_ptr = CString::new(cigar).unwrap().into_raw();
+                SigAlignHit {
+                    ref_index,
+                    query_start: alignment.position.query.0,
+                    query_end: alignment.position.query.1,
+                    target_start: alignment.position.target.0,
+                    target_end: alignment.position.target.1,
+                    penalty: alignment.penalty,
+                    length: alignment.length,
+                    cigar_ptr,
+                    cigar_len,
+                }
+            })
+        }).collect();
+        let hit_count = hits.len();
+        Self {
+            hits: Box::into_raw(hits) as *mut SigAlignHit,
+            hit_count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn align_with_capi() {
+        let fasta = b">target_1\nACACAGATCGCAAACTCACAATTGTATTTCTTTGCCACCTGGGCATATACTTTTTGCGCCCCCTCATTTA\n";
+        let query = b"CAAACTCACAATTGTATTTCTTTGCCAGCTGGGCATATACTTTTTCCGCCCCCTCATTTA";
+        unsafe {
+            let reference = sigalign_reference_new_from_fasta(fasta.as_ptr(), fasta.len());
+            assert!(!reference.is_null());
+            let aligner = sigalign_local_aligner_new(4, 6, 2, 50, 0.2);
+            assert!(!aligner.is_null());
+
+            let result = sigalign_align(aligner, reference, query.as_ptr(), query.len());
+            let hits = std::slice::from_raw_parts((*result).hits, (*result).hit_count);
+            assert!(!hits.is_empty());
+            for hit in hits {
+                let cigar = std::ffi::CStr::from_ptr(hit.cigar_ptr).to_str().unwrap();
+                assert_eq!(cigar.len(), hit.cigar_len);
+            }
+
+            sigalign_free_result(result);
+            // Invalid queries are not aligned
+            let invalid_query = b"ACGT ACGT";
+            assert!(sigalign_align(aligner, reference, invalid_query.as_ptr(), invalid_query.len()).is_null());
+            assert!(sigalign_align(aligner, reference, query.as_ptr(), 0).is_null());
+            sigalign_aligner_free(aligner);
+            sigalign_reference_free(reference);
+        }
+    }
+    #[test]
+    fn panic_is_returned_as_null() {
+        let result: *mut SigAlignResult = null_on_panic(|| panic!("broken invariant"));
+        assert!(result.is_null());
+    }
+}
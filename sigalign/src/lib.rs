@@ -82,18 +82,34 @@ pub mod results;
 mod reference;
 pub use reference::{
     Reference,
+    ReferenceSet,
     ReferenceBuilder,
     ReferenceBuildError,
     ReferenceLoadError,
+    ReferenceStats,
 };
 
 mod aligner;
 pub use aligner::{
     Aligner,
+    QueryError,
+    AlignmentError,
+    WorkEstimate,
     algorithms,
 };
 
 pub mod utils;
+pub mod blast_tab;
+
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "noodles")]
+pub mod bam;
+
+#[cfg(test)]
+use sigalign_core::test_utils;
 
 
 #[cfg(test)]
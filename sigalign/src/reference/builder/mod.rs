@@ -2,26 +2,46 @@ use std::{io::Read, fs::File};
 
 use thiserror::Error;
 
-use sigalign_core::reference::Reference as RawReference;
+use sigalign_core::reference::{
+    Reference as RawReference,
+    SequenceStorage as _,
+};
 use sigalign_impl::{
     pattern_index::dynamic_lfi::{
         DynamicLfiOption, LfiBuildError,
     },
     sequence_storage::in_memory::InMemoryStorage,
 };
+use sigalign_utils::sequence_reader::decompress::MaybeGzipReader;
 use super::Reference;
 
 /// Builder for `Reference`.
 /// 
 /// - Default configuration:
-///   - Uppercase: false
-///      - Reference treats uppercase and lowercase letters as different bases.
+///   - Uppercase: true
+///      - Lowercase letters (e.g., soft-masked regions) are matched to uppercase letters.
+///      - If false, reference treats uppercase and lowercase letters as different bases.
 ///   - Ignore bases: None
 ///      - Reference treats all characters as bases.
 pub struct ReferenceBuilder {
     uppercase: bool,
     to_ignore_bases: Vec<u8>,
     sequence_storage: InMemoryStorage,
+    circular_target_lengths: Vec<(u32, u32)>,
+}
+
+impl Reference {
+    /// Build `Reference` from FASTA with the default configuration of `ReferenceBuilder`.
+    pub fn from_fasta<R: Read>(reader: R) -> Result<Self, ReferenceBuildError> {
+        ReferenceBuilder::new().add_fasta(reader)?.build()
+    }
+    /// Build `Reference` from FASTA file with the default configuration of `ReferenceBuilder`.
+    ///  - Gzip compressed file is decompressed transparently.
+    pub fn from_fasta_file<P>(path: P) -> Result<Self, ReferenceBuildError> where
+        P: AsRef<std::path::Path> + std::fmt::Debug,
+    {
+        ReferenceBuilder::new().add_fasta_file(path)?.build()
+    }
 }
 
 /// Error for building `Reference`.
@@ -44,6 +64,7 @@ impl ReferenceBuilder {
             uppercase: true,
             to_ignore_bases: Vec::new(),
             sequence_storage: InMemoryStorage::new(),
+            circular_target_lengths: Vec::new(),
         }
     }
     /* Configuration */
@@ -72,20 +93,35 @@ impl ReferenceBuilder {
         self.sequence_storage.add_target(label, sequence);
         self
     }
+    /// Add a circular target (e.g., plasmid or mitochondrial genome).
+    ///  - Alignments spanning the origin are reported with the end position lower than the start position.
+    pub fn add_circular_target(mut self, label: &str, sequence: &[u8]) -> Self {
+        let target_index = self.sequence_storage.num_targets();
+        let length = sequence.len();
+        let wrapped_sequence = [sequence, &sequence[..length.saturating_sub(1)]].concat();
+        self.sequence_storage.add_target(label, &wrapped_sequence);
+        self.circular_target_lengths.push((target_index, length as u32));
+        self
+    }
     pub fn add_fasta<R: Read>(mut self, reader: R) -> Result<Self, ReferenceBuildError> {
         self.sequence_storage.add_fasta(reader).map_err(|_| ReferenceBuildError::invalid_fasta_record())?;
         Ok(self)
     }
+    /// Add targets from FASTA file. Gzip compressed file is decompressed transparently.
     pub fn add_fasta_file<P>(mut self, path: P) -> Result<Self, ReferenceBuildError> where
         P: AsRef<std::path::Path> + std::fmt::Debug,
     {
-        let file = File::open(path)?;
-        self.sequence_storage.add_fasta(file).map_err(|_| ReferenceBuildError::invalid_fasta_record())?;
+        let reader = MaybeGzipReader::new(File::open(path)?)?;
+        self.sequence_storage.add_fasta(reader).map_err(|_| ReferenceBuildError::invalid_fasta_record())?;
         Ok(self)
     }
 
     /// Finish building `Reference`.
+    ///  - Error if no base is added.
     pub fn build(mut self) -> Result<Reference, ReferenceBuildError> {
+        if self.sequence_storage.get_total_length() == 0 {
+            return Err(ReferenceBuildError::EmptySequence);
+        }
         // Sequence Storage
         if self.uppercase {
             self.sequence_storage.set_sequences_to_uppercase()
@@ -100,7 +136,9 @@ impl ReferenceBuilder {
             self.sequence_storage,
             dynamic_lfi_option,
         )?;
-        Ok(Reference::from(raw_reference))
+        let mut reference = Reference::from(raw_reference);
+        reference.circular_target_lengths = self.circular_target_lengths;
+        Ok(reference)
     }
 
     fn get_option_for_dynamic_lfi(sequence_storage: &InMemoryStorage) -> DynamicLfiOption {
@@ -0,0 +1,107 @@
+/*!
+Circular targets (e.g., plasmids and mitochondrial genomes).
+
+A circular target of length `L` is stored with its first `L - 1` bases appended (`S + S[..L-1]`),
+so that the alignments spanning the origin are found as usual.
+The alignments are then resolved to the original coordinates:
+  - Alignments starting in the appended part are duplicates and removed.
+  - The end position over `L` is wrapped to `end - L`.
+    Therefore, the alignment crossing the origin has the end position lower than the start position.
+*/
+use crate::results::QueryAlignment;
+use super::Reference;
+
+impl Reference {
+    /// Check if the target is circular.
+    pub fn is_circular(&self, target_index: u32) -> bool {
+        self.get_circular_length(target_index).is_some()
+    }
+    /// Get the original length of the circular target. None if the target is linear.
+    pub(super) fn get_circular_length(&self, target_index: u32) -> Option<u32> {
+        self.circular_target_lengths.binary_search_by_key(&target_index, |(index, _)| *index)
+            .ok()
+            .map(|position| self.circular_target_lengths[position].1)
+    }
+    pub(crate) fn has_circular_targets(&self) -> bool {
+        !self.circular_target_lengths.is_empty()
+    }
+    /// Remove the duplicated alignments in the appended part of circular targets.
+    pub(crate) fn remove_duplicated_circular_alignments(&self, query_alignment: &mut QueryAlignment) {
+        query_alignment.0.iter_mut().for_each(|target_alignment| {
+            if let Some(length) = self.get_circular_length(target_alignment.index) {
+                target_alignment.alignments.retain(|alignment| alignment.position.target.0 < length);
+            }
+        });
+        query_alignment.0.retain(|target_alignment| !target_alignment.alignments.is_empty());
+    }
+    /// Wrap the end positions of the alignments crossing the origin of circular targets.
+    pub(crate) fn wrap_circular_positions(&self, query_alignment: &mut QueryAlignment) {
+        query_alignment.0.iter_mut().for_each(|target_alignment| {
+            if let Some(length) = self.get_circular_length(target_alignment.index) {
+                target_alignment.alignments.iter_mut().for_each(|alignment| {
+                    if alignment.position.target.1 > length {
+                        alignment.position.target.1 -= length;
+                    }
+                });
+            }
+        });
+    }
+    // Encode (target index, length) pairs to bytes to be saved
+    pub(super) fn encode_circular_target_lengths(&self) -> Vec<u8> {
+        self.circular_target_lengths.iter().flat_map(|(index, length)| {
+            index.to_le_bytes().into_iter().chain(length.to_le_bytes())
+        }).collect()
+    }
+    pub(super) fn decode_circular_target_lengths(bytes: &[u8]) -> Vec<(u32, u32)> {
+        bytes.chunks_exact(8).map(|chunk| {
+            (
+                u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]),
+                u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]),
+            )
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Aligner,
+        ReferenceBuilder,
+        algorithms::SemiGlobal,
+    };
+    use crate::test_utils::TEST_TARGET;
+
+    #[test]
+    fn alignment_across_the_origin_of_circular_target() {
+        let unit = TEST_TARGET;
+        let circular = [&unit[..], b"TCTGGGGCCATTGTATTTCTTTGCCAGCTGGGGCATATACTTTTTCCGCCCCCTCATTTACGCTCATCAC"].concat();
+        let reference = ReferenceBuilder::new()
+            .add_target("linear", &circular)
+            .add_circular_target("circular", &circular)
+            .build().unwrap();
+        assert!(!reference.is_circular(0));
+        assert!(reference.is_circular(1));
+        assert_eq!(reference.get_sequence(1).unwrap(), circular);
+
+        // Query straddling position 0
+        let query = [&circular[100..], &circular[..40]].concat();
+        let mut aligner = Aligner::new(SemiGlobal::new(4, 6, 2, 50, 0.2).unwrap());
+        let result = aligner.align(&query, &reference);
+        assert_eq!(result.0.len(), 1);
+        assert_eq!(result.0[0].index, 1);
+        assert_eq!(result.0[0].alignments.len(), 1);
+        let alignment = &result.0[0].alignments[0];
+        assert_eq!(alignment.penalty, 0);
+        assert_eq!(alignment.position.target, (100, 40));
+        assert_eq!(aligner.get_aligned_sequences(&query, &reference, &result)[0][0].target, query);
+
+        // Alignments not crossing the origin are reported once
+        let query = &circular[10..100];
+        let result = aligner.align(query, &reference);
+        assert_eq!(result.0.len(), 2);
+        result.0.iter().for_each(|target_alignment| {
+            assert_eq!(target_alignment.alignments.len(), 1);
+            assert_eq!(target_alignment.alignments[0].position.target, (10, 100));
+        });
+    }
+}
@@ -8,6 +8,7 @@ impl Debug for Reference {
         f.debug_struct("Reference")
             .field("num_targets", &self.as_ref().num_targets())
             .field("estimated_size_in_byte", &self.as_ref().serialized_size())
+            .field("num_circular_targets", &self.circular_target_lengths.len())
             .finish()
     }
 }
@@ -21,7 +21,8 @@ impl Reference {
     {
         let signature = Self::get_base64_encoded_signature_of_current_version();
         signature.as_bytes().save_to(&mut writer)?;
-        self.raw_reference.save_to(writer)?;
+        self.raw_reference.save_to(&mut writer)?;
+        self.encode_circular_target_lengths().save_to(writer)?;
         Ok(())
     }
     /// Load `Reference` from a reader.
@@ -32,8 +33,16 @@ impl Reference {
         let encoded_signature: Vec<u8> = Vec::load_from(&mut reader)?;
         let signatures = Self::get_base64_decoded_signature(&encoded_signature)?;
         if signatures[0] == PREFIX && signatures[1] == LOWEST_COMPARABLE_WRAPPER_VERSION && signatures[2] == CORE_VERSION {
-            let raw_reference = RawReference::load_from(reader)?;
-            Ok(Self::from(raw_reference))
+            let raw_reference = RawReference::load_from(&mut reader)?;
+            let mut reference = Self::from(raw_reference);
+            // Files saved without circular targets end here
+            let encoded_circular_target_lengths: Vec<u8> = match Vec::load_from(reader) {
+                Ok(v) => v,
+                Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => Vec::new(),
+                Err(err) => return Err(err.into()),
+            };
+            reference.circular_target_lengths = Self::decode_circular_target_lengths(&encoded_circular_target_lengths);
+            Ok(reference)
         } else {
             Err(ReferenceLoadError::IncompatibleVersion(signatures[1].clone()))
         }
@@ -1,5 +1,7 @@
 use sigalign_core::reference::{
     Reference as RawReference,
+    SequenceStorage as _,
+    SequenceBuffer as _,
     extensions::EstimateSize as _,
 };
 use sigalign_impl::{
@@ -8,6 +10,7 @@ use sigalign_impl::{
 };
 use crate::results::{
     QueryAlignment, TargetAlignment, LabeledQueryAlignment, LabeledTargetAlignment,
+    SpanningAlignment, take_spanning_alignments,
 };
 
 mod io;
@@ -15,13 +18,21 @@ pub use io::ReferenceLoadError;
 mod debug;
 mod builder;
 pub use builder::{ReferenceBuilder, ReferenceBuildError};
+mod circular;
+mod set;
+pub use set::ReferenceSet;
+mod stats;
+pub use stats::ReferenceStats;
 
 pub type DefaultSequenceBuffer = InMemoryBuffer;
+pub type DefaultPatternIndex = DynamicLfi;
 /// A database for multiple target sequences.
 #[derive(Clone)]
 pub struct Reference {
     raw_reference: RawReference<DynamicLfi, InMemoryStorage>,
     full_sorted_target_indices: Vec<u32>,
+    // (target index, original length) of circular targets sorted by index
+    circular_target_lengths: Vec<(u32, u32)>,
 }
 
 impl AsRef<RawReference<DynamicLfi, InMemoryStorage>> for Reference {
@@ -34,7 +45,18 @@ impl Reference {
     /* Get Information */
     /// Get the sequence of the target. None if the target index is out of range.
     pub fn get_sequence(&self, target_index: u32) -> Option<Vec<u8>> {
-        self.as_ref().get_sequence_storage().get_sequence_safely(target_index)
+        let mut sequence = self.as_ref().get_sequence_storage().get_sequence_safely(target_index)?;
+        if let Some(length) = self.get_circular_length(target_index) {
+            sequence.truncate(length as usize);
+        }
+        Some(sequence)
+    }
+    /// Get the length of the target (original length for the circular target). None if the target index is out of range.
+    pub fn get_length(&self, target_index: u32) -> Option<u32> {
+        match self.get_circular_length(target_index) {
+            Some(length) => Some(length),
+            None => self.as_ref().get_sequence_storage().get_length_safely(target_index),
+        }
     }
     /// Get the label of the target. None if the target index is out of range.
     pub fn get_label(&self, target_index: u32) -> Option<String> {
@@ -48,6 +70,18 @@ impl Reference {
     pub fn get_total_length(&self) -> u32 {
         self.as_ref().get_sequence_storage().get_total_length()
     }
+    /// Get the sorted unique bases of all targets.
+    ///  - The bases set to be ignored in `ReferenceBuilder` are shown as `?`.
+    pub fn get_alphabet(&self) -> Vec<u8> {
+        let sequence_storage = self.as_ref().get_sequence_storage();
+        let mut buffer = sequence_storage.get_buffer();
+        let mut table = [false; 256];
+        (0..self.get_num_targets()).for_each(|target_index| {
+            sequence_storage.fill_buffer(target_index, &mut buffer);
+            buffer.buffered_sequence().iter().for_each(|&base| table[base as usize] = true);
+        });
+        (0..=255u8).filter(|&base| table[base as usize]).collect()
+    }
     /// Get estimated size in bytes. (This is an estimate, not the exact size.)
     pub fn get_estimated_size_in_bytes(&self) -> usize {
         self.as_ref().serialized_size()
@@ -58,27 +92,71 @@ impl Reference {
     pub fn get_sequence_buffer() -> InMemoryBuffer {
         InMemoryBuffer::new()
     }
+    /// Get the pattern index (FM-index) of the targets for advanced usages.
+    pub fn get_pattern_index(&self) -> &DefaultPatternIndex {
+        self.as_ref().get_pattern_index()
+    }
     /// Get the full sorted target indices
     pub fn get_full_sorted_target_indices(&self) -> &[u32] {
         &self.full_sorted_target_indices
     }
 
+    /* Screen Targets */
+    /// Get the fraction of the query's k-mers that are found in each target.
+    ///  - All overlapping k-mers of the query are counted.
+    ///  - Only the targets containing at least one k-mer are returned, in order of target index.
+    ///  - This only looks up the index without alignment, so it can be used to skip unrelated targets.
+    pub fn kmer_containment(&self, query: &[u8], kmer_size: u32) -> Vec<(u32, f32)> {
+        let kmer_size = kmer_size as usize;
+        if kmer_size == 0 || query.len() < kmer_size {
+            return Vec::new();
+        }
+        let kmer_count = query.len() - kmer_size + 1;
+        let mut found_count_by_target = vec![0_u32; self.get_num_targets() as usize];
+        query.windows(kmer_size).for_each(|kmer| {
+            self.as_ref().locate_pattern(kmer, &self.full_sorted_target_indices).into_iter().for_each(|pattern_location| {
+                found_count_by_target[pattern_location.target_index as usize] += 1;
+            });
+        });
+        found_count_by_target.into_iter().enumerate().filter_map(|(target_index, found_count)| {
+            if found_count == 0 {
+                None
+            } else {
+                Some((target_index as u32, found_count as f32 / kmer_count as f32))
+            }
+        }).collect()
+    }
+
     /* Manipulate Results */
-    /// Label the query alignment.
+    /// Label the query alignment with the labels and the lengths of the targets.
     pub fn label_query_alignment(&self, query_alignment: QueryAlignment) -> LabeledQueryAlignment {
         let labeled_target_alignments = query_alignment.0.into_iter().map(|x| {
             self.label_target_alignment(x)
         }).collect();
         LabeledQueryAlignment(labeled_target_alignments)
     }
+    /// Merge the alignments across the junctions of the adjacent targets (e.g., split contigs).
+    ///  - `adjacent_targets`: (left, right) target indices, where the end of the left is contiguous with the start of the right.
+    ///  - An alignment reaching the end of the left target and an alignment starting at the start of the right target
+    ///    are merged if the query ranges are contiguous.
+    ///  - The merged alignments are removed from the `query_alignment` and returned.
+    pub fn merge_alignments_of_adjacent_targets(
+        &self,
+        query_alignment: &mut QueryAlignment,
+        adjacent_targets: &[(u32, u32)],
+    ) -> Vec<SpanningAlignment> {
+        take_spanning_alignments(query_alignment, adjacent_targets, |target_index| self.get_length(target_index))
+    }
     /// Label the target alignment.
     #[inline]
     pub fn label_target_alignment(&self, target_alignment: TargetAlignment) -> LabeledTargetAlignment {
         let target_index = target_alignment.index;
         let label = self.get_label(target_index).unwrap_or_else(|| target_index.to_string());
+        let length = self.get_length(target_index).unwrap_or(0);
         LabeledTargetAlignment {
             index: target_index,
             label,
+            length,
             alignments: target_alignment.alignments,
         }
     }
@@ -95,6 +173,141 @@ impl From<RawReference<DynamicLfi, InMemoryStorage>> for Reference {
         Self {
             raw_reference,
             full_sorted_target_indices,
+            circular_target_lengths: Vec::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Reference, ReferenceBuilder};
+    use crate::test_utils::{TEST_TARGET, SEED, random_dna};
+
+    #[test]
+    fn kmer_containment_is_fraction_of_found_kmers() {
+        let unit = TEST_TARGET;
+        let reference = ReferenceBuilder::new()
+            .add_target("half", &unit[..35])
+            .add_target("unrelated", b"GGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGGG")
+            .add_target("full", unit)
+            .build().unwrap();
+
+        let containment = reference.kmer_containment(&unit[..45], 10);
+        assert_eq!(containment.len(), 2);
+        assert_eq!(containment[0].0, 0);
+        assert!((containment[0].1 - 26.0 / 36.0).abs() < f32::EPSILON);
+        assert_eq!(containment[1], (2, 1.0));
+
+        assert!(reference.kmer_containment(&unit[..5], 10).is_empty());
+        assert!(reference.kmer_containment(unit, 0).is_empty());
+    }
+
+    #[test]
+    fn stats_summarize_the_targets() {
+        let unit = TEST_TARGET;
+        let reference = ReferenceBuilder::new()
+            .add_target("unit", unit)
+            .add_target("repeats", &[&unit[..30], &unit[..30], b"NNNNNNNNNN"].concat())
+            .add_circular_target("circular", b"GGGGCCCCAAAATTTTGGCC")
+            .build().unwrap();
+        let stats = reference.stats(10);
+        assert_eq!(stats.target_count, 3);
+        assert_eq!(stats.total_length, 160);
+        assert!((stats.n_content - 10.0 / 160.0).abs() < f32::EPSILON);
+        let gc_count = [&unit[..], &unit[..30], &unit[..30]].concat().iter().filter(|v| matches!(v, b'G' | b'C')).count() + 12;
+        assert!((stats.gc_content - gc_count as f32 / 150.0).abs() < 1e-6);
+        // Patterns with N are not sampled: 61 + (61 - 10) + 11
+        assert_eq!(stats.sampled_pattern_count, 123);
+        // Patterns in the first 30 bases of the unit are located three times (21 in each copy)
+        assert!((stats.unique_pattern_fraction - (123 - 21 * 3) as f32 / 123.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn circular_targets_are_encoded_to_be_saved() {
+        let unit = TEST_TARGET;
+        let reference = ReferenceBuilder::new()
+            .add_target("linear", unit)
+            .add_circular_target("circular_1", unit)
+            .add_target("linear", unit)
+            .add_circular_target("circular_2", &unit[..50])
+            .build().unwrap();
+        assert_eq!(reference.circular_target_lengths, vec![(1, 70), (3, 50)]);
+
+        let encoded = reference.encode_circular_target_lengths();
+        assert_eq!(Reference::decode_circular_target_lengths(&encoded), reference.circular_target_lengths);
+        assert!(Reference::decode_circular_target_lengths(&[]).is_empty());
+    }
+
+    #[test]
+    fn labeled_alignments_have_the_lengths_of_targets() {
+        use crate::{Aligner, algorithms::Local, results::LabeledQueryAlignment};
+
+        let unit = TEST_TARGET;
+        let reference = ReferenceBuilder::new()
+            .add_target("linear", &[&unit[..], b"GGGGGGGGGG"].concat())
+            .add_circular_target("circular", unit)
+            .build().unwrap();
+        assert_eq!(reference.get_length(0), Some(80));
+        assert_eq!(reference.get_length(1), Some(70));
+        assert_eq!(reference.get_length(2), None);
+
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        let labeled = reference.label_query_alignment(aligner.align(unit, &reference));
+        let metadata: Vec<_> = labeled.0.iter().map(|v| (v.index, v.label.as_str(), v.length)).collect();
+        assert_eq!(metadata, vec![(0, "linear", 80), (1, "circular", 70)]);
+
+        // Results serialized without the length can be read
+        let json = labeled.to_json().replace(",\"length\":80", "");
+        assert_eq!(LabeledQueryAlignment::from_json(&json).unwrap().0[0].length, 0);
+    }
+
+    #[test]
+    fn alignments_are_merged_across_adjacent_targets() {
+        use crate::{Aligner, algorithms::Local};
+
+        let unit = TEST_TARGET;
+        // Random-like sequence
+        let random = random_dna(SEED, 70);
+        let query = [&unit[..], &random[..]].concat();
+        let mut contig_2 = random.clone();
+        contig_2[10] = if contig_2[10] == b'A' { b'C' } else { b'A' };
+        let reference = ReferenceBuilder::new()
+            .add_target("contig_1", unit)
+            .add_target("contig_2", &contig_2)
+            .build().unwrap();
+        let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.2).unwrap());
+        let mut query_alignment = aligner.align(&query, &reference);
+
+        // Not adjacent in this order
+        assert!(reference.merge_alignments_of_adjacent_targets(&mut query_alignment, &[(1, 0)]).is_empty());
+        let alignment_count: usize = query_alignment.0.iter().map(|v| v.alignments.len()).sum();
+        let spanning = reference.merge_alignments_of_adjacent_targets(&mut query_alignment, &[(0, 1)]);
+        assert_eq!(spanning.len(), 1);
+        let spanning = &spanning[0];
+        assert_eq!((spanning.left_target_index, spanning.right_target_index), (0, 1));
+        assert_eq!(spanning.query_range(), (0, 140));
+        assert_eq!(spanning.penalty, 4);
+        assert_eq!(spanning.length, 140);
+        assert_eq!(spanning.to_cigar(true), "70=0N10=1X59=");
+        assert_eq!(spanning.to_cigar(false), "70M0N70M");
+        let remained_count: usize = query_alignment.0.iter().map(|v| v.alignments.len()).sum();
+        assert_eq!(remained_count, alignment_count - 2);
+    }
+
+    #[test]
+    fn reference_is_built_from_fasta_with_its_alphabet() {
+        use sigalign_core::reference::PatternIndex as _;
+
+        let fasta = b">target_1 description\nACGTacgt\nACGT\n>target_2\nGGGGNNNNCCCC\n";
+        let reference = Reference::from_fasta(&fasta[..]).unwrap();
+        assert_eq!(reference.get_num_targets(), 2);
+        assert_eq!(reference.get_label(0).unwrap(), "target_1");
+        assert_eq!(reference.get_length(0), Some(12));
+        assert_eq!(reference.get_length(1), Some(12));
+        assert_eq!(reference.get_alphabet(), b"ACGNT".to_vec());
+        assert_eq!(reference.get_pattern_index().get_sorted_positions(b"GGGGN"), vec![12]);
+
+        let reference = ReferenceBuilder::new().ignore_base(b'N').add_fasta(&fasta[..]).unwrap().build().unwrap();
+        assert_eq!(reference.get_alphabet(), b"?ACGT".to_vec());
+    }
+}
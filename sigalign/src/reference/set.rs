@@ -0,0 +1,94 @@
+use super::Reference;
+use crate::results::{
+    QueryAlignment, LabeledQueryAlignment, LabeledTargetAlignment,
+};
+
+/// A collection of references aligned in sequence, as if they were one reference.
+///  - A reference can be pushed without rebuilding the others (e.g., when new contigs are assembled).
+///  - The targets are indexed globally: the targets of each pushed reference follow those of the previous ones.
+///  - Since each reference is searched separately, the alignment is slower than one reference with all targets.
+///    Rebuild to one reference when the set has grown large.
+#[derive(Debug, Clone, Default)]
+pub struct ReferenceSet {
+    references: Vec<Reference>,
+    // Global index of the first target of each reference
+    target_index_offsets: Vec<u32>,
+    num_targets: u32,
+}
+
+impl ReferenceSet {
+    /// Make an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Append a reference. Its targets are indexed from the current number of targets.
+    pub fn push(&mut self, reference: Reference) {
+        self.target_index_offsets.push(self.num_targets);
+        self.num_targets += reference.get_num_targets();
+        self.references.push(reference);
+    }
+    /// Get the references in order of the pushes.
+    pub fn get_references(&self) -> &[Reference] {
+        &self.references
+    }
+    /// Get the number of targets in all references.
+    pub fn get_num_targets(&self) -> u32 {
+        self.num_targets
+    }
+    /// Get the total length of all targets (in base pairs).
+    pub fn get_total_length(&self) -> u32 {
+        self.references.iter().map(|v| v.get_total_length()).sum()
+    }
+    /// Get the sequence of the target by global index. None if the target index is out of range.
+    pub fn get_sequence(&self, target_index: u32) -> Option<Vec<u8>> {
+        let (reference, local_index) = self.locate_target(target_index)?;
+        reference.get_sequence(local_index)
+    }
+    /// Get the length of the target by global index. None if the target index is out of range.
+    pub fn get_length(&self, target_index: u32) -> Option<u32> {
+        let (reference, local_index) = self.locate_target(target_index)?;
+        reference.get_length(local_index)
+    }
+    /// Get the label of the target by global index. None if the target index is out of range.
+    pub fn get_label(&self, target_index: u32) -> Option<String> {
+        let (reference, local_index) = self.locate_target(target_index)?;
+        reference.get_label(local_index)
+    }
+    /// Label the query alignment having the global target indices.
+    pub fn label_query_alignment(&self, query_alignment: QueryAlignment) -> LabeledQueryAlignment {
+        let labeled_target_alignments = query_alignment.0.into_iter().map(|x| {
+            let label = self.get_label(x.index).unwrap_or_else(|| x.index.to_string());
+            let length = self.get_length(x.index).unwrap_or(0);
+            LabeledTargetAlignment {
+                index: x.index,
+                label,
+                length,
+                alignments: x.alignments,
+            }
+        }).collect();
+        LabeledQueryAlignment(labeled_target_alignments)
+    }
+    // (reference, index in the reference) of the global target index
+    fn locate_target(&self, target_index: u32) -> Option<(&Reference, u32)> {
+        if target_index >= self.num_targets {
+            return None;
+        }
+        // Index of the last reference starting at or before the target
+        let reference_index = self.target_index_offsets.partition_point(|v| *v <= target_index) - 1;
+        Some((
+            &self.references[reference_index],
+            target_index - self.target_index_offsets[reference_index],
+        ))
+    }
+    pub(crate) fn iter_with_offsets(&self) -> impl Iterator<Item = (&Reference, u32)> {
+        self.references.iter().zip(self.target_index_offsets.iter().copied())
+    }
+}
+
+impl From<Vec<Reference>> for ReferenceSet {
+    fn from(references: Vec<Reference>) -> Self {
+        let mut reference_set = Self::new();
+        references.into_iter().for_each(|reference| reference_set.push(reference));
+        reference_set
+    }
+}
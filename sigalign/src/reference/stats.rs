@@ -0,0 +1,96 @@
+use super::Reference;
+
+// Patterns to locate for the uniqueness (one more for each target at most)
+const MAXIMUM_SAMPLED_PATTERN_COUNT: u64 = 10_000;
+
+/// Summary of the targets of a reference (see `Reference::stats`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReferenceStats {
+    /// Number of targets.
+    pub target_count: u32,
+    /// Total length of the targets (the original length for the circular targets).
+    pub total_length: u64,
+    /// Fraction of G and C in the A, C, G, and T bases (case-insensitive).
+    pub gc_content: f32,
+    /// Fraction of N in all bases.
+    pub n_content: f32,
+    /// Number of the patterns sampled to estimate the uniqueness.
+    pub sampled_pattern_count: u32,
+    /// Fraction of the sampled patterns located at exactly one position in all targets.
+    ///  - The anchors of the unique patterns point to one place, so the lower fraction means more ambiguous alignments
+    ///    and more anchors to extend.
+    pub unique_pattern_fraction: f32,
+}
+
+impl Reference {
+    /// Get the summary of the targets to predict the alignment before aligning any query.
+    ///  - `pattern_size` is the pattern size of the aligner (`Aligner::get_pattern_size` in the `debug` feature).
+    ///  - The uniqueness is estimated by locating about 10,000 patterns evenly sampled from the targets.
+    ///    The patterns with N are not sampled, and the locations in the appended part of the circular targets are ignored.
+    pub fn stats(&self, pattern_size: u32) -> ReferenceStats {
+        let target_count = self.get_num_targets();
+        let mut stats = ReferenceStats {
+            target_count,
+            ..Default::default()
+        };
+        let (mut gc_count, mut acgt_count, mut n_count) = (0_u64, 0_u64, 0_u64);
+        let sequences: Vec<Vec<u8>> = (0..target_count).filter_map(|target_index| self.get_sequence(target_index)).collect();
+        sequences.iter().for_each(|sequence| {
+            stats.total_length += sequence.len() as u64;
+            sequence.iter().for_each(|base| match base.to_ascii_uppercase() {
+                b'G' | b'C' => {
+                    gc_count += 1;
+                    acgt_count += 1;
+                },
+                b'A' | b'T' => acgt_count += 1,
+                b'N' => n_count += 1,
+                _ => {},
+            });
+        });
+        if acgt_count != 0 {
+            stats.gc_content = gc_count as f32 / acgt_count as f32;
+        }
+        if stats.total_length != 0 {
+            stats.n_content = n_count as f32 / stats.total_length as f32;
+        }
+
+        let pattern_size = pattern_size as usize;
+        if pattern_size == 0 {
+            return stats;
+        }
+        let pattern_count: u64 = sequences.iter()
+            .map(|sequence| (sequence.len() + 1).saturating_sub(pattern_size) as u64)
+            .sum();
+        let step = (pattern_count / MAXIMUM_SAMPLED_PATTERN_COUNT).max(1) as usize;
+        let mut unique_count = 0;
+        sequences.iter().for_each(|sequence| {
+            if sequence.len() < pattern_size {
+                return;
+            }
+            (0..=sequence.len() - pattern_size).step_by(step).for_each(|position| {
+                let pattern = &sequence[position..position + pattern_size];
+                if pattern.iter().any(|base| base.eq_ignore_ascii_case(&b'N')) {
+                    return;
+                }
+                stats.sampled_pattern_count += 1;
+                let location_count: usize = self.as_ref()
+                    .locate_pattern(pattern, &self.full_sorted_target_indices)
+                    .into_iter()
+                    .map(|pattern_location| {
+                        match self.get_circular_length(pattern_location.target_index) {
+                            Some(length) => pattern_location.sorted_positions.iter().filter(|position| **position < length).count(),
+                            None => pattern_location.sorted_positions.len(),
+                        }
+                    })
+                    .sum();
+                if location_count == 1 {
+                    unique_count += 1;
+                }
+            });
+        });
+        if stats.sampled_pattern_count != 0 {
+            stats.unique_pattern_fraction = unique_count as f32 / stats.sampled_pattern_count as f32;
+        }
+        stats
+    }
+}
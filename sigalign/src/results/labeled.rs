@@ -17,6 +17,10 @@ pub struct LabeledTargetAlignment {
     pub index: u32,
     #[cfg_attr(feature = "short_key", serde(rename = "lbl"))]
     pub label: String,
+    /// Length of the target (0 if deserialized from the result without the length)
+    #[serde(default)]
+    #[cfg_attr(feature = "short_key", serde(rename = "len"))]
+    pub length: u32,
     #[cfg_attr(feature = "short_key", serde(rename = "aln"))]
     pub alignments: Vec<Alignment>,
 }
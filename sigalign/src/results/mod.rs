@@ -49,12 +49,35 @@ pub use sigalign_core::results::{
     AlignmentPosition,
     AlignmentOperations,
     AlignmentOperation,
+    AlignedSequences,
+    TieBreak,
+    SignificanceModel,
+    Overlap,
+    OverlapOrientation,
+    MappingResult,
+    AlignmentVerificationError,
+    CigarDiff,
 };
 // Export labeled results
 pub use labeled::{
     LabeledQueryAlignment,
     LabeledTargetAlignment,
 };
+// Paired-end results
+mod paired;
+pub use paired::{
+    PairedAlignment,
+    PairStatus,
+    MateAlignment,
+    ConcordantPair,
+};
+// Alignments in both strands
+mod stranded;
+pub use stranded::StrandedAlignment;
+// Alignments across the adjacent targets
+mod spanning;
+pub use spanning::SpanningAlignment;
+pub(crate) use spanning::take_spanning_alignments;
 
 mod to_json;
 mod count_alignments;
@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+
+use super::{QueryAlignment, Alignment};
+
+/// Alignments of the paired-end reads.
+///  - `read1` and `read2`: all alignments of each mate in both strands.
+///  - `best_pair`: the concordant pair with the lowest total penalty (longer one if the same).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairedAlignment {
+    pub status: PairStatus,
+    pub best_pair: Option<ConcordantPair>,
+    pub read1: MateAlignment,
+    pub read2: MateAlignment,
+}
+
+/// How the mates are placed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PairStatus {
+    /// The mates are on the same target in the FR orientation within the insert size range.
+    Concordant,
+    /// Both mates are aligned, but not as a concordant pair.
+    Discordant,
+    /// Only one mate is aligned.
+    Single,
+    /// Neither mate is aligned.
+    Unmapped,
+}
+
+/// Alignments of a mate.
+///  - `reverse`: alignments of the reverse complement of the read.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MateAlignment {
+    pub forward: QueryAlignment,
+    pub reverse: QueryAlignment,
+}
+
+/// Pair of the alignments in the FR orientation.
+///  - `is_forward`: true if read1 is aligned in the forward strand (and read2 in the reverse).
+///  - `insert_size`: length of the fragment from the leftmost to the rightmost base of the mates,
+///    including the unaligned (clipped) ends of the reads.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConcordantPair {
+    pub target_index: u32,
+    pub is_forward: bool,
+    pub insert_size: u32,
+    pub read1: Alignment,
+    pub read2: Alignment,
+}
+
+impl MateAlignment {
+    fn is_empty(&self) -> bool {
+        self.forward.0.is_empty() && self.reverse.0.is_empty()
+    }
+}
+
+impl PairedAlignment {
+    /// Pair the alignments of the mates.
+    ///  - `read1_length`, `read2_length`: lengths of the reads, to include the clipped ends in the insert size.
+    pub fn from_mates(
+        read1: MateAlignment,
+        read2: MateAlignment,
+        read1_length: u32,
+        read2_length: u32,
+        minimum_insert_size: u32,
+        maximum_insert_size: u32,
+    ) -> Self {
+        let mut best_pair: Option<ConcordantPair> = None;
+        // Fragment in the forward strand: read1 (forward) -> read2 (reverse)
+        // Fragment in the reverse strand: read2 (forward) -> read1 (reverse)
+        [
+            (true, &read1.forward, &read2.reverse, read2_length),
+            (false, &read2.forward, &read1.reverse, read1_length),
+        ].into_iter().for_each(|(is_forward, left, right, right_length)| {
+            left.0.iter().for_each(|left_target_alignment| {
+                let Some(right_target_alignment) = right.0.iter().find(|v| v.index == left_target_alignment.index) else {
+                    return;
+                };
+                left_target_alignment.alignments.iter().for_each(|left_alignment| {
+                    right_target_alignment.alignments.iter().for_each(|right_alignment| {
+                        let Some(insert_size) = insert_size_of_fr_pair(
+                            left_alignment,
+                            right_alignment,
+                            right_length,
+                        ) else {
+                            return;
+                        };
+                        if insert_size < minimum_insert_size || maximum_insert_size < insert_size {
+                            return;
+                        }
+                        let (read1_alignment, read2_alignment) = if is_forward {
+                            (left_alignment, right_alignment)
+                        } else {
+                            (right_alignment, left_alignment)
+                        };
+                        let pair = ConcordantPair {
+                            target_index: left_target_alignment.index,
+                            is_forward,
+                            insert_size,
+                            read1: read1_alignment.clone(),
+                            read2: read2_alignment.clone(),
+                        };
+                        if best_pair.as_ref().map_or(true, |best| pair.rank_key() < best.rank_key()) {
+                            best_pair = Some(pair);
+                        }
+                    });
+                });
+            });
+        });
+        let status = if best_pair.is_some() {
+            PairStatus::Concordant
+        } else {
+            match (read1.is_empty(), read2.is_empty()) {
+                (false, false) => PairStatus::Discordant,
+                (true, true) => PairStatus::Unmapped,
+                _ => PairStatus::Single,
+            }
+        };
+        Self {
+            status,
+            best_pair,
+            read1,
+            read2,
+        }
+    }
+}
+
+impl ConcordantPair {
+    // Lower total penalty first, then longer total length
+    fn rank_key(&self) -> (u32, std::cmp::Reverse<u32>) {
+        (
+            self.read1.penalty + self.read2.penalty,
+            std::cmp::Reverse(self.read1.length + self.read2.length),
+        )
+    }
+}
+
+// None if the right mate starts before the left mate or ends before it
+fn insert_size_of_fr_pair(
+    left: &Alignment,
+    right: &Alignment,
+    right_length: u32,
+) -> Option<u32> {
+    if (right.position.target.0 < left.position.target.0) || (right.position.target.1 < left.position.target.1) {
+        return None;
+    }
+    let fragment_start = left.position.target.0.saturating_sub(left.position.query.0);
+    let fragment_end = right.position.target.1 + (right_length - right.position.query.1);
+    Some(fragment_end - fragment_start)
+}
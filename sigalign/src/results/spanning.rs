@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+
+use super::{QueryAlignment, Alignment};
+
+/// Alignment spanning the junction of two adjacent targets (e.g., split contigs of an assembly).
+///  - `left` ends at the end of the left target, and `right` starts at the start of the right target.
+///  - The query ranges of `left` and `right` are contiguous.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpanningAlignment {
+    pub left_target_index: u32,
+    pub right_target_index: u32,
+    pub penalty: u32,
+    pub length: u32,
+    pub left: Alignment,
+    pub right: Alignment,
+}
+
+impl SpanningAlignment {
+    /// Get the query range of the whole alignment.
+    pub fn query_range(&self) -> (u32, u32) {
+        (self.left.position.query.0, self.right.position.query.1)
+    }
+    /// Get the CIGAR string of the whole alignment.
+    ///  - The junction of the targets is marked with the zero-length reference skip (`0N`).
+    pub fn to_cigar(&self, extended: bool) -> String {
+        let mut cigar = self.left.to_cigar(extended);
+        cigar.push_str("0N");
+        // Writing to the `String` never fails
+        let _ = self.right.write_cigar(&mut cigar, extended);
+        cigar
+    }
+}
+
+// Take the pairs of the alignments meeting at the junctions out of the query alignment
+//  - `adjacent_targets`: (left, right) target indices.
+//  - Each alignment is merged at most once. The remained ones are left in the query alignment.
+pub(crate) fn take_spanning_alignments<F: Fn(u32) -> Option<u32>>(
+    query_alignment: &mut QueryAlignment,
+    adjacent_targets: &[(u32, u32)],
+    target_length: F,
+) -> Vec<SpanningAlignment> {
+    let mut spanning_alignments = Vec::new();
+    for &(left_target_index, right_target_index) in adjacent_targets {
+        let Some(left_target_length) = target_length(left_target_index) else {
+            continue;
+        };
+        let Some(left_position) = query_alignment.0.iter().position(|v| v.index == left_target_index) else {
+            continue;
+        };
+        let Some(right_position) = query_alignment.0.iter().position(|v| v.index == right_target_index) else {
+            continue;
+        };
+        if left_position == right_position {
+            continue;
+        }
+
+        let mut pairs: Vec<(usize, usize)> = Vec::new();
+        let left_alignments = &query_alignment.0[left_position].alignments;
+        let right_alignments = &query_alignment.0[right_position].alignments;
+        left_alignments.iter().enumerate().for_each(|(left_index, left)| {
+            if left.position.target.1 != left_target_length {
+                return;
+            }
+            let paired = right_alignments.iter().enumerate().find(|(right_index, right)| {
+                right.position.target.0 == 0
+                && right.position.query.0 == left.position.query.1
+                && pairs.iter().all(|(_, used)| used != right_index)
+            });
+            if let Some((right_index, _)) = paired {
+                pairs.push((left_index, right_index));
+            }
+        });
+        if pairs.is_empty() {
+            continue;
+        }
+
+        pairs.iter().for_each(|&(left_index, right_index)| {
+            let left = query_alignment.0[left_position].alignments[left_index].clone();
+            let right = query_alignment.0[right_position].alignments[right_index].clone();
+            spanning_alignments.push(SpanningAlignment {
+                left_target_index,
+                right_target_index,
+                penalty: left.penalty + right.penalty,
+                length: left.length + right.length,
+                left,
+                right,
+            });
+        });
+        remove_alignments(&mut query_alignment.0[left_position].alignments, pairs.iter().map(|v| v.0));
+        remove_alignments(&mut query_alignment.0[right_position].alignments, pairs.iter().map(|v| v.1));
+    }
+    query_alignment.0.retain(|target_alignment| !target_alignment.alignments.is_empty());
+    spanning_alignments
+}
+
+fn remove_alignments<I: Iterator<Item = usize>>(alignments: &mut Vec<Alignment>, indices: I) {
+    let mut to_remove = vec![false; alignments.len()];
+    indices.for_each(|index| to_remove[index] = true);
+    let mut removed = to_remove.into_iter();
+    alignments.retain(|_| !removed.next().unwrap());
+}
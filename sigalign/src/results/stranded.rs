@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+use super::{QueryAlignment, Alignment};
+
+/// Alignment of the query in either strand.
+///  - `is_forward`: false if the reverse complement of the query is aligned.
+///    Then, the query position of the `alignment` is on the reverse complement.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StrandedAlignment {
+    pub target_index: u32,
+    pub is_forward: bool,
+    pub alignment: Alignment,
+}
+
+impl StrandedAlignment {
+    /// Merge the alignments of the both strands, keeping only the better strand at the same locus.
+    ///  - The alignments of the different strands overlapping in the target are the duplicates
+    ///    (e.g., of the palindromic query), and only the best of them is kept.
+    ///  - The better alignment has the lower penalty, then the longer length (see `Alignment::rank_key`).
+    ///    The forward strand wins the ties.
+    ///  - The alignments of the same strand are kept as they are.
+    ///  - Sorted by the target index, the target position, and the strand (forward first).
+    pub fn from_strands(forward: QueryAlignment, reverse: QueryAlignment) -> Vec<Self> {
+        let mut candidates: Vec<Self> = [(true, forward), (false, reverse)].into_iter().flat_map(|(is_forward, query_alignment)| {
+            query_alignment.0.into_iter().flat_map(move |target_alignment| {
+                let target_index = target_alignment.index;
+                target_alignment.alignments.into_iter().map(move |alignment| Self {
+                    target_index,
+                    is_forward,
+                    alignment,
+                })
+            })
+        }).collect();
+        candidates.sort_by_key(|v| (v.alignment.rank_key(), !v.is_forward, v.target_index, v.alignment.position.target));
+
+        let mut kept: Vec<Self> = Vec::with_capacity(candidates.len());
+        candidates.into_iter().for_each(|candidate| {
+            let is_duplicated = kept.iter().any(|v| {
+                v.target_index == candidate.target_index
+                && v.is_forward != candidate.is_forward
+                && v.overlaps_in_target(&candidate)
+            });
+            if !is_duplicated {
+                kept.push(candidate);
+            }
+        });
+        kept.sort_by_key(|v| (v.target_index, v.alignment.position.target, !v.is_forward));
+        kept
+    }
+    fn overlaps_in_target(&self, other: &Self) -> bool {
+        let (start, end) = self.alignment.position.target;
+        let (other_start, other_end) = other.alignment.position.target;
+        start < other_end && other_start < end
+    }
+}
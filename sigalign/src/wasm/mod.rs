@@ -0,0 +1,140 @@
+/*!
+WebAssembly bindings for SigAlign (enabled with the `wasm` feature).
+
+The `WasmReference` is built once and can be used for any number of `align` calls.
+
+Every entry point returns the invalid input as a JS exception (`JsError`) instead of panicking:
+- The FASTA is parsed by the `ReferenceBuilder`, which returns the error of the invalid or empty input.
+- The penalties and the cutoff are checked by the constructors of the algorithms.
+- The query is checked by `Aligner::try_align` (empty, not ASCII letters, or too long).
+
+The inputs are validated up front, since wasm32 is built with `panic=abort`
+and a panic can not be caught: the module would abort and not be usable after it.
+Each entry point is a thin wrapper over a function returning the error message,
+so that the validation is tested on the native targets.
+*/
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    Aligner,
+    Reference,
+    ReferenceBuilder,
+    algorithms::{Local, SemiGlobal},
+};
+
+/// A reference built from FASTA formatted string.
+#[wasm_bindgen]
+pub struct WasmReference {
+    inner: Reference,
+}
+
+#[wasm_bindgen]
+impl WasmReference {
+    #[wasm_bindgen(constructor)]
+    pub fn new(fasta: &str) -> Result<WasmReference, JsError> {
+        let inner = build_reference(fasta).map_err(|message| JsError::new(&message))?;
+        Ok(Self { inner })
+    }
+    #[wasm_bindgen(getter)]
+    pub fn num_targets(&self) -> u32 {
+        self.inner.get_num_targets()
+    }
+    #[wasm_bindgen(getter)]
+    pub fn total_length(&self) -> u32 {
+        self.inner.get_total_length()
+    }
+}
+
+/// An aligner for local (default) or semi-global alignment.
+#[wasm_bindgen]
+pub struct WasmAligner {
+    inner: AlignerKind,
+}
+
+enum AlignerKind {
+    Local(Aligner<Local>),
+    SemiGlobal(Aligner<SemiGlobal>),
+}
+
+#[wasm_bindgen]
+impl WasmAligner {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        mismatch_penalty: u32,
+        gap_open_penalty: u32,
+        gap_extend_penalty: u32,
+        minimum_length: u32,
+        maximum_penalty_per_length: f32,
+        is_local: Option<bool>,
+    ) -> Result<WasmAligner, JsError> {
+        let inner = AlignerKind::new(
+            mismatch_penalty, gap_open_penalty, gap_extend_penalty,
+            minimum_length, maximum_penalty_per_length, is_local.unwrap_or(true),
+        ).map_err(|message| JsError::new(&message))?;
+        Ok(Self { inner })
+    }
+    /// Align the query to the reference and return the labeled result as JSON string.
+    ///  - The query rejected by `Aligner::try_align` (empty, not ASCII letters, or too long) is an exception.
+    pub fn align(&mut self, query: &str, reference: &WasmReference) -> Result<String, JsError> {
+        self.inner.align_to_json(query, &reference.inner).map_err(|message| JsError::new(&message))
+    }
+}
+
+fn build_reference(fasta: &str) -> Result<Reference, String> {
+    ReferenceBuilder::new()
+        .set_uppercase(true)
+        .add_fasta(fasta.as_bytes())
+        .and_then(|builder| builder.build())
+        .map_err(|err| err.to_string())
+}
+
+impl AlignerKind {
+    fn new(
+        mismatch_penalty: u32,
+        gap_open_penalty: u32,
+        gap_extend_penalty: u32,
+        minimum_length: u32,
+        maximum_penalty_per_length: f32,
+        is_local: bool,
+    ) -> Result<Self, String> {
+        if is_local {
+            Local::new(mismatch_penalty, gap_open_penalty, gap_extend_penalty, minimum_length, maximum_penalty_per_length)
+                .map(|v| Self::Local(Aligner::new(v)))
+        } else {
+            SemiGlobal::new(mismatch_penalty, gap_open_penalty, gap_extend_penalty, minimum_length, maximum_penalty_per_length)
+                .map(|v| Self::SemiGlobal(Aligner::new(v)))
+        }.map_err(|err| err.to_string())
+    }
+    fn align_to_json(&mut self, query: &str, reference: &Reference) -> Result<String, String> {
+        let query = query.as_bytes();
+        let query_alignment = match self {
+            Self::Local(v) => v.try_align(query, reference),
+            Self::SemiGlobal(v) => v.try_align(query, reference),
+        }.map_err(|err| err.to_string())?;
+        Ok(reference.label_query_alignment(query_alignment).to_json())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_inputs_are_errors_not_panics() {
+        // The messages of these errors are thrown as the JS exceptions
+        assert!(build_reference("").is_err());
+        assert!(AlignerKind::new(4, 6, 0, 50, 0.2, true).is_err());
+        assert!(AlignerKind::new(4, 6, 2, 50, f32::NAN, false).is_err());
+        assert!(AlignerKind::new(4, 6, 2, 50, 2.0, true).is_err());
+
+        let reference = build_reference(
+            ">target_1\nACACAGATCGCAAACTCACAATTGTATTTCTTTGCCACCTGGGCATATACTTTTTGCGCCCCCTCATTTA\n",
+        ).unwrap();
+        let mut aligner = AlignerKind::new(4, 6, 2, 50, 0.2, true).unwrap();
+        let query = "CAAACTCACAATTGTATTTCTTTGCCAGCTGGGCATATACTTTTTCCGCCCCCTCATTTA";
+        assert!(aligner.align_to_json(query, &reference).unwrap().contains("target_1"));
+        assert!(aligner.align_to_json("", &reference).is_err());
+        assert!(aligner.align_to_json("ACGT ACGT", &reference).is_err());
+        assert!(aligner.align_to_json("ACGTÅACGT", &reference).is_err());
+    }
+}
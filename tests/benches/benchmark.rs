@@ -12,9 +12,12 @@ mod count_matches;
 use count_matches::count_the_consecutive_match;
 mod fasta_reader;
 use fasta_reader::read_fasta_file;
+mod merge_anchors;
+use merge_anchors::merge_anchors;
 
 criterion_group!(
     benches,
     read_fasta_file,
+    merge_anchors,
 );
 criterion_main!(benches);
\ No newline at end of file
@@ -0,0 +1,43 @@
+use criterion::{
+    black_box, Criterion,
+};
+
+use sigalign_core::debug::UnmergedAnchors;
+
+// Positions of the anchors in a repetitive target:
+//  every pattern is located at every `period`.
+fn get_sorted_positions_list(
+    anchor_count: u32,
+    pattern_count: u32,
+    pattern_size: u32,
+    period: u32,
+) -> Vec<Vec<u32>> {
+    (0..pattern_count).map(|pattern_index| {
+        (0..anchor_count).map(|anchor_index| {
+            anchor_index * period + pattern_index * pattern_size
+        }).collect()
+    }).collect()
+}
+
+pub fn merge_anchors(c: &mut Criterion) {
+    let mut group = c.benchmark_group("merge_anchors");
+
+    let anchor_count: u32 = 5000;
+    let pattern_count: u32 = 10;
+    let pattern_size: u32 = 20;
+    let period: u32 = pattern_count * pattern_size + 7;
+
+    let sorted_positions_list = get_sorted_positions_list(anchor_count, pattern_count, pattern_size, period);
+
+    group.bench_function(
+        "merge_ungapped_anchors",
+        |b| b.iter_batched(
+            || UnmergedAnchors::new(sorted_positions_list.clone()),
+            |mut anchors| {
+                anchors.merge(pattern_size);
+                black_box(anchors)
+            },
+            criterion::BatchSize::LargeInput,
+        )
+    );
+}
@@ -11,6 +11,8 @@ mod sequence_reader;
 /* Test for `sigalign` crate */
 // Validation of SigAlign's result
 mod results_satisfy_cutoff;
+#[cfg(feature = "affine_dp_validation")]
+mod local_penalty_with_affine_dp;
 mod limitation_of_results_works;
 mod results_validation_with_032_and_dpm;
 pub mod result_validation_with_dynamic_programming_matrix;
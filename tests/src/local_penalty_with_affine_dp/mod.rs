@@ -0,0 +1,138 @@
+/*!
+Validate the penalty of local alignments with a brute-force gap-affine DP (Gotoh).
+
+For each local alignment of random short sequences, the optimal penalty
+to align the same target and query spans end-to-end is calculated.
+- The reported penalty must be the same as the penalty of its operations.
+- The reported penalty must not be lower than the optimum (lower is impossible).
+- The optimal alignment of the span must also satisfy the cutoffs.
+
+The reported penalty is not always the optimum because the alignment is forced
+to pass the anchor (exactly matched pattern) where the extension started.
+The count of such cases is logged.
+
+Enabled with the `affine_dp_validation` feature.
+*/
+use log::info;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use crate::common::init_logger;
+use sigalign::{
+    Aligner,
+    ReferenceBuilder,
+    algorithms::Local,
+    results::{Alignment, AlignmentOperation},
+};
+
+const BASES: [u8; 4] = *b"ACGT";
+const CASE_COUNT: u64 = 2000;
+
+#[test]
+fn local_penalty_is_not_lower_than_affine_dp_optimum() {
+    init_logger();
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let mut alignment_count = 0;
+    let mut not_optimal_count = 0;
+    for _ in 0..CASE_COUNT {
+        let (px, po, pe) = (rng.gen_range(1..8), rng.gen_range(0..8), rng.gen_range(1..6));
+        let (minl, maxp) = (40, 0.3);
+        let algorithm = match Local::new(px, po, pe, minl, maxp) {
+            Ok(v) => v,
+            Err(_) => continue, // Pattern size is too small
+        };
+        let mut aligner = Aligner::new(algorithm);
+
+        let target: Vec<u8> = (0..rng.gen_range(60..200)).map(|_| BASES[rng.gen_range(0..4)]).collect();
+        let query_start = rng.gen_range(0..20);
+        let query_end = target.len() - rng.gen_range(0..20);
+        let query = mutate(&mut rng, &target[query_start..query_end], 0.08);
+
+        let reference = ReferenceBuilder::new()
+            .add_target("target", &target)
+            .build().unwrap();
+        let query_alignment = aligner.align(&query, &reference);
+
+        for alignment in query_alignment.0.into_iter().flat_map(|v| v.alignments) {
+            alignment_count += 1;
+            assert_eq!(alignment.penalty, penalty_of_operations(&alignment, px, po, pe));
+
+            let target_span = &target[alignment.position.target.0 as usize..alignment.position.target.1 as usize];
+            let query_span = &query[alignment.position.query.0 as usize..alignment.position.query.1 as usize];
+            let (optimal_penalty, optimal_length) = global_affine_optimum(target_span, query_span, px, po, pe);
+            assert!(
+                alignment.penalty >= optimal_penalty,
+                "Penalty is lower than the optimum: {:?} (optimum: {})", alignment, optimal_penalty,
+            );
+            assert!(optimal_length >= minl);
+            assert!(optimal_penalty as f32 / optimal_length as f32 <= maxp);
+            if alignment.penalty != optimal_penalty {
+                not_optimal_count += 1;
+            }
+        }
+    }
+    info!(
+        "Alignments: {}, Penalty is larger than the optimum of the span: {}",
+        alignment_count, not_optimal_count,
+    );
+}
+
+// Substitution, deletion, and insertion occur with the same probability
+fn mutate(rng: &mut StdRng, sequence: &[u8], rate: f64) -> Vec<u8> {
+    let mut mutated = Vec::with_capacity(sequence.len());
+    for &base in sequence {
+        let r: f64 = rng.gen();
+        if r < rate / 3.0 {
+            mutated.push(BASES[rng.gen_range(0..4)]);
+        } else if r < rate * 2.0 / 3.0 {
+            // Deleted
+        } else if r < rate {
+            mutated.push(base);
+            mutated.push(BASES[rng.gen_range(0..4)]);
+        } else {
+            mutated.push(base);
+        }
+    }
+    mutated
+}
+
+fn penalty_of_operations(alignment: &Alignment, px: u32, po: u32, pe: u32) -> u32 {
+    alignment.operations.iter().map(|op| {
+        match op.operation {
+            AlignmentOperation::Match => 0,
+            AlignmentOperation::Subst => px * op.count,
+            AlignmentOperation::Deletion | AlignmentOperation::Insertion => po + pe * op.count,
+        }
+    }).sum()
+}
+
+// Quadratic DP for the optimal penalty to align the whole sequences.
+// Return (penalty, length). Among the optimal alignments, the longest one is chosen.
+fn global_affine_optimum(target: &[u8], query: &[u8], px: u32, po: u32, pe: u32) -> (u32, u32) {
+    const INF: (u32, u32) = (u32::MAX / 2, 0);
+    let gap = |count: usize| (po + pe * count as u32, count as u32);
+    let better = |a: (u32, u32), b: (u32, u32)| {
+        if a.0 < b.0 || (a.0 == b.0 && a.1 > b.1) { a } else { b }
+    };
+    let add = |a: (u32, u32), penalty: u32, length: u32| (a.0 + penalty, a.1 + length);
+
+    let m_len = query.len();
+    // Best of all states, ended with deletion, ended with insertion
+    let mut pre_best: Vec<(u32, u32)> = (0..=m_len).map(|j| if j == 0 { (0, 0) } else { gap(j) }).collect();
+    let mut pre_del: Vec<(u32, u32)> = vec![INF; m_len + 1];
+    for i in 1..=target.len() {
+        let mut best = vec![INF; m_len + 1];
+        let mut del = vec![INF; m_len + 1];
+        let mut ins = vec![INF; m_len + 1];
+        del[0] = gap(i);
+        best[0] = del[0];
+        for j in 1..=m_len {
+            del[j] = better(add(pre_best[j], po + pe, 1), add(pre_del[j], pe, 1));
+            ins[j] = better(add(best[j - 1], po + pe, 1), add(ins[j - 1], pe, 1));
+            let diag = add(pre_best[j - 1], if target[i - 1] == query[j - 1] { 0 } else { px }, 1);
+            best[j] = better(better(diag, del[j]), ins[j]);
+        }
+        pre_best = best;
+        pre_del = del;
+    }
+    pre_best[m_len]
+}
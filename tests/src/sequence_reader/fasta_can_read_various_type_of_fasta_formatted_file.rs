@@ -98,3 +98,38 @@ fn generate_same_record_regardless_of_line_ending() {
     assert!(crlf_reader.next().is_none());
     assert!(two_line_reader.next().is_none());
 }
+
+#[test]
+// Test if the gzip compressed FASTA file is detected when opened from path
+fn generate_same_record_with_gzip_file_opened_from_path() {
+    let fa = DataForRefBuild::LF.get_data_path();
+    let gzip_fa = DataForRefBuild::Gz.get_data_path();
+
+    let mut fa_reader = FastaReader::from_path(fa).unwrap();
+    let mut gzip_reader = FastaReader::from_path_maybe_gzip(gzip_fa).unwrap();
+
+    let mut fa_buf = Vec::new();
+    let mut gzip_buf = Vec::new();
+    let mut record_count = 0;
+    while let Some(mut fa_record) = fa_reader.next() {
+        let mut gzip_record = gzip_reader.next().unwrap();
+        record_count += 1;
+
+        // Compare ids
+        fa_buf.clear();
+        gzip_buf.clear();
+        fa_record.extend_id_buf(&mut fa_buf);
+        gzip_record.extend_id_buf(&mut gzip_buf);
+        assert_eq!(fa_buf, gzip_buf);
+
+        // Compare sequences
+        fa_buf.clear();
+        gzip_buf.clear();
+        fa_record.extend_seq_buf(&mut fa_buf);
+        gzip_record.extend_seq_buf(&mut gzip_buf);
+        assert_eq!(fa_buf, gzip_buf);
+    }
+
+    assert!(record_count > 1);
+    assert!(gzip_reader.next().is_none());
+}